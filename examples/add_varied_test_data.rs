@@ -45,8 +45,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             "INSERT INTO usage_snapshots 
              (date, input_tokens, output_tokens, reasoning_tokens, 
               cache_write_tokens, cache_read_tokens, total_cost, 
-              interaction_count, created_at) 
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+              interaction_count, created_at, per_day) 
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 1)",
             rusqlite::params![
                 date_str,
                 input_tokens,
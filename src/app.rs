@@ -20,25 +20,91 @@ use cosmic::{
     },
     iced_futures::stream,
     widget::{
-        autosize, button, checkbox, column, container, icon, row, scrollable, text, text_input, Id,
+        autosize, button, checkbox, column, container, icon, mouse_area, row, scrollable, text,
+        text_input, Id,
     },
     Application, Element,
 };
 use std::sync::LazyLock;
+use std::time::{Duration, Instant};
 use tokio::{sync::watch, time};
 
 static AUTOSIZE_MAIN_ID: LazyLock<Id> = LazyLock::new(|| Id::new("autosize-main"));
 
-use crate::core::collector::DataCollector;
+use crate::core::collector::{lock::InstanceLock, CollectorError, DataCollector};
 use crate::core::config::{
-    validate_refresh_interval, AppConfig, ConfigError, ConfigWarning, PanelMetric,
+    format_hex_color, parse_fetch_timeout, parse_monthly_budget, parse_warning_color,
+    validate_refresh_interval, AppConfig, ConfigError, ConfigWarning, MetricSource, PanelMetric,
 };
-use crate::core::database::DatabaseManager;
-use crate::core::opencode::OpenCodeUsageReader;
-use crate::ui::state::{AppState, DisplayMode, PanelState};
+use crate::core::database::source::SnapshotUsageSource;
+use crate::core::database::{DatabaseManager, Result as DatabaseResult};
+use crate::core::opencode::manifest::ManifestUsageSource;
+use crate::core::opencode::{
+    detect_opencode_storage, OpenCodeUsageReader, ReaderError, UsageMetrics, UsageSource,
+};
+use crate::core::socket::{default_socket_path, MetricsSocket};
+use crate::ui::messages::MetricsFetchResult;
+use crate::ui::state::{AppState, DisplayMode, PanelState, REFRESHED_FLASH_SECONDS};
 use crate::ui::Message;
 use std::sync::Arc;
 
+/// How often the config is reloaded from disk as a fallback for `watch_config`
+const CONFIG_FALLBACK_RELOAD_SECONDS: u64 = 300;
+
+/// Scale applied to popup text sizes and paddings when `compact_ui` is enabled
+const COMPACT_UI_SCALE: f32 = 0.8;
+
+/// Smallest and largest UI scale, so sizes stay readable and fit the popup
+const UI_SCALE_RANGE: (f32, f32) = (0.5, 1.5);
+
+/// Clamp a UI scale factor to a sane range, treating non-finite values as 1.0
+fn clamp_ui_scale(scale: f32) -> f32 {
+    if scale.is_finite() {
+        scale.clamp(UI_SCALE_RANGE.0, UI_SCALE_RANGE.1)
+    } else {
+        1.0
+    }
+}
+
+/// Time from `now` until "today" rolls over to the next day
+///
+/// Follows the reader's day boundary, so the refresh lands exactly when today's
+/// totals start over. That boundary is midnight UTC shifted by `reset_hour`, not
+/// local midnight, matching the UTC dates snapshots are stored under. Never zero,
+/// so a rollover is not fired twice in a row.
+fn duration_until_day_reset(now: std::time::SystemTime, reset_hour: u8) -> Duration {
+    OpenCodeUsageReader::next_day_start(now, reset_hour)
+        .duration_since(now)
+        .unwrap_or_default()
+        .max(Duration::from_secs(1))
+}
+
+/// Whether to show the first-run introduction
+///
+/// Shown until dismissed, but only to new users: an existing history database
+/// means the applet has been used before.
+fn needs_onboarding(config: &AppConfig, database_exists: bool) -> bool {
+    !config.onboarding_completed && !database_exists
+}
+
+/// Config to apply after a fallback reload, if it differs from the in-memory config
+///
+/// Load failures are logged and ignored so a broken config file never replaces a
+/// working in-memory config.
+fn changed_config(
+    current: &AppConfig,
+    loaded: Result<AppConfig, ConfigError>,
+) -> Option<AppConfig> {
+    match loaded {
+        Ok(config) if config != *current => Some(config),
+        Ok(_) => None,
+        Err(e) => {
+            eprintln!("[ConfigFallback] Failed to reload config: {e}");
+            None
+        }
+    }
+}
+
 /// Check if the viewer binary is available in PATH or build directory
 fn is_viewer_available() -> bool {
     // Check if binary exists in PATH
@@ -54,26 +120,290 @@ fn is_viewer_available() -> bool {
     std::path::Path::new("./target/release/cosmic-applet-opencode-usage-viewer").exists()
 }
 
+/// Show a desktop notification for a lifetime interaction milestone
+fn send_milestone_notification(milestone: u64) {
+    use crate::ui::formatters::format_number;
+
+    let body = format!(
+        "You've reached {} lifetime interactions!",
+        format_number(milestone)
+    );
+    if let Err(e) = std::process::Command::new("notify-send")
+        .args(["--app-name=OpenCode Usage", "OpenCode milestone", &body])
+        .spawn()
+    {
+        eprintln!("[Milestones] Failed to send notification: {e}");
+    }
+}
+
+/// Popup auto-close timeout from the configured seconds (`None` or 0 disables it)
+fn popup_auto_close_timeout(seconds: Option<u32>) -> Option<Duration> {
+    seconds
+        .filter(|&s| s > 0)
+        .map(|s| Duration::from_secs(u64::from(s)))
+}
+
+/// Time left before the popup should auto-close, or `None` if it is due now
+fn popup_auto_close_remaining(timeout: Duration, since_interaction: Duration) -> Option<Duration> {
+    timeout
+        .checked_sub(since_interaction)
+        .filter(|remaining| !remaining.is_zero())
+}
+
+/// Move the panel metric at `from` so it ends up at index `to`
+///
+/// `to` past the end moves the metric to the end. An out-of-range `from` is ignored.
+fn reorder_panel_metrics(order: &mut Vec<PanelMetric>, from: usize, to: usize) {
+    if from >= order.len() {
+        return;
+    }
+    let metric = order.remove(from);
+    order.insert(to.min(order.len()), metric);
+}
+
+/// Deadline for a blocking fetch from the configured seconds (`None` or 0 disables it)
+fn fetch_deadline(seconds: Option<u32>) -> Option<Duration> {
+    seconds
+        .filter(|&s| s > 0)
+        .map(|s| Duration::from_secs(u64::from(s)))
+}
+
+/// Fetch metrics on the blocking thread pool, giving up once the deadline passes
+///
+/// A scan that misses the deadline keeps running in the background, but its result
+/// is discarded and the UI gets a timeout error instead of waiting on it.
+/// Also returns the number of entries the scan skipped for lack of permission.
+async fn fetch_blocking_with_deadline(
+    mut source: Box<dyn UsageSource>,
+    display_mode: DisplayMode,
+    needs_today: bool,
+    deadline: Option<Duration>,
+) -> (MetricsFetchResult, usize) {
+    // Move the source into the blocking task to avoid blocking the async runtime
+    let handle = tokio::task::spawn_blocking(move || {
+        fetch_counting_skipped(source.as_mut(), display_mode, needs_today)
+    });
+
+    let joined = match deadline {
+        Some(deadline) => match tokio::time::timeout(deadline, handle).await {
+            Ok(joined) => joined,
+            Err(_) => {
+                eprintln!("[Async] Fetch timed out after {deadline:?}");
+                let error = format!(
+                    "Timed out reading OpenCode usage after {} seconds",
+                    deadline.as_secs()
+                );
+                return (Err(error), 0);
+            }
+        },
+        None => handle.await,
+    };
+
+    joined.unwrap_or_else(|e| (Err(format!("Blocking task join error: {e}")), 0))
+}
+
+/// Fetch the metrics for a display mode, along with the entries skipped for lack of permission
+fn fetch_counting_skipped(
+    source: &mut dyn UsageSource,
+    display_mode: DisplayMode,
+    needs_today: bool,
+) -> (MetricsFetchResult, usize) {
+    let result = fetch_usage_for_mode(source, display_mode, needs_today);
+    (result, source.skipped_permission_denied())
+}
+
+/// Popup warning for storage entries skipped for lack of permission, if any
+fn skipped_entries_warning(skipped: usize) -> Option<String> {
+    match skipped {
+        0 => None,
+        1 => Some("1 storage entry skipped (permission denied)".to_string()),
+        n => Some(format!("{n} storage entries skipped (permission denied)")),
+    }
+}
+
+/// Keep period metrics only if they recorded any interaction
+fn non_empty(metrics: UsageMetrics) -> Option<UsageMetrics> {
+    (metrics.interaction_count > 0).then_some(metrics)
+}
+
+/// Fetch the metrics needed for a display mode from a usage source
+///
+/// Returns the main metrics for the mode along with today's metrics (for the
+/// panel) and this month's metrics (for the cache) when they are fetched.
+/// Today's metrics are only read in other modes when `needs_today` is set.
+pub(crate) fn fetch_usage_for_mode(
+    source: &mut dyn UsageSource,
+    display_mode: DisplayMode,
+    needs_today: bool,
+) -> MetricsFetchResult {
+    let read_error = |e: ReaderError| {
+        eprintln!("[Async] Error reading metrics: {e}");
+        format!("Failed to read OpenCode usage: {e}")
+    };
+
+    match display_mode {
+        DisplayMode::Today => {
+            // One scan covers both today and the month cache
+            eprintln!("[Async] Fetching today's and this month's usage");
+            match source.get_usage_today_and_month() {
+                Ok((today, month)) => {
+                    let metrics =
+                        non_empty(today).ok_or_else(|| read_error(ReaderError::NoDataFound))?;
+                    Ok((metrics.clone(), Some(metrics), Some(month)))
+                }
+                Err(e) => {
+                    // The month cache is optional here, today alone may still be readable
+                    eprintln!("[Async] Month usage unavailable ({e}), fetching today only");
+                    let metrics = source.get_usage_today().map_err(read_error)?;
+                    Ok((metrics.clone(), Some(metrics), None))
+                }
+            }
+        }
+        DisplayMode::Month => {
+            if !needs_today {
+                eprintln!("[Async] Fetching this month's usage");
+                let metrics = source.get_usage_month().map_err(read_error)?;
+                return Ok((metrics, None, None));
+            }
+
+            // Fetch today's data for the panel in the same scan
+            eprintln!("[Async] Fetching this month's and today's usage");
+            let (today, month) = source.get_usage_today_and_month().map_err(read_error)?;
+
+            Ok((month, non_empty(today), None))
+        }
+        DisplayMode::LastMonth => {
+            eprintln!("[Async] Fetching last month's usage");
+            let metrics = source.get_usage_last_month().map_err(read_error)?;
+
+            // Fetch today's data for panel if needed
+            let today_metrics = if !needs_today {
+                None
+            } else {
+                eprintln!("[Async] Fetching today's usage for panel");
+                source.get_usage_today().ok()
+            };
+
+            // Fetch this month's data for cache if needed
+            let month_metrics = {
+                eprintln!("[Async] Fetching this month's usage for cache");
+                source.get_usage_month().ok()
+            };
+
+            Ok((metrics, today_metrics, month_metrics))
+        }
+        DisplayMode::AllTime => {
+            let metrics = source.get_usage().map_err(read_error)?;
+
+            // Fetch today's data for the panel or the daily snapshot if needed
+            let today_metrics = if needs_today {
+                eprintln!("[Async] Fetching today's usage for panel");
+                source.get_usage_today().ok()
+            } else {
+                None
+            };
+
+            Ok((metrics, today_metrics, None))
+        }
+    }
+}
+
+/// Create a usage reader configured with the storage path and read options from the config
+///
+/// # Errors
+/// Returns an error if the storage path is invalid or if the reader cannot be initialized.
+pub(crate) fn reader_for_config(
+    config: &AppConfig,
+) -> Result<OpenCodeUsageReader, Box<dyn std::error::Error>> {
+    let mut reader = if let Some(ref path) = config.storage_path {
+        OpenCodeUsageReader::new_with_path(path.to_str().ok_or("Invalid storage path")?)?
+    } else {
+        OpenCodeUsageReader::new()?
+    };
+    reader.set_parse_threads(config.parse_threads)?;
+    reader.set_day_reset_hour(config.day_reset_hour)?;
+    reader.set_lean_cache(config.lean_cache);
+    reader.set_interaction_unit(config.interaction_unit);
+
+    Ok(reader)
+}
+
+/// Build the usage source for the configuration: a manifest or a part-file reader
+///
+/// # Errors
+/// Returns an error if manifest mode has no `storage_path` or the reader cannot be created.
+pub(crate) fn source_for_config(
+    config: &AppConfig,
+) -> Result<Box<dyn UsageSource>, Box<dyn std::error::Error>> {
+    if config.manifest_mode {
+        let path = config
+            .storage_path
+            .clone()
+            .ok_or("Manifest mode needs storage_path set to the manifest file")?;
+        return Ok(Box::new(ManifestUsageSource::new(path)?));
+    }
+    Ok(Box::new(reader_for_config(config)?))
+}
+
+/// Whether two configs would build differently configured usage readers
+fn reader_settings_changed(old: &AppConfig, new: &AppConfig) -> bool {
+    old.storage_path != new.storage_path
+        || old.manifest_mode != new.manifest_mode
+        || old.parse_threads != new.parse_threads
+        || old.day_reset_hour != new.day_reset_hour
+        || old.lean_cache != new.lean_cache
+        || old.interaction_unit != new.interaction_unit
+}
+
 /// `OpenCode` usage monitor applet structure
+#[allow(clippy::struct_excessive_bools)] // Settings dialog keeps one pending value per toggle
 pub struct OpenCodeMonitorApplet {
     /// Application state managed by COSMIC runtime
     core: Core,
     /// Application state containing UI and data state
     state: AppState,
-    /// `OpenCode` usage reader
-    reader: OpenCodeUsageReader,
+    /// Source of `OpenCode` usage metrics
+    source: Box<dyn UsageSource>,
     /// Data collector for automatic snapshot management
     data_collector: Option<DataCollector>,
+    /// Local socket serving the current metrics as JSON (when enabled)
+    metrics_socket: Option<MetricsSocket>,
+    /// Whether the last snapshot save found the database locked (retried next fetch)
+    history_busy: bool,
+    /// All-time usage from the history database, for the pinned panel note
+    pinned_usage: Option<UsageMetrics>,
+    /// Whether the popup shows the first-run introduction
+    show_onboarding: bool,
+    /// Outcome of storage path detection from the introduction
+    onboarding_status: Option<String>,
     /// Settings UI state
     settings_dialog_open: bool,
     temp_refresh_interval: u32,
     temp_refresh_interval_str: String,
     temp_panel_metrics: Vec<PanelMetric>,
+    temp_panel_metric_order: Vec<PanelMetric>,
+    /// Index of the panel metric being dragged in the order list
+    dragged_panel_metric: Option<usize>,
     temp_use_raw_token_display: bool,
+    temp_use_raw_cost_display: bool,
+    temp_panel_only: bool,
+    temp_flag_usage_decrease: bool,
+    temp_compact_ui: bool,
+    temp_expose_socket: bool,
+    temp_panel_icon_only: bool,
+    temp_compact_interactions: bool,
+    temp_fold_cache_into_io: bool,
+    temp_history_enabled: bool,
+    temp_monthly_budget_str: String,
+    temp_fetch_timeout_str: String,
+    temp_budget_warning_color_str: String,
+    temp_budget_warning_icon: bool,
     config_error: Option<ConfigError>,
     config_warning: Option<ConfigWarning>,
     /// Popup window tracking
     popup: Option<cosmic::iced::window::Id>,
+    /// Last user interaction with the open popup (for auto-close)
+    popup_last_interaction: Instant,
     /// Watch channel sender for refresh interval updates
     refresh_interval_tx: watch::Sender<u32>,
     /// Request generation counter for tracking fetch requests
@@ -86,56 +416,211 @@ impl OpenCodeMonitorApplet {
     /// # Errors
     /// Returns an error if the storage path is invalid or if the reader cannot be initialized.
     pub fn new(config: AppConfig) -> Result<Self, Box<dyn std::error::Error>> {
-        let reader = if let Some(ref path) = config.storage_path {
-            OpenCodeUsageReader::new_with_path(path.to_str().ok_or("Invalid storage path")?)?
-        } else {
-            OpenCodeUsageReader::new()?
-        };
+        let source = source_for_config(&config)?;
+
+        Ok(Self::with_source(config, source))
+    }
 
+    /// Create a new `OpenCodeMonitorApplet` reading metrics from the given source
+    #[must_use]
+    pub fn with_source(config: AppConfig, source: Box<dyn UsageSource>) -> Self {
         let temp_refresh_interval = config.refresh_interval_seconds;
         let temp_panel_metrics = config.panel_metrics.clone();
+        let temp_panel_metric_order = config.panel_metric_order.clone();
         let temp_use_raw_token_display = config.use_raw_token_display;
+        let temp_use_raw_cost_display = config.use_raw_cost_display;
+        let temp_panel_only = config.panel_only;
+        let temp_flag_usage_decrease = config.flag_usage_decrease;
+        let temp_compact_ui = config.compact_ui;
+        let temp_expose_socket = config.expose_socket;
+        let temp_panel_icon_only = config.panel_icon_only;
+        let temp_compact_interactions = config.compact_interactions;
+        let temp_fold_cache_into_io = config.fold_cache_into_io;
+        let temp_history_enabled = config.history_enabled;
+        let temp_monthly_budget_str = config
+            .monthly_budget
+            .map(|b| b.to_string())
+            .unwrap_or_default();
+        let temp_budget_warning_color_str = config
+            .budget_warning_color
+            .map(format_hex_color)
+            .unwrap_or_default();
+        let temp_budget_warning_icon = config.budget_warning_icon;
+        let temp_fetch_timeout_str = config
+            .fetch_timeout_seconds
+            .map(|s| s.to_string())
+            .unwrap_or_default();
 
         // Create watch channel for refresh interval updates
         let (refresh_interval_tx, _rx) = watch::channel(config.refresh_interval_seconds);
 
-        // Initialize data collector with database
-        // This enables automatic daily snapshot collection when metrics are fetched.
-        // If initialization fails, we continue without collection (graceful degradation).
-        let data_collector = match DatabaseManager::new() {
-            Ok(db_manager) => {
-                eprintln!("[DataCollector] Database initialized successfully");
-                Some(DataCollector::new(Arc::new(db_manager)))
-            }
-            Err(e) => {
-                eprintln!("[DataCollector] Failed to initialize database: {e}");
-                eprintln!("[DataCollector] Continuing without automatic data collection");
-                None
-            }
-        };
+        // Checked before the collector creates the database
+        let show_onboarding = needs_onboarding(
+            &config,
+            DatabaseManager::default_database_exists(config.data_namespace.as_deref()),
+        );
+        let data_collector = Self::init_data_collector(&config);
+        let metrics_socket = Self::init_metrics_socket(&config);
 
-        Ok(Self {
+        Self {
             core: Core::default(),
             state: AppState::new(config),
-            reader,
+            source,
             data_collector,
+            metrics_socket,
+            history_busy: false,
+            pinned_usage: None,
+            show_onboarding,
+            onboarding_status: None,
             settings_dialog_open: false,
             temp_refresh_interval,
             temp_refresh_interval_str: temp_refresh_interval.to_string(),
             temp_panel_metrics,
+            temp_panel_metric_order,
+            dragged_panel_metric: None,
             temp_use_raw_token_display,
+            temp_use_raw_cost_display,
+            temp_panel_only,
+            temp_flag_usage_decrease,
+            temp_compact_ui,
+            temp_expose_socket,
+            temp_panel_icon_only,
+            temp_compact_interactions,
+            temp_fold_cache_into_io,
+            temp_history_enabled,
+            temp_monthly_budget_str,
+            temp_fetch_timeout_str,
+            temp_budget_warning_color_str,
+            temp_budget_warning_icon,
             config_error: None,
             config_warning: None,
             popup: None,
+            popup_last_interaction: Instant::now(),
             refresh_interval_tx,
             fetch_generation: 0,
+        }
+    }
+
+    /// Schedule an auto-close check for the popup after the given delay
+    fn popup_auto_close_after(id: window::Id, delay: Duration) -> Task<Message> {
+        Task::perform(time::sleep(delay), move |()| {
+            cosmic::Action::App(Message::PopupAutoClose(id))
+        })
+    }
+
+    /// Schedule the next refresh countdown redraw for the popup
+    fn refresh_countdown_tick(id: window::Id) -> Task<Message> {
+        Task::perform(time::sleep(Duration::from_secs(1)), move |()| {
+            cosmic::Action::App(Message::RefreshCountdownTick(id))
         })
     }
 
+    /// Start the metrics socket if it is enabled
+    ///
+    /// A socket that cannot be bound is logged and left off; the applet works without it.
+    fn init_metrics_socket(config: &AppConfig) -> Option<MetricsSocket> {
+        if !config.expose_socket {
+            return None;
+        }
+
+        let path = config
+            .socket_path
+            .clone()
+            .unwrap_or_else(|| default_socket_path(config.data_namespace.as_deref()));
+        match MetricsSocket::start(&path) {
+            Ok(socket) => Some(socket),
+            Err(e) => {
+                eprintln!("[MetricsSocket] {e}");
+                None
+            }
+        }
+    }
+
+    /// Restart the metrics socket to follow the current config
+    fn restart_metrics_socket(&mut self) {
+        // Drop the old socket first: dropping removes its file, which may be the new path
+        self.metrics_socket = None;
+        self.metrics_socket = Self::init_metrics_socket(&self.state.config);
+    }
+
+    /// Initialize the data collector unless history is disabled
+    fn init_data_collector(config: &AppConfig) -> Option<DataCollector> {
+        Self::init_data_collector_with(config, || Self::open_database(config))
+    }
+
+    /// Opens the usage database of the config's namespace
+    #[cfg(not(test))]
+    fn open_database(config: &AppConfig) -> DatabaseResult<DatabaseManager> {
+        DatabaseManager::new_or_in_memory(config.data_namespace.as_deref())
+    }
+
+    /// Tests never open the user's usage database
+    #[cfg(test)]
+    fn open_database(_config: &AppConfig) -> DatabaseResult<DatabaseManager> {
+        DatabaseManager::new_in_memory()
+    }
+
+    /// Initialize the data collector with the given database opener
+    ///
+    /// The opener is never called when history is disabled, so no database file is created.
+    fn init_data_collector_with(
+        config: &AppConfig,
+        open_db: impl FnOnce() -> DatabaseResult<DatabaseManager>,
+    ) -> Option<DataCollector> {
+        if !config.keeps_history() {
+            eprintln!("[DataCollector] History disabled, skipping database initialization");
+            return None;
+        }
+
+        // Initialize data collector with database
+        // This enables automatic daily snapshot collection when metrics are fetched.
+        // If initialization fails, we continue without collection (graceful degradation).
+        let collector = match open_db() {
+            Ok(db_manager) if db_manager.is_in_memory() => {
+                eprintln!("[DataCollector] Database is in memory, history will not persist");
+                Some(DataCollector::new(Arc::new(db_manager)))
+            }
+            Ok(db_manager) => {
+                eprintln!("[DataCollector] Database initialized successfully");
+                // Only one instance collects per database; others just show live metrics
+                let lock_path = InstanceLock::path_for(db_manager.path());
+                match InstanceLock::try_acquire(&lock_path) {
+                    Ok(Some(lock)) => {
+                        Some(DataCollector::new(Arc::new(db_manager)).with_instance_lock(lock))
+                    }
+                    Ok(None) => {
+                        eprintln!(
+                            "[DataCollector] Another applet instance holds {}, leaving collection to it",
+                            lock_path.display()
+                        );
+                        None
+                    }
+                    Err(e) => {
+                        eprintln!(
+                            "[DataCollector] Failed to take lock {}: {e}, collecting anyway",
+                            lock_path.display()
+                        );
+                        Some(DataCollector::new(Arc::new(db_manager)))
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("[DataCollector] Failed to initialize database: {e}");
+                eprintln!("[DataCollector] Continuing without automatic data collection");
+                None
+            }
+        };
+        collector.map(|collector| collector.with_retention_days(config.snapshot_retention_days))
+    }
+
     /// Handle incoming messages and update application state
     /// Handle incoming messages and perform async operations
     #[allow(clippy::too_many_lines)] // Message handler naturally has many branches
     pub fn handle_message(&mut self, message: Message) -> Task<Message> {
+        if self.popup.is_some() && message.is_popup_interaction() {
+            self.popup_last_interaction = Instant::now();
+        }
+
         match message {
             Message::FetchMetrics => {
                 eprintln!(
@@ -151,108 +636,49 @@ impl OpenCodeMonitorApplet {
                 // Set loading state - preserves previous data if available
                 self.state.set_loading();
 
-                // Clone the storage path for async task
-                let storage_path = self.reader.storage_path().clone();
+                // Fork the usage source so the async task owns its own reader
+                let source = self
+                    .source
+                    .fork()
+                    .map(|files| self.with_metric_source(files));
                 let display_mode = self.state.display_mode;
-                let panel_metrics = self.state.config.panel_metrics.clone();
+                // Snapshots store each day's own usage, so collecting always needs today
+                let needs_today =
+                    self.state.config.needs_today_usage() || self.data_collector.is_some();
+                let deadline = fetch_deadline(self.state.config.fetch_timeout_seconds);
 
                 // Spawn async task to fetch metrics in background
                 Task::perform(
                     async move {
-                        // Create a new reader in the async context
-                        let mut reader = match OpenCodeUsageReader::new_with_path(
-                            storage_path.to_str().unwrap_or(""),
-                        ) {
-                            Ok(r) => r,
-                            Err(e) => return Err(format!("Failed to create reader: {e}")),
+                        let mut source = match source {
+                            Ok(s) => s,
+                            Err(e) => return (Err(format!("Failed to create reader: {e}")), 0),
                         };
 
-                        // Fetch main metrics based on display mode
                         // Use spawn_blocking for AllTime mode to prevent UI freezing during cache building
-                        let (main_metrics, today_metrics, month_metrics) = match display_mode {
-                            DisplayMode::Today => {
-                                eprintln!("[Async] Fetching today's usage");
-                                let metrics = reader.get_usage_today().map_err(|e| {
-                                    eprintln!("[Async] Error reading metrics: {e}");
-                                    format!("Failed to read OpenCode usage: {e}")
-                                })?;
-
-                                // No additional fetches needed for Today mode
-                                let month_metrics = {
-                                    eprintln!("[Async] Fetching this month's usage for cache");
-                                    reader.get_usage_month().ok()
-                                };
-
-                                (metrics.clone(), Some(metrics), month_metrics)
-                            }
-                            DisplayMode::Month => {
-                                eprintln!("[Async] Fetching this month's usage");
-                                let metrics = reader.get_usage_month().map_err(|e| {
-                                    eprintln!("[Async] Error reading metrics: {e}");
-                                    format!("Failed to read OpenCode usage: {e}")
-                                })?;
-
-                                // Fetch today's data for panel if needed
-                                let today_metrics = if panel_metrics.is_empty() {
-                                    None
-                                } else {
-                                    eprintln!("[Async] Fetching today's usage for panel");
-                                    reader.get_usage_today().ok()
-                                };
-
-                                (metrics, today_metrics, None)
-                            }
-                            DisplayMode::LastMonth => {
-                                eprintln!("[Async] Fetching last month's usage");
-                                let metrics = reader.get_usage_last_month().map_err(|e| {
-                                    eprintln!("[Async] Error reading metrics: {e}");
-                                    format!("Failed to read OpenCode usage: {e}")
-                                })?;
-
-                                // Fetch today's data for panel if needed
-                                let today_metrics = if panel_metrics.is_empty() {
-                                    None
-                                } else {
-                                    eprintln!("[Async] Fetching today's usage for panel");
-                                    reader.get_usage_today().ok()
-                                };
-
-                                // Fetch this month's data for cache if needed
-                                let month_metrics = {
-                                    eprintln!("[Async] Fetching this month's usage for cache");
-                                    reader.get_usage_month().ok()
-                                };
-
-                                (metrics, today_metrics, month_metrics)
-                            }
-                            DisplayMode::AllTime => {
-                                eprintln!("[Async] Fetching all-time usage (using spawn_blocking)");
-                                // Move the reader into the blocking task to avoid blocking the async runtime
-                                let metrics =
-                                    tokio::task::spawn_blocking(move || reader.get_usage())
-                                        .await
-                                        .map_err(|e| format!("Blocking task join error: {e}"))?
-                                        .map_err(|e| {
-                                            eprintln!("[Async] Error reading metrics: {e}");
-                                            format!("Failed to read OpenCode usage: {e}")
-                                        })?;
-
-                                // For AllTime, we don't need additional metrics since we have everything
-                                (metrics, None, None)
-                            }
-                        };
-
-                        Ok((main_metrics, today_metrics, month_metrics))
+                        if display_mode == DisplayMode::AllTime {
+                            eprintln!("[Async] Fetching all-time usage (using spawn_blocking)");
+                            fetch_blocking_with_deadline(
+                                source,
+                                display_mode,
+                                needs_today,
+                                deadline,
+                            )
+                            .await
+                        } else {
+                            fetch_counting_skipped(source.as_mut(), display_mode, needs_today)
+                        }
                     },
-                    move |result| {
+                    move |(result, skipped)| {
                         cosmic::Action::App(Message::MetricsFetched(
                             current_generation,
                             Box::new(result),
+                            skipped,
                         ))
                     },
                 )
             }
-            Message::MetricsFetched(generation, boxed_result) => {
+            Message::MetricsFetched(generation, boxed_result, skipped) => {
                 // Ignore outdated responses from previous fetch requests
                 if generation < self.fetch_generation {
                     eprintln!(
@@ -263,23 +689,44 @@ impl OpenCodeMonitorApplet {
                 }
 
                 eprintln!("[MetricsFetched] Processing response (gen: {generation})");
+                self.state.skipped_permission_denied = skipped;
 
                 match *boxed_result {
                     Ok((usage, today_opt, month_opt)) => {
                         eprintln!("[MetricsFetched] Received successful metrics data");
 
-                        // Automatically save daily snapshot to database
-                        // This runs once per day and uses INSERT OR REPLACE to prevent duplicates.
+                        if let Some(ref socket) = self.metrics_socket {
+                            socket.publish(&usage);
+                        }
+
+                        // Save today's usage as today's snapshot on every fetch.
+                        // INSERT OR REPLACE keeps a single, up-to-date row per day.
                         // Errors are logged but don't prevent the UI from updating.
                         if let Some(ref collector) = self.data_collector {
-                            match collector.collect_and_save(&usage) {
-                                Ok(true) => {
+                            let saved = today_opt
+                                .as_ref()
+                                .map(|today| collector.collect_and_save(today));
+                            self.history_busy =
+                                matches!(saved, Some(Err(CollectorError::DatabaseBusy)));
+                            match saved {
+                                None => {
+                                    eprintln!("[MetricsFetched] No usage today, skipping snapshot");
+                                }
+                                Some(Ok(true)) => {
                                     eprintln!("[MetricsFetched] Snapshot saved successfully");
+                                    self.check_milestones();
                                 }
-                                Ok(false) => {
-                                    eprintln!("[MetricsFetched] Snapshot already saved today");
+                                Some(Ok(false)) => {
+                                    eprintln!("[MetricsFetched] Today's snapshot updated");
+                                    self.check_milestones();
                                 }
-                                Err(e) => {
+                                Some(Err(CollectorError::DatabaseBusy)) => {
+                                    // Not saved yet, so the next fetch tries again
+                                    eprintln!(
+                                        "[MetricsFetched] Database busy, will retry snapshot"
+                                    );
+                                }
+                                Some(Err(e)) => {
                                     eprintln!("[MetricsFetched] Failed to save snapshot: {e}");
                                     // Continue despite error - don't crash the applet
                                 }
@@ -289,6 +736,7 @@ impl OpenCodeMonitorApplet {
                                 "[MetricsFetched] Data collector not available, skipping snapshot"
                             );
                         }
+                        self.refresh_pinned_usage();
 
                         // If we're in Month mode, the main usage is the month data - cache it
                         if self.state.display_mode == DisplayMode::Month {
@@ -299,7 +747,28 @@ impl OpenCodeMonitorApplet {
                             self.state.update_month_usage(usage.clone());
                         }
 
+                        // Cache whether reasoning tokens were ever recorded (for Auto visibility)
+                        let is_all_time = self.state.display_mode == DisplayMode::AllTime;
+                        self.state.record_reasoning_presence(&usage, is_all_time);
+                        if is_all_time {
+                            self.state.record_all_time_total(&usage);
+                        }
+                        for period in today_opt.iter().chain(month_opt.iter()) {
+                            self.state.record_reasoning_presence(period, false);
+                        }
+                        self.record_stored_reasoning_presence();
+
                         self.state.update_success(usage);
+                        let clear_flash = if self.state.show_refreshed_flash() {
+                            Task::perform(
+                                time::sleep(Duration::from_secs(u64::from(
+                                    REFRESHED_FLASH_SECONDS,
+                                ))),
+                                |()| cosmic::Action::App(Message::ClearRefreshedFlash),
+                            )
+                        } else {
+                            Task::none()
+                        };
 
                         // Update today's usage if provided
                         if let Some(today) = today_opt {
@@ -319,32 +788,63 @@ impl OpenCodeMonitorApplet {
                             self.state.update_month_usage(month);
                         }
 
-                        Task::none()
+                        clear_flash
                     }
                     Err(error) => {
                         eprintln!("[MetricsFetched] Received error: {error}");
-                        self.state.update_error(error);
+                        self.state.record_fetch_failure(error);
                         Task::none()
                     }
                 }
             }
+            Message::RefreshNow => {
+                self.state.begin_manual_refresh();
+                self.handle_message(Message::FetchMetrics)
+            }
+            Message::ClearRefreshedFlash => {
+                self.state.clear_expired_refreshed_flash(chrono::Utc::now());
+                Task::none()
+            }
             Message::ThemeChanged | Message::UpdateTooltip | Message::None => Task::none(),
-            Message::ConfigChanged(new_config) => {
+            Message::ConfigChanged(mut new_config) => {
                 eprintln!("[ConfigChanged] Received config update from COSMIC watch_config");
 
+                // The namespace belongs to this process, it is never read from disk
+                new_config
+                    .data_namespace
+                    .clone_from(&self.state.config.data_namespace);
+
                 // Check if panel_metrics is changing (for cache invalidation)
                 let panel_metrics_changed =
                     self.state.config.panel_metrics != new_config.panel_metrics;
+                let history_changed =
+                    self.state.config.keeps_history() != new_config.keeps_history();
+                let socket_changed = self.state.config.expose_socket != new_config.expose_socket
+                    || self.state.config.socket_path != new_config.socket_path;
+                let reader_changed = reader_settings_changed(&self.state.config, &new_config);
 
                 // Update the in-memory config with the new values from disk
                 // This ensures all instances stay in sync when any instance saves config
                 self.state.config = new_config;
+                self.state.sync_refresh_interval();
 
                 // Update the refresh interval watch channel to apply the new interval
                 let _ = self
                     .refresh_interval_tx
                     .send(self.state.config.refresh_interval_seconds);
 
+                // Open or drop the history database to follow the new setting
+                if history_changed {
+                    self.data_collector = Self::init_data_collector(&self.state.config);
+                }
+                if socket_changed {
+                    self.restart_metrics_socket();
+                }
+                // Read from the new storage path (or with the new options) from the next fetch on
+                if reader_changed {
+                    self.rebuild_source();
+                }
+
                 // Invalidate today_usage cache if panel_metrics changed
                 // This ensures we fetch fresh data when the panel display configuration changes
                 if panel_metrics_changed {
@@ -354,14 +854,44 @@ impl OpenCodeMonitorApplet {
                     self.state.clear_today_usage();
                 }
 
-                // If panel_metrics is now empty, clear the cache
-                if self.state.config.panel_metrics.is_empty() {
+                // If the panel no longer shows today's figures, clear the cache
+                if !self.state.config.needs_today_usage() {
                     self.state.clear_today_usage();
                 }
 
                 // Trigger a refresh to update the display with the new settings
                 Task::done(cosmic::Action::App(Message::FetchMetrics))
             }
+            Message::DetectStoragePath => {
+                self.onboarding_status = Some(match detect_opencode_storage() {
+                    Some(path) => {
+                        let found = format!("Found OpenCode usage data in {}", path.display());
+                        let changed = self.state.config.storage_path.as_ref() != Some(&path)
+                            || self.state.config.manifest_mode;
+                        self.state.config.storage_path = Some(path);
+                        // The detected directory holds part files, not a manifest
+                        self.state.config.manifest_mode = false;
+                        if let Err(err) = self.state.config.save() {
+                            eprintln!("Warning: Failed to save storage_path to config: {err}");
+                        }
+                        if changed {
+                            self.rebuild_source();
+                        }
+                        found
+                    }
+                    None => "OpenCode usage data not found. Use OpenCode once, or set storage_path in the config.".to_string(),
+                });
+                Task::none()
+            }
+            Message::CompleteOnboarding => {
+                self.show_onboarding = false;
+                self.onboarding_status = None;
+                self.state.config.onboarding_completed = true;
+                if let Err(err) = self.state.config.save() {
+                    eprintln!("Warning: Failed to save onboarding_completed to config: {err}");
+                }
+                Task::none()
+            }
             Message::OpenSettings => {
                 // Use the current in-memory config (no reload needed)
                 // Multi-instance sync is handled by COSMIC's watch_config subscription,
@@ -371,7 +901,37 @@ impl OpenCodeMonitorApplet {
                 self.temp_refresh_interval = self.state.config.refresh_interval_seconds;
                 self.temp_refresh_interval_str = self.temp_refresh_interval.to_string();
                 self.temp_panel_metrics = self.state.config.panel_metrics.clone();
+                self.temp_panel_metric_order = self.state.config.panel_metric_order.clone();
+                self.dragged_panel_metric = None;
                 self.temp_use_raw_token_display = self.state.config.use_raw_token_display;
+                self.temp_use_raw_cost_display = self.state.config.use_raw_cost_display;
+                self.temp_panel_only = self.state.config.panel_only;
+                self.temp_flag_usage_decrease = self.state.config.flag_usage_decrease;
+                self.temp_compact_ui = self.state.config.compact_ui;
+                self.temp_expose_socket = self.state.config.expose_socket;
+                self.temp_panel_icon_only = self.state.config.panel_icon_only;
+                self.temp_compact_interactions = self.state.config.compact_interactions;
+                self.temp_fold_cache_into_io = self.state.config.fold_cache_into_io;
+                self.temp_history_enabled = self.state.config.history_enabled;
+                self.temp_monthly_budget_str = self
+                    .state
+                    .config
+                    .monthly_budget
+                    .map(|b| b.to_string())
+                    .unwrap_or_default();
+                self.temp_budget_warning_color_str = self
+                    .state
+                    .config
+                    .budget_warning_color
+                    .map(format_hex_color)
+                    .unwrap_or_default();
+                self.temp_budget_warning_icon = self.state.config.budget_warning_icon;
+                self.temp_fetch_timeout_str = self
+                    .state
+                    .config
+                    .fetch_timeout_seconds
+                    .map(|s| s.to_string())
+                    .unwrap_or_default();
                 self.config_error = None;
                 self.config_warning = None;
                 Task::none()
@@ -396,21 +956,83 @@ impl OpenCodeMonitorApplet {
                 Task::none()
             }
             Message::ResetPanelMetricsToDefaults => {
-                self.temp_panel_metrics = vec![
-                    PanelMetric::Cost,
-                    PanelMetric::Interactions,
-                    PanelMetric::InputTokens,
-                    PanelMetric::OutputTokens,
-                    PanelMetric::ReasoningTokens,
-                ];
+                self.temp_panel_metrics = PanelMetric::DEFAULT_ORDER.to_vec();
+                Task::none()
+            }
+            Message::ReorderPanelMetric(from, to) => {
+                self.dragged_panel_metric = None;
+                reorder_panel_metrics(&mut self.temp_panel_metric_order, from, to);
+                Task::none()
+            }
+            Message::StartPanelMetricDrag(index) => {
+                self.dragged_panel_metric = Some(index);
+                Task::none()
+            }
+            Message::ResetPanelMetricOrder => {
+                self.temp_panel_metric_order = PanelMetric::DEFAULT_ORDER.to_vec();
                 Task::none()
             }
             Message::ToggleRawTokenDisplay(enabled) => {
                 self.temp_use_raw_token_display = enabled;
                 Task::none()
             }
+            Message::ToggleHistoryEnabled(enabled) => {
+                self.temp_history_enabled = enabled;
+                Task::none()
+            }
+            Message::ToggleFoldCacheIntoIo(enabled) => {
+                self.temp_fold_cache_into_io = enabled;
+                Task::none()
+            }
+            Message::UpdateMonthlyBudget(value) => {
+                self.temp_monthly_budget_str = value;
+                Task::none()
+            }
+            Message::UpdateBudgetWarningColor(value) => {
+                self.temp_budget_warning_color_str = value;
+                Task::none()
+            }
+            Message::UpdateFetchTimeout(value) => {
+                self.temp_fetch_timeout_str = value;
+                Task::none()
+            }
+            Message::ToggleBudgetWarningIcon(enabled) => {
+                self.temp_budget_warning_icon = enabled;
+                Task::none()
+            }
+            Message::ToggleCompactInteractions(enabled) => {
+                self.temp_compact_interactions = enabled;
+                Task::none()
+            }
+            Message::TogglePanelIconOnly(enabled) => {
+                self.temp_panel_icon_only = enabled;
+                Task::none()
+            }
+            Message::ToggleExposeSocket(enabled) => {
+                self.temp_expose_socket = enabled;
+                Task::none()
+            }
+            Message::ToggleCompactUi(enabled) => {
+                self.temp_compact_ui = enabled;
+                Task::none()
+            }
+            Message::ToggleFlagUsageDecrease(enabled) => {
+                self.temp_flag_usage_decrease = enabled;
+                Task::none()
+            }
+            Message::TogglePanelOnly(enabled) => {
+                self.temp_panel_only = enabled;
+                Task::none()
+            }
+            Message::ToggleRawCostDisplay(enabled) => {
+                self.temp_use_raw_cost_display = enabled;
+                Task::none()
+            }
             Message::SelectDisplayMode(mode) => {
                 eprintln!("[SelectDisplayMode] Switching to {mode:?}");
+                if self.state.display_mode != mode {
+                    self.state.reset_refresh_baseline();
+                }
                 self.state.display_mode = mode;
 
                 // Update config and persist to disk
@@ -439,14 +1061,56 @@ impl OpenCodeMonitorApplet {
                     }
                 }
 
+                let budget_settings =
+                    parse_monthly_budget(&self.temp_monthly_budget_str).and_then(|budget| {
+                        parse_warning_color(&self.temp_budget_warning_color_str)
+                            .map(|color| (budget, color))
+                    });
+                let (monthly_budget, budget_warning_color) = match budget_settings {
+                    Ok(settings) => settings,
+                    Err(err) => {
+                        self.config_error = Some(err);
+                        self.config_warning = None;
+                        return Task::none();
+                    }
+                };
+                let fetch_timeout_seconds = match parse_fetch_timeout(&self.temp_fetch_timeout_str)
+                {
+                    Ok(timeout) => timeout,
+                    Err(err) => {
+                        self.config_error = Some(err);
+                        self.config_warning = None;
+                        return Task::none();
+                    }
+                };
+
                 // Check if panel_metrics is changing (for cache invalidation)
                 let panel_metrics_changed =
                     self.state.config.panel_metrics != self.temp_panel_metrics;
+                let history_changed = self.state.config.history_enabled
+                    != self.temp_history_enabled
+                    || self.state.config.panel_only != self.temp_panel_only;
+                let socket_changed = self.state.config.expose_socket != self.temp_expose_socket;
 
                 // Update config in state
                 self.state.config.refresh_interval_seconds = self.temp_refresh_interval;
+                self.state.sync_refresh_interval();
                 self.state.config.panel_metrics = self.temp_panel_metrics.clone();
+                self.state.config.panel_metric_order = self.temp_panel_metric_order.clone();
                 self.state.config.use_raw_token_display = self.temp_use_raw_token_display;
+                self.state.config.use_raw_cost_display = self.temp_use_raw_cost_display;
+                self.state.config.panel_only = self.temp_panel_only;
+                self.state.config.flag_usage_decrease = self.temp_flag_usage_decrease;
+                self.state.config.compact_ui = self.temp_compact_ui;
+                self.state.config.expose_socket = self.temp_expose_socket;
+                self.state.config.panel_icon_only = self.temp_panel_icon_only;
+                self.state.config.compact_interactions = self.temp_compact_interactions;
+                self.state.config.fold_cache_into_io = self.temp_fold_cache_into_io;
+                self.state.config.history_enabled = self.temp_history_enabled;
+                self.state.config.monthly_budget = monthly_budget;
+                self.state.config.budget_warning_color = budget_warning_color;
+                self.state.config.budget_warning_icon = self.temp_budget_warning_icon;
+                self.state.config.fetch_timeout_seconds = fetch_timeout_seconds;
 
                 // Notify subscription of refresh interval change
                 let _ = self.refresh_interval_tx.send(self.temp_refresh_interval);
@@ -463,6 +1127,13 @@ impl OpenCodeMonitorApplet {
                 self.settings_dialog_open = false;
                 self.popup = None;
 
+                if history_changed {
+                    self.data_collector = Self::init_data_collector(&self.state.config);
+                }
+                if socket_changed {
+                    self.restart_metrics_socket();
+                }
+
                 // Invalidate today_usage cache if panel_metrics changed
                 // This ensures we fetch fresh data when the panel display configuration changes
                 if panel_metrics_changed {
@@ -470,9 +1141,9 @@ impl OpenCodeMonitorApplet {
                     self.state.clear_today_usage();
                 }
 
-                // Clear today's usage cache if the panel metrics are now empty
+                // Clear today's usage cache if the panel no longer shows today's figures
                 // and don't trigger a fetch (no data to display)
-                if self.temp_panel_metrics.is_empty() {
+                if !self.state.config.needs_today_usage() {
                     self.state.clear_today_usage();
                     Task::none()
                 } else {
@@ -514,13 +1185,56 @@ impl OpenCodeMonitorApplet {
                             .max_height(600.0);
 
                         eprintln!("DEBUG: Created popup settings, calling get_popup");
-                        get_popup(popup_settings)
+                        self.popup_last_interaction = Instant::now();
+                        let show_primary = self.show_primary_period();
+                        let open = match popup_auto_close_timeout(
+                            self.state.config.popup_auto_close_seconds,
+                        ) {
+                            Some(timeout) => Task::batch([
+                                get_popup(popup_settings),
+                                Self::refresh_countdown_tick(new_id),
+                                Self::popup_auto_close_after(new_id, timeout),
+                            ]),
+                            None => Task::batch([
+                                get_popup(popup_settings),
+                                Self::refresh_countdown_tick(new_id),
+                            ]),
+                        };
+                        Task::batch([open, show_primary])
                     } else {
                         eprintln!("DEBUG: No main window ID - returning Task::none()");
                         Task::none()
                     }
                 }
             }
+            Message::PopupAutoClose(id) => {
+                // Ignore timers from a popup that was already closed
+                if self.popup != Some(id) {
+                    return Task::none();
+                }
+                let Some(timeout) =
+                    popup_auto_close_timeout(self.state.config.popup_auto_close_seconds)
+                else {
+                    return Task::none();
+                };
+
+                // Interactions push the deadline back, so wait for the time left
+                match popup_auto_close_remaining(timeout, self.popup_last_interaction.elapsed()) {
+                    Some(remaining) => Self::popup_auto_close_after(id, remaining),
+                    None => {
+                        eprintln!("[PopupAutoClose] Closing popup after {timeout:?} idle");
+                        self.handle_message(Message::TogglePopup)
+                    }
+                }
+            }
+            Message::RefreshCountdownTick(id) => {
+                // Stop ticking once the popup is closed; each tick redraws the countdown
+                if self.popup == Some(id) {
+                    Self::refresh_countdown_tick(id)
+                } else {
+                    Task::none()
+                }
+            }
             Message::OpenViewer => {
                 // Spawn the viewer application as a separate process
                 match std::process::Command::new("cosmic-applet-opencode-usage-viewer").spawn() {
@@ -551,6 +1265,16 @@ impl OpenCodeMonitorApplet {
                     }
                 }
             }
+            Message::ReloadConfigFallback => {
+                // Catches changes from other instances if the watch_config stream has died
+                match changed_config(&self.state.config, AppConfig::load()) {
+                    Some(config) => {
+                        eprintln!("[ConfigFallback] Config on disk differs, applying it");
+                        Task::done(cosmic::Action::App(Message::ConfigChanged(config)))
+                    }
+                    None => Task::none(),
+                }
+            }
             Message::Tick => {
                 // Check if we need to refresh based on last update time
                 if self.state.needs_refresh() {
@@ -563,8 +1287,132 @@ impl OpenCodeMonitorApplet {
         }
     }
 
+    /// Replace the usage source with one built from the current config
+    ///
+    /// Cached period usage and the refresh baseline came from the old reader, so they
+    /// are dropped as well. If the new reader cannot be created (e.g. the storage path
+    /// does not exist), the previous one is kept.
+    fn rebuild_source(&mut self) {
+        match source_for_config(&self.state.config) {
+            Ok(source) => {
+                eprintln!(
+                    "[Reader] Reading usage from {}",
+                    source.storage_path().display()
+                );
+                self.source = source;
+            }
+            Err(e) => {
+                eprintln!("[Reader] Failed to create reader: {e}, keeping the previous one");
+                return;
+            }
+        }
+
+        self.state.clear_today_usage();
+        self.state.clear_month_usage();
+        self.state.clear_last_month_usage();
+        self.state.reset_refresh_baseline();
+        self.state.previous_all_time = None;
+        self.state.usage_decreased = false;
+    }
+
+    /// Reads month and all-time totals from the history database when configured.
+    fn with_metric_source(&self, files: Box<dyn UsageSource>) -> Box<dyn UsageSource> {
+        match (self.state.config.metric_source, &self.data_collector) {
+            (MetricSource::Database, Some(collector)) => Box::new(SnapshotUsageSource::new(
+                files,
+                collector.repository().clone(),
+            )),
+            _ => files,
+        }
+    }
+
+    /// Reload the all-time totals behind the pinned panel note from the database
+    fn refresh_pinned_usage(&mut self) {
+        if self.state.config.pinned_panel_note.is_none() {
+            self.pinned_usage = None;
+            return;
+        }
+        let Some(ref collector) = self.data_collector else {
+            return;
+        };
+
+        match collector.all_time_usage() {
+            Ok(usage) => self.pinned_usage = Some(usage),
+            Err(e) => eprintln!("[PinnedNote] Failed to load all-time usage: {e}"),
+        }
+    }
+
+    /// Learn from the snapshot history whether reasoning tokens were ever recorded
+    ///
+    /// Outside all-time mode the fetched totals can't rule reasoning tokens out, but the
+    /// stored history can prove them. Only a nonzero sum is recorded, as the history may
+    /// not reach back to the first usage.
+    fn record_stored_reasoning_presence(&mut self) {
+        if self.state.has_reasoning_history == Some(true) {
+            return;
+        }
+        let Some(ref collector) = self.data_collector else {
+            return;
+        };
+
+        match collector.all_time_usage() {
+            Ok(usage) => self.state.record_reasoning_presence(&usage, false),
+            Err(e) => eprintln!("[MetricsFetched] Failed to load stored reasoning tokens: {e}"),
+        }
+    }
+
+    /// Panel text with the pinned all-time note appended, when configured and loaded
+    fn with_pinned_note(
+        &self,
+        panel_text: String,
+        options: crate::ui::formatters::PanelFormatOptions,
+    ) -> String {
+        use crate::ui::formatters::append_pinned_note;
+
+        match (self.state.config.pinned_panel_note, &self.pinned_usage) {
+            (Some(metric), Some(all_time)) => {
+                append_pinned_note(&panel_text, all_time, metric, options)
+            }
+            _ => panel_text,
+        }
+    }
+
+    /// Notify once for each newly crossed lifetime interaction milestone
+    fn check_milestones(&mut self) {
+        let Some(ref collector) = self.data_collector else {
+            return;
+        };
+
+        let crossed = match collector.check_milestones(&self.state.config.milestones_reached) {
+            Ok(crossed) => crossed,
+            Err(e) => {
+                eprintln!("[Milestones] Failed to check milestones: {e}");
+                return;
+            }
+        };
+        if crossed.is_empty() {
+            return;
+        }
+
+        for milestone in &crossed {
+            eprintln!("[Milestones] Reached {milestone} lifetime interactions");
+            send_milestone_notification(*milestone);
+        }
+
+        // Record the milestones so they only fire once, without touching other settings
+        self.state.config.milestones_reached.extend(crossed);
+        self.state.config.milestones_reached.sort_unstable();
+        if let Err(err) = self.state.config.save_milestones_reached() {
+            eprintln!("Warning: Failed to save milestones_reached to config: {err}");
+        }
+    }
+
     /// Get the icon name based on current state
     fn get_state_icon(&self) -> &'static str {
+        if self.state.config.budget_warning_icon && self.state.is_over_budget() {
+            return "dialog-warning-symbolic";
+        }
+
         match &self.state.panel_state {
             PanelState::Loading | PanelState::LoadingWithData(_) => "content-loading-symbolic",
             PanelState::Error(_) => "dialog-error-symbolic",
@@ -572,57 +1420,134 @@ impl OpenCodeMonitorApplet {
         }
     }
 
+    /// Switch to the primary period (if configured) and fetch its data
+    ///
+    /// Unlike selecting a tab, this does not change the persisted last-used mode.
+    fn show_primary_period(&mut self) -> Task<Message> {
+        match self.state.config.primary_period {
+            Some(primary) if primary != self.state.display_mode => {
+                self.state.reset_refresh_baseline();
+                self.state.display_mode = primary;
+                Task::done(cosmic::Action::App(Message::FetchMetrics))
+            }
+            _ => Task::none(),
+        }
+    }
+
+    /// Scale a popup text size, padding or spacing for the configured layout
+    fn scaled(&self, size: u16) -> f32 {
+        let scale = if self.state.config.compact_ui {
+            COMPACT_UI_SCALE
+        } else {
+            1.0
+        };
+        f32::from(size) * clamp_ui_scale(scale)
+    }
+
+    /// Build the first-run introduction shown in place of the metrics popup
+    fn onboarding_view(&self) -> Element<'_, Message> {
+        column()
+            .push(text("Welcome to OpenCode Monitor").size(self.scaled(20)))
+            .push(
+                text(
+                    "This applet reads the usage files OpenCode writes and shows your \
+                     token usage and costs in the panel.",
+                )
+                .size(self.scaled(14)),
+            )
+            .push(
+                text(
+                    "A daily snapshot is kept in a local history database so the \
+                     viewer can chart trends. You can turn this off in Settings.",
+                )
+                .size(self.scaled(14)),
+            )
+            .push_maybe(
+                self.onboarding_status
+                    .as_deref()
+                    .map(|status| text(status).size(self.scaled(12))),
+            )
+            .push(
+                row()
+                    .push(
+                        button::standard("Detect storage path")
+                            .on_press(Message::DetectStoragePath),
+                    )
+                    .push(button::standard("Settings").on_press(Message::OpenSettings))
+                    .push(button::suggested("Get started").on_press(Message::CompleteOnboarding))
+                    .spacing(self.scaled(8)),
+            )
+            .spacing(self.scaled(10))
+            .padding(self.scaled(20))
+            .into()
+    }
+
+    /// Whether the popup offers the "View Stats" button (not in panel-only mode)
+    fn shows_view_stats(&self) -> bool {
+        !self.state.config.panel_only
+    }
+
+    /// "View Stats" button for the popup, enabled when the viewer is installed
+    ///
+    /// `None` in panel-only mode, where there is no history to view.
+    fn view_stats_button(&self, viewer_available: bool) -> Option<button::Button<'_, Message>> {
+        self.shows_view_stats().then(|| {
+            if viewer_available {
+                button::standard("View Stats").on_press(Message::OpenViewer)
+            } else {
+                button::standard("View Stats")
+            }
+        })
+    }
+
     /// Build the metrics popup view
     #[allow(clippy::too_many_lines)] // UI function with many widget definitions
     fn metrics_popup_view(&self) -> Element<'_, Message> {
-        use crate::ui::formatters::{format_cost, format_number, format_tooltip};
+        use crate::ui::formatters::{
+            format_cache_ratio, format_number, format_popup_cost, format_refresh_countdown,
+            format_refresh_delta, format_tooltip, usage_for_display,
+        };
 
         let viewer_available = is_viewer_available();
 
         let main_content = match &self.state.panel_state {
             PanelState::Loading => {
-                let view_stats_btn = if viewer_available {
-                    button::standard("View Stats").on_press(Message::OpenViewer)
-                } else {
-                    button::standard("View Stats")
-                };
+                let view_stats_btn = self.view_stats_button(viewer_available);
 
                 column()
-                    .push(text("Loading...").size(16))
-                    .push(text("").size(8))
+                    .push(text("Loading...").size(self.scaled(16)))
+                    .push(text("").size(self.scaled(8)))
                     .push(
                         row()
-                            .push(view_stats_btn)
+                            .push_maybe(view_stats_btn)
                             .push(button::standard("Settings").on_press(Message::OpenSettings))
-                            .spacing(8),
+                            .spacing(self.scaled(8)),
                     )
-                    .spacing(10)
-                    .padding(20)
+                    .spacing(self.scaled(10))
+                    .padding(self.scaled(20))
             }
             PanelState::Error(err) => {
-                let view_stats_btn = if viewer_available {
-                    button::standard("View Stats").on_press(Message::OpenViewer)
-                } else {
-                    button::standard("View Stats")
-                };
+                let view_stats_btn = self.view_stats_button(viewer_available);
 
                 column()
-                    .push(text("Error").size(20))
-                    .push(text(err).size(14))
-                    .push(text("").size(8))
+                    .push(text("Error").size(self.scaled(20)))
+                    .push(text(err).size(self.scaled(14)))
+                    .push(text("").size(self.scaled(8)))
                     .push(button::standard("Retry").on_press(Message::FetchMetrics))
                     .push(
                         row()
-                            .push(view_stats_btn)
+                            .push_maybe(view_stats_btn)
                             .push(button::standard("Settings").on_press(Message::OpenSettings))
-                            .spacing(8),
+                            .spacing(self.scaled(8)),
                     )
-                    .spacing(10)
-                    .padding(20)
+                    .spacing(self.scaled(10))
+                    .padding(self.scaled(20))
             }
             PanelState::Success(usage)
             | PanelState::Stale(usage)
             | PanelState::LoadingWithData(usage) => {
+                let usage = usage_for_display(usage, self.state.config.fold_cache_into_io);
+
                 // Determine title based on current mode
                 let title = match self.state.display_mode {
                     DisplayMode::Today => "Today's Usage",
@@ -725,77 +1650,266 @@ impl OpenCodeMonitorApplet {
                             .width(Length::FillPortion(1))
                             .height(Length::Fill),
                     )
-                    .spacing(8)
+                    .spacing(self.scaled(8))
                     .width(Length::Fill);
 
-                let second_row_tabs = row().push(alltime_button).spacing(8);
+                let second_row_tabs = row().push(alltime_button).spacing(self.scaled(8));
 
                 column()
-                    .push(text(title).size(20))
-                    .push(text("").size(4))
+                    .push(text(title).size(self.scaled(20)))
+                    .push(text("").size(self.scaled(4)))
                     .push(first_row_tabs)
-                    .push(text("").size(4))
+                    .push(text("").size(self.scaled(4)))
                     .push(second_row_tabs)
-                    .push(text("").size(8))
-                    .push(
+                    .push(text("").size(self.scaled(8)))
+                    .push({
+                        // The primary period's headline figure is larger and bold
+                        let cost = text(format_popup_cost(
+                            usage.total_cost,
+                            self.state.config.use_raw_cost_display,
+                        ));
+                        let cost = if self.state.is_primary_period() {
+                            cost.size(self.scaled(20)).font(cosmic::font::bold())
+                        } else {
+                            cost.size(self.scaled(14))
+                        };
                         row()
-                            .push(text("Total Cost: ").size(14))
-                            .push(text(format_cost(usage.total_cost)).size(14))
-                            .spacing(5),
-                    )
+                            .push(text("Total Cost: ").size(self.scaled(14)))
+                            .push(cost)
+                            .spacing(self.scaled(5))
+                            .align_y(Alignment::Center)
+                    })
+                    // Only shown when the storage attributes part of the cost to the cache
+                    .push_maybe(usage.has_cache_cost().then(|| {
+                        row()
+                            .push(text("Cache / Other Cost: ").size(self.scaled(14)))
+                            .push(
+                                text(format!(
+                                    "{} / {}",
+                                    format_popup_cost(
+                                        usage.cache_cost(),
+                                        self.state.config.use_raw_cost_display
+                                    ),
+                                    format_popup_cost(
+                                        usage.non_cache_cost(),
+                                        self.state.config.use_raw_cost_display
+                                    )
+                                ))
+                                .size(self.scaled(14)),
+                            )
+                            .spacing(self.scaled(5))
+                    }))
                     .push(
                         row()
-                            .push(text("Interactions: ").size(14))
-                            .push(text(format_number(usage.interaction_count as u64)).size(14))
-                            .spacing(5),
+                            .push(text("Interactions: ").size(self.scaled(14)))
+                            .push(
+                                text(format_number(usage.interaction_count as u64))
+                                    .size(self.scaled(14)),
+                            )
+                            .spacing(self.scaled(5)),
                     )
                     .push(
                         row()
-                            .push(text("Input Tokens: ").size(14))
-                            .push(text(format_number(usage.total_input_tokens)).size(14))
-                            .spacing(5),
+                            .push(text("Input Tokens: ").size(self.scaled(14)))
+                            .push(
+                                text(format_number(usage.total_input_tokens)).size(self.scaled(14)),
+                            )
+                            .spacing(self.scaled(5)),
                     )
                     .push(
                         row()
-                            .push(text("Output Tokens: ").size(14))
-                            .push(text(format_number(usage.total_output_tokens)).size(14))
-                            .spacing(5),
+                            .push(text("Output Tokens: ").size(self.scaled(14)))
+                            .push(
+                                text(format_number(usage.total_output_tokens))
+                                    .size(self.scaled(14)),
+                            )
+                            .spacing(self.scaled(5)),
                     )
+                    .push_maybe(self.state.show_reasoning().then(|| {
+                        row()
+                            .push(text("Reasoning Tokens: ").size(self.scaled(14)))
+                            .push(
+                                text(format_number(usage.total_reasoning_tokens))
+                                    .size(self.scaled(14)),
+                            )
+                            .spacing(self.scaled(5))
+                    }))
                     .push(
                         row()
-                            .push(text("Reasoning Tokens: ").size(14))
-                            .push(text(format_number(usage.total_reasoning_tokens)).size(14))
-                            .spacing(5),
+                            .push(text("Cache R/W: ").size(self.scaled(14)))
+                            .push(text(format_cache_ratio(&usage)).size(self.scaled(14)))
+                            .spacing(self.scaled(5)),
+                    )
+                    .push(text("").size(self.scaled(8)))
+                    .push(text(format_tooltip(self.state.last_update())).size(self.scaled(12)))
+                    // Frozen while a fetch is running; it restarts from the new update
+                    .push_maybe(
+                        self.state
+                            .seconds_until_refresh(chrono::Utc::now())
+                            .filter(|_| !self.state.panel_state.is_loading())
+                            .map(|seconds| {
+                                text(format_refresh_countdown(seconds)).size(self.scaled(11))
+                            }),
+                    )
+                    .push_maybe(
+                        self.state
+                            .refresh_delta
+                            .as_ref()
+                            .and_then(format_refresh_delta)
+                            .map(|delta| text(delta).size(self.scaled(11))),
+                    )
+                    .push_maybe(
+                        self.data_collector
+                            .as_ref()
+                            .filter(|collector| collector.is_in_memory())
+                            .map(|_| {
+                                text("History is not being saved: the data folder is read-only")
+                                    .size(self.scaled(11))
+                            }),
+                    )
+                    .push_maybe(
+                        self.state.show_usage_decrease().then(|| {
+                            text("Usage decreased — files removed?").size(self.scaled(11))
+                        }),
+                    )
+                    .push_maybe(
+                        skipped_entries_warning(self.state.skipped_permission_denied)
+                            .map(|warning| text(warning).size(self.scaled(11))),
                     )
-                    .push(text("").size(8))
-                    .push(text(format_tooltip(self.state.last_update)).size(12))
-                    .push(text("").size(8))
+                    .push_maybe(self.history_busy.then(|| {
+                        text(CollectorError::DatabaseBusy.to_string()).size(self.scaled(11))
+                    }))
+                    .push(text("").size(self.scaled(8)))
                     .push({
-                        let view_stats_btn = if viewer_available {
-                            button::standard("View Stats").on_press(Message::OpenViewer)
+                        let view_stats_btn = self.view_stats_button(viewer_available);
+                        let refresh_btn = if self.state.show_refreshed_flash() {
+                            button::standard("✓ Refreshed")
+                        } else if is_loading {
+                            button::standard("Refresh")
                         } else {
-                            button::standard("View Stats")
+                            button::standard("Refresh").on_press(Message::RefreshNow)
                         };
 
                         row()
-                            .push(view_stats_btn)
+                            .push(refresh_btn)
+                            .push_maybe(view_stats_btn)
                             .push(button::standard("Settings").on_press(Message::OpenSettings))
-                            .spacing(8)
+                            .spacing(self.scaled(8))
                     })
-                    .spacing(10)
-                    .padding(20)
+                    .spacing(self.scaled(10))
+                    .padding(self.scaled(20))
             }
         };
 
         scrollable(main_content).into()
     }
 
+    /// Panel text as it would look with the unsaved settings
+    ///
+    /// Uses today's usage when available, otherwise sample figures.
+    fn panel_preview_text(&self) -> String {
+        use crate::ui::formatters::{
+            fit_panel_text, format_multiple_panel_metrics_in_order, format_primary_metric,
+            usage_for_display, PanelFormatOptions,
+        };
+
+        const ICON_ONLY: &str = "(icon only)";
+        let primary_metric = self.state.config.primary_metric;
+        if self.temp_panel_icon_only
+            || (self.temp_panel_metrics.is_empty() && primary_metric.is_none())
+        {
+            return ICON_ONLY.to_string();
+        }
+
+        let sample = UsageMetrics {
+            total_input_tokens: 12_400,
+            total_output_tokens: 3_100,
+            total_reasoning_tokens: 800,
+            total_cache_write_tokens: 1_500,
+            total_cache_read_tokens: 20_000,
+            total_cost: 1.23,
+            total_cache_cost: 0.0,
+            interaction_count: 14,
+            timestamp: std::time::SystemTime::now(),
+        };
+        let usage = self.state.today_usage.as_ref().unwrap_or(&sample);
+        let usage = usage_for_display(usage, self.temp_fold_cache_into_io);
+
+        let options = PanelFormatOptions {
+            use_raw: self.temp_use_raw_token_display,
+            compact_interactions: self.temp_compact_interactions,
+            ..PanelFormatOptions::from_config(&self.state.config)
+        };
+        let text = match primary_metric {
+            Some(primary) if self.temp_panel_metrics.is_empty() => {
+                format_primary_metric(&usage, primary, options)
+            }
+            _ => format_multiple_panel_metrics_in_order(
+                &usage,
+                &self.temp_panel_metrics,
+                &self.temp_panel_metric_order,
+                options,
+            ),
+        };
+        let text = self.with_pinned_note(text, options);
+        fit_panel_text(
+            &text,
+            self.state.config.panel_max_chars,
+            self.state.config.panel_min_chars,
+        )
+        .unwrap_or_else(|| ICON_ONLY.to_string())
+    }
+
+    /// Message for releasing a drag over the metric at `index`, if one is being dragged
+    fn panel_metric_drop(&self, index: usize) -> Option<Message> {
+        self.dragged_panel_metric
+            .map(|from| Message::ReorderPanelMetric(from, index))
+    }
+
+    /// Build the reorderable list of panel metrics for the settings dialog
+    ///
+    /// Rows are dragged by pressing on them and released over the target row; the
+    /// arrow buttons move a metric one step without a pointer.
+    fn panel_metric_order_list(&self) -> Element<'_, Message> {
+        let last = self.temp_panel_metric_order.len().saturating_sub(1);
+        let mut list = column().spacing(self.scaled(4));
+
+        for (index, metric) in self.temp_panel_metric_order.iter().enumerate() {
+            let up = button::standard("↑")
+                .on_press_maybe((index > 0).then(|| Message::ReorderPanelMetric(index, index - 1)));
+            let down = button::standard("↓").on_press_maybe(
+                (index < last).then(|| Message::ReorderPanelMetric(index, index + 1)),
+            );
+            let handle = if self.dragged_panel_metric == Some(index) {
+                "▶"
+            } else {
+                "⠿"
+            };
+
+            let metric_row = row()
+                .push(up)
+                .push(down)
+                .push(text(handle).size(self.scaled(12)))
+                .push(text(metric.label()).size(self.scaled(12)))
+                .spacing(self.scaled(8))
+                .align_y(Alignment::Center);
+            let mut draggable =
+                mouse_area(metric_row).on_press(Message::StartPanelMetricDrag(index));
+            if let Some(drop) = self.panel_metric_drop(index) {
+                draggable = draggable.on_release(drop);
+            }
+            list = list.push(draggable);
+        }
+
+        list.into()
+    }
+
     /// Build the settings dialog UI
     fn settings_view(&self) -> Element<'_, Message> {
         let mut content = column()
-            .push(text("OpenCode Monitor Settings").size(24))
-            .push(text("").size(8))
-            .push(text("Refresh Interval (seconds)").size(14))
+            .push(text("OpenCode Monitor Settings").size(self.scaled(24)))
+            .push(text("").size(self.scaled(8)))
+            .push(text("Refresh Interval (seconds)").size(self.scaled(14)))
             .push(
                 text_input("Enter refresh interval", &self.temp_refresh_interval_str).on_input(
                     |s| {
@@ -805,9 +1919,14 @@ impl OpenCodeMonitorApplet {
                     },
                 ),
             )
-            .push(text("").size(8))
-            .push(text("Display Options").size(14))
-            .push(text("Panel metrics to show next to icon:").size(12))
+            .push(text("All-time scan timeout (seconds, empty for none)").size(self.scaled(12)))
+            .push(
+                text_input("e.g. 60", &self.temp_fetch_timeout_str)
+                    .on_input(Message::UpdateFetchTimeout),
+            )
+            .push(text("").size(self.scaled(8)))
+            .push(text("Display Options").size(self.scaled(14)))
+            .push(text("Panel metrics to show next to icon:").size(self.scaled(12)))
             .push(
                 checkbox(
                     "Cost (e.g., $1.23)",
@@ -848,7 +1967,11 @@ impl OpenCodeMonitorApplet {
                 button::standard("Reset to Defaults")
                     .on_press(Message::ResetPanelMetricsToDefaults),
             )
-            .push(text("").size(8))
+            .push(text("Panel order:").size(self.scaled(12)))
+            .push(self.panel_metric_order_list())
+            .push(button::standard("Reset Order").on_press(Message::ResetPanelMetricOrder))
+            .push(text(format!("Preview: {}", self.panel_preview_text())).size(self.scaled(12)))
+            .push(text("").size(self.scaled(8)))
             .push(
                 checkbox(
                     "Use raw token values (no K/M suffixes)",
@@ -856,14 +1979,97 @@ impl OpenCodeMonitorApplet {
                 )
                 .on_toggle(Message::ToggleRawTokenDisplay),
             )
-            .spacing(10)
-            .padding(20);
+            .push(
+                checkbox(
+                    "Show full-precision cost in the popup (e.g., $1.2345)",
+                    self.temp_use_raw_cost_display,
+                )
+                .on_toggle(Message::ToggleRawCostDisplay),
+            )
+            .push(
+                checkbox(
+                    "Show compact interaction counts in the panel (e.g., 1.2kx)",
+                    self.temp_compact_interactions,
+                )
+                .on_toggle(Message::ToggleCompactInteractions),
+            )
+            .push(
+                checkbox(
+                    "Show only the icon in the panel (metrics stay in the popup)",
+                    self.temp_panel_icon_only,
+                )
+                .on_toggle(Message::TogglePanelIconOnly),
+            )
+            .push(
+                checkbox(
+                    "Count cache reads as input and cache writes as output",
+                    self.temp_fold_cache_into_io,
+                )
+                .on_toggle(Message::ToggleFoldCacheIntoIo),
+            )
+            .push(
+                checkbox(
+                    "Compact popup layout for small screens",
+                    self.temp_compact_ui,
+                )
+                .on_toggle(Message::ToggleCompactUi),
+            )
+            .push(
+                checkbox(
+                    "Flag all-time usage that decreases (e.g., usage files removed)",
+                    self.temp_flag_usage_decrease,
+                )
+                .on_toggle(Message::ToggleFlagUsageDecrease),
+            )
+            .push(text("").size(self.scaled(8)))
+            .push(text("History").size(self.scaled(14)))
+            .push(
+                checkbox(
+                    "Keep daily usage history (used by View Stats)",
+                    self.temp_history_enabled,
+                )
+                .on_toggle(Message::ToggleHistoryEnabled),
+            )
+            .push(
+                checkbox(
+                    "Panel only: live metrics without history, database or View Stats",
+                    self.temp_panel_only,
+                )
+                .on_toggle(Message::TogglePanelOnly),
+            )
+            .push(
+                checkbox(
+                    "Serve metrics as JSON on a local socket (for waybar, eww, ...)",
+                    self.temp_expose_socket,
+                )
+                .on_toggle(Message::ToggleExposeSocket),
+            )
+            .push(text("").size(self.scaled(8)))
+            .push(text("Monthly Budget ($, empty to disable)").size(self.scaled(14)))
+            .push(
+                text_input("e.g. 50", &self.temp_monthly_budget_str)
+                    .on_input(Message::UpdateMonthlyBudget),
+            )
+            .push(text("Warning color (hex, empty for theme red)").size(self.scaled(12)))
+            .push(
+                text_input("e.g. #e01b24", &self.temp_budget_warning_color_str)
+                    .on_input(Message::UpdateBudgetWarningColor),
+            )
+            .push(
+                checkbox(
+                    "Show a warning icon when over budget",
+                    self.temp_budget_warning_icon,
+                )
+                .on_toggle(Message::ToggleBudgetWarningIcon),
+            )
+            .spacing(self.scaled(10))
+            .padding(self.scaled(20));
 
         // Show error if present (red/critical style)
         if let Some(ref err) = self.config_error {
             content = content
-                .push(text("").size(8))
-                .push(text(format!("❌ Error: {err}")).size(14));
+                .push(text("").size(self.scaled(8)))
+                .push(text(format!("❌ Error: {err}")).size(self.scaled(14)));
         }
 
         // Show warning if present (yellow/info style)
@@ -874,16 +2080,16 @@ impl OpenCodeMonitorApplet {
                 }
             };
             content = content
-                .push(text("").size(8))
-                .push(text(warning_text).size(14));
+                .push(text("").size(self.scaled(8)))
+                .push(text(warning_text).size(self.scaled(14)));
         }
 
         // Add action buttons
-        content = content.push(text("").size(12)).push(
+        content = content.push(text("").size(self.scaled(12))).push(
             row()
                 .push(button::standard("Cancel").on_press(Message::CloseSettings))
                 .push(button::suggested("Save").on_press(Message::SaveConfig))
-                .spacing(12),
+                .spacing(self.scaled(12)),
         );
 
         scrollable(content).into()
@@ -891,20 +2097,53 @@ impl OpenCodeMonitorApplet {
 
     /// Create the panel button content layout
     fn panel_button_content(&self) -> Element<'_, Message> {
-        use crate::ui::formatters::format_multiple_panel_metrics;
+        use crate::ui::formatters::{
+            fit_panel_text, format_multiple_panel_metrics_in_order, format_primary_metric,
+            usage_for_display, PanelFormatOptions,
+        };
 
-        // If panel_metrics is not empty and we have today's data, show icon + metrics
-        if !self.state.config.panel_metrics.is_empty() {
+        // If the panel shows today's figures and we have today's data, show icon + metrics
+        if !self.state.config.panel_icon_only && self.state.config.needs_today_usage() {
             if let Some(today_usage) = &self.state.today_usage {
-                let display_text = format_multiple_panel_metrics(
-                    today_usage,
-                    &self.state.config.panel_metrics,
-                    self.state.config.use_raw_token_display,
-                );
+                let today_usage =
+                    usage_for_display(today_usage, self.state.config.fold_cache_into_io);
+                let options = PanelFormatOptions::from_config(&self.state.config);
+                // Without explicit panel metrics, feature the primary metric on its own
+                let display_text = match self.state.config.primary_metric {
+                    Some(primary) if self.state.config.panel_metrics.is_empty() => {
+                        format_primary_metric(&today_usage, primary, options)
+                    }
+                    _ => format_multiple_panel_metrics_in_order(
+                        &today_usage,
+                        &self.state.config.panel_metrics,
+                        &self.state.config.panel_metric_order,
+                        options,
+                    ),
+                };
+                let display_text = self.with_pinned_note(display_text, options);
+                // Too narrow to show anything useful: fall back to the icon
+                let Some(display_text) = fit_panel_text(
+                    &display_text,
+                    self.state.config.panel_max_chars,
+                    self.state.config.panel_min_chars,
+                ) else {
+                    return icon::from_name(self.get_state_icon()).size(16).into();
+                };
+                let mut panel_text = self.core.applet.text(display_text);
+                if self.state.is_over_budget() {
+                    let [r, g, b] = self
+                        .state
+                        .config
+                        .budget_warning_rgb(cosmic::theme::is_dark());
+                    panel_text = panel_text.class(cosmic::theme::Text::Color(
+                        cosmic::iced::Color::from_rgb8(r, g, b),
+                    ));
+                }
+
                 // Show icon + text in a row
                 return row()
                     .push(icon::from_name(self.get_state_icon()).size(16))
-                    .push(self.core.applet.text(display_text))
+                    .push(panel_text)
                     .spacing(8)
                     .align_y(Alignment::Center)
                     .into();
@@ -932,51 +2171,23 @@ impl Application for OpenCodeMonitorApplet {
     }
 
     fn init(core: Core, flags: Self::Flags) -> (Self, Task<Self::Message>) {
-        let reader = if let Some(ref path) = flags.storage_path {
+        let mut reader = if let Some(ref path) = flags.storage_path {
             OpenCodeUsageReader::new_with_path(path.to_str().unwrap_or(""))
                 .expect("Failed to create OpenCode reader")
         } else {
             OpenCodeUsageReader::new().expect("Failed to create OpenCode reader")
         };
+        if let Err(err) = reader.set_parse_threads(flags.parse_threads) {
+            eprintln!("Warning: {err}, using the default thread pool");
+        }
+        if let Err(err) = reader.set_day_reset_hour(flags.day_reset_hour) {
+            eprintln!("Warning: {err}, days start at midnight UTC");
+        }
+        reader.set_lean_cache(flags.lean_cache);
+        reader.set_interaction_unit(flags.interaction_unit);
 
-        let temp_refresh_interval = flags.refresh_interval_seconds;
-        let temp_panel_metrics = flags.panel_metrics.clone();
-        let temp_use_raw_token_display = flags.use_raw_token_display;
-
-        // Create watch channel for refresh interval updates
-        let (refresh_interval_tx, _rx) = watch::channel(flags.refresh_interval_seconds);
-
-        // Initialize data collector with database
-        // This enables automatic daily snapshot collection when metrics are fetched.
-        // If initialization fails, we continue without collection (graceful degradation).
-        let data_collector = match DatabaseManager::new() {
-            Ok(db_manager) => {
-                eprintln!("[DataCollector] Database initialized successfully");
-                Some(DataCollector::new(Arc::new(db_manager)))
-            }
-            Err(e) => {
-                eprintln!("[DataCollector] Failed to initialize database: {e}");
-                eprintln!("[DataCollector] Continuing without automatic data collection");
-                None
-            }
-        };
-
-        let applet = Self {
-            core,
-            state: AppState::new(flags),
-            reader,
-            data_collector,
-            settings_dialog_open: false,
-            temp_refresh_interval,
-            temp_refresh_interval_str: temp_refresh_interval.to_string(),
-            temp_panel_metrics,
-            temp_use_raw_token_display,
-            config_error: None,
-            config_warning: None,
-            popup: None,
-            refresh_interval_tx,
-            fetch_generation: 0,
-        };
+        let mut applet = Self::with_source(flags, Box::new(reader));
+        applet.core = core;
 
         eprintln!("[init] Application initialized, triggering initial FetchMetrics");
         (
@@ -1027,27 +2238,77 @@ impl Application for OpenCodeMonitorApplet {
                         Ok(()) = refresh_interval_rx.changed() => {
                             interval_seconds = u64::from(*refresh_interval_rx.borrow_and_update());
 
-                            #[cfg(debug_assertions)]
-                            eprintln!("[Subscription] Refresh interval changed to {interval_seconds} seconds");
+                            #[cfg(debug_assertions)]
+                            eprintln!("[Subscription] Refresh interval changed to {interval_seconds} seconds");
+
+                            let period = time::Duration::from_secs(interval_seconds);
+                            let start = time::Instant::now() + period;
+                            timer = time::interval_at(start, period);
+                            timer.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+                        }
+                    }
+                }
+            }),
+        );
+
+        // Watch for config changes from other instances via COSMIC's watch_config.
+        // A namespaced config lives under another ID and is synced by the fallback reload.
+        let config_watch_sub = if self.state.config.data_namespace.is_none() {
+            self.core
+                .watch_config::<AppConfig>(Self::APP_ID)
+                .map(|update| {
+                    // Keys that failed to parse keep their previous or default value
+                    for err in &update.errors {
+                        eprintln!("[ConfigChanged] watch_config reported an error: {err}");
+                    }
+                    Message::ConfigChanged(update.config)
+                })
+        } else {
+            Subscription::none()
+        };
+
+        // Periodically reload the config so instances still sync if the watcher stops
+        let config_fallback_sub = Subscription::run_with_id(
+            "opencode-config-fallback-sub",
+            stream::channel(1, |mut output| async move {
+                let period = time::Duration::from_secs(CONFIG_FALLBACK_RELOAD_SECONDS);
+                let mut timer = time::interval_at(time::Instant::now() + period, period);
+                timer.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+
+                loop {
+                    timer.tick().await;
+                    if let Err(err) = output.send(Message::ReloadConfigFallback).await {
+                        eprintln!("[Subscription] Failed sending config reload: {err:?}");
+                    }
+                }
+            }),
+        );
+
+        // Refresh when the (UTC) day rolls over so the panel doesn't show yesterday's totals
+        let day_reset_hour = self.state.config.day_reset_hour;
+        let day_rollover_sub = Subscription::run_with_id(
+            ("opencode-day-rollover-sub", day_reset_hour),
+            stream::channel(1, move |mut output| async move {
+                loop {
+                    let wait =
+                        duration_until_day_reset(std::time::SystemTime::now(), day_reset_hour);
+                    time::sleep(wait).await;
 
-                            let period = time::Duration::from_secs(interval_seconds);
-                            let start = time::Instant::now() + period;
-                            timer = time::interval_at(start, period);
-                            timer.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
-                        }
+                    eprintln!("[Subscription] Day rolled over, refreshing metrics");
+                    if let Err(err) = output.send(Message::FetchMetrics).await {
+                        eprintln!("[Subscription] Failed sending day rollover: {err:?}");
                     }
                 }
             }),
         );
 
-        // Watch for config changes from other instances via COSMIC's watch_config
-        let config_watch_sub = self
-            .core
-            .watch_config::<AppConfig>(Self::APP_ID)
-            .map(|update| Message::ConfigChanged(update.config));
-
-        // Combine both subscriptions
-        Subscription::batch([refresh_sub, config_watch_sub])
+        // Combine all subscriptions
+        Subscription::batch([
+            refresh_sub,
+            config_watch_sub,
+            config_fallback_sub,
+            day_rollover_sub,
+        ])
     }
 
     fn style(&self) -> Option<cosmic::iced_runtime::Appearance> {
@@ -1058,6 +2319,8 @@ impl Application for OpenCodeMonitorApplet {
         if self.popup.is_some() && self.popup == Some(id) {
             let content = if self.settings_dialog_open {
                 self.settings_view()
+            } else if self.show_onboarding {
+                self.onboarding_view()
             } else {
                 self.metrics_popup_view()
             };
@@ -1110,6 +2373,7 @@ mod tests {
             panel_metrics: vec![],
             use_raw_token_display: false,
             display_mode: crate::ui::state::DisplayMode::Today,
+            ..AppConfig::default()
         }
     }
 
@@ -1121,6 +2385,7 @@ mod tests {
             total_cache_write_tokens: 100,
             total_cache_read_tokens: 50,
             total_cost: 12.50,
+            total_cache_cost: 0.0,
             interaction_count: 10,
             timestamp: SystemTime::now(),
         }
@@ -1146,10 +2411,11 @@ mod tests {
             let _ = applet.handle_message(Message::MetricsFetched(
                 1,
                 Box::new(Ok((usage.clone(), None, None))),
+                0,
             ));
 
             assert!(matches!(applet.state.panel_state, PanelState::Success(_)));
-            assert!(applet.state.last_update.is_some());
+            assert!(applet.state.last_update().is_some());
         }
     }
 
@@ -1159,7 +2425,7 @@ mod tests {
         if let Ok(mut applet) = OpenCodeMonitorApplet::new(config) {
             let error = "Test error".to_string();
 
-            let _ = applet.handle_message(Message::MetricsFetched(1, Box::new(Err(error))));
+            let _ = applet.handle_message(Message::MetricsFetched(1, Box::new(Err(error)), 0));
 
             assert!(matches!(applet.state.panel_state, PanelState::Error(_)));
         }
@@ -1342,6 +2608,7 @@ mod tests {
             let _ = applet.handle_message(Message::MetricsFetched(
                 1,
                 Box::new(Ok((main_usage, None, Some(month_usage.clone())))),
+                0,
             ));
 
             // Month cache should be updated
@@ -1363,6 +2630,7 @@ mod tests {
             let _ = applet.handle_message(Message::MetricsFetched(
                 1,
                 Box::new(Ok((month_usage.clone(), None, None))),
+                0,
             ));
 
             // Month cache should be populated when in Month mode
@@ -1383,6 +2651,7 @@ mod tests {
             let _ = applet.handle_message(Message::MetricsFetched(
                 1,
                 Box::new(Ok((all_time_usage, None, Some(month_usage.clone())))),
+                0,
             ));
             assert!(applet.state.month_usage.is_some());
 
@@ -1418,6 +2687,7 @@ mod tests {
                     None,
                     Some(initial_month.clone()),
                 ))),
+                0,
             ));
             assert_eq!(applet.state.month_usage.as_ref().unwrap().total_cost, 5.0);
 
@@ -1428,6 +2698,7 @@ mod tests {
             let _ = applet.handle_message(Message::MetricsFetched(
                 1,
                 Box::new(Ok((all_time_usage, None, Some(updated_month)))),
+                0,
             ));
 
             // Month cache should be updated
@@ -1444,10 +2715,14 @@ mod tests {
             let _ = applet.handle_message(Message::MetricsFetched(
                 1,
                 Box::new(Ok((usage, None, None))),
+                0,
             ));
 
             // Manually set last_update to old time to trigger refresh
-            applet.state.last_update = Some(Utc::now() - chrono::Duration::seconds(1000));
+            applet
+                .state
+                .refresh_schedule
+                .record_fetch(Utc::now() - chrono::Duration::seconds(1000));
 
             // Tick should trigger fetch since needs_refresh() returns true
             assert!(applet.state.needs_refresh());
@@ -1469,6 +2744,7 @@ mod tests {
             let _ = applet.handle_message(Message::MetricsFetched(
                 1,
                 Box::new(Ok((usage, None, None))),
+                0,
             ));
 
             // last_update should be recent (just set by update_success)
@@ -1775,6 +3051,7 @@ mod tests {
                     Some(today_metrics.clone()),
                     None,
                 ))),
+                0,
             ));
 
             // Verify today_usage is populated
@@ -1789,4 +3066,965 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_fetch_usage_for_mode_with_mock_source() {
+        use crate::core::opencode::MockUsageSource;
+
+        let mut month = create_mock_usage_metrics();
+        month.total_cost = 99.0;
+        let mut source = MockUsageSource {
+            today: Some(create_mock_usage_metrics()),
+            month: Some(month),
+            ..Default::default()
+        };
+
+        let (main, today, month) = fetch_usage_for_mode(&mut source, DisplayMode::Today, true)
+            .expect("Today fetch should succeed");
+        assert_eq!(main.total_cost, 12.50);
+        assert_eq!(today.map(|m| m.total_cost), Some(12.50));
+        assert_eq!(month.map(|m| m.total_cost), Some(99.0));
+
+        // Month mode skips today's fetch when no panel metrics are shown
+        let (main, today, month) = fetch_usage_for_mode(&mut source, DisplayMode::Month, false)
+            .expect("Month fetch should succeed");
+        assert_eq!(main.total_cost, 99.0);
+        assert!(today.is_none());
+        assert!(month.is_none());
+
+        // Missing all-time data surfaces as an error
+        let result = fetch_usage_for_mode(&mut source, DisplayMode::AllTime, false);
+        assert!(result.is_err());
+
+        // All-time mode also reads today when the panel or the snapshot needs it
+        source.all_time = Some(create_mock_usage_metrics());
+        let (_, today, _) = fetch_usage_for_mode(&mut source, DisplayMode::AllTime, true)
+            .expect("All-time fetch should succeed");
+        assert_eq!(today.map(|m| m.total_cost), Some(12.50));
+    }
+
+    #[test]
+    fn test_snapshot_stores_today_not_displayed_total() {
+        use crate::core::opencode::MockUsageSource;
+
+        let source = MockUsageSource::new(create_mock_usage_metrics());
+        let mut applet = OpenCodeMonitorApplet::with_source(create_mock_config(), Box::new(source));
+        let db = Arc::new(DatabaseManager::new_in_memory().unwrap());
+        applet.data_collector = Some(DataCollector::new(Arc::clone(&db)));
+        applet.state.display_mode = DisplayMode::Month;
+
+        let mut month = create_mock_usage_metrics();
+        month.total_cost = 99.0;
+        let mut today = create_mock_usage_metrics();
+        today.total_cost = 1.0;
+        let _ = applet.handle_message(Message::MetricsFetched(
+            0,
+            Box::new(Ok((month, Some(today.clone()), None))),
+            0,
+        ));
+
+        // A later fetch the same day replaces today's snapshot
+        today.total_cost = 2.0;
+        let _ = applet.handle_message(Message::MetricsFetched(
+            0,
+            Box::new(Ok((create_mock_usage_metrics(), Some(today), None))),
+            0,
+        ));
+
+        let repository = crate::core::database::repository::UsageRepository::new(db);
+        let snapshot = repository
+            .get_snapshot(chrono::Utc::now().date_naive())
+            .unwrap()
+            .expect("Today's snapshot should be saved");
+        assert_eq!(snapshot.total_cost, 2.0);
+        assert_eq!(repository.snapshot_count().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_pinned_usage_counts_each_day_once() {
+        use crate::core::opencode::MockUsageSource;
+
+        let mut config = create_mock_config();
+        config.pinned_panel_note = Some(PanelMetric::Cost);
+        let source = MockUsageSource::new(create_mock_usage_metrics());
+        let mut applet = OpenCodeMonitorApplet::with_source(config, Box::new(source));
+        let db = Arc::new(DatabaseManager::new_in_memory().unwrap());
+        let collector = DataCollector::new(Arc::clone(&db));
+        let mut yesterday = create_mock_usage_metrics();
+        yesterday.total_cost = 5.0;
+        collector
+            .recompute_and_save(
+                chrono::Utc::now().date_naive() - chrono::Duration::days(1),
+                &yesterday,
+            )
+            .unwrap();
+        applet.data_collector = Some(collector);
+        applet.state.display_mode = DisplayMode::AllTime;
+
+        // Repeated fetches of the all-time total must not add it to the history
+        for today_cost in [1.0, 2.0] {
+            let mut today = create_mock_usage_metrics();
+            today.total_cost = today_cost;
+            let mut all_time = create_mock_usage_metrics();
+            all_time.total_cost = 5.0 + today_cost;
+            let _ = applet.handle_message(Message::MetricsFetched(
+                0,
+                Box::new(Ok((all_time, Some(today), None))),
+                0,
+            ));
+        }
+
+        assert_eq!(
+            applet.pinned_usage.as_ref().map(|usage| usage.total_cost),
+            Some(7.0)
+        );
+    }
+
+    #[test]
+    fn test_stored_reasoning_keeps_row_when_today_has_none() {
+        use crate::core::config::ReasoningVisibility;
+        use crate::core::opencode::MockUsageSource;
+
+        let config = AppConfig {
+            reasoning_visibility: ReasoningVisibility::Auto,
+            ..create_mock_config()
+        };
+        let source = MockUsageSource::new(create_mock_usage_metrics());
+        let mut applet = OpenCodeMonitorApplet::with_source(config, Box::new(source));
+        let collector = DataCollector::new(Arc::new(DatabaseManager::new_in_memory().unwrap()));
+        collector
+            .recompute_and_save(
+                chrono::Utc::now().date_naive() - chrono::Duration::days(1),
+                &create_mock_usage_metrics(),
+            )
+            .unwrap();
+        applet.data_collector = Some(collector);
+        applet.state.display_mode = DisplayMode::Today;
+
+        let mut today = create_mock_usage_metrics();
+        today.total_reasoning_tokens = 0;
+        let _ = applet.handle_message(Message::MetricsFetched(
+            0,
+            Box::new(Ok((today.clone(), Some(today), None))),
+            0,
+        ));
+
+        assert_eq!(applet.state.has_reasoning_history, Some(true));
+        assert!(applet.state.show_reasoning());
+    }
+
+    #[test]
+    fn test_today_mode_falls_back_when_month_fails() {
+        use crate::core::opencode::MockUsageSource;
+
+        // Today has data but the month part cannot be read
+        let mut source = MockUsageSource {
+            today: Some(create_mock_usage_metrics()),
+            ..Default::default()
+        };
+
+        let (main, today, month) = fetch_usage_for_mode(&mut source, DisplayMode::Today, false)
+            .expect("Today mode should not depend on the month");
+        assert_eq!(main.total_cost, create_mock_usage_metrics().total_cost);
+        assert!(today.is_some());
+        assert!(month.is_none());
+
+        // Without today's data the error still surfaces
+        let mut empty = MockUsageSource::default();
+        assert!(fetch_usage_for_mode(&mut empty, DisplayMode::Today, false).is_err());
+    }
+
+    #[test]
+    fn test_applet_with_mock_source_message_flow() {
+        use crate::core::opencode::MockUsageSource;
+
+        let source = MockUsageSource::new(create_mock_usage_metrics());
+        let mut applet = OpenCodeMonitorApplet::with_source(create_mock_config(), Box::new(source));
+        assert!(applet
+            .data_collector
+            .as_ref()
+            .is_some_and(DataCollector::is_in_memory));
+
+        let _ = applet.handle_message(Message::FetchMetrics);
+        assert!(applet.state.panel_state.is_loading());
+        assert_eq!(applet.fetch_generation, 1);
+
+        // Feed the result the background task would produce
+        let result = fetch_usage_for_mode(
+            applet.source.as_mut(),
+            applet.state.display_mode,
+            applet.state.config.needs_today_usage(),
+        );
+        let _ = applet.handle_message(Message::MetricsFetched(1, Box::new(result), 0));
+
+        assert!(matches!(applet.state.panel_state, PanelState::Success(_)));
+        assert_eq!(
+            applet.state.today_usage.as_ref().map(|m| m.total_cost),
+            Some(12.50)
+        );
+        assert_eq!(
+            applet.state.month_usage.as_ref().map(|m| m.total_cost),
+            Some(12.50)
+        );
+    }
+
+    #[test]
+    fn test_history_disabled_creates_no_database() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("usage.db");
+
+        let config = AppConfig {
+            history_enabled: false,
+            ..create_mock_config()
+        };
+        let collector = OpenCodeMonitorApplet::init_data_collector_with(&config, || {
+            DatabaseManager::new_with_path(&db_path)
+        });
+        assert!(collector.is_none());
+        assert!(!db_path.exists(), "No database should be created");
+
+        let applet = OpenCodeMonitorApplet::with_source(
+            config,
+            Box::new(crate::core::opencode::MockUsageSource::default()),
+        );
+        assert!(applet.data_collector.is_none());
+    }
+
+    #[test]
+    fn test_panel_only_creates_no_database_and_hides_viewer() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("usage.db");
+
+        let config = AppConfig {
+            panel_only: true,
+            ..create_mock_config()
+        };
+        assert!(config.history_enabled);
+        let collector = OpenCodeMonitorApplet::init_data_collector_with(&config, || {
+            DatabaseManager::new_with_path(&db_path)
+        });
+        assert!(collector.is_none());
+        assert!(!db_path.exists(), "No database should be created");
+
+        let applet = OpenCodeMonitorApplet::with_source(
+            config,
+            Box::new(crate::core::opencode::MockUsageSource::default()),
+        );
+        assert!(applet.data_collector.is_none());
+        assert!(!applet.shows_view_stats());
+        assert!(applet.view_stats_button(true).is_none());
+
+        let applet = OpenCodeMonitorApplet::with_source(
+            create_mock_config(),
+            Box::new(crate::core::opencode::MockUsageSource::default()),
+        );
+        assert!(applet.shows_view_stats());
+    }
+
+    #[test]
+    fn test_second_instance_defers_collection() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("usage.db");
+        let open = || DatabaseManager::new_with_path(&db_path);
+
+        let primary = OpenCodeMonitorApplet::init_data_collector_with(&create_mock_config(), open);
+        assert!(primary.is_some());
+        let secondary =
+            OpenCodeMonitorApplet::init_data_collector_with(&create_mock_config(), open);
+        assert!(secondary.is_none());
+
+        // Once the primary goes away, the next instance collects again
+        drop(primary);
+        let next = OpenCodeMonitorApplet::init_data_collector_with(&create_mock_config(), open);
+        assert!(next.is_some());
+    }
+
+    #[test]
+    fn test_history_enabled_creates_database() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("usage.db");
+
+        let collector =
+            OpenCodeMonitorApplet::init_data_collector_with(&create_mock_config(), || {
+                DatabaseManager::new_with_path(&db_path)
+            });
+        assert!(collector.is_some());
+        assert!(db_path.exists());
+    }
+
+    #[test]
+    fn test_read_only_database_keeps_collecting_in_memory() {
+        let collector =
+            OpenCodeMonitorApplet::init_data_collector_with(&create_mock_config(), || {
+                DatabaseManager::or_in_memory(Err(crate::core::database::DatabaseError::ReadOnly(
+                    "read-only".to_string(),
+                )))
+            })
+            .expect("Collection continues in memory");
+        assert!(collector.is_in_memory());
+        assert!(collector
+            .collect_and_save(&create_mock_usage_metrics())
+            .expect("In-memory save should succeed"));
+    }
+
+    #[test]
+    fn test_retry_moves_error_to_loading_before_results() {
+        use crate::core::opencode::MockUsageSource;
+
+        let source = MockUsageSource::new(create_mock_usage_metrics());
+        let mut applet = OpenCodeMonitorApplet::with_source(create_mock_config(), Box::new(source));
+
+        let _ = applet.handle_message(Message::MetricsFetched(
+            0,
+            Box::new(Err("Test error".to_string())),
+            0,
+        ));
+        assert!(applet.state.panel_state.is_error());
+
+        // Retry button sends FetchMetrics; the error is replaced immediately
+        let _ = applet.handle_message(Message::FetchMetrics);
+        assert!(matches!(applet.state.panel_state, PanelState::Loading));
+
+        // A stale error from an earlier request doesn't bring the error back
+        let _ = applet.handle_message(Message::MetricsFetched(
+            0,
+            Box::new(Err("Old error".to_string())),
+            0,
+        ));
+        assert!(matches!(applet.state.panel_state, PanelState::Loading));
+    }
+
+    #[test]
+    fn test_budget_warning_icon_follows_config() {
+        let config = AppConfig {
+            monthly_budget: Some(10.0),
+            history_enabled: false,
+            ..create_mock_config()
+        };
+        let mut applet = OpenCodeMonitorApplet::with_source(
+            config,
+            Box::new(crate::core::opencode::MockUsageSource::default()),
+        );
+        applet.state.update_success(create_mock_usage_metrics());
+        applet.state.update_month_usage(create_mock_usage_metrics());
+
+        assert!(applet.state.is_over_budget());
+        assert_eq!(applet.get_state_icon(), "dialog-warning-symbolic");
+
+        applet.state.config.budget_warning_icon = false;
+        assert_eq!(applet.get_state_icon(), "dialog-information-symbolic");
+    }
+
+    #[test]
+    fn test_invalid_budget_color_blocks_save() {
+        let config = AppConfig {
+            history_enabled: false,
+            ..create_mock_config()
+        };
+        let mut applet = OpenCodeMonitorApplet::with_source(
+            config,
+            Box::new(crate::core::opencode::MockUsageSource::default()),
+        );
+
+        let _ = applet.handle_message(Message::OpenSettings);
+        let _ = applet.handle_message(Message::UpdateBudgetWarningColor("red".to_string()));
+        let _ = applet.handle_message(Message::SaveConfig);
+
+        assert!(applet.settings_dialog_open);
+        assert!(matches!(
+            applet.config_error,
+            Some(ConfigError::InvalidColor(_))
+        ));
+        assert_eq!(applet.state.config.budget_warning_color, None);
+    }
+
+    #[test]
+    fn test_popup_auto_close_scheduling() {
+        assert_eq!(popup_auto_close_timeout(None), None);
+        assert_eq!(popup_auto_close_timeout(Some(0)), None);
+        assert_eq!(
+            popup_auto_close_timeout(Some(15)),
+            Some(Duration::from_secs(15))
+        );
+
+        let timeout = Duration::from_secs(15);
+        assert_eq!(
+            popup_auto_close_remaining(timeout, Duration::from_secs(5)),
+            Some(Duration::from_secs(10)),
+            "Recent interaction postpones the close"
+        );
+        assert_eq!(popup_auto_close_remaining(timeout, timeout), None);
+        assert_eq!(
+            popup_auto_close_remaining(timeout, Duration::from_secs(60)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_popup_auto_close_ignores_stale_popup() {
+        let config = AppConfig {
+            popup_auto_close_seconds: Some(1),
+            history_enabled: false,
+            ..create_mock_config()
+        };
+        let mut applet = OpenCodeMonitorApplet::with_source(
+            config,
+            Box::new(crate::core::opencode::MockUsageSource::default()),
+        );
+        let open_id = window::Id::unique();
+        applet.popup = Some(open_id);
+
+        // A timer from an earlier popup must not close the current one
+        let _ = applet.handle_message(Message::PopupAutoClose(window::Id::unique()));
+        assert_eq!(applet.popup, Some(open_id));
+
+        // Recent interaction reschedules instead of closing
+        let _ = applet.handle_message(Message::PopupAutoClose(open_id));
+        assert_eq!(applet.popup, Some(open_id));
+    }
+
+    #[test]
+    fn test_refresh_now_shows_refreshed_flash() {
+        use crate::core::opencode::MockUsageSource;
+
+        let source = MockUsageSource::new(create_mock_usage_metrics());
+        let mut applet = OpenCodeMonitorApplet::with_source(create_mock_config(), Box::new(source));
+
+        let _ = applet.handle_message(Message::RefreshNow);
+        assert!(applet.state.panel_state.is_loading());
+        assert!(!applet.state.show_refreshed_flash());
+
+        let result = fetch_usage_for_mode(
+            applet.source.as_mut(),
+            applet.state.display_mode,
+            applet.state.config.needs_today_usage(),
+        );
+        let _ = applet.handle_message(Message::MetricsFetched(1, Box::new(result), 0));
+        assert!(applet.state.show_refreshed_flash());
+
+        // The follow-up tick fires after the flash has expired
+        applet.state.refreshed_at = Some(
+            chrono::Utc::now() - chrono::Duration::seconds(i64::from(REFRESHED_FLASH_SECONDS)),
+        );
+        let _ = applet.handle_message(Message::ClearRefreshedFlash);
+        assert!(!applet.state.show_refreshed_flash());
+    }
+
+    #[test]
+    fn test_reset_panel_metric_order() {
+        let config = AppConfig {
+            panel_metric_order: vec![PanelMetric::Interactions, PanelMetric::Cost],
+            ..create_mock_config()
+        };
+        let mut applet = OpenCodeMonitorApplet::with_source(
+            config,
+            Box::new(crate::core::opencode::MockUsageSource::default()),
+        );
+
+        let _ = applet.handle_message(Message::OpenSettings);
+        assert_eq!(
+            applet.temp_panel_metric_order,
+            vec![PanelMetric::Interactions, PanelMetric::Cost]
+        );
+
+        let _ = applet.handle_message(Message::ResetPanelMetricOrder);
+        assert_eq!(applet.temp_panel_metric_order, PanelMetric::DEFAULT_ORDER);
+        // Saved order is untouched until the settings are saved
+        assert_eq!(
+            applet.state.config.panel_metric_order,
+            vec![PanelMetric::Interactions, PanelMetric::Cost]
+        );
+    }
+
+    #[test]
+    fn test_reorder_panel_metrics_index_math() {
+        use PanelMetric::{Cost, InputTokens, Interactions, OutputTokens, ReasoningTokens};
+
+        // Move to the end
+        let mut order = PanelMetric::DEFAULT_ORDER.to_vec();
+        reorder_panel_metrics(&mut order, 0, 4);
+        assert_eq!(
+            order,
+            vec![
+                Interactions,
+                InputTokens,
+                OutputTokens,
+                ReasoningTokens,
+                Cost
+            ]
+        );
+
+        // Move to the start
+        reorder_panel_metrics(&mut order, 3, 0);
+        assert_eq!(
+            order,
+            vec![
+                ReasoningTokens,
+                Interactions,
+                InputTokens,
+                OutputTokens,
+                Cost
+            ]
+        );
+
+        // Move down by one
+        reorder_panel_metrics(&mut order, 1, 2);
+        assert_eq!(
+            order,
+            vec![
+                ReasoningTokens,
+                InputTokens,
+                Interactions,
+                OutputTokens,
+                Cost
+            ]
+        );
+
+        // A target past the end clamps; an invalid source is ignored
+        reorder_panel_metrics(&mut order, 0, 99);
+        assert_eq!(
+            order,
+            vec![
+                InputTokens,
+                Interactions,
+                OutputTokens,
+                Cost,
+                ReasoningTokens
+            ]
+        );
+        reorder_panel_metrics(&mut order, 5, 0);
+        assert_eq!(
+            order,
+            vec![
+                InputTokens,
+                Interactions,
+                OutputTokens,
+                Cost,
+                ReasoningTokens
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reorder_panel_metric_message() {
+        let mut applet = OpenCodeMonitorApplet::with_source(
+            create_mock_config(),
+            Box::new(crate::core::opencode::MockUsageSource::default()),
+        );
+        let _ = applet.handle_message(Message::OpenSettings);
+        let _ = applet.handle_message(Message::ReorderPanelMetric(0, 4));
+        assert_eq!(applet.temp_panel_metric_order[4], PanelMetric::Cost);
+        assert_eq!(
+            applet.state.config.panel_metric_order,
+            PanelMetric::DEFAULT_ORDER,
+            "Order only applies on save"
+        );
+    }
+
+    #[test]
+    fn test_drag_and_drop_reorders_panel_metrics() {
+        let config = AppConfig {
+            history_enabled: false,
+            ..create_mock_config()
+        };
+        let mut applet = OpenCodeMonitorApplet::with_source(
+            config,
+            Box::new(crate::core::opencode::MockUsageSource::default()),
+        );
+        let _ = applet.handle_message(Message::OpenSettings);
+
+        // Releasing without a drag does nothing
+        assert!(applet.panel_metric_drop(2).is_none());
+
+        // Dragging the last metric onto the first row moves it to the start
+        let last = applet.temp_panel_metric_order.len() - 1;
+        let dragged = applet.temp_panel_metric_order[last];
+        let _ = applet.handle_message(Message::StartPanelMetricDrag(last));
+        let drop = applet.panel_metric_drop(0).expect("A drag is in progress");
+        let _ = applet.handle_message(drop);
+        assert_eq!(applet.temp_panel_metric_order[0], dragged);
+        assert!(applet.dragged_panel_metric.is_none());
+
+        // Dropping a metric on its own row leaves the order unchanged
+        let order = applet.temp_panel_metric_order.clone();
+        let _ = applet.handle_message(Message::StartPanelMetricDrag(1));
+        let _ = applet.handle_message(applet.panel_metric_drop(1).unwrap());
+        assert_eq!(applet.temp_panel_metric_order, order);
+
+        // Dragging the first metric onto the last row moves it to the end
+        let _ = applet.handle_message(Message::StartPanelMetricDrag(0));
+        let _ = applet.handle_message(applet.panel_metric_drop(last).unwrap());
+        assert_eq!(applet.temp_panel_metric_order[last], dragged);
+    }
+
+    #[test]
+    fn test_panel_preview_uses_unsaved_settings() {
+        use crate::core::opencode::MockUsageSource;
+
+        let config = AppConfig {
+            panel_metrics: vec![PanelMetric::Cost],
+            ..create_mock_config()
+        };
+        let mut applet =
+            OpenCodeMonitorApplet::with_source(config, Box::new(MockUsageSource::default()));
+        applet.state.update_today_usage(create_mock_usage_metrics());
+
+        let _ = applet.handle_message(Message::OpenSettings);
+        let saved_preview = applet.panel_preview_text();
+        assert!(saved_preview.starts_with('$'));
+
+        let _ = applet.handle_message(Message::TogglePanelMetric(PanelMetric::Cost));
+        let _ = applet.handle_message(Message::TogglePanelMetric(PanelMetric::InputTokens));
+        let _ = applet.handle_message(Message::ToggleRawTokenDisplay(true));
+
+        let preview = applet.panel_preview_text();
+        assert!(
+            preview.starts_with("↑ "),
+            "Preview follows temp metrics: {preview}"
+        );
+        assert!(!preview.contains('$'));
+        let digits: String = preview.chars().filter(char::is_ascii_digit).collect();
+        assert_eq!(
+            digits,
+            create_mock_usage_metrics().total_input_tokens.to_string(),
+            "Preview uses raw token display"
+        );
+
+        // The saved config is untouched
+        assert_eq!(applet.state.config.panel_metrics, vec![PanelMetric::Cost]);
+        assert!(!applet.state.config.use_raw_token_display);
+    }
+
+    #[test]
+    fn test_panel_preview_icon_only() {
+        use crate::core::opencode::MockUsageSource;
+
+        let config = AppConfig {
+            panel_metrics: vec![PanelMetric::Cost, PanelMetric::Interactions],
+            ..create_mock_config()
+        };
+        let mut applet =
+            OpenCodeMonitorApplet::with_source(config, Box::new(MockUsageSource::default()));
+        applet.state.update_today_usage(create_mock_usage_metrics());
+        let _ = applet.handle_message(Message::OpenSettings);
+
+        let _ = applet.handle_message(Message::TogglePanelIconOnly(true));
+        assert_eq!(applet.panel_preview_text(), "(icon only)");
+        let _ = applet.handle_message(Message::TogglePanelIconOnly(false));
+        assert_ne!(applet.panel_preview_text(), "(icon only)");
+
+        // A width that would cut the text below the threshold drops to icon-only
+        applet.state.config.panel_max_chars = Some(3);
+        applet.state.config.panel_min_chars = 4;
+        assert_eq!(applet.panel_preview_text(), "(icon only)");
+
+        applet.state.config.panel_max_chars = Some(6);
+        assert!(applet.panel_preview_text().ends_with('…'));
+    }
+
+    /// Usage source whose all-time read blocks, like a scan over a huge storage tree
+    #[derive(Clone)]
+    struct SlowUsageSource {
+        delay: Duration,
+        storage_path: std::path::PathBuf,
+    }
+
+    impl UsageSource for SlowUsageSource {
+        fn get_usage(&mut self) -> Result<UsageMetrics, ReaderError> {
+            std::thread::sleep(self.delay);
+            Ok(create_mock_usage_metrics())
+        }
+
+        fn get_usage_today(&mut self) -> Result<UsageMetrics, ReaderError> {
+            Err(ReaderError::NoDataFound)
+        }
+
+        fn get_usage_month(&mut self) -> Result<UsageMetrics, ReaderError> {
+            Err(ReaderError::NoDataFound)
+        }
+
+        fn get_usage_last_month(&mut self) -> Result<UsageMetrics, ReaderError> {
+            Err(ReaderError::NoDataFound)
+        }
+
+        fn storage_path(&self) -> &std::path::PathBuf {
+            &self.storage_path
+        }
+
+        fn fork(&self) -> Result<Box<dyn UsageSource>, ReaderError> {
+            Ok(Box::new(self.clone()))
+        }
+    }
+
+    #[test]
+    fn test_storage_path_change_rebuilds_reader() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config = AppConfig {
+            history_enabled: false,
+            ..create_mock_config()
+        };
+        let mut applet = OpenCodeMonitorApplet::with_source(
+            config.clone(),
+            Box::new(crate::core::opencode::MockUsageSource::default()),
+        );
+        applet.state.update_month_usage(create_mock_usage_metrics());
+        assert_ne!(applet.source.storage_path(), temp_dir.path());
+
+        let _ = applet.handle_message(Message::ConfigChanged(AppConfig {
+            storage_path: Some(temp_dir.path().to_path_buf()),
+            ..config.clone()
+        }));
+
+        assert_eq!(applet.source.storage_path(), temp_dir.path());
+        assert!(applet.state.month_usage.is_none());
+
+        // A path that does not exist keeps the working reader
+        let _ = applet.handle_message(Message::ConfigChanged(AppConfig {
+            storage_path: Some(temp_dir.path().join("missing")),
+            ..config
+        }));
+        assert_eq!(applet.source.storage_path(), temp_dir.path());
+    }
+
+    #[test]
+    fn test_source_for_config_dispatches_manifest_mode() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let manifest = temp_dir.path().join("usage.json");
+        std::fs::write(
+            &manifest,
+            r#"{"days": [{"date": "2025-10-01", "input": 10, "cost": 0.5, "interactions": 2}]}"#,
+        )
+        .unwrap();
+
+        let config = AppConfig {
+            manifest_mode: true,
+            storage_path: Some(manifest.clone()),
+            ..create_mock_config()
+        };
+        let mut source = source_for_config(&config).unwrap();
+        assert_eq!(source.storage_path(), &manifest);
+        assert_eq!(source.get_usage().unwrap().interaction_count, 2);
+
+        // A manifest has to be named explicitly
+        let without_path = AppConfig {
+            storage_path: None,
+            ..config
+        };
+        assert!(source_for_config(&without_path).is_err());
+    }
+
+    #[test]
+    fn test_needs_onboarding_only_on_first_run() {
+        let config = create_mock_config();
+        assert!(needs_onboarding(&config, false));
+        // An existing history database means the applet was used before
+        assert!(!needs_onboarding(&config, true));
+
+        let completed = AppConfig {
+            onboarding_completed: true,
+            ..create_mock_config()
+        };
+        assert!(!needs_onboarding(&completed, false));
+    }
+
+    #[test]
+    fn test_complete_onboarding_sets_flag() {
+        use crate::core::opencode::MockUsageSource;
+
+        let mut applet = OpenCodeMonitorApplet::with_source(
+            create_mock_config(),
+            Box::new(MockUsageSource::default()),
+        );
+        applet.show_onboarding = true;
+
+        let _ = applet.handle_message(Message::CompleteOnboarding);
+
+        assert!(!applet.show_onboarding);
+        assert!(applet.state.config.onboarding_completed);
+    }
+
+    #[test]
+    fn test_clamp_ui_scale_stays_in_range() {
+        assert!((clamp_ui_scale(COMPACT_UI_SCALE) - COMPACT_UI_SCALE).abs() < f32::EPSILON);
+        assert!((clamp_ui_scale(0.1) - UI_SCALE_RANGE.0).abs() < f32::EPSILON);
+        assert!((clamp_ui_scale(10.0) - UI_SCALE_RANGE.1).abs() < f32::EPSILON);
+        assert!((clamp_ui_scale(f32::NAN) - 1.0).abs() < f32::EPSILON);
+        assert!((clamp_ui_scale(f32::INFINITY) - 1.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_scaled_follows_compact_ui() {
+        let config = AppConfig {
+            compact_ui: false,
+            history_enabled: false,
+            ..create_mock_config()
+        };
+        let source = crate::core::opencode::MockUsageSource::new(create_mock_usage_metrics());
+        let mut applet = OpenCodeMonitorApplet::with_source(config, Box::new(source));
+        assert!(applet.data_collector.is_none());
+        assert!((applet.scaled(20) - 20.0).abs() < f32::EPSILON);
+
+        applet.state.config.compact_ui = true;
+        assert!((applet.scaled(20) - 20.0 * COMPACT_UI_SCALE).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_duration_until_day_reset_across_boundary() {
+        let at = |day: u64, secs: u64| {
+            std::time::SystemTime::UNIX_EPOCH + Duration::from_secs(day * 86400 + secs)
+        };
+
+        // 30 seconds before midnight UTC, then exactly at midnight a full day remains
+        assert_eq!(
+            duration_until_day_reset(at(100, 86370), 0),
+            Duration::from_secs(30)
+        );
+        assert_eq!(
+            duration_until_day_reset(at(101, 0), 0),
+            Duration::from_secs(86400)
+        );
+
+        // With a 04:00 reset, 03:00 is still the previous day
+        assert_eq!(
+            duration_until_day_reset(at(100, 3 * 3600), 4),
+            Duration::from_secs(3600)
+        );
+        assert_eq!(
+            duration_until_day_reset(at(100, 5 * 3600), 4),
+            Duration::from_secs(23 * 3600)
+        );
+    }
+
+    #[test]
+    fn test_fetch_deadline() {
+        assert_eq!(fetch_deadline(None), None);
+        assert_eq!(fetch_deadline(Some(0)), None);
+        assert_eq!(fetch_deadline(Some(30)), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn test_skipped_permission_denied_reaches_popup_state() {
+        use crate::core::opencode::MockUsageSource;
+
+        let mut source = MockUsageSource {
+            skipped_permission_denied: 2,
+            ..MockUsageSource::new(create_mock_usage_metrics())
+        };
+        let (result, skipped) = fetch_counting_skipped(&mut source, DisplayMode::Today, false);
+        assert!(result.is_ok());
+        assert_eq!(skipped, 2);
+
+        let mut applet =
+            OpenCodeMonitorApplet::with_source(create_mock_config(), Box::new(source.clone()));
+        let _ = applet.handle_message(Message::FetchMetrics);
+        let _ = applet.handle_message(Message::MetricsFetched(1, Box::new(result), skipped));
+        assert_eq!(applet.state.skipped_permission_denied, 2);
+        assert_eq!(
+            skipped_entries_warning(applet.state.skipped_permission_denied).as_deref(),
+            Some("2 storage entries skipped (permission denied)")
+        );
+        assert_eq!(skipped_entries_warning(0), None);
+        assert_eq!(
+            skipped_entries_warning(1).as_deref(),
+            Some("1 storage entry skipped (permission denied)")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_blocking_with_deadline_times_out_on_slow_source() {
+        let slow = SlowUsageSource {
+            delay: Duration::from_millis(500),
+            storage_path: std::path::PathBuf::new(),
+        };
+
+        let err = fetch_blocking_with_deadline(
+            Box::new(slow.clone()),
+            DisplayMode::AllTime,
+            false,
+            Some(Duration::from_millis(20)),
+        )
+        .await
+        .0
+        .expect_err("Slow scan should miss the deadline");
+        assert!(err.contains("Timed out"), "unexpected error: {err}");
+
+        // Without a deadline the same scan is awaited to completion
+        let (usage, _, _) =
+            fetch_blocking_with_deadline(Box::new(slow), DisplayMode::AllTime, false, None)
+                .await
+                .0
+                .expect("Scan without deadline should finish");
+        assert_eq!(usage.total_cost, create_mock_usage_metrics().total_cost);
+    }
+
+    #[test]
+    fn test_invalid_fetch_timeout_blocks_save() {
+        let config = AppConfig {
+            history_enabled: false,
+            ..create_mock_config()
+        };
+        let mut applet = OpenCodeMonitorApplet::with_source(
+            config,
+            Box::new(crate::core::opencode::MockUsageSource::default()),
+        );
+
+        let _ = applet.handle_message(Message::OpenSettings);
+        let _ = applet.handle_message(Message::UpdateFetchTimeout("soon".to_string()));
+        let _ = applet.handle_message(Message::SaveConfig);
+
+        assert!(applet.settings_dialog_open);
+        assert!(matches!(
+            applet.config_error,
+            Some(ConfigError::InvalidFetchTimeout(_))
+        ));
+        assert_eq!(applet.state.config.fetch_timeout_seconds, None);
+    }
+
+    #[test]
+    fn test_changed_config_emits_only_differences() {
+        let current = AppConfig::default();
+
+        assert_eq!(changed_config(&current, Ok(current.clone())), None);
+
+        let updated = AppConfig {
+            refresh_interval_seconds: current.refresh_interval_seconds + 60,
+            ..current.clone()
+        };
+        assert_eq!(changed_config(&current, Ok(updated.clone())), Some(updated));
+
+        let failed = Err(ConfigError::LoadError("schema changed".to_string()));
+        assert_eq!(changed_config(&current, failed), None);
+    }
+
+    #[test]
+    fn test_fallback_reload_keeps_saved_panel_metrics() {
+        let test_id = "com.test.CosmicAppletOpencodeUsage.fallback_reload_panel_metrics";
+        let current = AppConfig {
+            panel_metrics: vec![PanelMetric::Cost],
+            ..AppConfig::default()
+        };
+        current.save_with_id(test_id).unwrap();
+
+        // A reload of unchanged settings must not revert the user's metrics
+        assert_eq!(
+            changed_config(&current, AppConfig::load_with_id(test_id)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_metrics_socket_follows_setting() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = create_mock_config();
+        config.socket_path = Some(temp_dir.path().join("metrics.sock"));
+
+        let source = crate::core::opencode::MockUsageSource::new(create_mock_usage_metrics());
+        let mut applet = OpenCodeMonitorApplet::with_source(config.clone(), Box::new(source));
+        assert!(applet.metrics_socket.is_none());
+
+        config.expose_socket = true;
+        let _ = applet.handle_message(Message::ConfigChanged(config));
+        let socket = applet.metrics_socket.as_ref().expect("Socket should start");
+        assert!(socket.path().exists());
+    }
 }
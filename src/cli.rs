@@ -0,0 +1,256 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Command-line modes that print usage to stdout instead of launching the applet
+//!
+//! `--once` prints a one-line summary and exits, which suits scripts and status bars.
+//! `--watch [seconds]` prints a new line every interval, reusing one reader so repeated
+//! scans only re-parse changed files.
+//! `--dump-cache` reads all usage once and lists the cached files with their tracked
+//! modification times, to check that incremental caching behaves.
+
+use crate::app::{fetch_usage_for_mode, reader_for_config, source_for_config};
+use crate::core::config::AppConfig;
+use crate::core::opencode::{OpenCodeUsageReader, UsageMetrics};
+use crate::ui::formatters::{format_cost, format_tokens_compact};
+use crate::ui::state::DisplayMode;
+use chrono::{DateTime, Local};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use thiserror::Error;
+
+/// How the binary should run, as selected on the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CliMode {
+    /// Launch the panel applet (no mode flag given)
+    Applet,
+    /// Print the current usage once and exit
+    Once,
+    /// Print the current usage every interval until interrupted
+    ///
+    /// `None` uses the configured refresh interval.
+    Watch(Option<u64>),
+    /// Print the reader cache contents after a full read and exit
+    DumpCache,
+}
+
+/// Errors from parsing command-line arguments
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CliError {
+    #[error("Invalid watch interval '{0}': expected a positive number of seconds")]
+    InvalidInterval(String),
+    #[error("Unknown argument '{0}' (expected --once, --watch [seconds] or --dump-cache)")]
+    UnknownArgument(String),
+    #[error("--once, --watch and --dump-cache cannot be combined")]
+    ConflictingModes,
+}
+
+/// Parse the command-line arguments (without the program name)
+///
+/// # Errors
+/// Returns an error for unknown arguments, a non-positive watch interval, or when
+/// more than one mode is given.
+pub fn parse_args<I>(args: I) -> Result<CliMode, CliError>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut mode = CliMode::Applet;
+    let mut args = args.into_iter().peekable();
+
+    while let Some(arg) = args.next() {
+        let next = match arg.as_str() {
+            "--once" => CliMode::Once,
+            "--dump-cache" => CliMode::DumpCache,
+            "--watch" => {
+                // The interval is optional, so only consume the next argument if it isn't a flag
+                let interval = match args.next_if(|value| !value.starts_with("--")) {
+                    Some(value) => Some(
+                        value
+                            .parse::<u64>()
+                            .ok()
+                            .filter(|&secs| secs > 0)
+                            .ok_or(CliError::InvalidInterval(value))?,
+                    ),
+                    None => None,
+                };
+                CliMode::Watch(interval)
+            }
+            _ => return Err(CliError::UnknownArgument(arg)),
+        };
+
+        if mode != CliMode::Applet && std::mem::discriminant(&mode) != std::mem::discriminant(&next)
+        {
+            return Err(CliError::ConflictingModes);
+        }
+        mode = next;
+    }
+
+    Ok(mode)
+}
+
+/// Short label for the period a display mode covers
+fn period_label(display_mode: DisplayMode) -> &'static str {
+    match display_mode {
+        DisplayMode::Today => "Today",
+        DisplayMode::Month => "This month",
+        DisplayMode::LastMonth => "Last month",
+        DisplayMode::AllTime => "All time",
+    }
+}
+
+/// Format usage as a single line (e.g., "Today: $1.23 | 5x | 10k in / 1.5k out / 500 reasoning")
+#[must_use]
+pub fn format_watch_line(usage: &UsageMetrics, display_mode: DisplayMode) -> String {
+    format!(
+        "{}: {} | {}x | {} in / {} out / {} reasoning",
+        period_label(display_mode),
+        format_cost(usage.total_cost),
+        usage.interaction_count,
+        format_tokens_compact(usage.total_input_tokens),
+        format_tokens_compact(usage.total_output_tokens),
+        format_tokens_compact(usage.total_reasoning_tokens),
+    )
+}
+
+/// Format one reader cache entry (e.g., "2025-10-01 14:03:22  /path/to/prt_x.json")
+#[must_use]
+pub fn format_cache_entry(path: &Path, modified: SystemTime) -> String {
+    let modified: DateTime<Local> = modified.into();
+    format!(
+        "{}  {}",
+        modified.format("%Y-%m-%d %H:%M:%S"),
+        path.display()
+    )
+}
+
+/// Read all usage once and print the reader cache, returning the process exit code
+fn dump_cache(reader: &mut OpenCodeUsageReader) -> i32 {
+    if let Err(e) = reader.get_usage() {
+        eprintln!("Error: {e}");
+        return 1;
+    }
+
+    let entries = reader.dump_cache();
+    for (path, modified) in &entries {
+        println!("{}", format_cache_entry(path, *modified));
+    }
+    println!("{} cached files", entries.len());
+    0
+}
+
+/// Print usage for the configured display mode to stdout, once or every interval
+///
+/// Returns the process exit code: non-zero if the reader cannot be created or a
+/// single `--once` read fails. Read errors in watch mode are reported on stderr
+/// and the loop keeps going.
+#[must_use]
+pub fn run(mode: CliMode, config: &AppConfig) -> i32 {
+    if mode == CliMode::DumpCache {
+        return match reader_for_config(config) {
+            Ok(mut reader) => dump_cache(&mut reader),
+            Err(e) => {
+                eprintln!("Error: failed to create OpenCode reader: {e}");
+                1
+            }
+        };
+    }
+
+    let mut source = match source_for_config(config) {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("Error: failed to create OpenCode reader: {e}");
+            return 1;
+        }
+    };
+
+    let interval = match mode {
+        CliMode::Applet | CliMode::Once | CliMode::DumpCache => None,
+        CliMode::Watch(secs) => {
+            Some(Duration::from_secs(secs.unwrap_or_else(|| {
+                u64::from(config.refresh_interval_seconds.max(1))
+            })))
+        }
+    };
+
+    loop {
+        match fetch_usage_for_mode(source.as_mut(), config.display_mode, false) {
+            Ok((usage, _, _)) => println!("{}", format_watch_line(&usage, config.display_mode)),
+            Err(e) => {
+                eprintln!("Error: {e}");
+                if interval.is_none() {
+                    return 1;
+                }
+            }
+        }
+
+        match interval {
+            Some(interval) => std::thread::sleep(interval),
+            None => return 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_parse_args_modes() {
+        assert_eq!(parse_args(args(&[])), Ok(CliMode::Applet));
+        assert_eq!(parse_args(args(&["--once"])), Ok(CliMode::Once));
+        assert_eq!(parse_args(args(&["--watch"])), Ok(CliMode::Watch(None)));
+        assert_eq!(parse_args(args(&["--dump-cache"])), Ok(CliMode::DumpCache));
+        assert_eq!(
+            parse_args(args(&["--watch", "15"])),
+            Ok(CliMode::Watch(Some(15)))
+        );
+    }
+
+    #[test]
+    fn test_parse_args_rejects_invalid_input() {
+        assert_eq!(
+            parse_args(args(&["--watch", "0"])),
+            Err(CliError::InvalidInterval("0".to_string()))
+        );
+        assert_eq!(
+            parse_args(args(&["--watch", "soon"])),
+            Err(CliError::InvalidInterval("soon".to_string()))
+        );
+        assert_eq!(
+            parse_args(args(&["--verbose"])),
+            Err(CliError::UnknownArgument("--verbose".to_string()))
+        );
+        assert_eq!(
+            parse_args(args(&["--watch", "--once"])),
+            Err(CliError::ConflictingModes)
+        );
+        assert_eq!(
+            parse_args(args(&["--dump-cache", "--once"])),
+            Err(CliError::ConflictingModes)
+        );
+    }
+
+    #[test]
+    fn test_format_watch_line() {
+        let usage = UsageMetrics {
+            total_input_tokens: 10_000,
+            total_output_tokens: 1_500,
+            total_reasoning_tokens: 500,
+            total_cache_write_tokens: 0,
+            total_cache_read_tokens: 0,
+            total_cost: 1.23,
+            total_cache_cost: 0.0,
+            interaction_count: 5,
+            timestamp: SystemTime::now(),
+        };
+
+        assert_eq!(
+            format_watch_line(&usage, DisplayMode::Today),
+            "Today: $1.23 | 5x | 10k in / 1.5k out / 500 reasoning"
+        );
+        assert!(format_watch_line(&usage, DisplayMode::AllTime).starts_with("All time: "));
+    }
+}
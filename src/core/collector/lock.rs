@@ -0,0 +1,134 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Lock file marking the applet instance that collects snapshots.
+//!
+//! When several applet instances run against the same database, only the one
+//! holding the lock collects; the others keep showing live metrics.
+
+use rustix::fs::{flock, FlockOperation};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Extension of the lock file placed next to the database.
+pub const LOCK_FILE_EXTENSION: &str = "lock";
+
+/// Exclusive claim on snapshot collection for one database, released on drop.
+///
+/// The claim is an advisory `flock` on the lock file, held for as long as the
+/// file stays open, so the kernel releases it when the holder exits or crashes.
+#[derive(Debug)]
+pub struct InstanceLock {
+    path: PathBuf,
+    _file: File,
+}
+
+impl InstanceLock {
+    /// Returns the lock file path for a database file.
+    #[must_use]
+    pub fn path_for(db_path: &Path) -> PathBuf {
+        db_path.with_extension(LOCK_FILE_EXTENSION)
+    }
+
+    /// Tries to take the lock at `path`, recording this process id in it.
+    ///
+    /// Returns `Ok(None)` if another instance already holds the lock. The lock
+    /// file itself is never removed, so every instance locks the same file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the lock file cannot be opened, locked or written.
+    pub fn try_acquire(path: &Path) -> io::Result<Option<Self>> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        match flock(&file, FlockOperation::NonBlockingLockExclusive) {
+            Ok(()) => {}
+            Err(errno) if errno == rustix::io::Errno::WOULDBLOCK => return Ok(None),
+            Err(errno) => return Err(errno.into()),
+        }
+
+        // The pid is informational only: holding the flock is what counts
+        file.set_len(0)?;
+        write!(file, "{}", std::process::id())?;
+
+        Ok(Some(Self {
+            path: path.to_path_buf(),
+            _file: file,
+        }))
+    }
+
+    /// Returns the path of the held lock file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_lock_path_sits_next_to_database() {
+        assert_eq!(
+            InstanceLock::path_for(Path::new("/data/usage.db")),
+            PathBuf::from("/data/usage.lock")
+        );
+    }
+
+    #[test]
+    fn test_second_acquire_contends_until_release() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("usage.lock");
+
+        let first = InstanceLock::try_acquire(&path).unwrap();
+        assert!(first.is_some());
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            std::process::id().to_string()
+        );
+
+        // The first lock is still open, so the flock is held
+        assert!(InstanceLock::try_acquire(&path).unwrap().is_none());
+
+        drop(first);
+        assert!(path.exists(), "Releasing the lock keeps the file");
+        assert!(InstanceLock::try_acquire(&path).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_leftover_lock_file_is_reused() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("usage.lock");
+        // A crashed holder leaves its file behind, but not its flock
+        fs::write(&path, u32::MAX.to_string()).unwrap();
+
+        let lock = InstanceLock::try_acquire(&path).unwrap();
+
+        assert!(lock.is_some());
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            std::process::id().to_string()
+        );
+    }
+
+    #[test]
+    fn test_contending_acquire_leaves_holder_file_alone() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("usage.lock");
+
+        let _held = InstanceLock::try_acquire(&path).unwrap().unwrap();
+        assert!(InstanceLock::try_acquire(&path).unwrap().is_none());
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            std::process::id().to_string()
+        );
+    }
+}
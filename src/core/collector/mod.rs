@@ -4,9 +4,11 @@
 //!
 //! This module provides business logic for when and how to collect usage snapshots.
 
+pub mod lock;
+
 use crate::core::database::{repository::UsageRepository, DatabaseManager};
-use crate::core::opencode::UsageMetrics;
-use chrono::NaiveDate;
+use crate::core::opencode::{OpenCodeUsageReader, ReaderError, UsageMetrics};
+use chrono::{Days, NaiveDate};
 use std::sync::{Arc, Mutex};
 
 /// Error type for collector operations.
@@ -14,17 +16,53 @@ use std::sync::{Arc, Mutex};
 pub enum CollectorError {
     /// Database error occurred
     #[error("Database error: {0}")]
-    Database(#[from] crate::core::database::DatabaseError),
+    Database(crate::core::database::DatabaseError),
+
+    /// Database is locked by another connection; the write can be retried later
+    #[error("History temporarily busy, will retry")]
+    DatabaseBusy,
 
     /// Failed to acquire lock
     #[error("Failed to acquire lock")]
     LockError,
+
+    /// Retention period of zero days, which would delete every snapshot
+    #[error("Snapshots must be kept for at least one day")]
+    InvalidRetention,
+}
+
+impl From<crate::core::database::DatabaseError> for CollectorError {
+    fn from(err: crate::core::database::DatabaseError) -> Self {
+        if err.is_busy() {
+            Self::DatabaseBusy
+        } else {
+            Self::Database(err)
+        }
+    }
+}
+
+/// Lifetime interaction counts that trigger a one-time notification.
+pub const INTERACTION_MILESTONES: [u64; 3] = [1_000, 5_000, 10_000];
+
+/// Returns the milestones reached by `total` that are not yet in `reached`.
+#[must_use]
+pub fn new_milestones(total: u64, reached: &[u64]) -> Vec<u64> {
+    INTERACTION_MILESTONES
+        .iter()
+        .copied()
+        .filter(|milestone| total >= *milestone && !reached.contains(milestone))
+        .collect()
 }
 
 /// Manages data collection timing and logic.
 pub struct DataCollector {
     repository: UsageRepository,
     last_collection: Arc<Mutex<Option<NaiveDate>>>,
+    in_memory: bool,
+    /// Days of snapshots kept when pruning after each daily collection, `None` keeps all
+    retention_days: Option<u32>,
+    /// Held while this instance collects, so other instances defer to it
+    instance_lock: Option<lock::InstanceLock>,
 }
 
 impl DataCollector {
@@ -32,11 +70,36 @@ impl DataCollector {
     #[must_use]
     pub fn new(db: Arc<DatabaseManager>) -> Self {
         Self {
+            in_memory: db.is_in_memory(),
             repository: UsageRepository::new(db),
             last_collection: Arc::new(Mutex::new(None)),
+            retention_days: None,
+            instance_lock: None,
         }
     }
 
+    /// Prunes snapshots older than `keep_days` after each daily collection.
+    ///
+    /// `None` keeps every snapshot.
+    #[must_use]
+    pub fn with_retention_days(mut self, keep_days: Option<u32>) -> Self {
+        self.retention_days = keep_days;
+        self
+    }
+
+    /// Keeps the instance lock for as long as this collector lives.
+    #[must_use]
+    pub fn with_instance_lock(mut self, lock: lock::InstanceLock) -> Self {
+        self.instance_lock = Some(lock);
+        self
+    }
+
+    /// Returns whether snapshots are kept in memory only and lost on exit.
+    #[must_use]
+    pub fn is_in_memory(&self) -> bool {
+        self.in_memory
+    }
+
     /// Checks if collection should happen based on date change.
     #[must_use]
     pub fn should_collect(&self) -> bool {
@@ -59,14 +122,20 @@ impl DataCollector {
         self.last_collection.lock().ok().and_then(|guard| *guard)
     }
 
-    /// Collects and saves a usage snapshot if it hasn't been done today.
+    /// Saves today's usage as today's snapshot, replacing the one saved earlier today.
     ///
-    /// Returns `Ok(true)` if snapshot was saved, `Ok(false)` if already collected today.
+    /// `today` must cover the current day only, never a month or all-time total, so
+    /// that summing snapshots gives correct totals. Called on every fetch, the snapshot
+    /// follows the day's usage as it grows.
+    ///
+    /// Returns `Ok(true)` for the first save of the day, `Ok(false)` for an update.
+    /// With a retention period set, old snapshots are pruned after the first save of
+    /// the day; a failed prune is logged and retried with the next day's collection.
     ///
     /// # Errors
     ///
     /// Returns `CollectorError` if database operation fails or lock cannot be acquired.
-    pub fn collect_and_save(&self, metrics: &UsageMetrics) -> Result<bool, CollectorError> {
+    pub fn collect_and_save(&self, today: &UsageMetrics) -> Result<bool, CollectorError> {
         let current_date = chrono::Utc::now().date_naive();
 
         // Acquire lock
@@ -75,23 +144,121 @@ impl DataCollector {
             .lock()
             .map_err(|_| CollectorError::LockError)?;
 
-        // Check if we should collect
-        let should_save = match *last_guard {
+        let first_today = match *last_guard {
             None => true,
             Some(last_date) => current_date != last_date,
         };
 
-        if should_save {
-            // Save to database
-            self.repository.save_snapshot(current_date, metrics)?;
+        // Upsert, so a later fetch replaces today's earlier figures
+        self.repository.save_snapshot(current_date, today)?;
+        *last_guard = Some(current_date);
+
+        if first_today {
+            if let Some(keep_days) = self.retention_days {
+                match self.prune_old_snapshots(keep_days) {
+                    Ok(0) => {}
+                    Ok(pruned) => eprintln!("[DataCollector] Pruned {pruned} old snapshot(s)"),
+                    Err(e) => eprintln!("[DataCollector] Failed to prune old snapshots: {e}"),
+                }
+            }
+        }
 
-            // Update last collection date
-            *last_guard = Some(current_date);
+        Ok(first_today)
+    }
 
-            Ok(true)
-        } else {
-            Ok(false)
+    /// Deletes snapshots older than `keep_days` days before today.
+    ///
+    /// Returns how many snapshots were deleted; calling it again the same day deletes none.
+    /// A period reaching past the earliest representable date keeps everything.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CollectorError::InvalidRetention` if `keep_days` is 0, or
+    /// `CollectorError` if the database operation fails.
+    pub fn prune_old_snapshots(&self, keep_days: u32) -> Result<usize, CollectorError> {
+        if keep_days == 0 {
+            return Err(CollectorError::InvalidRetention);
         }
+        let Some(cutoff) = chrono::Utc::now()
+            .date_naive()
+            .checked_sub_days(Days::new(u64::from(keep_days)))
+        else {
+            return Ok(0);
+        };
+        Ok(self.repository.delete_snapshots_before(cutoff)?)
+    }
+
+    /// Saves a snapshot for a specific date, replacing any existing one.
+    ///
+    /// Unlike `collect_and_save`, this does not affect the daily collection schedule.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CollectorError` if the database operation fails.
+    pub fn recompute_and_save(
+        &self,
+        date: NaiveDate,
+        metrics: &UsageMetrics,
+    ) -> Result<(), CollectorError> {
+        self.repository.save_snapshot(date, metrics)?;
+        Ok(())
+    }
+
+    /// Fills snapshots for missing dates from the usage files.
+    ///
+    /// Dates without any usage files are skipped. Returns how many dates were filled.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CollectorError` if saving a snapshot fails.
+    pub fn backfill_dates(
+        &self,
+        reader: &mut OpenCodeUsageReader,
+        dates: &[NaiveDate],
+    ) -> Result<usize, CollectorError> {
+        let mut filled = 0;
+        for &date in dates {
+            match reader.get_usage_for_day(date) {
+                Ok(metrics) if metrics.interaction_count > 0 => {
+                    self.recompute_and_save(date, &metrics)?;
+                    filled += 1;
+                }
+                Ok(_) | Err(ReaderError::NoDataFound) => {}
+                Err(e) => eprintln!("[Backfill] Skipping {date}: {e}"),
+            }
+        }
+        Ok(filled)
+    }
+
+    /// Checks the lifetime interaction total against the milestone thresholds.
+    ///
+    /// Returns the milestones crossed that are not already in `reached`, so each
+    /// milestone is reported only once.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CollectorError` if the database operation fails.
+    pub fn check_milestones(&self, reached: &[u64]) -> Result<Vec<u64>, CollectorError> {
+        let total = self.repository.get_total_interactions()?;
+        Ok(new_milestones(u64::try_from(total).unwrap_or(0), reached))
+    }
+
+    /// Returns the repository the snapshots are stored in.
+    #[must_use]
+    pub fn repository(&self) -> &UsageRepository {
+        &self.repository
+    }
+
+    /// Returns all-time usage summed from the stored per-day snapshots.
+    ///
+    /// This avoids a full scan of the usage files. Today's snapshot is replaced on
+    /// every collection, so the total only lags behind by the latest fetch.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CollectorError` if the database operation fails.
+    pub fn all_time_usage(&self) -> Result<UsageMetrics, CollectorError> {
+        Ok(self.repository.get_all_time_usage()?)
     }
 }
 
@@ -107,6 +274,20 @@ mod tests {
         Arc::new(DatabaseManager::new_with_path(&db_path).unwrap())
     }
 
+    fn create_test_metrics() -> UsageMetrics {
+        UsageMetrics {
+            total_input_tokens: 100,
+            total_output_tokens: 50,
+            total_reasoning_tokens: 0,
+            total_cache_write_tokens: 0,
+            total_cache_read_tokens: 0,
+            total_cost: 0.1,
+            total_cache_cost: 0.0,
+            interaction_count: 1,
+            timestamp: std::time::SystemTime::now(),
+        }
+    }
+
     #[test]
     fn test_collector_creation() {
         let db = create_test_db();
@@ -114,6 +295,14 @@ mod tests {
         // If we get here, collector was created successfully
     }
 
+    #[test]
+    fn test_collector_reports_in_memory_database() {
+        assert!(!DataCollector::new(create_test_db()).is_in_memory());
+
+        let memory = Arc::new(DatabaseManager::new_in_memory().unwrap());
+        assert!(DataCollector::new(memory).is_in_memory());
+    }
+
     #[test]
     fn test_should_collect_first_time() {
         let db = create_test_db();
@@ -188,6 +377,7 @@ mod tests {
             total_cache_write_tokens: 10,
             total_cache_read_tokens: 5,
             total_cost: 1.5,
+            total_cache_cost: 0.0,
             interaction_count: 1,
             timestamp: SystemTime::now(),
         };
@@ -219,6 +409,7 @@ mod tests {
             total_cache_write_tokens: 10,
             total_cache_read_tokens: 5,
             total_cost: 1.5,
+            total_cache_cost: 0.0,
             interaction_count: 1,
             timestamp: SystemTime::now(),
         };
@@ -228,16 +419,23 @@ mod tests {
         assert!(result1.is_ok());
         assert!(result1.unwrap());
 
-        // Second collection same day
-        let result2 = collector.collect_and_save(&metrics);
+        // Second collection same day, after more usage
+        let later = UsageMetrics {
+            total_input_tokens: 300,
+            interaction_count: 3,
+            ..metrics
+        };
+        let result2 = collector.collect_and_save(&later);
         assert!(result2.is_ok());
         assert!(!result2.unwrap());
 
-        // Verify only one snapshot in database
+        // The single snapshot for today holds the latest figures
         let today = chrono::Utc::now().date_naive();
         let repository = UsageRepository::new(db);
-        let snapshot = repository.get_snapshot(today).unwrap();
-        assert!(snapshot.is_some());
+        let snapshot = repository.get_snapshot(today).unwrap().unwrap();
+        assert_eq!(snapshot.input_tokens, 300);
+        assert_eq!(snapshot.interaction_count, 3);
+        assert_eq!(repository.snapshot_count().unwrap(), 1);
     }
 
     #[test]
@@ -254,6 +452,7 @@ mod tests {
             total_cache_write_tokens: 10,
             total_cache_read_tokens: 5,
             total_cost: 1.5,
+            total_cache_cost: 0.0,
             interaction_count: 1,
             timestamp: SystemTime::now(),
         };
@@ -298,6 +497,7 @@ mod tests {
             total_cache_write_tokens: 10,
             total_cache_read_tokens: 5,
             total_cost: 1.5,
+            total_cache_cost: 0.0,
             interaction_count: 1,
             timestamp: SystemTime::now(),
         };
@@ -331,4 +531,167 @@ mod tests {
             .count();
         assert_eq!(false_count, 4);
     }
+
+    #[test]
+    fn test_busy_database_error_maps_to_database_busy() {
+        use crate::core::database::DatabaseError;
+
+        let busy = |code| {
+            DatabaseError::SqlError(rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error::new(code),
+                Some("database is locked".to_string()),
+            ))
+        };
+
+        assert!(matches!(
+            CollectorError::from(busy(rusqlite::ffi::SQLITE_BUSY)),
+            CollectorError::DatabaseBusy
+        ));
+        assert!(matches!(
+            CollectorError::from(busy(rusqlite::ffi::SQLITE_LOCKED)),
+            CollectorError::DatabaseBusy
+        ));
+        assert!(matches!(
+            CollectorError::from(busy(rusqlite::ffi::SQLITE_CORRUPT)),
+            CollectorError::Database(_)
+        ));
+    }
+
+    #[test]
+    fn test_new_milestones() {
+        assert!(new_milestones(999, &[]).is_empty());
+        assert_eq!(new_milestones(1_000, &[]), vec![1_000]);
+        assert_eq!(new_milestones(6_000, &[1_000]), vec![5_000]);
+        assert_eq!(new_milestones(12_000, &[]), vec![1_000, 5_000, 10_000]);
+    }
+
+    #[test]
+    fn test_check_milestones_fires_once() {
+        let db = create_test_db();
+        let repository = UsageRepository::new(Arc::clone(&db));
+        let collector = DataCollector::new(db);
+
+        let metrics = UsageMetrics {
+            total_input_tokens: 0,
+            total_output_tokens: 0,
+            total_reasoning_tokens: 0,
+            total_cache_write_tokens: 0,
+            total_cache_read_tokens: 0,
+            total_cost: 0.0,
+            total_cache_cost: 0.0,
+            interaction_count: 600,
+            timestamp: std::time::SystemTime::now(),
+        };
+        repository
+            .save_snapshot(NaiveDate::from_ymd_opt(2025, 10, 1).unwrap(), &metrics)
+            .unwrap();
+        assert!(collector.check_milestones(&[]).unwrap().is_empty());
+
+        // Crossing 1000 fires the milestone
+        repository
+            .save_snapshot(NaiveDate::from_ymd_opt(2025, 10, 2).unwrap(), &metrics)
+            .unwrap();
+        let crossed = collector.check_milestones(&[]).unwrap();
+        assert_eq!(crossed, vec![1_000]);
+
+        // Once recorded as reached, it does not fire again
+        assert!(collector.check_milestones(&crossed).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_backfill_fills_only_dates_with_files() {
+        let storage_dir = TempDir::new().unwrap();
+        let filled_date = NaiveDate::from_ymd_opt(2025, 3, 10).unwrap();
+        let empty_date = NaiveDate::from_ymd_opt(2025, 3, 11).unwrap();
+
+        let part = storage_dir.path().join("prt_backfill.json");
+        std::fs::write(
+            &part,
+            r#"{
+                "id": "prt_backfill",
+                "messageID": "msg_backfill",
+                "sessionID": "ses_backfill",
+                "type": "step-finish",
+                "tokens": {"input": 1000, "output": 500, "reasoning": 0, "cache": {"write": 0, "read": 0}},
+                "cost": 0.25
+            }"#,
+        )
+        .unwrap();
+        let noon = filled_date.and_hms_opt(12, 0, 0).unwrap().and_utc();
+        filetime::set_file_mtime(
+            &part,
+            filetime::FileTime::from_unix_time(noon.timestamp(), 0),
+        )
+        .unwrap();
+
+        let db = create_test_db();
+        let collector = DataCollector::new(Arc::clone(&db));
+        let mut reader =
+            OpenCodeUsageReader::new_with_path(storage_dir.path().to_str().unwrap()).unwrap();
+
+        let filled = collector
+            .backfill_dates(&mut reader, &[filled_date, empty_date])
+            .unwrap();
+        assert_eq!(filled, 1);
+
+        let repository = UsageRepository::new(db);
+        let snapshot = repository.get_snapshot(filled_date).unwrap().unwrap();
+        assert_eq!(snapshot.input_tokens, 1000);
+        assert_eq!(snapshot.interaction_count, 1);
+        assert!(repository.get_snapshot(empty_date).unwrap().is_none());
+
+        // Backfilling leaves today's collection schedule alone
+        assert!(collector.get_last_collection_date().is_none());
+    }
+
+    #[test]
+    fn test_prune_old_snapshots() {
+        let db = create_test_db();
+        let collector = DataCollector::new(Arc::clone(&db));
+        let today = chrono::Utc::now().date_naive();
+        let metrics = create_test_metrics();
+        for days_ago in [0, 10, 400, 800] {
+            collector
+                .recompute_and_save(today - chrono::Duration::days(days_ago), &metrics)
+                .unwrap();
+        }
+
+        assert_eq!(collector.prune_old_snapshots(365).unwrap(), 2);
+        assert_eq!(collector.prune_old_snapshots(365).unwrap(), 0);
+        assert_eq!(UsageRepository::new(db).snapshot_count().unwrap(), 2);
+
+        assert!(matches!(
+            collector.prune_old_snapshots(0),
+            Err(CollectorError::InvalidRetention)
+        ));
+    }
+
+    #[test]
+    fn test_prune_with_huge_retention_keeps_everything() {
+        let db = create_test_db();
+        let collector = DataCollector::new(Arc::clone(&db));
+        let today = chrono::Utc::now().date_naive();
+        collector
+            .recompute_and_save(today - chrono::Duration::days(800), &create_test_metrics())
+            .unwrap();
+
+        assert_eq!(collector.prune_old_snapshots(u32::MAX).unwrap(), 0);
+        assert_eq!(UsageRepository::new(db).snapshot_count().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_collect_and_save_prunes_with_retention() {
+        let db = create_test_db();
+        let collector = DataCollector::new(Arc::clone(&db)).with_retention_days(Some(30));
+        let today = chrono::Utc::now().date_naive();
+        let metrics = create_test_metrics();
+        let old = today - chrono::Duration::days(60);
+        collector.recompute_and_save(old, &metrics).unwrap();
+
+        assert!(collector.collect_and_save(&metrics).unwrap());
+
+        let repository = UsageRepository::new(db);
+        assert!(repository.get_snapshot(old).unwrap().is_none());
+        assert!(repository.get_snapshot(today).unwrap().is_some());
+    }
 }
@@ -2,6 +2,7 @@
 
 //! Configuration management for the `OpenCode` usage applet
 
+use crate::core::opencode::InteractionUnit;
 use crate::ui::state::DisplayMode;
 use cosmic_config::CosmicConfigEntry;
 use serde::{Deserialize, Serialize};
@@ -11,12 +12,32 @@ use thiserror::Error;
 /// Application identifier for COSMIC config system
 pub const APP_ID: &str = "com.vasilvestre.CosmicAppletOpencodeUsage";
 pub const CONFIG_VERSION: u64 = 1;
+/// Environment variable overriding the directory configuration is stored in
+pub const CONFIG_DIR_ENV: &str = "OPENCODE_USAGE_CONFIG_DIR";
+/// Environment variable choosing the data namespace of this process
+pub const DATA_NAMESPACE_ENV: &str = "OPENCODE_USAGE_NAMESPACE";
 
 /// Configuration error types
 #[derive(Debug, Error, PartialEq, Clone)]
 pub enum ConfigError {
     #[error("Refresh interval must be between 1 and 3600 seconds (got {0})")]
     InvalidRefreshInterval(u32),
+    #[error("Monthly budget must be a positive amount (got {0:?})")]
+    InvalidBudget(String),
+    #[error("Warning color must be a hex color like #e01b24 (got {0:?})")]
+    InvalidColor(String),
+    #[error("Day reset hour must be between 0 and 23 (got {0})")]
+    InvalidDayResetHour(u8),
+    #[error("Compact token decimals must be between 0 and 2 (got {0})")]
+    InvalidCompactTokenDecimals(u8),
+    #[error("Percent precision must be between 0 and 2 (got {0})")]
+    InvalidPercentPrecision(u8),
+    #[error("Snapshot retention must be at least 1 day (got {0})")]
+    InvalidSnapshotRetention(u32),
+    #[error("Data namespace must be a single non-empty directory name (got {0:?})")]
+    InvalidDataNamespace(String),
+    #[error("Fetch timeout must be a whole number of seconds (got {0:?})")]
+    InvalidFetchTimeout(String),
     #[error("Failed to load config: {0}")]
     LoadError(String),
     #[error("Failed to save config: {0}")]
@@ -51,6 +72,113 @@ impl Default for PanelMetric {
     }
 }
 
+impl PanelMetric {
+    /// All panel metrics in their default display order
+    pub const DEFAULT_ORDER: [PanelMetric; 5] = [
+        PanelMetric::Cost,
+        PanelMetric::Interactions,
+        PanelMetric::InputTokens,
+        PanelMetric::OutputTokens,
+        PanelMetric::ReasoningTokens,
+    ];
+
+    /// Human-readable name for settings
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            PanelMetric::Cost => "Cost",
+            PanelMetric::Interactions => "Interactions",
+            PanelMetric::InputTokens => "Input Tokens",
+            PanelMetric::OutputTokens => "Output Tokens",
+            PanelMetric::ReasoningTokens => "Reasoning Tokens",
+        }
+    }
+}
+
+/// When to show the reasoning tokens row in the popup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ReasoningVisibility {
+    /// Always show reasoning tokens
+    #[default]
+    Always,
+    /// Never show reasoning tokens
+    Never,
+    /// Hide reasoning tokens only if no reasoning tokens were ever recorded
+    Auto,
+}
+
+impl ReasoningVisibility {
+    /// Decide whether to show reasoning tokens
+    ///
+    /// `has_reasoning_history` is the cached all-time determination: `Some(false)` means no
+    /// reasoning tokens were ever recorded, `None` means it is not known yet (shown in Auto).
+    #[must_use]
+    pub fn should_show(self, has_reasoning_history: Option<bool>) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto => has_reasoning_history.unwrap_or(true),
+        }
+    }
+}
+
+/// Statistic used for daily summaries in the viewer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CentralTendency {
+    /// Average over the days of the range
+    #[default]
+    Mean,
+    /// Middle value of the days of the range, less skewed by occasional huge days
+    Median,
+}
+
+/// Which days count as active for per-active-day averages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ActiveDayDefinition {
+    /// Days with any interactions or tokens
+    #[default]
+    AnyUsage,
+    /// Only days with a reported cost, so free local-model days are skipped
+    NonzeroCost,
+}
+
+/// Single metric featured in the panel when no panel metrics are selected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PrimaryMetric {
+    /// Total cost (e.g., "$1.23")
+    #[default]
+    Cost,
+    /// Input plus output tokens (e.g., "15.5k")
+    TotalTokens,
+    /// Interaction count (e.g., "5x")
+    Interactions,
+}
+
+/// Colors used for the series in the viewer charts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ChartPalette {
+    /// Original chart colors
+    #[default]
+    Default,
+    /// Okabe-Ito colors, distinguishable with common color vision deficiencies
+    ColorBlindSafe,
+}
+
+/// Where month and all-time totals are read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MetricSource {
+    /// Aggregate every storage file
+    #[default]
+    Files,
+    /// Sum the stored daily snapshots, reading only today from the files
+    Database,
+}
+
+/// Budget warning color used on light themes when no custom color is set
+pub const BUDGET_WARNING_LIGHT: [u8; 3] = [0xc0, 0x1c, 0x28];
+/// Budget warning color used on dark themes when no custom color is set
+pub const BUDGET_WARNING_DARK: [u8; 3] = [0xf6, 0x61, 0x51];
+
 /// Application configuration
 #[derive(
     Debug,
@@ -61,17 +189,111 @@ impl Default for PanelMetric {
     cosmic_config::cosmic_config_derive::CosmicConfigEntry,
 )]
 #[version = 1]
+#[allow(clippy::struct_excessive_bools)] // Independent user-facing toggles
 pub struct AppConfig {
-    /// Path to `OpenCode` storage directory (optional, defaults to ~/.local/share/opencode/storage/part)
+    /// Path to `OpenCode` storage directory (optional, auto-detected from the XDG data directory)
     pub storage_path: Option<PathBuf>,
     /// Refresh interval in seconds (default: 60 = 1 minute)
     pub refresh_interval_seconds: u32,
     /// Which metrics to show next to the icon in the panel (default: all metrics enabled)
     pub panel_metrics: Vec<PanelMetric>,
+    /// Order in which the selected panel metrics are shown (default: cost first, reasoning last)
+    pub panel_metric_order: Vec<PanelMetric>,
     /// Use raw token values instead of formatted (K/M) suffixes (default: false)
     pub use_raw_token_display: bool,
     /// Display mode for usage metrics (default: Today)
     pub display_mode: DisplayMode,
+    /// Store daily usage snapshots in the local history database (default: true)
+    pub history_enabled: bool,
+    /// Lifetime interaction milestones already notified (default: none)
+    pub milestones_reached: Vec<u64>,
+    /// Divide viewer averages by active days instead of calendar days (default: false)
+    pub average_per_active_day: bool,
+    /// Add cache tokens to input/output token display (default: false)
+    pub fold_cache_into_io: bool,
+    /// When to show the reasoning tokens row (default: Always)
+    pub reasoning_visibility: ReasoningVisibility,
+    /// Monthly cost budget in dollars, `None` disables the budget warning (default: none)
+    pub monthly_budget: Option<f64>,
+    /// RGB color of the panel text once over budget, `None` uses a theme-appropriate red (default: none)
+    pub budget_warning_color: Option<[u8; 3]>,
+    /// Also switch the panel icon to a warning icon once over budget (default: true)
+    pub budget_warning_icon: bool,
+    /// Whether viewer daily summaries use the mean or the median (default: Mean)
+    pub central_tendency: CentralTendency,
+    /// Show interaction counts compactly in the panel, e.g. "1.2kx" (default: false)
+    pub compact_interactions: bool,
+    /// Maximum threads used to scan and parse usage files for all-time reads, `None` uses rayon's default (default: none)
+    pub parse_threads: Option<usize>,
+    /// Close the popup after this many seconds without interaction, `None` keeps it open (default: none)
+    pub popup_auto_close_seconds: Option<u32>,
+    /// Count cache read/write tokens in total-token figures (default: false)
+    pub include_cache_in_total: bool,
+    /// Hour (0-23, UTC) at which "today" starts, for days that span midnight (default: 0)
+    ///
+    /// Days follow UTC like the stored snapshot dates, so 0 is midnight UTC rather
+    /// than local midnight.
+    pub day_reset_hour: u8,
+    /// Keep only per-file token and cost figures in the parse cache to save memory (default: false)
+    pub lean_cache: bool,
+    /// Thousands separator for raw token display (locale default when unset)
+    pub raw_token_separator: Option<char>,
+    /// Deadline in seconds for the all-time scan (`None` or 0 waits indefinitely)
+    pub fetch_timeout_seconds: Option<u32>,
+    /// Show only the icon in the panel, leaving metrics to the popup
+    pub panel_icon_only: bool,
+    /// Maximum characters of panel text before it is truncated (`None` for no limit)
+    pub panel_max_chars: Option<usize>,
+    /// Fewest characters worth showing after truncation; below this the panel is icon-only
+    pub panel_min_chars: usize,
+    /// Decimals shown for compact token values under 10k/10M (0-2)
+    pub compact_token_decimals: u8,
+    /// Metric shown next to the icon when no panel metrics are selected, `None` leaves just the icon (default: none)
+    pub primary_metric: Option<PrimaryMetric>,
+    /// Quick-range presets for the viewer charts, e.g. "7d", "YTD" or "All" (default: 7d, 30d, 90d, YTD, All)
+    pub viewer_range_presets: Vec<String>,
+    /// Rows per page in the viewer history table (default: 50)
+    pub viewer_page_size: usize,
+    /// Serve the current metrics as JSON on a local socket for status bars (default: false)
+    pub expose_socket: bool,
+    /// Path of the metrics socket, `None` uses the runtime directory (default: none)
+    pub socket_path: Option<PathBuf>,
+    /// Shrink text sizes and paddings in the popup and settings for small screens
+    pub compact_ui: bool,
+    /// Period shown first when the popup opens and emphasized in it (None keeps the last-used period)
+    pub primary_period: Option<DisplayMode>,
+    /// Colors used for the series in the viewer charts
+    pub chart_palette: ChartPalette,
+    /// Whether the first-run introduction has been dismissed
+    pub onboarding_completed: bool,
+    /// Smallest token count shown in compact "k" form; lower counts stay exact (default: 1000)
+    pub compact_threshold: u64,
+    /// All-time metric appended to the panel text whatever the display mode, e.g. "· All $500" (default: none)
+    pub pinned_panel_note: Option<PanelMetric>,
+    /// Flag all-time totals that drop below the previous scan, e.g. after usage files were removed (default: false)
+    pub flag_usage_decrease: bool,
+    /// Run with live panel metrics only: no history database, collection or viewer (default: false)
+    pub panel_only: bool,
+    /// What counts as one interaction: each usage part or each session (default: Part)
+    pub interaction_unit: InteractionUnit,
+    /// Where month and all-time totals are read from
+    pub metric_source: MetricSource,
+    /// Show full-precision cost in the popup instead of rounding to cents
+    pub use_raw_cost_display: bool,
+    /// Decimals shown in percent changes (0-2)
+    pub percent_precision: u8,
+    /// Read usage from a summary manifest at `storage_path` instead of scanning part files
+    pub manifest_mode: bool,
+    /// Consecutive fetch failures that keep showing the last data as stale before showing an error
+    pub error_grace_failures: u32,
+    /// Which days count as active when averaging per active day (default: AnyUsage)
+    pub active_day_definition: ActiveDayDefinition,
+    /// Days of history snapshots to keep, older ones are pruned daily; `None` keeps everything (default: none)
+    pub snapshot_retention_days: Option<u32>,
+    /// Keeps config and history in a subdirectory with this name, `None` uses the shared location (default: none)
+    ///
+    /// Chosen per process through `OPENCODE_USAGE_NAMESPACE`; never saved.
+    pub data_namespace: Option<String>,
 }
 
 impl Default for AppConfig {
@@ -79,20 +301,90 @@ impl Default for AppConfig {
         Self {
             storage_path: None, // Will use default path from OpenCodeUsageReader
             refresh_interval_seconds: 60,
-            panel_metrics: vec![
-                PanelMetric::Cost,
-                PanelMetric::Interactions,
-                PanelMetric::InputTokens,
-                PanelMetric::OutputTokens,
-                PanelMetric::ReasoningTokens,
-            ],
+            panel_metrics: PanelMetric::DEFAULT_ORDER.to_vec(),
+            panel_metric_order: PanelMetric::DEFAULT_ORDER.to_vec(),
             use_raw_token_display: false,
             display_mode: DisplayMode::Today,
+            history_enabled: true,
+            milestones_reached: Vec::new(),
+            average_per_active_day: false,
+            fold_cache_into_io: false,
+            reasoning_visibility: ReasoningVisibility::Always,
+            monthly_budget: None,
+            budget_warning_color: None,
+            budget_warning_icon: true,
+            central_tendency: CentralTendency::Mean,
+            compact_interactions: false,
+            parse_threads: None,
+            popup_auto_close_seconds: None,
+            include_cache_in_total: false,
+            day_reset_hour: 0,
+            lean_cache: false,
+            raw_token_separator: None,
+            fetch_timeout_seconds: None,
+            panel_icon_only: false,
+            panel_max_chars: None,
+            panel_min_chars: 4,
+            compact_token_decimals: 1,
+            primary_metric: None,
+            viewer_range_presets: vec![
+                "7d".to_string(),
+                "30d".to_string(),
+                "90d".to_string(),
+                "YTD".to_string(),
+                "All".to_string(),
+            ],
+            viewer_page_size: 50,
+            expose_socket: false,
+            socket_path: None,
+            compact_ui: false,
+            primary_period: None,
+            chart_palette: ChartPalette::Default,
+            onboarding_completed: false,
+            compact_threshold: 1_000,
+            pinned_panel_note: None,
+            flag_usage_decrease: false,
+            panel_only: false,
+            interaction_unit: InteractionUnit::Part,
+            metric_source: MetricSource::Files,
+            use_raw_cost_display: false,
+            percent_precision: 0,
+            manifest_mode: false,
+            error_grace_failures: 0,
+            active_day_definition: ActiveDayDefinition::AnyUsage,
+            snapshot_retention_days: None,
+            data_namespace: None,
         }
     }
 }
 
 impl AppConfig {
+    /// Whether daily history is kept, which needs the database
+    ///
+    /// False when history is turned off or the applet runs panel-only.
+    #[must_use]
+    pub fn keeps_history(&self) -> bool {
+        self.history_enabled && !self.panel_only
+    }
+
+    /// Whether the panel shows any of today's figures, so today's usage must be fetched
+    #[must_use]
+    pub fn needs_today_usage(&self) -> bool {
+        !self.panel_metrics.is_empty() || self.primary_metric.is_some()
+    }
+
+    /// Color to use for the panel text once over budget
+    ///
+    /// Falls back to a red suited to the current theme when no custom color is set.
+    #[must_use]
+    pub fn budget_warning_rgb(&self, is_dark_theme: bool) -> [u8; 3] {
+        self.budget_warning_color.unwrap_or(if is_dark_theme {
+            BUDGET_WARNING_DARK
+        } else {
+            BUDGET_WARNING_LIGHT
+        })
+    }
+
     /// Creates a new config with default values
     #[must_use]
     pub fn new() -> Self {
@@ -104,8 +396,27 @@ impl AppConfig {
     ///
     /// # Errors
     /// Returns an error if the config system cannot be accessed or initialized.
+    ///
+    /// When `OPENCODE_USAGE_NAMESPACE` names a data namespace, the config nested
+    /// under that namespace is loaded instead of the shared one.
     pub fn load() -> Result<Self, ConfigError> {
-        Self::load_with_id(APP_ID)
+        Self::load_in(APP_ID, data_namespace_override())
+    }
+
+    /// Loads the config of `app_id`, or the one nested under `namespace` when given
+    ///
+    /// An invalid namespace is logged and the shared config is used.
+    fn load_in(app_id: &str, namespace: Option<String>) -> Result<Self, ConfigError> {
+        let namespace = namespace.filter(|namespace| match validate_data_namespace(namespace) {
+            Ok(()) => true,
+            Err(e) => {
+                eprintln!("Warning: {e}, using the shared config and history");
+                false
+            }
+        });
+        let mut config = Self::load_with_id(&namespaced_config_id(app_id, namespace.as_deref()))?;
+        config.data_namespace = namespace;
+        Ok(config)
     }
 
     /// Cleans up obsolete configuration keys from previous versions
@@ -130,68 +441,187 @@ impl AppConfig {
     /// Returns an error if the config cannot be loaded from the COSMIC config system.
     #[cfg(test)]
     pub fn load_with_id(app_id: &str) -> Result<Self, ConfigError> {
-        use cosmic::cosmic_config::{Config, ConfigGet};
+        Self::load_from_id(app_id)
+    }
 
+    #[cfg(not(test))]
+    fn load_with_id(app_id: &str) -> Result<Self, ConfigError> {
+        Self::load_from_id(app_id)
+    }
+
+    fn load_from_id(app_id: &str) -> Result<Self, ConfigError> {
         // Try to open config, if it fails, return defaults
-        let config = Config::new(app_id, CONFIG_VERSION)
+        let config = Self::open_config(app_id)
             .map_err(|e| ConfigError::LoadError(format!("Failed to open config: {e}")))?;
 
         // Clean up obsolete keys from previous versions
         Self::cleanup_obsolete_keys(&config);
 
-        // Load each field individually, using defaults for missing values
-        let default = Self::default();
+        Ok(Self::read_fields(&config))
+    }
 
-        Ok(Self {
-            storage_path: config.get("storage_path").unwrap_or(default.storage_path),
-            refresh_interval_seconds: config
-                .get("refresh_interval_seconds")
-                .unwrap_or(default.refresh_interval_seconds),
-            // Always use default panel_metrics (all stats enabled)
-            // This ensures all users see all metrics regardless of saved config
-            panel_metrics: default.panel_metrics,
-            use_raw_token_display: config
-                .get("use_raw_token_display")
-                .unwrap_or(default.use_raw_token_display),
-            display_mode: config.get("display_mode").unwrap_or(default.display_mode),
-        })
+    /// Opens the COSMIC config, honoring the `OPENCODE_USAGE_CONFIG_DIR` override
+    fn open_config(
+        app_id: &str,
+    ) -> Result<cosmic::cosmic_config::Config, cosmic::cosmic_config::Error> {
+        Self::open_config_in(app_id, config_dir_override())
     }
 
-    #[cfg(not(test))]
-    fn load_with_id(app_id: &str) -> Result<Self, ConfigError> {
-        use cosmic::cosmic_config::{Config, ConfigGet};
+    fn open_config_in(
+        app_id: &str,
+        config_dir: Option<PathBuf>,
+    ) -> Result<cosmic::cosmic_config::Config, cosmic::cosmic_config::Error> {
+        use cosmic::cosmic_config::Config;
 
-        // Try to open config, if it fails, return defaults
-        let config = Config::new(app_id, CONFIG_VERSION)
-            .map_err(|e| ConfigError::LoadError(format!("Failed to open config: {e}")))?;
+        match config_dir {
+            Some(dir) => Config::with_custom_path(app_id, CONFIG_VERSION, dir),
+            None => Config::new(app_id, CONFIG_VERSION),
+        }
+    }
 
-        // Clean up obsolete keys from previous versions
-        Self::cleanup_obsolete_keys(&config);
+    /// Reads each field individually, using defaults for missing values
+    fn read_fields(config: &cosmic::cosmic_config::Config) -> Self {
+        use cosmic::cosmic_config::ConfigGet;
 
-        // Load each field individually, using defaults for missing values
         let default = Self::default();
 
-        Ok(Self {
+        Self {
             storage_path: config.get("storage_path").unwrap_or(default.storage_path),
             refresh_interval_seconds: config
                 .get("refresh_interval_seconds")
                 .unwrap_or(default.refresh_interval_seconds),
-            // Always use default panel_metrics (all stats enabled)
-            // This ensures all users see all metrics regardless of saved config
-            panel_metrics: default.panel_metrics,
+            panel_metrics: config.get("panel_metrics").unwrap_or(default.panel_metrics),
+            panel_metric_order: config
+                .get("panel_metric_order")
+                .unwrap_or(default.panel_metric_order),
             use_raw_token_display: config
                 .get("use_raw_token_display")
                 .unwrap_or(default.use_raw_token_display),
             display_mode: config.get("display_mode").unwrap_or(default.display_mode),
-        })
+            history_enabled: config
+                .get("history_enabled")
+                .unwrap_or(default.history_enabled),
+            milestones_reached: config
+                .get("milestones_reached")
+                .unwrap_or(default.milestones_reached),
+            average_per_active_day: config
+                .get("average_per_active_day")
+                .unwrap_or(default.average_per_active_day),
+            fold_cache_into_io: config
+                .get("fold_cache_into_io")
+                .unwrap_or(default.fold_cache_into_io),
+            reasoning_visibility: config
+                .get("reasoning_visibility")
+                .unwrap_or(default.reasoning_visibility),
+            monthly_budget: config
+                .get("monthly_budget")
+                .unwrap_or(default.monthly_budget),
+            budget_warning_color: config
+                .get("budget_warning_color")
+                .unwrap_or(default.budget_warning_color),
+            budget_warning_icon: config
+                .get("budget_warning_icon")
+                .unwrap_or(default.budget_warning_icon),
+            central_tendency: config
+                .get("central_tendency")
+                .unwrap_or(default.central_tendency),
+            compact_interactions: config
+                .get("compact_interactions")
+                .unwrap_or(default.compact_interactions),
+            parse_threads: config.get("parse_threads").unwrap_or(default.parse_threads),
+            popup_auto_close_seconds: config
+                .get("popup_auto_close_seconds")
+                .unwrap_or(default.popup_auto_close_seconds),
+            include_cache_in_total: config
+                .get("include_cache_in_total")
+                .unwrap_or(default.include_cache_in_total),
+            day_reset_hour: config
+                .get("day_reset_hour")
+                .unwrap_or(default.day_reset_hour),
+            lean_cache: config.get("lean_cache").unwrap_or(default.lean_cache),
+            raw_token_separator: config
+                .get("raw_token_separator")
+                .unwrap_or(default.raw_token_separator),
+            fetch_timeout_seconds: config
+                .get("fetch_timeout_seconds")
+                .unwrap_or(default.fetch_timeout_seconds),
+            panel_icon_only: config
+                .get("panel_icon_only")
+                .unwrap_or(default.panel_icon_only),
+            panel_max_chars: config
+                .get("panel_max_chars")
+                .unwrap_or(default.panel_max_chars),
+            panel_min_chars: config
+                .get("panel_min_chars")
+                .unwrap_or(default.panel_min_chars),
+            compact_token_decimals: config
+                .get("compact_token_decimals")
+                .unwrap_or(default.compact_token_decimals),
+            primary_metric: config
+                .get("primary_metric")
+                .unwrap_or(default.primary_metric),
+            viewer_range_presets: config
+                .get("viewer_range_presets")
+                .unwrap_or(default.viewer_range_presets),
+            viewer_page_size: config
+                .get("viewer_page_size")
+                .unwrap_or(default.viewer_page_size),
+            expose_socket: config.get("expose_socket").unwrap_or(default.expose_socket),
+            socket_path: config.get("socket_path").unwrap_or(default.socket_path),
+            compact_ui: config.get("compact_ui").unwrap_or(default.compact_ui),
+            primary_period: config
+                .get("primary_period")
+                .unwrap_or(default.primary_period),
+            chart_palette: config.get("chart_palette").unwrap_or(default.chart_palette),
+            onboarding_completed: config
+                .get("onboarding_completed")
+                .unwrap_or(default.onboarding_completed),
+            compact_threshold: config
+                .get("compact_threshold")
+                .unwrap_or(default.compact_threshold),
+            pinned_panel_note: config
+                .get("pinned_panel_note")
+                .unwrap_or(default.pinned_panel_note),
+            flag_usage_decrease: config
+                .get("flag_usage_decrease")
+                .unwrap_or(default.flag_usage_decrease),
+            panel_only: config.get("panel_only").unwrap_or(default.panel_only),
+            interaction_unit: config
+                .get("interaction_unit")
+                .unwrap_or(default.interaction_unit),
+            metric_source: config.get("metric_source").unwrap_or(default.metric_source),
+            use_raw_cost_display: config
+                .get("use_raw_cost_display")
+                .unwrap_or(default.use_raw_cost_display),
+            percent_precision: config
+                .get("percent_precision")
+                .unwrap_or(default.percent_precision),
+            manifest_mode: config.get("manifest_mode").unwrap_or(default.manifest_mode),
+            error_grace_failures: config
+                .get("error_grace_failures")
+                .unwrap_or(default.error_grace_failures),
+            active_day_definition: config
+                .get("active_day_definition")
+                .unwrap_or(default.active_day_definition),
+            snapshot_retention_days: config
+                .get("snapshot_retention_days")
+                .unwrap_or(default.snapshot_retention_days),
+            // Chosen per process, see `data_namespace_override`
+            data_namespace: default.data_namespace,
+        }
     }
 
     /// Saves configuration to COSMIC config system
     ///
     /// # Errors
     /// Returns an error if the config cannot be saved to the COSMIC config system.
+    ///
+    /// A config with a `data_namespace` is saved to the config nested under it.
     pub fn save(&self) -> Result<(), ConfigError> {
-        Self::save_with_id(self, APP_ID)
+        Self::save_with_id(
+            self,
+            &namespaced_config_id(APP_ID, self.data_namespace.as_deref()),
+        )
     }
 
     /// Saves configuration with a custom app ID (useful for testing)
@@ -200,12 +630,54 @@ impl AppConfig {
     /// Returns an error if the config cannot be saved to the COSMIC config system.
     #[cfg(test)]
     pub fn save_with_id(&self, app_id: &str) -> Result<(), ConfigError> {
-        use cosmic::cosmic_config::{Config, ConfigSet};
+        self.save_to_id(app_id)
+    }
 
-        let config = Config::new(app_id, CONFIG_VERSION)
+    #[cfg(not(test))]
+    fn save_with_id(&self, app_id: &str) -> Result<(), ConfigError> {
+        self.save_to_id(app_id)
+    }
+
+    fn save_to_id(&self, app_id: &str) -> Result<(), ConfigError> {
+        let config = Self::open_config(app_id)
             .map_err(|e| ConfigError::SaveError(format!("Failed to open config: {e}")))?;
 
-        // Save each field individually
+        self.write_fields(&config)
+    }
+
+    /// Saves only `milestones_reached`, leaving every other saved field untouched
+    ///
+    /// # Errors
+    /// Returns an error if the config cannot be saved to the COSMIC config system.
+    pub fn save_milestones_reached(&self) -> Result<(), ConfigError> {
+        self.save_milestones_reached_to_id(&namespaced_config_id(
+            APP_ID,
+            self.data_namespace.as_deref(),
+        ))
+    }
+
+    fn save_milestones_reached_to_id(&self, app_id: &str) -> Result<(), ConfigError> {
+        let config = Self::open_config(app_id)
+            .map_err(|e| ConfigError::SaveError(format!("Failed to open config: {e}")))?;
+
+        self.write_milestones_reached(&config)
+    }
+
+    fn write_milestones_reached(
+        &self,
+        config: &cosmic::cosmic_config::Config,
+    ) -> Result<(), ConfigError> {
+        use cosmic::cosmic_config::ConfigSet;
+
+        config
+            .set("milestones_reached", &self.milestones_reached)
+            .map_err(|e| ConfigError::SaveError(format!("Failed to save milestones_reached: {e}")))
+    }
+
+    /// Saves each field individually
+    fn write_fields(&self, config: &cosmic::cosmic_config::Config) -> Result<(), ConfigError> {
+        use cosmic::cosmic_config::ConfigSet;
+
         config
             .set("storage_path", &self.storage_path)
             .map_err(|e| ConfigError::SaveError(format!("Failed to save storage_path: {e}")))?;
@@ -217,6 +689,11 @@ impl AppConfig {
         config
             .set("panel_metrics", &self.panel_metrics)
             .map_err(|e| ConfigError::SaveError(format!("Failed to save panel_metrics: {e}")))?;
+        config
+            .set("panel_metric_order", &self.panel_metric_order)
+            .map_err(|e| {
+                ConfigError::SaveError(format!("Failed to save panel_metric_order: {e}"))
+            })?;
         config
             .set("use_raw_token_display", self.use_raw_token_display)
             .map_err(|e| {
@@ -225,37 +702,172 @@ impl AppConfig {
         config
             .set("display_mode", self.display_mode)
             .map_err(|e| ConfigError::SaveError(format!("Failed to save display_mode: {e}")))?;
-
-        Ok(())
-    }
-
-    #[cfg(not(test))]
-    fn save_with_id(&self, app_id: &str) -> Result<(), ConfigError> {
-        use cosmic::cosmic_config::{Config, ConfigSet};
-
-        let config = Config::new(app_id, CONFIG_VERSION)
-            .map_err(|e| ConfigError::SaveError(format!("Failed to open config: {e}")))?;
-
-        // Save each field individually
         config
-            .set("storage_path", &self.storage_path)
-            .map_err(|e| ConfigError::SaveError(format!("Failed to save storage_path: {e}")))?;
+            .set("history_enabled", self.history_enabled)
+            .map_err(|e| ConfigError::SaveError(format!("Failed to save history_enabled: {e}")))?;
+        self.write_milestones_reached(config)?;
         config
-            .set("refresh_interval_seconds", self.refresh_interval_seconds)
+            .set("average_per_active_day", self.average_per_active_day)
             .map_err(|e| {
-                ConfigError::SaveError(format!("Failed to save refresh_interval_seconds: {e}"))
+                ConfigError::SaveError(format!("Failed to save average_per_active_day: {e}"))
             })?;
         config
-            .set("panel_metrics", &self.panel_metrics)
-            .map_err(|e| ConfigError::SaveError(format!("Failed to save panel_metrics: {e}")))?;
+            .set("fold_cache_into_io", self.fold_cache_into_io)
+            .map_err(|e| {
+                ConfigError::SaveError(format!("Failed to save fold_cache_into_io: {e}"))
+            })?;
         config
-            .set("use_raw_token_display", self.use_raw_token_display)
+            .set("reasoning_visibility", self.reasoning_visibility)
             .map_err(|e| {
-                ConfigError::SaveError(format!("Failed to save use_raw_token_display: {e}"))
+                ConfigError::SaveError(format!("Failed to save reasoning_visibility: {e}"))
             })?;
         config
-            .set("display_mode", self.display_mode)
-            .map_err(|e| ConfigError::SaveError(format!("Failed to save display_mode: {e}")))?;
+            .set("monthly_budget", self.monthly_budget)
+            .map_err(|e| ConfigError::SaveError(format!("Failed to save monthly_budget: {e}")))?;
+        config
+            .set("budget_warning_color", self.budget_warning_color)
+            .map_err(|e| {
+                ConfigError::SaveError(format!("Failed to save budget_warning_color: {e}"))
+            })?;
+        config
+            .set("budget_warning_icon", self.budget_warning_icon)
+            .map_err(|e| {
+                ConfigError::SaveError(format!("Failed to save budget_warning_icon: {e}"))
+            })?;
+        config
+            .set("central_tendency", self.central_tendency)
+            .map_err(|e| ConfigError::SaveError(format!("Failed to save central_tendency: {e}")))?;
+        config
+            .set("compact_interactions", self.compact_interactions)
+            .map_err(|e| {
+                ConfigError::SaveError(format!("Failed to save compact_interactions: {e}"))
+            })?;
+        config
+            .set("parse_threads", self.parse_threads)
+            .map_err(|e| ConfigError::SaveError(format!("Failed to save parse_threads: {e}")))?;
+        config
+            .set("popup_auto_close_seconds", self.popup_auto_close_seconds)
+            .map_err(|e| {
+                ConfigError::SaveError(format!("Failed to save popup_auto_close_seconds: {e}"))
+            })?;
+        config
+            .set("include_cache_in_total", self.include_cache_in_total)
+            .map_err(|e| {
+                ConfigError::SaveError(format!("Failed to save include_cache_in_total: {e}"))
+            })?;
+        config
+            .set("day_reset_hour", self.day_reset_hour)
+            .map_err(|e| ConfigError::SaveError(format!("Failed to save day_reset_hour: {e}")))?;
+        config
+            .set("lean_cache", self.lean_cache)
+            .map_err(|e| ConfigError::SaveError(format!("Failed to save lean_cache: {e}")))?;
+        config
+            .set("raw_token_separator", self.raw_token_separator)
+            .map_err(|e| {
+                ConfigError::SaveError(format!("Failed to save raw_token_separator: {e}"))
+            })?;
+        config
+            .set("fetch_timeout_seconds", self.fetch_timeout_seconds)
+            .map_err(|e| {
+                ConfigError::SaveError(format!("Failed to save fetch_timeout_seconds: {e}"))
+            })?;
+        config
+            .set("panel_icon_only", self.panel_icon_only)
+            .map_err(|e| ConfigError::SaveError(format!("Failed to save panel_icon_only: {e}")))?;
+        config
+            .set("panel_max_chars", self.panel_max_chars)
+            .map_err(|e| ConfigError::SaveError(format!("Failed to save panel_max_chars: {e}")))?;
+        config
+            .set("panel_min_chars", self.panel_min_chars)
+            .map_err(|e| ConfigError::SaveError(format!("Failed to save panel_min_chars: {e}")))?;
+        config
+            .set("compact_token_decimals", self.compact_token_decimals)
+            .map_err(|e| {
+                ConfigError::SaveError(format!("Failed to save compact_token_decimals: {e}"))
+            })?;
+        config
+            .set("primary_metric", self.primary_metric)
+            .map_err(|e| ConfigError::SaveError(format!("Failed to save primary_metric: {e}")))?;
+        config
+            .set("viewer_range_presets", &self.viewer_range_presets)
+            .map_err(|e| {
+                ConfigError::SaveError(format!("Failed to save viewer_range_presets: {e}"))
+            })?;
+        config
+            .set("viewer_page_size", self.viewer_page_size)
+            .map_err(|e| ConfigError::SaveError(format!("Failed to save viewer_page_size: {e}")))?;
+        config
+            .set("expose_socket", self.expose_socket)
+            .map_err(|e| ConfigError::SaveError(format!("Failed to save expose_socket: {e}")))?;
+        config
+            .set("socket_path", &self.socket_path)
+            .map_err(|e| ConfigError::SaveError(format!("Failed to save socket_path: {e}")))?;
+        config
+            .set("compact_ui", self.compact_ui)
+            .map_err(|e| ConfigError::SaveError(format!("Failed to save compact_ui: {e}")))?;
+        config
+            .set("primary_period", self.primary_period)
+            .map_err(|e| ConfigError::SaveError(format!("Failed to save primary_period: {e}")))?;
+        config
+            .set("chart_palette", self.chart_palette)
+            .map_err(|e| ConfigError::SaveError(format!("Failed to save chart_palette: {e}")))?;
+        config
+            .set("onboarding_completed", self.onboarding_completed)
+            .map_err(|e| {
+                ConfigError::SaveError(format!("Failed to save onboarding_completed: {e}"))
+            })?;
+        config
+            .set("compact_threshold", self.compact_threshold)
+            .map_err(|e| {
+                ConfigError::SaveError(format!("Failed to save compact_threshold: {e}"))
+            })?;
+        config
+            .set("pinned_panel_note", self.pinned_panel_note)
+            .map_err(|e| {
+                ConfigError::SaveError(format!("Failed to save pinned_panel_note: {e}"))
+            })?;
+        config
+            .set("flag_usage_decrease", self.flag_usage_decrease)
+            .map_err(|e| {
+                ConfigError::SaveError(format!("Failed to save flag_usage_decrease: {e}"))
+            })?;
+        config
+            .set("panel_only", self.panel_only)
+            .map_err(|e| ConfigError::SaveError(format!("Failed to save panel_only: {e}")))?;
+        config
+            .set("interaction_unit", self.interaction_unit)
+            .map_err(|e| ConfigError::SaveError(format!("Failed to save interaction_unit: {e}")))?;
+        config
+            .set("metric_source", self.metric_source)
+            .map_err(|e| ConfigError::SaveError(format!("Failed to save metric_source: {e}")))?;
+        config
+            .set("use_raw_cost_display", self.use_raw_cost_display)
+            .map_err(|e| {
+                ConfigError::SaveError(format!("Failed to save use_raw_cost_display: {e}"))
+            })?;
+        config
+            .set("percent_precision", self.percent_precision)
+            .map_err(|e| {
+                ConfigError::SaveError(format!("Failed to save percent_precision: {e}"))
+            })?;
+        config
+            .set("manifest_mode", self.manifest_mode)
+            .map_err(|e| ConfigError::SaveError(format!("Failed to save manifest_mode: {e}")))?;
+        config
+            .set("error_grace_failures", self.error_grace_failures)
+            .map_err(|e| {
+                ConfigError::SaveError(format!("Failed to save error_grace_failures: {e}"))
+            })?;
+        config
+            .set("active_day_definition", self.active_day_definition)
+            .map_err(|e| {
+                ConfigError::SaveError(format!("Failed to save active_day_definition: {e}"))
+            })?;
+        config
+            .set("snapshot_retention_days", self.snapshot_retention_days)
+            .map_err(|e| {
+                ConfigError::SaveError(format!("Failed to save snapshot_retention_days: {e}"))
+            })?;
 
         Ok(())
     }
@@ -265,10 +877,128 @@ impl AppConfig {
     /// # Errors
     /// Returns an error if the configuration has invalid values (e.g., refresh interval out of range).
     pub fn validate(&self) -> Result<Option<ConfigWarning>, ConfigError> {
+        validate_day_reset_hour(self.day_reset_hour)?;
+        validate_compact_token_decimals(self.compact_token_decimals)?;
+        validate_percent_precision(self.percent_precision)?;
+        validate_snapshot_retention_days(self.snapshot_retention_days)?;
         validate_refresh_interval(self.refresh_interval_seconds)
     }
 }
 
+/// Returns the config directory set via `OPENCODE_USAGE_CONFIG_DIR`, if any
+#[must_use]
+pub fn config_dir_override() -> Option<PathBuf> {
+    resolve_config_dir(std::env::var_os(CONFIG_DIR_ENV))
+}
+
+/// Returns the data namespace set via `OPENCODE_USAGE_NAMESPACE`, if any
+///
+/// The namespace belongs to the process rather than the shared config, so a "work"
+/// and a "personal" instance can run side by side.
+#[must_use]
+pub fn data_namespace_override() -> Option<String> {
+    std::env::var(DATA_NAMESPACE_ENV)
+        .ok()
+        .filter(|namespace| !namespace.is_empty())
+}
+
+/// Returns the config ID for a data namespace, nested under `app_id`
+///
+/// Without a namespace this is `app_id` itself.
+#[must_use]
+pub fn namespaced_config_id(app_id: &str, namespace: Option<&str>) -> String {
+    match namespace {
+        Some(namespace) => format!("{app_id}/{namespace}"),
+        None => app_id.to_string(),
+    }
+}
+
+/// Validates a data namespace, which becomes a single directory name
+///
+/// # Errors
+/// Returns `ConfigError::InvalidDataNamespace` if the namespace is empty, `.` or `..`,
+/// or contains a path separator.
+pub fn validate_data_namespace(namespace: &str) -> Result<(), ConfigError> {
+    if namespace.is_empty()
+        || namespace == "."
+        || namespace == ".."
+        || namespace.contains(['/', '\\'])
+    {
+        return Err(ConfigError::InvalidDataNamespace(namespace.to_string()));
+    }
+    Ok(())
+}
+
+/// Resolves a config directory override value, ignoring empty values
+fn resolve_config_dir(value: Option<std::ffi::OsString>) -> Option<PathBuf> {
+    value.filter(|v| !v.is_empty()).map(PathBuf::from)
+}
+
+/// Parses a `#rrggbb` (or `rrggbb`) hex color into RGB components
+#[must_use]
+pub fn parse_hex_color(input: &str) -> Option<[u8; 3]> {
+    let hex = input.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+
+    let component = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([component(0)?, component(2)?, component(4)?])
+}
+
+/// Parses the monthly budget settings input, where an empty input disables the budget
+///
+/// # Errors
+/// Returns an error if the input is not a positive amount.
+pub fn parse_monthly_budget(input: &str) -> Result<Option<f64>, ConfigError> {
+    let trimmed = input.trim().trim_start_matches('$');
+    if trimmed.is_empty() {
+        return Ok(None);
+    }
+
+    match trimmed.parse::<f64>() {
+        Ok(budget) if budget.is_finite() && budget > 0.0 => Ok(Some(budget)),
+        _ => Err(ConfigError::InvalidBudget(input.to_string())),
+    }
+}
+
+/// Parses the fetch timeout settings input, where an empty input or 0 means no deadline
+///
+/// # Errors
+/// Returns an error if the input is not a whole number of seconds.
+pub fn parse_fetch_timeout(input: &str) -> Result<Option<u32>, ConfigError> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Ok(None);
+    }
+
+    match trimmed.parse::<u32>() {
+        Ok(0) => Ok(None),
+        Ok(seconds) => Ok(Some(seconds)),
+        Err(_) => Err(ConfigError::InvalidFetchTimeout(input.to_string())),
+    }
+}
+
+/// Parses the warning color settings input, where an empty input means the theme default
+///
+/// # Errors
+/// Returns an error if the input is not a `#rrggbb` hex color.
+pub fn parse_warning_color(input: &str) -> Result<Option<[u8; 3]>, ConfigError> {
+    if input.trim().is_empty() {
+        return Ok(None);
+    }
+
+    parse_hex_color(input)
+        .map(Some)
+        .ok_or_else(|| ConfigError::InvalidColor(input.to_string()))
+}
+
+/// Formats RGB components as a `#rrggbb` hex color
+#[must_use]
+pub fn format_hex_color(rgb: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", rgb[0], rgb[1], rgb[2])
+}
+
 /// Validates refresh interval is within acceptable range (1-3600 seconds)
 /// Returns a warning (not an error) if interval is < 60 seconds
 ///
@@ -287,6 +1017,50 @@ pub fn validate_refresh_interval(interval: u32) -> Result<Option<ConfigWarning>,
     Ok(None)
 }
 
+/// Validates the hour at which the day boundary is placed
+///
+/// # Errors
+/// Returns `ConfigError::InvalidDayResetHour` if the hour is not between 0 and 23.
+pub fn validate_day_reset_hour(hour: u8) -> Result<(), ConfigError> {
+    if hour > 23 {
+        return Err(ConfigError::InvalidDayResetHour(hour));
+    }
+    Ok(())
+}
+
+/// Validates the number of decimals shown for compact token values
+///
+/// # Errors
+/// Returns `ConfigError::InvalidCompactTokenDecimals` if the value is above 2.
+pub fn validate_compact_token_decimals(decimals: u8) -> Result<(), ConfigError> {
+    if decimals > 2 {
+        return Err(ConfigError::InvalidCompactTokenDecimals(decimals));
+    }
+    Ok(())
+}
+
+/// Validates the number of decimals shown in percent changes
+///
+/// # Errors
+/// Returns `ConfigError::InvalidPercentPrecision` if the value is above 2.
+pub fn validate_percent_precision(precision: u8) -> Result<(), ConfigError> {
+    if precision > 2 {
+        return Err(ConfigError::InvalidPercentPrecision(precision));
+    }
+    Ok(())
+}
+
+/// Validates the snapshot retention period, where `None` keeps every snapshot
+///
+/// # Errors
+/// Returns `ConfigError::InvalidSnapshotRetention` if the period is 0 days.
+pub fn validate_snapshot_retention_days(keep_days: Option<u32>) -> Result<(), ConfigError> {
+    if keep_days == Some(0) {
+        return Err(ConfigError::InvalidSnapshotRetention(0));
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -326,8 +1100,55 @@ mod tests {
         assert!(config.panel_metrics.contains(&PanelMetric::InputTokens));
         assert!(config.panel_metrics.contains(&PanelMetric::OutputTokens));
         assert!(config.panel_metrics.contains(&PanelMetric::ReasoningTokens));
+        assert_eq!(config.panel_metric_order, PanelMetric::DEFAULT_ORDER);
         assert!(!config.use_raw_token_display);
         assert_eq!(config.display_mode, DisplayMode::Today);
+        assert!(config.history_enabled);
+        assert!(config.milestones_reached.is_empty());
+        assert!(!config.average_per_active_day);
+        assert!(!config.fold_cache_into_io);
+        assert_eq!(config.reasoning_visibility, ReasoningVisibility::Always);
+        assert_eq!(config.monthly_budget, None);
+        assert_eq!(config.budget_warning_color, None);
+        assert!(config.budget_warning_icon);
+        assert_eq!(config.central_tendency, CentralTendency::Mean);
+        assert!(!config.compact_interactions);
+        assert_eq!(config.parse_threads, None);
+        assert_eq!(config.popup_auto_close_seconds, None);
+        assert!(!config.include_cache_in_total);
+        assert_eq!(config.day_reset_hour, 0);
+        assert!(!config.lean_cache);
+        assert_eq!(config.raw_token_separator, None);
+        assert_eq!(config.fetch_timeout_seconds, None);
+        assert!(!config.panel_icon_only);
+        assert_eq!(config.panel_max_chars, None);
+        assert_eq!(config.panel_min_chars, 4);
+        assert_eq!(config.compact_token_decimals, 1);
+        assert_eq!(config.primary_metric, None);
+        assert_eq!(
+            config.viewer_range_presets,
+            vec!["7d", "30d", "90d", "YTD", "All"]
+        );
+        assert_eq!(config.viewer_page_size, 50);
+        assert!(!config.expose_socket);
+        assert_eq!(config.socket_path, None);
+        assert!(!config.compact_ui);
+        assert_eq!(config.primary_period, None);
+        assert_eq!(config.chart_palette, ChartPalette::Default);
+        assert!(!config.onboarding_completed);
+        assert_eq!(config.compact_threshold, 1_000);
+        assert_eq!(config.pinned_panel_note, None);
+        assert!(!config.flag_usage_decrease);
+        assert!(!config.panel_only);
+        assert_eq!(config.interaction_unit, InteractionUnit::Part);
+        assert_eq!(config.metric_source, MetricSource::Files);
+        assert!(!config.use_raw_cost_display);
+        assert_eq!(config.percent_precision, 0);
+        assert!(!config.manifest_mode);
+        assert_eq!(config.error_grace_failures, 0);
+        assert_eq!(config.active_day_definition, ActiveDayDefinition::AnyUsage);
+        assert_eq!(config.snapshot_retention_days, None);
+        assert_eq!(config.data_namespace, None);
     }
 
     #[test]
@@ -336,6 +1157,26 @@ mod tests {
         assert!(config.validate().is_ok());
     }
 
+    #[test]
+    fn test_validate_snapshot_retention() {
+        let config = AppConfig {
+            snapshot_retention_days: Some(0),
+            ..AppConfig::default()
+        };
+        assert_eq!(
+            config.validate(),
+            Err(ConfigError::InvalidSnapshotRetention(0))
+        );
+
+        for keep_days in [None, Some(1), Some(u32::MAX)] {
+            let config = AppConfig {
+                snapshot_retention_days: keep_days,
+                ..AppConfig::default()
+            };
+            assert!(config.validate().is_ok());
+        }
+    }
+
     #[test]
     fn test_validate_interval_too_low() {
         let config = AppConfig {
@@ -344,6 +1185,7 @@ mod tests {
             panel_metrics: vec![PanelMetric::Cost],
             use_raw_token_display: false,
             display_mode: DisplayMode::Today,
+            ..AppConfig::default()
         };
         assert_eq!(
             config.validate(),
@@ -359,6 +1201,7 @@ mod tests {
             panel_metrics: vec![PanelMetric::Cost],
             use_raw_token_display: false,
             display_mode: DisplayMode::Today,
+            ..AppConfig::default()
         };
         assert_eq!(
             config.validate(),
@@ -375,6 +1218,7 @@ mod tests {
             panel_metrics: vec![PanelMetric::Cost],
             use_raw_token_display: false,
             display_mode: DisplayMode::Today,
+            ..AppConfig::default()
         };
         assert_eq!(
             config_min.validate(),
@@ -388,6 +1232,7 @@ mod tests {
             panel_metrics: vec![PanelMetric::Cost],
             use_raw_token_display: false,
             display_mode: DisplayMode::Today,
+            ..AppConfig::default()
         };
         assert_eq!(
             config_warning.validate(),
@@ -401,6 +1246,7 @@ mod tests {
             panel_metrics: vec![PanelMetric::Cost],
             use_raw_token_display: false,
             display_mode: DisplayMode::Today,
+            ..AppConfig::default()
         };
         assert_eq!(config_no_warning.validate(), Ok(None));
 
@@ -411,10 +1257,63 @@ mod tests {
             panel_metrics: vec![PanelMetric::Cost],
             use_raw_token_display: false,
             display_mode: DisplayMode::Today,
+            ..AppConfig::default()
         };
         assert_eq!(config_max.validate(), Ok(None));
     }
 
+    #[test]
+    fn test_validate_day_reset_hour() {
+        assert_eq!(validate_day_reset_hour(0), Ok(()));
+        assert_eq!(validate_day_reset_hour(4), Ok(()));
+        assert_eq!(validate_day_reset_hour(23), Ok(()));
+        assert_eq!(
+            validate_day_reset_hour(24),
+            Err(ConfigError::InvalidDayResetHour(24))
+        );
+
+        let config = AppConfig {
+            day_reset_hour: 30,
+            ..AppConfig::default()
+        };
+        assert_eq!(config.validate(), Err(ConfigError::InvalidDayResetHour(30)));
+    }
+
+    #[test]
+    fn test_validate_percent_precision() {
+        for precision in 0..=2 {
+            assert_eq!(validate_percent_precision(precision), Ok(()));
+        }
+        let config = AppConfig {
+            percent_precision: 3,
+            ..AppConfig::default()
+        };
+        assert_eq!(
+            config.validate(),
+            Err(ConfigError::InvalidPercentPrecision(3))
+        );
+    }
+
+    #[test]
+    fn test_validate_compact_token_decimals() {
+        for decimals in 0..=2 {
+            assert_eq!(validate_compact_token_decimals(decimals), Ok(()));
+        }
+        assert_eq!(
+            validate_compact_token_decimals(3),
+            Err(ConfigError::InvalidCompactTokenDecimals(3))
+        );
+
+        let config = AppConfig {
+            compact_token_decimals: 5,
+            ..AppConfig::default()
+        };
+        assert_eq!(
+            config.validate(),
+            Err(ConfigError::InvalidCompactTokenDecimals(5))
+        );
+    }
+
     #[test]
     fn test_validate_refresh_interval_function() {
         // Valid intervals without warning (>= 60 seconds)
@@ -466,6 +1365,7 @@ mod tests {
             panel_metrics: vec![PanelMetric::Interactions, PanelMetric::Cost],
             use_raw_token_display: true,
             display_mode: DisplayMode::Month,
+            ..AppConfig::default()
         };
 
         // Save should succeed
@@ -500,6 +1400,7 @@ mod tests {
             panel_metrics: vec![PanelMetric::ReasoningTokens, PanelMetric::InputTokens],
             use_raw_token_display: true,
             display_mode: DisplayMode::AllTime,
+            ..AppConfig::default()
         };
 
         // Save it
@@ -508,13 +1409,13 @@ mod tests {
         // Load it back
         let loaded = AppConfig::load_with_id(&app_id).expect("load should succeed");
 
-        // Should match the original, except panel_metrics which always loads as default
+        // Should match the original
         assert_eq!(loaded.storage_path, original.storage_path);
         assert_eq!(
             loaded.refresh_interval_seconds,
             original.refresh_interval_seconds
         );
-        assert_eq!(loaded.panel_metrics, AppConfig::default().panel_metrics);
+        assert_eq!(loaded.panel_metrics, original.panel_metrics);
         assert_eq!(loaded.use_raw_token_display, original.use_raw_token_display);
         assert_eq!(loaded.display_mode, original.display_mode);
     }
@@ -530,14 +1431,14 @@ mod tests {
             panel_metrics: vec![PanelMetric::Cost],
             use_raw_token_display: false,
             display_mode: DisplayMode::Today,
+            ..AppConfig::default()
         };
         config1.save_with_id(&app_id).expect("save should succeed");
 
         // Load and verify
         let loaded1 = AppConfig::load_with_id(&app_id).expect("load should succeed");
         assert_eq!(loaded1.refresh_interval_seconds, 600);
-        // panel_metrics always loads as default (all 5 metrics), regardless of what was saved
-        assert_eq!(loaded1.panel_metrics, AppConfig::default().panel_metrics);
+        assert_eq!(loaded1.panel_metrics, vec![PanelMetric::Cost]);
 
         // Change one field and save again
         let config2 = AppConfig {
@@ -546,14 +1447,14 @@ mod tests {
             panel_metrics: vec![PanelMetric::InputTokens],
             use_raw_token_display: true,
             display_mode: DisplayMode::Month,
+            ..AppConfig::default()
         };
         config2.save_with_id(&app_id).expect("save should succeed");
 
         // Load and verify the change
         let loaded2 = AppConfig::load_with_id(&app_id).expect("load should succeed");
         assert_eq!(loaded2.refresh_interval_seconds, 1800);
-        // panel_metrics always loads as default (all 5 metrics), regardless of what was saved
-        assert_eq!(loaded2.panel_metrics, AppConfig::default().panel_metrics);
+        assert_eq!(loaded2.panel_metrics, vec![PanelMetric::InputTokens]);
         assert!(loaded2.use_raw_token_display);
     }
 
@@ -580,6 +1481,7 @@ mod tests {
             panel_metrics: vec![PanelMetric::Cost],
             use_raw_token_display: false,
             display_mode: DisplayMode::Today,
+            ..AppConfig::default()
         };
         config_today
             .save_with_id(&app_id)
@@ -594,6 +1496,7 @@ mod tests {
             panel_metrics: vec![PanelMetric::Cost],
             use_raw_token_display: false,
             display_mode: DisplayMode::Month,
+            ..AppConfig::default()
         };
         config_month
             .save_with_id(&app_id)
@@ -608,6 +1511,7 @@ mod tests {
             panel_metrics: vec![PanelMetric::Cost],
             use_raw_token_display: false,
             display_mode: DisplayMode::AllTime,
+            ..AppConfig::default()
         };
         config_alltime
             .save_with_id(&app_id)
@@ -627,13 +1531,13 @@ mod tests {
             panel_metrics: vec![PanelMetric::Cost],
             use_raw_token_display: false,
             display_mode: DisplayMode::Today,
+            ..AppConfig::default()
         };
         config_single
             .save_with_id(&app_id)
             .expect("save should succeed");
         let loaded = AppConfig::load_with_id(&app_id).expect("load should succeed");
-        // panel_metrics always loads as default (all 5 metrics), regardless of what was saved
-        assert_eq!(loaded.panel_metrics, AppConfig::default().panel_metrics);
+        assert_eq!(loaded.panel_metrics, config_single.panel_metrics);
 
         // Test multiple metrics
         let config_multiple = AppConfig {
@@ -646,13 +1550,13 @@ mod tests {
             ],
             use_raw_token_display: false,
             display_mode: DisplayMode::Today,
+            ..AppConfig::default()
         };
         config_multiple
             .save_with_id(&app_id)
             .expect("save should succeed");
         let loaded = AppConfig::load_with_id(&app_id).expect("load should succeed");
-        // panel_metrics always loads as default (all 5 metrics), regardless of what was saved
-        assert_eq!(loaded.panel_metrics, AppConfig::default().panel_metrics);
+        assert_eq!(loaded.panel_metrics, config_multiple.panel_metrics);
 
         // Test empty Vec
         let config_empty = AppConfig {
@@ -661,13 +1565,13 @@ mod tests {
             panel_metrics: vec![],
             use_raw_token_display: false,
             display_mode: DisplayMode::Today,
+            ..AppConfig::default()
         };
         config_empty
             .save_with_id(&app_id)
             .expect("save should succeed");
         let loaded = AppConfig::load_with_id(&app_id).expect("load should succeed");
-        // panel_metrics always loads as default (all 5 metrics), regardless of what was saved
-        assert_eq!(loaded.panel_metrics, AppConfig::default().panel_metrics);
+        assert!(loaded.panel_metrics.is_empty());
 
         // Test all metrics
         let config_all = AppConfig {
@@ -682,6 +1586,7 @@ mod tests {
             ],
             use_raw_token_display: false,
             display_mode: DisplayMode::Today,
+            ..AppConfig::default()
         };
         config_all
             .save_with_id(&app_id)
@@ -694,4 +1599,393 @@ mod tests {
         assert!(loaded.panel_metrics.contains(&PanelMetric::OutputTokens));
         assert!(loaded.panel_metrics.contains(&PanelMetric::ReasoningTokens));
     }
+
+    #[test]
+    fn test_history_enabled_roundtrip() {
+        let app_id = test_app_id("history_enabled_roundtrip");
+
+        let config = AppConfig {
+            history_enabled: false,
+            ..AppConfig::default()
+        };
+        config.save_with_id(&app_id).expect("save should succeed");
+        let loaded = AppConfig::load_with_id(&app_id).expect("load should succeed");
+        assert!(!loaded.history_enabled);
+
+        let config = AppConfig {
+            history_enabled: true,
+            ..AppConfig::default()
+        };
+        config.save_with_id(&app_id).expect("save should succeed");
+        let loaded = AppConfig::load_with_id(&app_id).expect("load should succeed");
+        assert!(loaded.history_enabled);
+    }
+
+    #[test]
+    fn test_raw_token_separator_roundtrip() {
+        let app_id = test_app_id("raw_token_separator_roundtrip");
+
+        for separator in [Some(','), Some(' '), None] {
+            let config = AppConfig {
+                raw_token_separator: separator,
+                ..AppConfig::default()
+            };
+            config.save_with_id(&app_id).expect("save should succeed");
+            let loaded = AppConfig::load_with_id(&app_id).expect("load should succeed");
+            assert_eq!(loaded.raw_token_separator, separator);
+        }
+    }
+
+    #[test]
+    fn test_resolve_config_dir() {
+        assert_eq!(resolve_config_dir(None), None);
+        assert_eq!(resolve_config_dir(Some("".into())), None);
+        assert_eq!(
+            resolve_config_dir(Some("/tmp/opencode-config".into())),
+            Some(PathBuf::from("/tmp/opencode-config"))
+        );
+    }
+
+    #[test]
+    fn test_config_dir_override_roundtrip() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let app_id = test_app_id("config_dir_override");
+
+        let config = AppConfig::open_config_in(&app_id, Some(temp_dir.path().to_path_buf()))
+            .expect("config should open in custom dir");
+        let original = AppConfig {
+            refresh_interval_seconds: 900,
+            ..AppConfig::default()
+        };
+        original.write_fields(&config).expect("save should succeed");
+
+        // Settings land under the custom directory
+        assert!(std::fs::read_dir(temp_dir.path()).unwrap().next().is_some());
+
+        let reopened = AppConfig::open_config_in(&app_id, Some(temp_dir.path().to_path_buf()))
+            .expect("config should reopen in custom dir");
+        let loaded = AppConfig::read_fields(&reopened);
+        assert_eq!(loaded.refresh_interval_seconds, 900);
+    }
+
+    #[test]
+    fn test_milestones_reached_roundtrip() {
+        let app_id = test_app_id("milestones_reached_roundtrip");
+
+        let config = AppConfig {
+            milestones_reached: vec![1_000, 5_000],
+            ..AppConfig::default()
+        };
+        config.save_with_id(&app_id).expect("save should succeed");
+        let loaded = AppConfig::load_with_id(&app_id).expect("load should succeed");
+        assert_eq!(loaded.milestones_reached, vec![1_000, 5_000]);
+    }
+
+    #[test]
+    fn test_save_milestones_reached_keeps_other_fields() {
+        let app_id = test_app_id("save_milestones_reached_only");
+
+        let saved = AppConfig {
+            refresh_interval_seconds: 600,
+            panel_metrics: vec![PanelMetric::Cost],
+            ..AppConfig::default()
+        };
+        saved.save_with_id(&app_id).expect("save should succeed");
+
+        // An in-memory config that differs from disk only records its milestones
+        let in_memory = AppConfig {
+            milestones_reached: vec![1_000],
+            ..AppConfig::default()
+        };
+        in_memory
+            .save_milestones_reached_to_id(&app_id)
+            .expect("save should succeed");
+
+        let loaded = AppConfig::load_with_id(&app_id).expect("load should succeed");
+        assert_eq!(loaded.milestones_reached, vec![1_000]);
+        assert_eq!(loaded.refresh_interval_seconds, 600);
+        assert_eq!(loaded.panel_metrics, vec![PanelMetric::Cost]);
+    }
+
+    #[test]
+    fn test_reasoning_visibility_auto_decision() {
+        // Zero reasoning history hides the row, nonzero history shows it
+        assert!(!ReasoningVisibility::Auto.should_show(Some(false)));
+        assert!(ReasoningVisibility::Auto.should_show(Some(true)));
+        // Unknown history keeps the row visible until determined
+        assert!(ReasoningVisibility::Auto.should_show(None));
+    }
+
+    #[test]
+    fn test_reasoning_visibility_always_and_never() {
+        for history in [None, Some(false), Some(true)] {
+            assert!(ReasoningVisibility::Always.should_show(history));
+            assert!(!ReasoningVisibility::Never.should_show(history));
+        }
+    }
+
+    #[test]
+    fn test_reasoning_visibility_roundtrip() {
+        let app_id = test_app_id("reasoning_visibility_roundtrip");
+
+        let config = AppConfig {
+            reasoning_visibility: ReasoningVisibility::Auto,
+            ..AppConfig::default()
+        };
+        config.save_with_id(&app_id).expect("save should succeed");
+        let loaded = AppConfig::load_with_id(&app_id).expect("load should succeed");
+        assert_eq!(loaded.reasoning_visibility, ReasoningVisibility::Auto);
+    }
+
+    #[test]
+    fn test_parse_hex_color() {
+        assert_eq!(parse_hex_color("#e01b24"), Some([0xe0, 0x1b, 0x24]));
+        assert_eq!(parse_hex_color(" 00FF7f "), Some([0x00, 0xff, 0x7f]));
+        assert_eq!(parse_hex_color("#fff"), None);
+        assert_eq!(parse_hex_color("#gg0000"), None);
+        assert_eq!(parse_hex_color(""), None);
+        assert_eq!(format_hex_color([0xe0, 0x1b, 0x24]), "#e01b24");
+    }
+
+    #[test]
+    fn test_parse_budget_settings_inputs() {
+        assert_eq!(parse_monthly_budget("  "), Ok(None));
+        assert_eq!(parse_monthly_budget("$20.5"), Ok(Some(20.5)));
+        assert!(matches!(
+            parse_monthly_budget("0"),
+            Err(ConfigError::InvalidBudget(_))
+        ));
+        assert!(matches!(
+            parse_monthly_budget("abc"),
+            Err(ConfigError::InvalidBudget(_))
+        ));
+
+        assert_eq!(parse_fetch_timeout(""), Ok(None));
+        assert_eq!(parse_fetch_timeout("0"), Ok(None));
+        assert_eq!(parse_fetch_timeout(" 30 "), Ok(Some(30)));
+        assert!(matches!(
+            parse_fetch_timeout("-5"),
+            Err(ConfigError::InvalidFetchTimeout(_))
+        ));
+        assert!(matches!(
+            parse_fetch_timeout("soon"),
+            Err(ConfigError::InvalidFetchTimeout(_))
+        ));
+        assert_eq!(parse_warning_color(""), Ok(None));
+        assert_eq!(parse_warning_color("#ff0000"), Ok(Some([0xff, 0, 0])));
+        assert!(matches!(
+            parse_warning_color("red"),
+            Err(ConfigError::InvalidColor(_))
+        ));
+    }
+
+    #[test]
+    fn test_budget_warning_color_custom_and_fallback() {
+        let mut config = AppConfig::default();
+        assert_eq!(config.budget_warning_rgb(true), BUDGET_WARNING_DARK);
+        assert_eq!(config.budget_warning_rgb(false), BUDGET_WARNING_LIGHT);
+
+        config.budget_warning_color = parse_hex_color("#123456");
+        assert_eq!(config.budget_warning_rgb(true), [0x12, 0x34, 0x56]);
+        assert_eq!(config.budget_warning_rgb(false), [0x12, 0x34, 0x56]);
+    }
+
+    #[test]
+    fn test_budget_settings_roundtrip() {
+        let app_id = test_app_id("budget_settings_roundtrip");
+        let config = AppConfig {
+            monthly_budget: Some(25.0),
+            budget_warning_color: Some([1, 2, 3]),
+            budget_warning_icon: false,
+            ..AppConfig::default()
+        };
+        config.save_with_id(&app_id).expect("save should succeed");
+        let loaded = AppConfig::load_with_id(&app_id).expect("load should succeed");
+        assert_eq!(loaded.monthly_budget, Some(25.0));
+        assert_eq!(loaded.budget_warning_color, Some([1, 2, 3]));
+        assert!(!loaded.budget_warning_icon);
+    }
+
+    #[test]
+    fn test_central_tendency_roundtrip() {
+        let app_id = test_app_id("central_tendency_roundtrip");
+        let config = AppConfig {
+            central_tendency: CentralTendency::Median,
+            ..AppConfig::default()
+        };
+        config.save_with_id(&app_id).expect("save should succeed");
+        let loaded = AppConfig::load_with_id(&app_id).expect("load should succeed");
+        assert_eq!(loaded.central_tendency, CentralTendency::Median);
+    }
+
+    #[test]
+    fn test_include_cache_in_total_roundtrip() {
+        let app_id = test_app_id("include_cache_in_total_roundtrip");
+        let config = AppConfig {
+            include_cache_in_total: true,
+            ..AppConfig::default()
+        };
+        config.save_with_id(&app_id).expect("save should succeed");
+        let loaded = AppConfig::load_with_id(&app_id).expect("load should succeed");
+        assert!(loaded.include_cache_in_total);
+    }
+
+    #[test]
+    fn test_panel_metric_order_roundtrip() {
+        let app_id = test_app_id("panel_metric_order_roundtrip");
+        let order = vec![
+            PanelMetric::Interactions,
+            PanelMetric::InputTokens,
+            PanelMetric::OutputTokens,
+            PanelMetric::ReasoningTokens,
+            PanelMetric::Cost,
+        ];
+        let config = AppConfig {
+            panel_metric_order: order.clone(),
+            ..AppConfig::default()
+        };
+        config.save_with_id(&app_id).expect("save should succeed");
+        let loaded = AppConfig::load_with_id(&app_id).expect("load should succeed");
+        assert_eq!(loaded.panel_metric_order, order);
+    }
+
+    #[test]
+    fn test_primary_metric_roundtrip() {
+        let app_id = test_app_id("primary_metric_roundtrip");
+        let config = AppConfig {
+            panel_metrics: vec![],
+            primary_metric: Some(PrimaryMetric::TotalTokens),
+            ..AppConfig::default()
+        };
+        config.save_with_id(&app_id).unwrap();
+
+        let loaded = AppConfig::load_with_id(&app_id).unwrap();
+        assert_eq!(loaded.primary_metric, Some(PrimaryMetric::TotalTokens));
+        // The primary metric only drives the layout while panel_metrics is empty
+        assert!(loaded.panel_metrics.is_empty());
+        assert!(loaded.needs_today_usage());
+    }
+
+    #[test]
+    fn test_needs_today_usage() {
+        let config = AppConfig {
+            panel_metrics: vec![],
+            ..AppConfig::default()
+        };
+        assert!(!config.needs_today_usage());
+        assert!(AppConfig {
+            panel_metrics: vec![PanelMetric::Cost],
+            ..config.clone()
+        }
+        .needs_today_usage());
+    }
+
+    #[test]
+    fn test_viewer_range_presets_roundtrip() {
+        let app_id = test_app_id("viewer_range_presets_roundtrip");
+        let config = AppConfig {
+            viewer_range_presets: vec!["14d".to_string(), "YTD".to_string()],
+            ..AppConfig::default()
+        };
+        config.save_with_id(&app_id).unwrap();
+
+        let loaded = AppConfig::load_with_id(&app_id).unwrap();
+        assert_eq!(loaded.viewer_range_presets, vec!["14d", "YTD"]);
+    }
+
+    #[test]
+    fn test_viewer_page_size_roundtrip() {
+        let app_id = test_app_id("viewer_page_size_roundtrip");
+        let config = AppConfig {
+            viewer_page_size: 20,
+            ..AppConfig::default()
+        };
+        config.save_with_id(&app_id).unwrap();
+
+        assert_eq!(
+            AppConfig::load_with_id(&app_id).unwrap().viewer_page_size,
+            20
+        );
+    }
+
+    #[test]
+    fn test_metrics_socket_settings_roundtrip() {
+        let app_id = test_app_id("metrics_socket_roundtrip");
+        let config = AppConfig {
+            expose_socket: true,
+            socket_path: Some(PathBuf::from("/tmp/opencode-usage.sock")),
+            ..AppConfig::default()
+        };
+        config.save_with_id(&app_id).unwrap();
+
+        let loaded = AppConfig::load_with_id(&app_id).unwrap();
+        assert!(loaded.expose_socket);
+        assert_eq!(
+            loaded.socket_path,
+            Some(PathBuf::from("/tmp/opencode-usage.sock"))
+        );
+    }
+
+    #[test]
+    fn test_namespaced_config_id() {
+        assert_eq!(namespaced_config_id(APP_ID, None), APP_ID);
+        assert_eq!(
+            namespaced_config_id(APP_ID, Some("work")),
+            format!("{APP_ID}/work")
+        );
+    }
+
+    #[test]
+    fn test_load_resolves_namespaced_config() {
+        let app_id = test_app_id("load_namespaced");
+        let shared = AppConfig {
+            refresh_interval_seconds: 120,
+            ..AppConfig::default()
+        };
+        shared.save_with_id(&app_id).unwrap();
+        let work = AppConfig {
+            refresh_interval_seconds: 600,
+            ..AppConfig::default()
+        };
+        work.save_with_id(&namespaced_config_id(&app_id, Some("work")))
+            .unwrap();
+
+        let loaded = AppConfig::load_in(&app_id, Some("work".to_string())).unwrap();
+        assert_eq!(loaded.refresh_interval_seconds, 600);
+        assert_eq!(loaded.data_namespace.as_deref(), Some("work"));
+
+        let loaded = AppConfig::load_in(&app_id, None).unwrap();
+        assert_eq!(loaded.refresh_interval_seconds, 120);
+        assert_eq!(loaded.data_namespace, None);
+
+        // An invalid namespace falls back to the shared config
+        let loaded = AppConfig::load_in(&app_id, Some("../work".to_string())).unwrap();
+        assert_eq!(loaded.refresh_interval_seconds, 120);
+        assert_eq!(loaded.data_namespace, None);
+    }
+
+    #[test]
+    fn test_namespace_is_not_saved() {
+        let app_id = test_app_id("namespace_not_saved");
+        let config = AppConfig {
+            data_namespace: Some("work".to_string()),
+            ..AppConfig::default()
+        };
+        config.save_with_id(&app_id).unwrap();
+
+        let loaded = AppConfig::load_with_id(&app_id).unwrap();
+        assert_eq!(loaded.data_namespace, None);
+    }
+
+    #[test]
+    fn test_validate_data_namespace() {
+        assert!(validate_data_namespace("work").is_ok());
+        assert!(validate_data_namespace("personal-2").is_ok());
+        for invalid in ["", ".", "..", "a/b", "a\\b"] {
+            assert!(matches!(
+                validate_data_namespace(invalid),
+                Err(ConfigError::InvalidDataNamespace(_))
+            ));
+        }
+    }
 }
@@ -9,13 +9,35 @@ use crate::core::database::{DatabaseError, Result};
 use rusqlite::Connection;
 use std::path::Path;
 
+/// `errno` for a read-only file system on Linux.
+const EROFS: i32 = 30;
+
 /// Creates a new database connection at the specified path.
 ///
 /// # Errors
 ///
 /// Returns an error if the connection cannot be established.
 pub fn create_connection(path: &Path) -> Result<Connection> {
-    Connection::open(path).map_err(|e| DatabaseError::ConnectionFailed(e.to_string()))
+    Connection::open(path).map_err(|e| {
+        if is_read_only_sqlite_error(&e) {
+            DatabaseError::ReadOnly(format!("{}: {e}", path.display()))
+        } else {
+            DatabaseError::ConnectionFailed(e.to_string())
+        }
+    })
+}
+
+/// Whether an `SQLite` error means the database file cannot be opened or written.
+fn is_read_only_sqlite_error(e: &rusqlite::Error) -> bool {
+    matches!(
+        e.sqlite_error_code(),
+        Some(rusqlite::ErrorCode::ReadOnly | rusqlite::ErrorCode::CannotOpen)
+    )
+}
+
+/// Whether an IO error means the location cannot be written.
+fn is_read_only_io_error(e: &std::io::Error) -> bool {
+    e.kind() == std::io::ErrorKind::PermissionDenied || e.raw_os_error() == Some(EROFS)
 }
 
 /// Configures a database connection with optimal settings.
@@ -30,8 +52,15 @@ pub fn create_connection(path: &Path) -> Result<Connection> {
 /// Returns an error if any pragma cannot be set.
 pub fn configure_connection(conn: &Connection) -> Result<()> {
     // Enable WAL mode for better concurrency
+    // This is the first write, so a read-only database file fails here
     conn.pragma_update(None, "journal_mode", "WAL")
-        .map_err(|e| DatabaseError::ConnectionFailed(format!("Failed to enable WAL mode: {e}")))?;
+        .map_err(|e| {
+            if is_read_only_sqlite_error(&e) {
+                DatabaseError::ReadOnly(format!("Failed to enable WAL mode: {e}"))
+            } else {
+                DatabaseError::ConnectionFailed(format!("Failed to enable WAL mode: {e}"))
+            }
+        })?;
 
     // Enable foreign key constraints
     conn.pragma_update(None, "foreign_keys", "ON")
@@ -57,7 +86,13 @@ pub fn configure_connection(conn: &Connection) -> Result<()> {
 /// Returns an error if the directory cannot be created.
 pub fn ensure_directory(path: &Path) -> Result<()> {
     if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)?;
+        std::fs::create_dir_all(parent).map_err(|e| {
+            if is_read_only_io_error(&e) {
+                DatabaseError::ReadOnly(format!("{}: {e}", parent.display()))
+            } else {
+                DatabaseError::IoError(e)
+            }
+        })?;
     }
     Ok(())
 }
@@ -139,6 +174,17 @@ mod tests {
         ensure_directory(&db_path).unwrap();
     }
 
+    #[test]
+    fn test_read_only_io_errors() {
+        use std::io::{Error, ErrorKind};
+
+        assert!(is_read_only_io_error(&Error::from(
+            ErrorKind::PermissionDenied
+        )));
+        assert!(is_read_only_io_error(&Error::from_raw_os_error(EROFS)));
+        assert!(!is_read_only_io_error(&Error::from(ErrorKind::NotFound)));
+    }
+
     #[test]
     fn test_ensure_directory_no_parent() {
         // Path with no parent (like ":memory:") should not error
@@ -0,0 +1,241 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! CSV export and import of the stored daily snapshots.
+//!
+//! Every field is a date or a number, so values are written without quoting.
+
+use super::repository::{UsageRepository, UsageSnapshot};
+use super::{DatabaseError, Result};
+use crate::core::opencode::UsageMetrics;
+use chrono::NaiveDate;
+use std::io::{BufRead, BufReader, Read, Write};
+
+/// Header row of the snapshot CSV export.
+pub const CSV_HEADER: &str = "date,input_tokens,output_tokens,reasoning_tokens,cache_write_tokens,cache_read_tokens,cost,interactions";
+
+/// Writes every snapshot to `writer` as CSV, oldest first.
+///
+/// Rows are streamed from the database one at a time. Costs have two decimals.
+///
+/// # Errors
+///
+/// Returns an error if reading the snapshots or writing to `writer` fails.
+pub fn export_snapshots_csv(repository: &UsageRepository, mut writer: impl Write) -> Result<()> {
+    writeln!(writer, "{CSV_HEADER}")?;
+    repository.for_each_snapshot(|snapshot| Ok(write_csv_row(&mut writer, snapshot)?))?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes a single snapshot as a CSV row.
+fn write_csv_row(writer: &mut impl Write, snapshot: &UsageSnapshot) -> std::io::Result<()> {
+    writeln!(
+        writer,
+        "{},{},{},{},{},{},{:.2},{}",
+        snapshot.date,
+        snapshot.input_tokens,
+        snapshot.output_tokens,
+        snapshot.reasoning_tokens,
+        snapshot.cache_write_tokens,
+        snapshot.cache_read_tokens,
+        snapshot.total_cost,
+        snapshot.interaction_count
+    )
+}
+
+/// Imports snapshots from CSV in the export layout, replacing stored days.
+///
+/// The header row is optional and blank lines are skipped. Every row is validated
+/// before anything is saved, so a malformed file imports nothing. Returns the number
+/// of rows imported.
+///
+/// # Errors
+///
+/// Returns `DatabaseError::InvalidCsv` with the line number for a malformed row,
+/// or an error if reading the input or saving a snapshot fails.
+pub fn import_snapshots_csv(repository: &UsageRepository, reader: impl Read) -> Result<usize> {
+    let mut rows = Vec::new();
+    for (index, line) in BufReader::new(reader).lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || (index == 0 && line == CSV_HEADER) {
+            continue;
+        }
+        let row = parse_csv_row(line).map_err(|reason| DatabaseError::InvalidCsv {
+            line: index + 1,
+            reason,
+        })?;
+        rows.push(row);
+    }
+
+    for (date, metrics) in &rows {
+        repository.save_snapshot(*date, metrics)?;
+    }
+    Ok(rows.len())
+}
+
+/// Parses a CSV row into the snapshot date and its metrics.
+fn parse_csv_row(line: &str) -> std::result::Result<(NaiveDate, UsageMetrics), String> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    let expected = CSV_HEADER.split(',').count();
+    if fields.len() != expected {
+        return Err(format!(
+            "expected {expected} columns, found {}",
+            fields.len()
+        ));
+    }
+
+    let date = NaiveDate::parse_from_str(fields[0], "%Y-%m-%d")
+        .map_err(|e| format!("invalid date {:?}: {e}", fields[0]))?;
+    let cost: f64 = fields[6]
+        .parse()
+        .map_err(|_| format!("invalid cost {:?}", fields[6]))?;
+    if !cost.is_finite() || cost < 0.0 {
+        return Err(format!("cost must be non-negative (got {:?})", fields[6]));
+    }
+
+    Ok((
+        date,
+        UsageMetrics {
+            total_input_tokens: parse_count(fields[1], "input_tokens")?,
+            total_output_tokens: parse_count(fields[2], "output_tokens")?,
+            total_reasoning_tokens: parse_count(fields[3], "reasoning_tokens")?,
+            total_cache_write_tokens: parse_count(fields[4], "cache_write_tokens")?,
+            total_cache_read_tokens: parse_count(fields[5], "cache_read_tokens")?,
+            total_cost: cost,
+            total_cache_cost: 0.0,
+            interaction_count: usize::try_from(parse_count(fields[7], "interactions")?)
+                .map_err(|_| format!("interactions out of range (got {:?})", fields[7]))?,
+            timestamp: std::time::SystemTime::now(),
+        },
+    ))
+}
+
+/// Parses a non-negative integer column that fits the database.
+fn parse_count(value: &str, column: &str) -> std::result::Result<u64, String> {
+    value
+        .parse::<i64>()
+        .ok()
+        .and_then(|count| u64::try_from(count).ok())
+        .ok_or_else(|| format!("{column} must be a non-negative integer (got {value:?})"))
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)] // Tests use exact float comparisons for simplicity
+mod tests {
+    use super::*;
+    use crate::core::database::DatabaseManager;
+    use std::sync::Arc;
+    use std::time::SystemTime;
+
+    fn create_metrics(tokens: u64, cost: f64) -> UsageMetrics {
+        UsageMetrics {
+            total_input_tokens: tokens,
+            total_output_tokens: tokens / 2,
+            total_reasoning_tokens: 10,
+            total_cache_write_tokens: 20,
+            total_cache_read_tokens: 30,
+            total_cost: cost,
+            total_cache_cost: 0.0,
+            interaction_count: 3,
+            timestamp: SystemTime::now(),
+        }
+    }
+
+    #[test]
+    fn test_export_snapshots_csv_ordered_by_date() {
+        let repository = UsageRepository::new(Arc::new(DatabaseManager::new_in_memory().unwrap()));
+        repository
+            .save_snapshot(
+                NaiveDate::from_ymd_opt(2025, 10, 2).unwrap(),
+                &create_metrics(2000, 1.234),
+            )
+            .unwrap();
+        repository
+            .save_snapshot(
+                NaiveDate::from_ymd_opt(2025, 10, 1).unwrap(),
+                &create_metrics(1000, 0.5),
+            )
+            .unwrap();
+
+        let mut output = Vec::new();
+        export_snapshots_csv(&repository, &mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!(
+                "{CSV_HEADER}\n2025-10-01,1000,500,10,20,30,0.50,3\n2025-10-02,2000,1000,10,20,30,1.23,3\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_export_empty_history_writes_header_only() {
+        let repository = UsageRepository::new(Arc::new(DatabaseManager::new_in_memory().unwrap()));
+
+        let mut output = Vec::new();
+        export_snapshots_csv(&repository, &mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!("{CSV_HEADER}\n")
+        );
+    }
+
+    #[test]
+    fn test_import_round_trips_export() {
+        let source = UsageRepository::new(Arc::new(DatabaseManager::new_in_memory().unwrap()));
+        for day in 1..=3 {
+            source
+                .save_snapshot(
+                    NaiveDate::from_ymd_opt(2025, 10, day).unwrap(),
+                    &create_metrics(u64::from(day) * 1000, 0.25),
+                )
+                .unwrap();
+        }
+        let mut exported = Vec::new();
+        export_snapshots_csv(&source, &mut exported).unwrap();
+
+        let target = UsageRepository::new(Arc::new(DatabaseManager::new_in_memory().unwrap()));
+        assert_eq!(
+            import_snapshots_csv(&target, exported.as_slice()).unwrap(),
+            3
+        );
+
+        let imported = target
+            .get_snapshot(NaiveDate::from_ymd_opt(2025, 10, 2).unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(imported.input_tokens, 2000);
+        assert_eq!(imported.output_tokens, 1000);
+        assert_eq!(imported.cache_read_tokens, 30);
+        assert_eq!(imported.total_cost, 0.25);
+        assert_eq!(imported.interaction_count, 3);
+    }
+
+    #[test]
+    fn test_import_rejects_malformed_rows_with_line_number() {
+        let repository = UsageRepository::new(Arc::new(DatabaseManager::new_in_memory().unwrap()));
+        let cases = [
+            (
+                "2025-10-01,1,2,3,4,5,0.50,1\n2025-13-01,1,2,3,4,5,0.50,1\n",
+                2,
+            ),
+            ("2025-10-01,1,2,3,4,5,0.50\n", 1),
+            ("2025-10-01,1,2,3,4,5,0.50,1,9\n", 1),
+            ("\n2025-10-01,-1,2,3,4,5,0.50,1\n", 2),
+            ("2025-10-01,1,2,3,4,5,-0.50,1\n", 1),
+            ("2025-10-01,1,x,3,4,5,0.50,1\n", 1),
+        ];
+        for (csv, expected_line) in cases {
+            let result = import_snapshots_csv(&repository, csv.as_bytes());
+            assert!(
+                matches!(result, Err(DatabaseError::InvalidCsv { line, .. }) if line == expected_line),
+                "{csv:?} gave {result:?}"
+            );
+        }
+
+        // A malformed row imports nothing, not even the valid rows before it
+        assert_eq!(repository.snapshot_count().unwrap(), 0);
+    }
+}
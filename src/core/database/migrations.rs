@@ -70,6 +70,16 @@ CREATE INDEX IF NOT EXISTS idx_usage_snapshots_date ON usage_snapshots(date);
 "
                 .to_string(),
         },
+        Migration {
+            version: 3,
+            description: "Flag snapshots that hold a single day's usage".to_string(),
+            sql: r"
+-- Older snapshots may hold the displayed month or all-time totals instead of the
+-- day's own usage, so existing rows start out unflagged
+ALTER TABLE usage_snapshots ADD COLUMN per_day INTEGER NOT NULL DEFAULT 0;
+"
+            .to_string(),
+        },
     ]
 }
 
@@ -207,7 +217,7 @@ mod tests {
         apply_migrations(&conn).unwrap();
 
         let version = get_current_version(&conn).unwrap();
-        assert_eq!(version, 2); // Updated to expect version 2
+        assert_eq!(version, 3); // Updated to expect version 3
     }
 
     #[test]
@@ -260,9 +270,35 @@ mod tests {
         apply_migrations(&conn).unwrap();
         apply_migrations(&conn).unwrap(); // Should not error
 
-        // Verify version is 2 (latest migration)
+        // Verify version is 3 (latest migration)
         let version = get_current_version(&conn).unwrap();
-        assert_eq!(version, 2);
+        assert_eq!(version, 3);
+    }
+
+    #[test]
+    fn test_existing_snapshots_are_not_flagged_per_day() {
+        let conn = Connection::open_in_memory().unwrap();
+        for migration in get_migrations().iter().filter(|m| m.version <= 2) {
+            apply_single_migration(&conn, migration).unwrap();
+        }
+        conn.execute(
+            "INSERT INTO usage_snapshots
+             (date, input_tokens, output_tokens, reasoning_tokens, cache_write_tokens, cache_read_tokens, total_cost, interaction_count, created_at)
+             VALUES ('2025-10-01', 1, 1, 0, 0, 0, 1.0, 1, '2025-10-01T00:00:00Z')",
+            [],
+        )
+        .unwrap();
+
+        apply_migrations(&conn).unwrap();
+
+        let per_day: i64 = conn
+            .query_row(
+                "SELECT per_day FROM usage_snapshots WHERE date = '2025-10-01'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(per_day, 0);
     }
 
     #[test]
@@ -10,9 +10,11 @@ use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
 pub mod connection;
+pub mod export;
 pub mod migrations;
 pub mod repository;
 pub mod schema;
+pub mod source;
 
 /// Custom error type for database operations.
 #[derive(Debug, thiserror::Error)]
@@ -36,11 +38,42 @@ pub enum DatabaseError {
     /// SQL error
     #[error("SQL error: {0}")]
     SqlError(#[from] rusqlite::Error),
+
+    /// The database location cannot be written to
+    #[error("Database location is read-only: {0}")]
+    ReadOnly(String),
+
+    /// A row of an imported CSV file is malformed
+    #[error("Invalid CSV on line {line}: {reason}")]
+    InvalidCsv { line: usize, reason: String },
+}
+
+impl DatabaseError {
+    /// Whether the operation failed because another connection holds the database lock.
+    ///
+    /// Such failures are transient and worth retrying later.
+    #[must_use]
+    pub fn is_busy(&self) -> bool {
+        matches!(
+            self,
+            Self::SqlError(rusqlite::Error::SqliteFailure(err, _))
+                if matches!(
+                    err.code,
+                    rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked
+                )
+        )
+    }
 }
 
 /// Result type for database operations.
 pub type Result<T> = std::result::Result<T, DatabaseError>;
 
+/// Environment variable overriding the default database path.
+pub const DB_PATH_ENV: &str = "OPENCODE_USAGE_DB";
+
+/// `SQLite` path for a database that lives only in memory.
+pub const IN_MEMORY_PATH: &str = ":memory:";
+
 /// Manages database connection and operations.
 pub struct DatabaseManager {
     db_path: PathBuf,
@@ -50,7 +83,8 @@ pub struct DatabaseManager {
 impl DatabaseManager {
     /// Creates a new `DatabaseManager` with the default database path.
     ///
-    /// The default path is `~/.local/share/cosmic-applet-opencode-usage/usage.db`.
+    /// The default path is `~/.local/share/cosmic-applet-opencode-usage/usage.db`,
+    /// unless overridden by the `OPENCODE_USAGE_DB` environment variable.
     ///
     /// # Errors
     ///
@@ -59,7 +93,20 @@ impl DatabaseManager {
     /// - The database connection cannot be established
     /// - Schema migrations fail
     pub fn new() -> Result<Self> {
-        let db_path = Self::default_path()?;
+        Self::new_in_namespace(None)
+    }
+
+    /// Creates a new `DatabaseManager` with the default path for a data namespace.
+    ///
+    /// A namespace nests the database in a subdirectory, e.g.
+    /// `~/.local/share/cosmic-applet-opencode-usage/work/usage.db`. The
+    /// `OPENCODE_USAGE_DB` environment variable still takes precedence.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be created or opened, as for `new`.
+    pub fn new_in_namespace(namespace: Option<&str>) -> Result<Self> {
+        let db_path = Self::default_path(namespace)?;
         Self::new_with_path(&db_path)
     }
 
@@ -90,6 +137,50 @@ impl DatabaseManager {
         })
     }
 
+    /// Creates a `DatabaseManager` backed by an in-memory database.
+    ///
+    /// Nothing is written to disk, so the data is lost when the manager is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the connection cannot be configured or migrations fail.
+    pub fn new_in_memory() -> Result<Self> {
+        Self::new_with_path(Path::new(IN_MEMORY_PATH))
+    }
+
+    /// Creates a `DatabaseManager` with the default path for a data namespace, falling back
+    /// to memory if it is read-only.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be opened for any other reason.
+    pub fn new_or_in_memory(namespace: Option<&str>) -> Result<Self> {
+        Self::or_in_memory(Self::new_in_namespace(namespace))
+    }
+
+    /// Falls back to an in-memory database when opening failed because the location is read-only.
+    ///
+    /// Other errors are returned unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns the original error unless it is `DatabaseError::ReadOnly`.
+    pub fn or_in_memory(opened: Result<Self>) -> Result<Self> {
+        match opened {
+            Err(DatabaseError::ReadOnly(reason)) => {
+                eprintln!("[Database] {reason}; keeping history in memory for this session only");
+                Self::new_in_memory()
+            }
+            other => other,
+        }
+    }
+
+    /// Returns whether the database lives only in memory and will not persist.
+    #[must_use]
+    pub fn is_in_memory(&self) -> bool {
+        self.db_path == Path::new(IN_MEMORY_PATH)
+    }
+
     /// Gets a reference to the database connection.
     ///
     /// This returns a `MutexGuard` which will block if another thread is
@@ -109,19 +200,47 @@ impl DatabaseManager {
         &self.db_path
     }
 
-    /// Returns the default database path.
+    /// Returns whether a database file already exists at the default path for a data namespace.
+    #[must_use]
+    pub fn default_database_exists(namespace: Option<&str>) -> bool {
+        Self::default_path(namespace).is_ok_and(|path| path.exists())
+    }
+
+    /// Returns the default database path for a data namespace.
     ///
     /// # Errors
     ///
     /// Returns an error if the home directory cannot be determined.
-    fn default_path() -> Result<PathBuf> {
-        let home = std::env::var("HOME").map_err(|e| {
+    fn default_path(namespace: Option<&str>) -> Result<PathBuf> {
+        Self::resolve_path(
+            std::env::var_os(DB_PATH_ENV),
+            std::env::var("HOME"),
+            namespace,
+        )
+    }
+
+    /// Resolves the database path from an optional override and the home directory.
+    ///
+    /// A non-empty override takes precedence over the home-based default, which is
+    /// nested under `namespace` when one is given.
+    fn resolve_path(
+        env_override: Option<std::ffi::OsString>,
+        home: std::result::Result<String, std::env::VarError>,
+        namespace: Option<&str>,
+    ) -> Result<PathBuf> {
+        if let Some(path) = env_override.filter(|p| !p.is_empty()) {
+            return Ok(PathBuf::from(path));
+        }
+
+        let home = home.map_err(|e| {
             DatabaseError::ConnectionFailed(format!("Could not determine HOME directory: {e}"))
         })?;
 
-        Ok(PathBuf::from(home)
-            .join(".local/share/cosmic-applet-opencode-usage")
-            .join("usage.db"))
+        let mut dir = PathBuf::from(home).join(".local/share/cosmic-applet-opencode-usage");
+        if let Some(namespace) = namespace {
+            dir.push(namespace);
+        }
+        Ok(dir.join("usage.db"))
     }
 }
 
@@ -204,4 +323,133 @@ mod tests {
         let manager = DatabaseManager::new_with_path(&db_path).unwrap();
         assert_eq!(manager.path(), db_path);
     }
+
+    #[test]
+    fn test_read_only_location_falls_back_to_memory() {
+        use crate::core::database::repository::UsageRepository;
+        use crate::core::opencode::UsageMetrics;
+        use chrono::NaiveDate;
+        use std::sync::Arc;
+
+        let failed = Err(DatabaseError::ReadOnly(
+            "/readonly/usage.db: Read-only file system".to_string(),
+        ));
+        let manager = DatabaseManager::or_in_memory(failed).expect("Should fall back to memory");
+        assert!(manager.is_in_memory());
+
+        // The fallback database works for the rest of the session
+        let repository = UsageRepository::new(Arc::new(manager));
+        let date = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+        let metrics = UsageMetrics {
+            total_input_tokens: 100,
+            total_output_tokens: 50,
+            total_reasoning_tokens: 0,
+            total_cache_write_tokens: 0,
+            total_cache_read_tokens: 0,
+            total_cost: 0.5,
+            total_cache_cost: 0.0,
+            interaction_count: 2,
+            timestamp: std::time::SystemTime::now(),
+        };
+        repository.save_snapshot(date, &metrics).unwrap();
+        let snapshot = repository.get_snapshot(date).unwrap().unwrap();
+        assert_eq!(snapshot.input_tokens, 100);
+        assert_eq!(snapshot.interaction_count, 2);
+    }
+
+    #[test]
+    fn test_other_open_errors_do_not_fall_back() {
+        let failed = Err(DatabaseError::MigrationFailed("bad schema".to_string()));
+        assert!(matches!(
+            DatabaseManager::or_in_memory(failed),
+            Err(DatabaseError::MigrationFailed(_))
+        ));
+
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let manager =
+            DatabaseManager::or_in_memory(DatabaseManager::new_with_path(&db_path)).unwrap();
+        assert!(!manager.is_in_memory());
+        assert_eq!(manager.path(), db_path);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_only_directory_is_reported_as_read_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let locked = temp_dir.path().join("locked");
+        std::fs::create_dir(&locked).unwrap();
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o555)).unwrap();
+
+        let result = DatabaseManager::new_with_path(&locked.join("data").join("usage.db"));
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        // Root ignores directory permissions, so only check when access was denied
+        if let Err(err) = result {
+            assert!(matches!(err, DatabaseError::ReadOnly(_)), "got {err:?}");
+        }
+    }
+
+    #[test]
+    fn test_resolve_path_prefers_env_override() {
+        let path = DatabaseManager::resolve_path(
+            Some("/tmp/custom/usage.db".into()),
+            Ok("/home/user".to_string()),
+            Some("work"),
+        )
+        .unwrap();
+        assert_eq!(path, PathBuf::from("/tmp/custom/usage.db"));
+    }
+
+    #[test]
+    fn test_resolve_path_ignores_empty_override() {
+        let path =
+            DatabaseManager::resolve_path(Some("".into()), Ok("/home/user".to_string()), None)
+                .unwrap();
+        assert_eq!(
+            path,
+            PathBuf::from("/home/user/.local/share/cosmic-applet-opencode-usage/usage.db")
+        );
+    }
+
+    #[test]
+    fn test_resolve_path_nests_namespace() {
+        let path = DatabaseManager::resolve_path(None, Ok("/home/user".to_string()), Some("work"))
+            .unwrap();
+        assert_eq!(
+            path,
+            PathBuf::from("/home/user/.local/share/cosmic-applet-opencode-usage/work/usage.db")
+        );
+        let path = DatabaseManager::resolve_path(None, Ok("/home/user".to_string()), None).unwrap();
+        assert_eq!(
+            path,
+            PathBuf::from("/home/user/.local/share/cosmic-applet-opencode-usage/usage.db")
+        );
+    }
+
+    #[test]
+    fn test_resolve_path_without_home_or_override_fails() {
+        let result = DatabaseManager::resolve_path(None, Err(std::env::VarError::NotPresent), None);
+        assert!(matches!(result, Err(DatabaseError::ConnectionFailed(_))));
+    }
+
+    #[test]
+    fn test_db_env_override_path_is_opened() {
+        // Resolved directly instead of setting the variable, which other tests read in parallel
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("env").join("usage.db");
+
+        let resolved = DatabaseManager::resolve_path(
+            Some(db_path.clone().into_os_string()),
+            Ok("/home/user".to_string()),
+            None,
+        )
+        .unwrap();
+        let manager = DatabaseManager::new_with_path(&resolved).unwrap();
+
+        assert_eq!(manager.path(), db_path);
+        assert!(db_path.exists());
+    }
 }
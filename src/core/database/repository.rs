@@ -4,10 +4,46 @@
 //!
 //! This module provides a clean API for storing and retrieving usage snapshots.
 
-use super::{DatabaseManager, Result};
-use chrono::NaiveDate;
+use super::{DatabaseError, DatabaseManager, Result};
+use crate::core::config::ActiveDayDefinition;
+use chrono::{Datelike, NaiveDate};
 use rusqlite;
+use std::collections::HashSet;
 use std::sync::Arc;
+use std::time::Duration;
+
+/// Default number of attempts for a write that finds the database busy.
+pub const DEFAULT_WRITE_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry of a busy write; doubled for each further retry.
+const WRITE_RETRY_BASE_DELAY: Duration = Duration::from_millis(50);
+
+/// Runs `op`, retrying up to `attempts` times in total while it fails with a busy database.
+///
+/// Sleeps between attempts with an exponential backoff starting at `base_delay`.
+/// Other errors, and the last busy error, are returned unchanged.
+///
+/// # Errors
+///
+/// Returns the error from the last attempt.
+pub fn retry_busy<T, F>(attempts: u32, base_delay: Duration, mut op: F) -> Result<T>
+where
+    F: FnMut() -> Result<T>,
+{
+    let mut delay = base_delay;
+    let mut attempt = 1;
+    loop {
+        match op() {
+            Err(err) if err.is_busy() && attempt < attempts => {
+                eprintln!("Database busy (attempt {attempt}/{attempts}), retrying in {delay:?}");
+                std::thread::sleep(delay);
+                delay = delay.saturating_mul(2);
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
 
 /// A snapshot of usage metrics for a specific date.
 #[derive(Debug, Clone, PartialEq)]
@@ -22,21 +58,102 @@ pub struct UsageSnapshot {
     pub interaction_count: i64,
 }
 
+impl UsageSnapshot {
+    /// Returns the total of input, output and reasoning tokens.
+    #[must_use]
+    pub fn total_tokens(&self) -> i64 {
+        self.input_tokens
+            .saturating_add(self.output_tokens)
+            .saturating_add(self.reasoning_tokens)
+    }
+
+    /// Returns true if the day has any recorded usage.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.interaction_count > 0 || self.input_tokens > 0 || self.output_tokens > 0
+    }
+
+    /// Returns true if the day counts as active under `definition`.
+    #[must_use]
+    pub fn is_active_under(&self, definition: ActiveDayDefinition) -> bool {
+        match definition {
+            ActiveDayDefinition::AnyUsage => self.is_active(),
+            ActiveDayDefinition::NonzeroCost => self.total_cost > 0.0,
+        }
+    }
+
+    /// Converts the snapshot into `UsageMetrics` (negative values are clamped to 0).
+    #[must_use]
+    pub fn to_usage_metrics(&self) -> crate::core::opencode::UsageMetrics {
+        crate::core::opencode::UsageMetrics {
+            total_input_tokens: u64::try_from(self.input_tokens).unwrap_or(0),
+            total_output_tokens: u64::try_from(self.output_tokens).unwrap_or(0),
+            total_reasoning_tokens: u64::try_from(self.reasoning_tokens).unwrap_or(0),
+            total_cache_write_tokens: u64::try_from(self.cache_write_tokens).unwrap_or(0),
+            total_cache_read_tokens: u64::try_from(self.cache_read_tokens).unwrap_or(0),
+            total_cost: self.total_cost,
+            total_cache_cost: 0.0,
+            interaction_count: usize::try_from(self.interaction_count).unwrap_or(0),
+            timestamp: std::time::SystemTime::now(),
+        }
+    }
+
+    /// Returns the cost in dollars per 1,000 tokens, or 0 for days without tokens.
+    #[must_use]
+    pub fn cost_per_1k_tokens(&self) -> f64 {
+        crate::core::opencode::aggregator::cost_per_1k(
+            self.total_cost,
+            u64::try_from(self.total_tokens()).unwrap_or(0),
+        )
+    }
+
+    /// Returns the average cost in dollars per interaction, or `None` for days without interactions.
+    #[must_use]
+    pub fn cost_per_interaction(&self) -> Option<f64> {
+        crate::core::opencode::aggregator::cost_per_interaction(
+            self.total_cost,
+            u64::try_from(self.interaction_count).unwrap_or(0),
+        )
+    }
+}
+
 /// High-level repository for usage snapshot operations.
+#[derive(Clone)]
 pub struct UsageRepository {
     db: Arc<DatabaseManager>,
+    write_attempts: u32,
+    active_day_definition: ActiveDayDefinition,
 }
 
 impl UsageRepository {
     /// Creates a new `UsageRepository`.
     #[must_use]
     pub fn new(db: Arc<DatabaseManager>) -> Self {
-        Self { db }
+        Self {
+            db,
+            write_attempts: DEFAULT_WRITE_ATTEMPTS,
+            active_day_definition: ActiveDayDefinition::default(),
+        }
+    }
+
+    /// Sets which days `active_day_count` and week summaries count as active.
+    #[must_use]
+    pub fn with_active_day_definition(mut self, definition: ActiveDayDefinition) -> Self {
+        self.active_day_definition = definition;
+        self
+    }
+
+    /// Sets how many times a write is attempted while the database is busy (at least once).
+    #[must_use]
+    pub fn with_write_attempts(mut self, attempts: u32) -> Self {
+        self.write_attempts = attempts.max(1);
+        self
     }
 
     /// Saves a usage snapshot for a specific date.
     ///
     /// If a snapshot already exists for this date, it will be replaced.
+    /// A busy database is retried with a short backoff before giving up.
     ///
     /// # Errors
     ///
@@ -45,13 +162,26 @@ impl UsageRepository {
         &self,
         date: NaiveDate,
         metrics: &crate::core::opencode::UsageMetrics,
+    ) -> Result<()> {
+        retry_busy(self.write_attempts, WRITE_RETRY_BASE_DELAY, || {
+            self.insert_snapshot(date, metrics)
+        })
+    }
+
+    /// Writes a single snapshot row, replacing any existing one for the date.
+    ///
+    /// The row is flagged as holding the day's own usage, which is what gets summed.
+    fn insert_snapshot(
+        &self,
+        date: NaiveDate,
+        metrics: &crate::core::opencode::UsageMetrics,
     ) -> Result<()> {
         let conn = self.db.get_connection();
 
         conn.execute(
             "INSERT OR REPLACE INTO usage_snapshots 
-             (date, input_tokens, output_tokens, reasoning_tokens, cache_write_tokens, cache_read_tokens, total_cost, interaction_count, created_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+             (date, input_tokens, output_tokens, reasoning_tokens, cache_write_tokens, cache_read_tokens, total_cost, interaction_count, created_at, per_day)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 1)",
             rusqlite::params![
                 date.to_string(),
                 i64::try_from(metrics.total_input_tokens).unwrap_or(0),
@@ -118,6 +248,196 @@ impl UsageRepository {
         Ok(snapshots)
     }
 
+    /// Calls `f` with every stored snapshot, oldest first.
+    ///
+    /// Rows are read one at a time instead of being collected, so the whole history
+    /// is never held in memory. Stops at the first error returned by `f`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails, or the error from `f`.
+    pub fn for_each_snapshot<F>(&self, mut f: F) -> Result<()>
+    where
+        F: FnMut(&UsageSnapshot) -> Result<()>,
+    {
+        let conn = self.db.get_connection();
+
+        let mut stmt = conn.prepare(
+            "SELECT date, input_tokens, output_tokens, reasoning_tokens, cache_write_tokens, cache_read_tokens, total_cost, interaction_count
+             FROM usage_snapshots
+             ORDER BY date ASC"
+        )?;
+
+        for snapshot in stmt.query_map([], Self::row_to_snapshot)? {
+            f(&snapshot?)?;
+        }
+
+        Ok(())
+    }
+
+    /// Retrieves the snapshots between two dates (inclusive), oldest first.
+    ///
+    /// Unlike `get_range`, a reversed range is rejected instead of matching nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::SchemaError` if `start` is after `end`, or an error if
+    /// the database operation fails.
+    pub fn get_snapshots_in_range(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<UsageSnapshot>> {
+        if start > end {
+            return Err(DatabaseError::SchemaError(format!(
+                "Invalid date range: start {start} is after end {end}"
+            )));
+        }
+        self.get_range(start, end)
+    }
+
+    /// Sums the snapshots between two dates (inclusive) per ISO week, oldest first.
+    ///
+    /// Weeks start on Monday, so a week spanning New Year (including week 53) is one
+    /// total keyed by its Monday in the previous year. Weeks without snapshots are left
+    /// out. A partially covered week only sums the days within the range.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::SchemaError` if `start` is after `end`, or an error if
+    /// the database operation fails.
+    pub fn get_weekly_totals(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<WeeklyTotal>> {
+        let mut totals: Vec<WeeklyTotal> = Vec::new();
+        for snapshot in self.get_snapshots_in_range(start, end)? {
+            let week_start = snapshot.date
+                - chrono::Duration::days(i64::from(snapshot.date.weekday().num_days_from_monday()));
+            // Snapshots are ordered by date, so a new week always comes last
+            match totals.last_mut() {
+                Some(total) if total.week_start == week_start => total.add(&snapshot),
+                _ => {
+                    let mut total = WeeklyTotal {
+                        week_start,
+                        ..WeeklyTotal::default()
+                    };
+                    total.add(&snapshot);
+                    totals.push(total);
+                }
+            }
+        }
+        Ok(totals)
+    }
+
+    /// Returns the running total of cost for each snapshot within a range (inclusive).
+    ///
+    /// Each entry holds the cost of all snapshots from `start` up to and including
+    /// that day. Days without a snapshot are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    pub fn cumulative_cost(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, f64)>> {
+        let mut running = 0.0;
+        Ok(self
+            .get_range(start, end)?
+            .into_iter()
+            .map(|snapshot| {
+                running += snapshot.total_cost;
+                (snapshot.date, running)
+            })
+            .collect())
+    }
+
+    /// Returns the calendar dates within a range (inclusive) that have no snapshot.
+    ///
+    /// An inverted range has no dates and returns an empty list.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    pub fn missing_dates(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<NaiveDate>> {
+        let recorded: HashSet<NaiveDate> = self
+            .get_range(start, end)?
+            .into_iter()
+            .map(|snapshot| snapshot.date)
+            .collect();
+
+        Ok(start
+            .iter_days()
+            .take_while(|date| *date <= end)
+            .filter(|date| !recorded.contains(date))
+            .collect())
+    }
+
+    /// Lists the dates of snapshots written by older versions, oldest first.
+    ///
+    /// Those snapshots may hold the displayed month or all-time totals instead of the
+    /// day's own usage, so they are left out of sums until recomputed from the usage
+    /// files (saving a snapshot for the date replaces them).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    pub fn legacy_dates(&self) -> Result<Vec<NaiveDate>> {
+        let conn = self.db.get_connection();
+
+        let mut stmt =
+            conn.prepare("SELECT date FROM usage_snapshots WHERE per_day = 0 ORDER BY date")?;
+        let mut rows = stmt.query([])?;
+
+        let mut dates = Vec::new();
+        while let Some(row) = rows.next()? {
+            let date: String = row.get(0)?;
+            dates
+                .push(NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|e| {
+                    DatabaseError::SchemaError(format!("Invalid date format: {e}"))
+                })?);
+        }
+
+        Ok(dates)
+    }
+
+    /// Counts the consecutive days with usage, walking backward from `today`.
+    ///
+    /// A day counts when its snapshot records usage, so a missing or idle `today`
+    /// means no current streak.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    pub fn current_streak(&self, today: NaiveDate) -> Result<u32> {
+        let conn = self.db.get_connection();
+
+        let mut stmt = conn.prepare(
+            "SELECT date FROM usage_snapshots
+             WHERE date <= ?1
+               AND (interaction_count > 0 OR input_tokens > 0 OR output_tokens > 0)
+             ORDER BY date DESC",
+        )?;
+        let mut rows = stmt.query(rusqlite::params![today.to_string()])?;
+
+        let mut streak = 0;
+        let mut expected = today;
+        while let Some(row) = rows.next()? {
+            let date: String = row.get(0)?;
+            let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                .map_err(|e| DatabaseError::SchemaError(format!("Invalid date format: {e}")))?;
+            if date != expected {
+                break;
+            }
+            streak += 1;
+            let Some(previous) = expected.pred_opt() else {
+                break;
+            };
+            expected = previous;
+        }
+
+        Ok(streak)
+    }
+
     /// Retrieves the most recent usage snapshot.
     ///
     /// # Errors
@@ -142,24 +462,140 @@ impl UsageRepository {
         }
     }
 
+    /// Retrieves the oldest usage snapshot.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    pub fn get_earliest(&self) -> Result<Option<UsageSnapshot>> {
+        let conn = self.db.get_connection();
+
+        let mut stmt = conn.prepare(
+            "SELECT date, input_tokens, output_tokens, reasoning_tokens, cache_write_tokens, cache_read_tokens, total_cost, interaction_count
+             FROM usage_snapshots
+             ORDER BY date ASC
+             LIMIT 1"
+        )?;
+
+        let result = stmt.query_row([], Self::row_to_snapshot);
+
+        match result {
+            Ok(snapshot) => Ok(Some(snapshot)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Retrieves the `n` most recent usage snapshots, newest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    pub fn recent_snapshots(&self, n: usize) -> Result<Vec<UsageSnapshot>> {
+        let conn = self.db.get_connection();
+
+        let mut stmt = conn.prepare(
+            "SELECT date, input_tokens, output_tokens, reasoning_tokens, cache_write_tokens, cache_read_tokens, total_cost, interaction_count
+             FROM usage_snapshots
+             ORDER BY date DESC
+             LIMIT ?1"
+        )?;
+
+        let limit = i64::try_from(n).unwrap_or(i64::MAX);
+        let snapshots = stmt
+            .query_map([limit], Self::row_to_snapshot)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(snapshots)
+    }
+
+    /// Retrieves one page of usage snapshots, newest first.
+    ///
+    /// Pages are numbered from 0; a page past the end is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    pub fn get_page(&self, page: usize, page_size: usize) -> Result<Vec<UsageSnapshot>> {
+        let conn = self.db.get_connection();
+
+        let mut stmt = conn.prepare(
+            "SELECT date, input_tokens, output_tokens, reasoning_tokens, cache_write_tokens, cache_read_tokens, total_cost, interaction_count
+             FROM usage_snapshots
+             ORDER BY date DESC
+             LIMIT ?1 OFFSET ?2"
+        )?;
+
+        let limit = i64::try_from(page_size).unwrap_or(i64::MAX);
+        let offset = i64::try_from(page.saturating_mul(page_size)).unwrap_or(i64::MAX);
+        let snapshots = stmt
+            .query_map([limit, offset], Self::row_to_snapshot)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(snapshots)
+    }
+
+    /// Counts the stored usage snapshots.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    pub fn snapshot_count(&self) -> Result<usize> {
+        let conn = self.db.get_connection();
+
+        let count: i64 =
+            conn.query_row("SELECT COUNT(*) FROM usage_snapshots", [], |row| row.get(0))?;
+
+        Ok(usize::try_from(count).unwrap_or(0))
+    }
+
     /// Deletes snapshots older than the specified number of days.
     ///
     /// Returns the number of snapshots deleted.
+    /// A busy database is retried with a short backoff before giving up.
     ///
     /// # Errors
     ///
     /// Returns an error if the database operation fails.
     pub fn delete_old(&self, days: u32) -> Result<usize> {
-        let conn = self.db.get_connection();
-
         let cutoff_date = chrono::Utc::now().date_naive() - chrono::Duration::days(i64::from(days));
+        self.delete_snapshots_before(cutoff_date)
+    }
 
-        let deleted = conn.execute(
-            "DELETE FROM usage_snapshots WHERE date < ?1",
-            rusqlite::params![cutoff_date.to_string()],
-        )?;
+    /// Deletes all snapshots dated before `cutoff` (the cutoff day itself is kept).
+    ///
+    /// Returns the number of snapshots deleted.
+    /// A busy database is retried with a short backoff before giving up.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    pub fn delete_snapshots_before(&self, cutoff: NaiveDate) -> Result<usize> {
+        retry_busy(self.write_attempts, WRITE_RETRY_BASE_DELAY, || {
+            let conn = self.db.get_connection();
+            Ok(conn.execute(
+                "DELETE FROM usage_snapshots WHERE date < ?1",
+                rusqlite::params![cutoff.to_string()],
+            )?)
+        })
+    }
 
-        Ok(deleted)
+    /// Deletes the snapshot for a single date.
+    ///
+    /// Returns `true` if a snapshot was deleted, `false` if none was stored for the date.
+    /// A busy database is retried with a short backoff before giving up.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    pub fn delete_snapshot(&self, date: NaiveDate) -> Result<bool> {
+        retry_busy(self.write_attempts, WRITE_RETRY_BASE_DELAY, || {
+            let conn = self.db.get_connection();
+            Ok(conn.execute(
+                "DELETE FROM usage_snapshots WHERE date = ?1",
+                rusqlite::params![date.to_string()],
+            )? > 0)
+        })
     }
 
     /// Aggregates usage data for a week into a single summary.
@@ -181,42 +617,328 @@ impl UsageRepository {
             total_cache_read_tokens: snapshots.iter().map(|s| s.cache_read_tokens).sum(),
             total_cost: snapshots.iter().map(|s| s.total_cost).sum(),
             total_interactions: snapshots.iter().map(|s| s.interaction_count).sum(),
+            active_days: self.active_day_count(week_start, week_end)?,
+            active_day_definition: self.active_day_definition,
+        };
+
+        Ok(summary)
+    }
+
+    /// Returns the total number of interactions across all stored snapshots.
+    ///
+    /// Snapshots from older versions that may hold display totals are left out, see
+    /// `legacy_dates`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    pub fn get_total_interactions(&self) -> Result<i64> {
+        let conn = self.db.get_connection();
+
+        let total = conn.query_row(
+            "SELECT COALESCE(SUM(interaction_count), 0) FROM usage_snapshots WHERE per_day = 1",
+            [],
+            |row| row.get(0),
+        )?;
+
+        Ok(total)
+    }
+
+    /// Sums all stored snapshots into all-time usage totals.
+    ///
+    /// Each snapshot holds a single day's usage, so the sum never counts a day twice.
+    /// Snapshots from older versions that may hold display totals are left out until
+    /// they are recomputed, see `legacy_dates`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    pub fn get_all_time_usage(&self) -> Result<crate::core::opencode::UsageMetrics> {
+        self.sum_snapshots(None, None)
+    }
+
+    /// Sums the snapshots within a range (inclusive) into usage totals.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    pub fn get_range_usage(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<crate::core::opencode::UsageMetrics> {
+        self.sum_snapshots(Some(start), Some(end))
+    }
+
+    /// Sums all snapshots up to and including `end` into usage totals.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    pub fn get_usage_until(&self, end: NaiveDate) -> Result<crate::core::opencode::UsageMetrics> {
+        self.sum_snapshots(None, Some(end))
+    }
+
+    /// Sums the snapshots between optional bounds (inclusive) into usage totals.
+    ///
+    /// Only snapshots flagged as holding a single day's usage are summed.
+    fn sum_snapshots(
+        &self,
+        start: Option<NaiveDate>,
+        end: Option<NaiveDate>,
+    ) -> Result<crate::core::opencode::UsageMetrics> {
+        let conn = self.db.get_connection();
+
+        let snapshot = conn.query_row(
+            "SELECT COALESCE(SUM(input_tokens), 0), COALESCE(SUM(output_tokens), 0),
+                    COALESCE(SUM(reasoning_tokens), 0), COALESCE(SUM(cache_write_tokens), 0),
+                    COALESCE(SUM(cache_read_tokens), 0), COALESCE(SUM(total_cost), 0.0),
+                    COALESCE(SUM(interaction_count), 0)
+             FROM usage_snapshots
+             WHERE per_day = 1 AND (?1 IS NULL OR date >= ?1) AND (?2 IS NULL OR date <= ?2)",
+            rusqlite::params![
+                start.map(|date| date.to_string()),
+                end.map(|date| date.to_string())
+            ],
+            |row| {
+                Ok(UsageSnapshot {
+                    date: NaiveDate::MIN,
+                    input_tokens: row.get(0)?,
+                    output_tokens: row.get(1)?,
+                    reasoning_tokens: row.get(2)?,
+                    cache_write_tokens: row.get(3)?,
+                    cache_read_tokens: row.get(4)?,
+                    total_cost: row.get(5)?,
+                    interaction_count: row.get(6)?,
+                })
+            },
+        )?;
+
+        Ok(snapshot.to_usage_metrics())
+    }
+
+    /// Sums the cost of all stored snapshots by day of the week.
+    ///
+    /// Index 0 is Monday and index 6 is Sunday.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    pub fn cost_by_weekday(&self) -> Result<[f64; 7]> {
+        let conn = self.db.get_connection();
+
+        // strftime('%w') numbers the days from Sunday = 0
+        let mut stmt = conn.prepare(
+            "SELECT CAST(strftime('%w', date) AS INTEGER) AS weekday, SUM(total_cost)
+             FROM usage_snapshots
+             GROUP BY weekday",
+        )?;
+
+        let mut totals = [0.0; 7];
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, f64>(1)?)))?;
+        for row in rows {
+            let (weekday, cost) = row?;
+            if let Ok(sunday_based) = usize::try_from(weekday) {
+                totals[(sunday_based + 6) % 7] += cost;
+            }
+        }
+
+        Ok(totals)
+    }
+
+    /// Counts the active days in a date range (inclusive).
+    ///
+    /// Days are active by the repository's `ActiveDayDefinition`: any recorded usage by
+    /// default, or only a nonzero cost.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    pub fn active_day_count(&self, start: NaiveDate, end: NaiveDate) -> Result<i64> {
+        let conn = self.db.get_connection();
+
+        let active = match self.active_day_definition {
+            ActiveDayDefinition::AnyUsage => {
+                "interaction_count > 0 OR input_tokens > 0 OR output_tokens > 0"
+            }
+            ActiveDayDefinition::NonzeroCost => "total_cost > 0",
         };
+        let count = conn.query_row(
+            &format!(
+                "SELECT COUNT(*) FROM usage_snapshots
+                 WHERE date >= ?1 AND date <= ?2 AND ({active})"
+            ),
+            rusqlite::params![start.to_string(), end.to_string()],
+            |row| row.get(0),
+        )?;
+
+        Ok(count)
+    }
+
+    /// Helper to convert a database row to a `UsageSnapshot`.
+    fn row_to_snapshot(row: &rusqlite::Row) -> std::result::Result<UsageSnapshot, rusqlite::Error> {
+        let date_str: String = row.get(0)?;
+        let date = NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+
+        Ok(UsageSnapshot {
+            date,
+            input_tokens: row.get(1)?,
+            output_tokens: row.get(2)?,
+            reasoning_tokens: row.get(3)?,
+            cache_write_tokens: row.get(4)?,
+            cache_read_tokens: row.get(5)?,
+            total_cost: row.get(6)?,
+            interaction_count: row.get(7)?,
+        })
+    }
+}
+
+/// A weekly summary of aggregated usage metrics.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeekSummary {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub total_input_tokens: i64,
+    pub total_output_tokens: i64,
+    pub total_reasoning_tokens: i64,
+    pub total_cache_write_tokens: i64,
+    pub total_cache_read_tokens: i64,
+    pub total_cost: f64,
+    pub total_interactions: i64,
+    /// Number of active days in the week
+    pub active_days: i64,
+    /// Which days `active_days` and per-active-day medians count
+    pub active_day_definition: ActiveDayDefinition,
+}
+
+impl WeekSummary {
+    /// Returns the number of calendar days covered by the summary.
+    #[must_use]
+    pub fn calendar_days(&self) -> i64 {
+        (self.end_date - self.start_date).num_days() + 1
+    }
+
+    /// Returns the number of days averages are divided by.
+    ///
+    /// With `per_active_day`, idle days are excluded from the divisor.
+    #[must_use]
+    pub fn average_divisor(&self, per_active_day: bool) -> i64 {
+        if per_active_day {
+            self.active_days
+        } else {
+            self.calendar_days()
+        }
+    }
+
+    /// Returns the average daily cost, or 0 when there are no days to divide by.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn average_daily_cost(&self, per_active_day: bool) -> f64 {
+        match self.average_divisor(per_active_day) {
+            0 => 0.0,
+            days => self.total_cost / days as f64,
+        }
+    }
+
+    /// Returns the median of a daily value over the summary's days.
+    ///
+    /// Only snapshots within the summary range are used. With `per_active_day`, inactive days
+    /// are skipped; otherwise days without a snapshot count as 0.
+    #[must_use]
+    pub fn median_daily<F>(
+        &self,
+        snapshots: &[UsageSnapshot],
+        per_active_day: bool,
+        value: F,
+    ) -> f64
+    where
+        F: Fn(&UsageSnapshot) -> f64,
+    {
+        let mut values: Vec<f64> = snapshots
+            .iter()
+            .filter(|s| s.date >= self.start_date && s.date <= self.end_date)
+            .filter(|s| !per_active_day || s.is_active_under(self.active_day_definition))
+            .map(value)
+            .collect();
+
+        if !per_active_day {
+            let calendar_days = usize::try_from(self.calendar_days()).unwrap_or(0);
+            values.resize(calendar_days.max(values.len()), 0.0);
+        }
+
+        median(&mut values).unwrap_or(0.0)
+    }
+
+    /// Returns the average daily interactions, or 0 when there are no days to divide by.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn average_daily_interactions(&self, per_active_day: bool) -> f64 {
+        match self.average_divisor(per_active_day) {
+            0 => 0.0,
+            days => self.total_interactions as f64 / days as f64,
+        }
+    }
+}
+
+/// Usage summed over one ISO week (Monday to Sunday).
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct WeeklyTotal {
+    /// Monday the week starts on
+    pub week_start: NaiveDate,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub reasoning_tokens: i64,
+    pub cache_write_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub total_cost: f64,
+    pub interaction_count: i64,
+}
 
-        Ok(summary)
+impl WeeklyTotal {
+    /// Returns the ISO week of the total, e.g. 2020-W53 for a week starting 2020-12-28.
+    #[must_use]
+    pub fn iso_week(&self) -> chrono::IsoWeek {
+        self.week_start.iso_week()
     }
 
-    /// Helper to convert a database row to a `UsageSnapshot`.
-    fn row_to_snapshot(row: &rusqlite::Row) -> std::result::Result<UsageSnapshot, rusqlite::Error> {
-        let date_str: String = row.get(0)?;
-        let date = NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
-            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
-
-        Ok(UsageSnapshot {
-            date,
-            input_tokens: row.get(1)?,
-            output_tokens: row.get(2)?,
-            reasoning_tokens: row.get(3)?,
-            cache_write_tokens: row.get(4)?,
-            cache_read_tokens: row.get(5)?,
-            total_cost: row.get(6)?,
-            interaction_count: row.get(7)?,
-        })
+    /// Adds a snapshot's usage to the total.
+    fn add(&mut self, snapshot: &UsageSnapshot) {
+        self.input_tokens = self.input_tokens.saturating_add(snapshot.input_tokens);
+        self.output_tokens = self.output_tokens.saturating_add(snapshot.output_tokens);
+        self.reasoning_tokens = self
+            .reasoning_tokens
+            .saturating_add(snapshot.reasoning_tokens);
+        self.cache_write_tokens = self
+            .cache_write_tokens
+            .saturating_add(snapshot.cache_write_tokens);
+        self.cache_read_tokens = self
+            .cache_read_tokens
+            .saturating_add(snapshot.cache_read_tokens);
+        self.total_cost += snapshot.total_cost;
+        self.interaction_count = self
+            .interaction_count
+            .saturating_add(snapshot.interaction_count);
     }
 }
 
-/// A weekly summary of aggregated usage metrics.
-#[derive(Debug, Clone, PartialEq)]
-pub struct WeekSummary {
-    pub start_date: NaiveDate,
-    pub end_date: NaiveDate,
-    pub total_input_tokens: i64,
-    pub total_output_tokens: i64,
-    pub total_reasoning_tokens: i64,
-    pub total_cache_write_tokens: i64,
-    pub total_cache_read_tokens: i64,
-    pub total_cost: f64,
-    pub total_interactions: i64,
+/// Returns the median of the values, or `None` if there are none.
+///
+/// With an even count, the median is the mean of the two middle values.
+/// The slice is sorted in place.
+#[must_use]
+pub fn median(values: &mut [f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len() % 2 == 0 {
+        Some((values[mid - 1] + values[mid]) / 2.0)
+    } else {
+        Some(values[mid])
+    }
 }
 
 #[cfg(test)]
@@ -241,6 +963,7 @@ mod tests {
             total_cache_write_tokens: 100,
             total_cache_read_tokens: 200,
             total_cost: 0.15,
+            total_cache_cost: 0.0,
             interaction_count: 5,
             timestamp: SystemTime::now(),
         }
@@ -302,6 +1025,7 @@ mod tests {
             total_cache_write_tokens: 100,
             total_cache_read_tokens: 200,
             total_cost: 0.15,
+            total_cache_cost: 0.0,
             interaction_count: 5,
             timestamp: SystemTime::now(),
         };
@@ -313,6 +1037,7 @@ mod tests {
             total_cache_write_tokens: 150,
             total_cache_read_tokens: 250,
             total_cost: 0.25,
+            total_cache_cost: 0.0,
             interaction_count: 8,
             timestamp: SystemTime::now(),
         };
@@ -399,6 +1124,78 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_get_snapshots_in_range() {
+        let repository = UsageRepository::new(create_test_db());
+        for day in [3, 1, 2, 9] {
+            let date = NaiveDate::from_ymd_opt(2025, 10, day).unwrap();
+            repository
+                .save_snapshot(date, &create_test_metrics())
+                .unwrap();
+        }
+        let start = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 10, 3).unwrap();
+
+        let dates: Vec<_> = repository
+            .get_snapshots_in_range(start, end)
+            .unwrap()
+            .into_iter()
+            .map(|snapshot| chrono::Datelike::day(&snapshot.date))
+            .collect();
+        assert_eq!(dates, vec![1, 2, 3]);
+
+        let empty_start = NaiveDate::from_ymd_opt(2025, 10, 4).unwrap();
+        let empty_end = NaiveDate::from_ymd_opt(2025, 10, 8).unwrap();
+        assert!(repository
+            .get_snapshots_in_range(empty_start, empty_end)
+            .unwrap()
+            .is_empty());
+
+        assert!(matches!(
+            repository.get_snapshots_in_range(end, start),
+            Err(DatabaseError::SchemaError(_))
+        ));
+    }
+
+    #[test]
+    fn test_current_streak() {
+        let repository = UsageRepository::new(create_test_db());
+        let today = NaiveDate::from_ymd_opt(2025, 10, 10).unwrap();
+        assert_eq!(repository.current_streak(today).unwrap(), 0);
+
+        // Oct 3 breaks the streak; Oct 6-10 is a full streak
+        for day in [1, 2, 4, 5, 6, 7, 8, 9, 10] {
+            let date = NaiveDate::from_ymd_opt(2025, 10, day).unwrap();
+            repository
+                .save_snapshot(date, &create_test_metrics())
+                .unwrap();
+        }
+        let idle = UsageMetrics {
+            total_input_tokens: 0,
+            total_output_tokens: 0,
+            interaction_count: 0,
+            ..create_test_metrics()
+        };
+        repository
+            .save_snapshot(NaiveDate::from_ymd_opt(2025, 10, 3).unwrap(), &idle)
+            .unwrap();
+
+        assert_eq!(repository.current_streak(today).unwrap(), 7);
+        assert_eq!(
+            repository
+                .current_streak(NaiveDate::from_ymd_opt(2025, 10, 2).unwrap())
+                .unwrap(),
+            2
+        );
+        // Nothing recorded today yet: no current streak
+        assert_eq!(
+            repository
+                .current_streak(NaiveDate::from_ymd_opt(2025, 10, 11).unwrap())
+                .unwrap(),
+            0
+        );
+    }
+
     #[test]
     fn test_get_range_empty() {
         let db = create_test_db();
@@ -452,6 +1249,140 @@ mod tests {
         assert!(result.unwrap().is_none());
     }
 
+    #[test]
+    fn test_recent_snapshots_newest_first() {
+        let db = create_test_db();
+        let repository = UsageRepository::new(db);
+
+        for day in 1..=10 {
+            let date = NaiveDate::from_ymd_opt(2025, 10, day).unwrap();
+            repository
+                .save_snapshot(date, &create_test_metrics())
+                .unwrap();
+        }
+
+        let snapshots = repository.recent_snapshots(3).unwrap();
+        let dates: Vec<NaiveDate> = snapshots.iter().map(|s| s.date).collect();
+        assert_eq!(
+            dates,
+            vec![
+                NaiveDate::from_ymd_opt(2025, 10, 10).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 10, 9).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 10, 8).unwrap(),
+            ]
+        );
+
+        // Asking for more than exist returns everything, and zero returns nothing
+        assert_eq!(repository.recent_snapshots(50).unwrap().len(), 10);
+        assert!(repository.recent_snapshots(0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_missing_dates_with_known_gaps() {
+        let db = create_test_db();
+        let repository = UsageRepository::new(db);
+        let date = |day| NaiveDate::from_ymd_opt(2025, 10, day).unwrap();
+
+        for day in [1, 2, 5, 9, 10] {
+            repository
+                .save_snapshot(date(day), &create_test_metrics())
+                .unwrap();
+        }
+
+        assert_eq!(
+            repository.missing_dates(date(1), date(10)).unwrap(),
+            vec![date(3), date(4), date(6), date(7), date(8)]
+        );
+        // Range edges count as missing too, and a full range has no gaps
+        assert_eq!(
+            repository.missing_dates(date(10), date(12)).unwrap(),
+            vec![date(11), date(12)]
+        );
+        assert!(repository
+            .missing_dates(date(1), date(2))
+            .unwrap()
+            .is_empty());
+        assert!(repository
+            .missing_dates(date(5), date(1))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_get_page_with_known_offsets() {
+        let db = create_test_db();
+        let repository = UsageRepository::new(db);
+        let date = |day| NaiveDate::from_ymd_opt(2025, 10, day).unwrap();
+
+        for day in 1..=7 {
+            repository
+                .save_snapshot(date(day), &create_test_metrics())
+                .unwrap();
+        }
+        assert_eq!(repository.snapshot_count().unwrap(), 7);
+
+        let dates = |page| -> Vec<NaiveDate> {
+            repository
+                .get_page(page, 3)
+                .unwrap()
+                .iter()
+                .map(|s| s.date)
+                .collect()
+        };
+        assert_eq!(dates(0), vec![date(7), date(6), date(5)]);
+        assert_eq!(dates(1), vec![date(4), date(3), date(2)]);
+        // The last page is partial and pages past the end are empty
+        assert_eq!(dates(2), vec![date(1)]);
+        assert!(dates(3).is_empty());
+    }
+
+    #[test]
+    fn test_get_earliest() {
+        let db = create_test_db();
+        let repository = UsageRepository::new(db);
+        assert!(repository.get_earliest().unwrap().is_none());
+
+        for day in [12, 3, 20] {
+            let date = NaiveDate::from_ymd_opt(2025, 10, day).unwrap();
+            repository
+                .save_snapshot(date, &create_test_metrics())
+                .unwrap();
+        }
+
+        let earliest = repository.get_earliest().unwrap().unwrap();
+        assert_eq!(earliest.date, NaiveDate::from_ymd_opt(2025, 10, 3).unwrap());
+    }
+
+    #[test]
+    fn test_cost_by_weekday_buckets() {
+        let db = create_test_db();
+        let repository = UsageRepository::new(db);
+
+        // 2025-10-06 is a Monday
+        let monday = NaiveDate::from_ymd_opt(2025, 10, 6).unwrap();
+        let mut metrics = create_test_metrics();
+        for (offset, cost) in [(0, 1.0), (2, 2.5), (6, 4.0), (7, 0.5)] {
+            metrics.total_cost = cost;
+            repository
+                .save_snapshot(monday + chrono::Duration::days(offset), &metrics)
+                .unwrap();
+        }
+
+        let totals = repository.cost_by_weekday().unwrap();
+        let expected = [1.5, 0.0, 2.5, 0.0, 0.0, 0.0, 4.0];
+        for (day, (total, want)) in totals.iter().zip(expected).enumerate() {
+            assert!((total - want).abs() < 1e-9, "day {day}: {total} != {want}");
+        }
+    }
+
+    #[test]
+    fn test_cost_by_weekday_empty() {
+        let db = create_test_db();
+        let repository = UsageRepository::new(db);
+
+        assert_eq!(repository.cost_by_weekday().unwrap(), [0.0; 7]);
+    }
+
     #[test]
     fn test_delete_old_removes_old() {
         let db = create_test_db();
@@ -489,6 +1420,43 @@ mod tests {
         assert_eq!(all.len(), 2);
     }
 
+    #[test]
+    fn test_delete_snapshot() {
+        let repository = UsageRepository::new(create_test_db());
+        let date = NaiveDate::from_ymd_opt(2025, 10, 5).unwrap();
+        let other = NaiveDate::from_ymd_opt(2025, 10, 6).unwrap();
+        repository
+            .save_snapshot(date, &create_test_metrics())
+            .unwrap();
+        repository
+            .save_snapshot(other, &create_test_metrics())
+            .unwrap();
+
+        assert!(repository.delete_snapshot(date).unwrap());
+        assert!(repository.get_snapshot(date).unwrap().is_none());
+        assert!(repository.get_snapshot(other).unwrap().is_some());
+
+        // Nothing left to delete for that date
+        assert!(!repository.delete_snapshot(date).unwrap());
+    }
+
+    #[test]
+    fn test_delete_snapshots_before() {
+        let repository = UsageRepository::new(create_test_db());
+        for day in 1..=5 {
+            let date = NaiveDate::from_ymd_opt(2025, 10, day).unwrap();
+            repository
+                .save_snapshot(date, &create_test_metrics())
+                .unwrap();
+        }
+        let cutoff = NaiveDate::from_ymd_opt(2025, 10, 3).unwrap();
+
+        assert_eq!(repository.delete_snapshots_before(cutoff).unwrap(), 2);
+        assert_eq!(repository.snapshot_count().unwrap(), 3);
+        assert!(repository.get_snapshot(cutoff).unwrap().is_some());
+        assert_eq!(repository.delete_snapshots_before(cutoff).unwrap(), 0);
+    }
+
     #[test]
     fn test_delete_old_empty_database() {
         let db = create_test_db();
@@ -519,6 +1487,7 @@ mod tests {
                 total_cache_write_tokens: 50 * day_num,
                 total_cache_read_tokens: 200 * day_num,
                 total_cost: 0.10 * day_num as f64,
+                total_cache_cost: 0.0,
                 interaction_count: 10 * (day_num as usize),
                 timestamp: SystemTime::now(),
             };
@@ -582,4 +1551,398 @@ mod tests {
         assert_eq!(summary.total_input_tokens, 1800);
         assert_eq!(summary.total_output_tokens, 1200);
     }
+
+    #[test]
+    fn test_get_total_interactions() {
+        let db = create_test_db();
+        let repository = UsageRepository::new(db);
+
+        assert_eq!(repository.get_total_interactions().unwrap(), 0);
+
+        let metrics = create_test_metrics();
+        repository
+            .save_snapshot(NaiveDate::from_ymd_opt(2025, 10, 1).unwrap(), &metrics)
+            .unwrap();
+        repository
+            .save_snapshot(NaiveDate::from_ymd_opt(2025, 10, 2).unwrap(), &metrics)
+            .unwrap();
+
+        assert_eq!(repository.get_total_interactions().unwrap(), 10);
+    }
+
+    #[test]
+    fn test_cumulative_cost() {
+        let db = create_test_db();
+        let repository = UsageRepository::new(db);
+        let start = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+
+        // Costs 1.0, 0.5 and 2.0 with a gap on the third day, plus a day outside the range
+        for (offset, cost) in [(0, 1.0), (1, 0.5), (3, 2.0), (10, 9.0)] {
+            repository
+                .save_snapshot(
+                    start + chrono::Duration::days(offset),
+                    &UsageMetrics {
+                        total_cost: cost,
+                        ..create_test_metrics()
+                    },
+                )
+                .unwrap();
+        }
+
+        let totals = repository
+            .cumulative_cost(start, start + chrono::Duration::days(6))
+            .unwrap();
+
+        let dates: Vec<NaiveDate> = totals.iter().map(|(date, _)| *date).collect();
+        assert_eq!(
+            dates,
+            vec![
+                start,
+                start + chrono::Duration::days(1),
+                start + chrono::Duration::days(3)
+            ]
+        );
+        for ((_, actual), expected) in totals.iter().zip([1.0, 1.5, 3.5]) {
+            assert!((actual - expected).abs() < 1e-9);
+        }
+        assert!(totals.windows(2).all(|pair| pair[1].1 >= pair[0].1));
+
+        assert!(repository
+            .cumulative_cost(
+                start + chrono::Duration::days(20),
+                start + chrono::Duration::days(30)
+            )
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_legacy_snapshots_are_not_summed() {
+        let db = create_test_db();
+        let repository = UsageRepository::new(Arc::clone(&db));
+        let metrics = create_test_metrics();
+        let legacy = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+        let current = NaiveDate::from_ymd_opt(2025, 10, 2).unwrap();
+        repository.save_snapshot(legacy, &metrics).unwrap();
+        repository.save_snapshot(current, &metrics).unwrap();
+
+        // As written by a version that stored the displayed totals
+        db.get_connection()
+            .execute(
+                "UPDATE usage_snapshots SET per_day = 0 WHERE date = ?1",
+                [legacy.to_string()],
+            )
+            .unwrap();
+
+        assert_eq!(repository.legacy_dates().unwrap(), vec![legacy]);
+        assert_eq!(
+            repository.get_all_time_usage().unwrap().total_input_tokens,
+            metrics.total_input_tokens
+        );
+        assert_eq!(
+            repository.get_total_interactions().unwrap(),
+            i64::try_from(metrics.interaction_count).unwrap()
+        );
+
+        // Recomputing the day replaces the legacy snapshot
+        repository.save_snapshot(legacy, &metrics).unwrap();
+        assert!(repository.legacy_dates().unwrap().is_empty());
+        assert_eq!(
+            repository.get_all_time_usage().unwrap().total_input_tokens,
+            metrics.total_input_tokens * 2
+        );
+    }
+
+    #[test]
+    fn test_get_all_time_usage() {
+        let db = create_test_db();
+        let repository = UsageRepository::new(db);
+
+        let empty = repository.get_all_time_usage().unwrap();
+        assert_eq!(empty.total_input_tokens, 0);
+        assert!(empty.total_cost.abs() < f64::EPSILON);
+
+        let metrics = create_test_metrics();
+        repository
+            .save_snapshot(NaiveDate::from_ymd_opt(2025, 10, 1).unwrap(), &metrics)
+            .unwrap();
+        repository
+            .save_snapshot(NaiveDate::from_ymd_opt(2025, 10, 2).unwrap(), &metrics)
+            .unwrap();
+
+        let totals = repository.get_all_time_usage().unwrap();
+        assert_eq!(totals.total_input_tokens, metrics.total_input_tokens * 2);
+        let day = NaiveDate::from_ymd_opt(2025, 10, 2).unwrap();
+        assert_eq!(
+            repository
+                .get_range_usage(day, day)
+                .unwrap()
+                .interaction_count,
+            metrics.interaction_count
+        );
+        assert_eq!(
+            repository
+                .get_usage_until(day.pred_opt().unwrap())
+                .unwrap()
+                .interaction_count,
+            metrics.interaction_count
+        );
+        assert_eq!(totals.total_output_tokens, metrics.total_output_tokens * 2);
+        assert_eq!(totals.interaction_count, metrics.interaction_count * 2);
+        assert!((totals.total_cost - metrics.total_cost * 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_active_day_count_with_gaps() {
+        let db = create_test_db();
+        let repository = UsageRepository::new(db);
+        let metrics = create_test_metrics();
+
+        // Usage on 2 of the 7 days in the week, with an idle snapshot in between
+        let week_start = NaiveDate::from_ymd_opt(2025, 10, 6).unwrap();
+        repository.save_snapshot(week_start, &metrics).unwrap();
+        repository
+            .save_snapshot(
+                week_start + chrono::Duration::days(2),
+                &UsageMetrics {
+                    total_input_tokens: 0,
+                    total_output_tokens: 0,
+                    total_reasoning_tokens: 0,
+                    total_cache_write_tokens: 0,
+                    total_cache_read_tokens: 0,
+                    total_cost: 0.0,
+                    total_cache_cost: 0.0,
+                    interaction_count: 0,
+                    timestamp: SystemTime::now(),
+                },
+            )
+            .unwrap();
+        repository
+            .save_snapshot(week_start + chrono::Duration::days(4), &metrics)
+            .unwrap();
+
+        let week_end = week_start + chrono::Duration::days(6);
+        assert_eq!(
+            repository.active_day_count(week_start, week_end).unwrap(),
+            2
+        );
+
+        let summary = repository.get_week_summary(week_start).unwrap();
+        assert_eq!(summary.active_days, 2);
+        assert_eq!(summary.calendar_days(), 7);
+
+        // 10 interactions over 7 calendar days vs 2 active days
+        assert!((summary.average_daily_interactions(false) - 10.0 / 7.0).abs() < 1e-9);
+        assert_eq!(summary.average_daily_interactions(true), 5.0);
+        assert!((summary.average_daily_cost(false) - 0.30 / 7.0).abs() < 1e-9);
+        assert!((summary.average_daily_cost(true) - 0.15).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_active_day_definition_zero_cost_days() {
+        let db = create_test_db();
+        let week_start = NaiveDate::from_ymd_opt(2025, 10, 6).unwrap();
+        let paid = create_test_metrics();
+        // A local model day: tokens and interactions but no reported cost
+        let local = UsageMetrics {
+            total_cost: 0.0,
+            ..create_test_metrics()
+        };
+        let any_usage = UsageRepository::new(Arc::clone(&db));
+        any_usage.save_snapshot(week_start, &paid).unwrap();
+        any_usage
+            .save_snapshot(week_start + chrono::Duration::days(1), &local)
+            .unwrap();
+        any_usage
+            .save_snapshot(week_start + chrono::Duration::days(2), &local)
+            .unwrap();
+        let nonzero_cost =
+            UsageRepository::new(db).with_active_day_definition(ActiveDayDefinition::NonzeroCost);
+        let week_end = week_start + chrono::Duration::days(6);
+
+        assert_eq!(any_usage.active_day_count(week_start, week_end).unwrap(), 3);
+        assert_eq!(
+            nonzero_cost.active_day_count(week_start, week_end).unwrap(),
+            1
+        );
+
+        // $0.15 spread over every used day, or only over the paid one
+        let summary = any_usage.get_week_summary(week_start).unwrap();
+        assert!((summary.average_daily_cost(true) - 0.05).abs() < 1e-9);
+        let summary = nonzero_cost.get_week_summary(week_start).unwrap();
+        assert_eq!(summary.active_days, 1);
+        assert!((summary.average_daily_cost(true) - 0.15).abs() < 1e-9);
+
+        let snapshots = nonzero_cost.get_range(week_start, week_end).unwrap();
+        assert!((summary.median_daily(&snapshots, true, |s| s.total_cost) - 0.15).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_averages_with_no_active_days() {
+        let db = create_test_db();
+        let repository = UsageRepository::new(db);
+
+        let summary = repository
+            .get_week_summary(NaiveDate::from_ymd_opt(2025, 10, 6).unwrap())
+            .unwrap();
+        assert_eq!(summary.active_days, 0);
+        assert_eq!(summary.average_daily_cost(true), 0.0);
+        assert_eq!(summary.average_daily_interactions(true), 0.0);
+        assert_eq!(summary.average_daily_interactions(false), 0.0);
+    }
+
+    #[test]
+    fn test_weekly_totals_across_year_rollover() {
+        let repository = UsageRepository::new(create_test_db());
+        let metrics = create_test_metrics();
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        // 2020-W53 runs from Monday 2020-12-28 to Sunday 2021-01-03
+        for day in [
+            date(2020, 12, 28),
+            date(2020, 12, 31),
+            date(2021, 1, 3),
+            date(2021, 1, 4),
+            // 2021-01-11 week has no data, 2021-01-18 does
+            date(2021, 1, 18),
+        ] {
+            repository.save_snapshot(day, &metrics).unwrap();
+        }
+
+        let totals = repository
+            .get_weekly_totals(date(2020, 12, 1), date(2021, 1, 31))
+            .unwrap();
+
+        let weeks: Vec<_> = totals
+            .iter()
+            .map(|t| (t.week_start, t.interaction_count))
+            .collect();
+        assert_eq!(
+            weeks,
+            vec![
+                (date(2020, 12, 28), 15),
+                (date(2021, 1, 4), 5),
+                (date(2021, 1, 18), 5)
+            ]
+        );
+        assert_eq!(totals[0].iso_week().year(), 2020);
+        assert_eq!(totals[0].iso_week().week(), 53);
+        assert_eq!(totals[0].input_tokens, 1800);
+        assert!((totals[0].total_cost - 0.45).abs() < 1e-9);
+        assert_eq!(totals[1].iso_week().week(), 1);
+
+        // Only days within the range are summed
+        let partial = repository
+            .get_weekly_totals(date(2020, 12, 31), date(2021, 1, 3))
+            .unwrap();
+        assert_eq!(partial.len(), 1);
+        assert_eq!(partial[0].week_start, date(2020, 12, 28));
+        assert_eq!(partial[0].interaction_count, 10);
+
+        assert!(repository
+            .get_weekly_totals(date(2021, 1, 31), date(2020, 12, 1))
+            .is_err());
+    }
+
+    #[test]
+    fn test_median_odd_and_even_counts() {
+        assert_eq!(median(&mut [3.0, 1.0, 100.0]), Some(3.0));
+        assert_eq!(median(&mut [4.0, 1.0, 3.0, 2.0]), Some(2.5));
+        assert_eq!(median(&mut [7.0]), Some(7.0));
+        assert_eq!(median(&mut []), None);
+    }
+
+    #[test]
+    fn test_week_summary_median_daily() {
+        let start = NaiveDate::from_ymd_opt(2025, 10, 6).unwrap();
+        let summary = WeekSummary {
+            start_date: start,
+            end_date: start + chrono::Duration::days(6),
+            total_input_tokens: 0,
+            total_output_tokens: 0,
+            total_reasoning_tokens: 0,
+            total_cache_write_tokens: 0,
+            total_cache_read_tokens: 0,
+            total_cost: 0.0,
+            total_interactions: 0,
+            active_days: 3,
+            active_day_definition: ActiveDayDefinition::AnyUsage,
+        };
+        let snapshot = |offset: i64, cost: f64| UsageSnapshot {
+            date: start + chrono::Duration::days(offset),
+            input_tokens: 100,
+            output_tokens: 0,
+            reasoning_tokens: 0,
+            cache_write_tokens: 0,
+            cache_read_tokens: 0,
+            total_cost: cost,
+            interaction_count: 1,
+        };
+        // One huge day, plus a snapshot outside the week that must be ignored
+        let snapshots = vec![
+            snapshot(0, 1.0),
+            snapshot(1, 2.0),
+            snapshot(2, 50.0),
+            snapshot(7, 1000.0),
+        ];
+
+        assert_eq!(
+            summary.median_daily(&snapshots, true, |s| s.total_cost),
+            2.0
+        );
+        // Four idle days count as 0 per calendar day
+        assert_eq!(
+            summary.median_daily(&snapshots, false, |s| s.total_cost),
+            0.0
+        );
+    }
+
+    fn busy_error() -> crate::core::database::DatabaseError {
+        crate::core::database::DatabaseError::SqlError(rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_BUSY),
+            None,
+        ))
+    }
+
+    #[test]
+    fn test_write_succeeds_after_transient_busy_error() {
+        let repo = UsageRepository::new(create_test_db());
+        let date = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+        let mut calls = 0;
+
+        retry_busy(DEFAULT_WRITE_ATTEMPTS, Duration::from_millis(1), || {
+            calls += 1;
+            if calls == 1 {
+                return Err(busy_error());
+            }
+            repo.insert_snapshot(date, &create_test_metrics())
+        })
+        .unwrap();
+
+        assert_eq!(calls, 2);
+        assert!(repo.get_snapshot(date).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_retry_busy_gives_up_after_max_attempts() {
+        let mut calls = 0;
+        let result: Result<()> = retry_busy(3, Duration::from_millis(1), || {
+            calls += 1;
+            Err(busy_error())
+        });
+
+        assert!(result.unwrap_err().is_busy());
+        assert_eq!(calls, 3);
+
+        // Other errors are not retried
+        calls = 0;
+        let result: Result<()> = retry_busy(3, Duration::from_millis(1), || {
+            calls += 1;
+            Err(crate::core::database::DatabaseError::ReadOnly(
+                "test".to_string(),
+            ))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
 }
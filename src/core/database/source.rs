@@ -0,0 +1,239 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Usage source backed by the stored daily snapshots.
+//!
+//! Month and all-time totals are summed from the history database, so they do
+//! not require re-reading every storage file. Today is still read from the
+//! files, keeping the current day fresh.
+
+use crate::core::database::repository::UsageRepository;
+use crate::core::opencode::{ReaderError, UsageAggregator, UsageMetrics, UsageSource};
+use chrono::{Datelike, NaiveDate};
+use std::path::PathBuf;
+
+/// Usage source reading past days from snapshots and today from files
+pub struct SnapshotUsageSource {
+    files: Box<dyn UsageSource>,
+    repository: UsageRepository,
+    today: Option<NaiveDate>,
+}
+
+impl SnapshotUsageSource {
+    /// Creates a source summing snapshots from `repository` on top of `files`.
+    #[must_use]
+    pub fn new(files: Box<dyn UsageSource>, repository: UsageRepository) -> Self {
+        Self {
+            files,
+            repository,
+            today: None,
+        }
+    }
+
+    /// Uses a fixed date as today instead of the current UTC date.
+    #[must_use]
+    pub fn with_today(mut self, today: NaiveDate) -> Self {
+        self.today = Some(today);
+        self
+    }
+
+    fn today_date(&self) -> NaiveDate {
+        self.today
+            .unwrap_or_else(|| chrono::Utc::now().date_naive())
+    }
+
+    /// Today's usage from the files, all zero when there was no usage today.
+    fn files_today(&mut self) -> Result<UsageMetrics, ReaderError> {
+        match self.files.get_usage_today() {
+            Ok(today) => Ok(today),
+            Err(ReaderError::NoDataFound) => Ok(UsageAggregator::new().finalize()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Snapshots up to yesterday, from `start` when given.
+    fn past_usage(&self, start: Option<NaiveDate>) -> Result<UsageMetrics, ReaderError> {
+        let Some(yesterday) = self.today_date().pred_opt() else {
+            return Ok(UsageAggregator::new().finalize());
+        };
+        match start {
+            Some(start) if start > yesterday => Ok(UsageAggregator::new().finalize()),
+            Some(start) => self.repository.get_range_usage(start, yesterday),
+            None => self.repository.get_usage_until(yesterday),
+        }
+        .map_err(|e| ReaderError::AccessError(e.to_string()))
+    }
+
+    /// Snapshots up to yesterday, from `start` when given, plus today from the files.
+    fn up_to_today(&mut self, start: Option<NaiveDate>) -> Result<UsageMetrics, ReaderError> {
+        let past = self.past_usage(start)?;
+        non_empty(combine(&past, &self.files_today()?))
+    }
+}
+
+/// Adds two sets of usage metrics together.
+fn combine(a: &UsageMetrics, b: &UsageMetrics) -> UsageMetrics {
+    UsageMetrics {
+        total_input_tokens: a.total_input_tokens.saturating_add(b.total_input_tokens),
+        total_output_tokens: a.total_output_tokens.saturating_add(b.total_output_tokens),
+        total_reasoning_tokens: a
+            .total_reasoning_tokens
+            .saturating_add(b.total_reasoning_tokens),
+        total_cache_write_tokens: a
+            .total_cache_write_tokens
+            .saturating_add(b.total_cache_write_tokens),
+        total_cache_read_tokens: a
+            .total_cache_read_tokens
+            .saturating_add(b.total_cache_read_tokens),
+        total_cost: a.total_cost + b.total_cost,
+        total_cache_cost: a.total_cache_cost + b.total_cache_cost,
+        interaction_count: a.interaction_count.saturating_add(b.interaction_count),
+        timestamp: std::time::SystemTime::now(),
+    }
+}
+
+/// Reports `NoDataFound` for totals without any usage, like the file reader.
+fn non_empty(metrics: UsageMetrics) -> Result<UsageMetrics, ReaderError> {
+    if metrics.interaction_count == 0 && metrics.total_tokens() == 0 {
+        Err(ReaderError::NoDataFound)
+    } else {
+        Ok(metrics)
+    }
+}
+
+impl UsageSource for SnapshotUsageSource {
+    fn get_usage(&mut self) -> Result<UsageMetrics, ReaderError> {
+        self.up_to_today(None)
+    }
+
+    fn get_usage_today(&mut self) -> Result<UsageMetrics, ReaderError> {
+        self.files.get_usage_today()
+    }
+
+    fn get_usage_month(&mut self) -> Result<UsageMetrics, ReaderError> {
+        let today = self.today_date();
+        self.up_to_today(today.with_day(1))
+    }
+
+    fn get_usage_last_month(&mut self) -> Result<UsageMetrics, ReaderError> {
+        let today = self.today_date();
+        let Some(end) = today.with_day(1).and_then(|first| first.pred_opt()) else {
+            return Err(ReaderError::NoDataFound);
+        };
+        let start = end.with_day(1).unwrap_or(end);
+        let usage = self
+            .repository
+            .get_range_usage(start, end)
+            .map_err(|e| ReaderError::AccessError(e.to_string()))?;
+        non_empty(usage)
+    }
+
+    fn get_usage_today_and_month(&mut self) -> Result<(UsageMetrics, UsageMetrics), ReaderError> {
+        let today = self.files_today()?;
+        let past = self.past_usage(self.today_date().with_day(1))?;
+        let month = non_empty(combine(&past, &today))?;
+        Ok((today, month))
+    }
+
+    fn storage_path(&self) -> &PathBuf {
+        self.files.storage_path()
+    }
+
+    fn skipped_permission_denied(&self) -> usize {
+        self.files.skipped_permission_denied()
+    }
+
+    fn fork(&self) -> Result<Box<dyn UsageSource>, ReaderError> {
+        Ok(Box::new(Self {
+            files: self.files.fork()?,
+            repository: self.repository.clone(),
+            today: self.today,
+        }))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)] // Tests use exact float comparisons for simplicity
+mod tests {
+    use super::*;
+    use crate::core::database::DatabaseManager;
+    use crate::core::opencode::MockUsageSource;
+    use std::sync::Arc;
+    use std::time::SystemTime;
+
+    fn create_metrics(tokens: u64, cost: f64, interactions: usize) -> UsageMetrics {
+        UsageMetrics {
+            total_input_tokens: tokens,
+            total_output_tokens: tokens / 2,
+            total_reasoning_tokens: 0,
+            total_cache_write_tokens: 0,
+            total_cache_read_tokens: 0,
+            total_cost: cost,
+            total_cache_cost: 0.0,
+            interaction_count: interactions,
+            timestamp: SystemTime::now(),
+        }
+    }
+
+    fn create_repository() -> UsageRepository {
+        UsageRepository::new(Arc::new(DatabaseManager::new_in_memory().unwrap()))
+    }
+
+    #[test]
+    fn test_database_month_matches_file_month() {
+        let today = NaiveDate::from_ymd_opt(2025, 10, 15).unwrap();
+        let repository = create_repository();
+        let mut file_month = create_metrics(0, 0.0, 0);
+        for day in [1, 7, 14] {
+            let daily = create_metrics(1_000, 0.5, 2);
+            repository
+                .save_snapshot(NaiveDate::from_ymd_opt(2025, 10, day).unwrap(), &daily)
+                .unwrap();
+            file_month = combine(&file_month, &daily);
+        }
+        // Outside the month, so only the all-time total includes it
+        repository
+            .save_snapshot(
+                NaiveDate::from_ymd_opt(2025, 9, 30).unwrap(),
+                &create_metrics(4_000, 2.0, 8),
+            )
+            .unwrap();
+        let today_usage = create_metrics(200, 0.25, 1);
+        file_month = combine(&file_month, &today_usage);
+
+        let mut files = MockUsageSource {
+            today: Some(today_usage.clone()),
+            month: Some(file_month.clone()),
+            ..Default::default()
+        };
+        let mut source =
+            SnapshotUsageSource::new(Box::new(files.clone()), repository).with_today(today);
+
+        let db_month = source.get_usage_month().unwrap();
+        let expected = files.get_usage_month().unwrap();
+        assert_eq!(db_month.total_input_tokens, expected.total_input_tokens);
+        assert_eq!(db_month.total_output_tokens, expected.total_output_tokens);
+        assert_eq!(db_month.total_cost, expected.total_cost);
+        assert_eq!(db_month.interaction_count, expected.interaction_count);
+
+        let (today_read, month_read) = source.get_usage_today_and_month().unwrap();
+        assert_eq!(today_read.interaction_count, today_usage.interaction_count);
+        assert_eq!(month_read.interaction_count, expected.interaction_count);
+
+        assert_eq!(source.get_usage().unwrap().interaction_count, 15);
+        assert_eq!(source.get_usage_last_month().unwrap().interaction_count, 8);
+    }
+
+    #[test]
+    fn test_database_source_without_usage_reports_no_data() {
+        let today = NaiveDate::from_ymd_opt(2025, 10, 15).unwrap();
+        let mut source =
+            SnapshotUsageSource::new(Box::new(MockUsageSource::default()), create_repository())
+                .with_today(today);
+
+        assert!(matches!(
+            source.get_usage_month(),
+            Err(ReaderError::NoDataFound)
+        ));
+        assert!(matches!(source.get_usage(), Err(ReaderError::NoDataFound)));
+    }
+}
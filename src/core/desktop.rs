@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Helpers for handing files and folders over to the desktop environment.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Returns the folder containing `path`, or `path` itself if it has no parent.
+#[must_use]
+pub fn containing_folder(path: &Path) -> PathBuf {
+    path.parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .map_or_else(|| path.to_path_buf(), Path::to_path_buf)
+}
+
+/// Opens a folder in the default file manager using `xdg-open`.
+///
+/// # Errors
+/// Returns an error if `xdg-open` cannot be started.
+pub fn open_folder(folder: &Path) -> io::Result<()> {
+    Command::new("xdg-open").arg(folder).spawn()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_containing_folder() {
+        assert_eq!(
+            containing_folder(Path::new("/home/user/.local/share/app/usage.db")),
+            PathBuf::from("/home/user/.local/share/app")
+        );
+        assert_eq!(
+            containing_folder(Path::new("usage.db")),
+            PathBuf::from("usage.db")
+        );
+        assert_eq!(containing_folder(Path::new("/")), PathBuf::from("/"));
+    }
+}
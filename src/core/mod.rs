@@ -3,5 +3,7 @@
 pub mod collector;
 pub mod config;
 pub mod database;
+pub mod desktop;
 pub mod localization;
 pub mod opencode;
+pub mod socket;
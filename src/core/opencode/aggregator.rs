@@ -1,5 +1,80 @@
 use crate::core::opencode::parser::UsagePart;
-use std::time::SystemTime;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, SystemTime};
+
+/// What counts as one interaction in `interaction_count`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum InteractionUnit {
+    /// Every usage part with token data
+    #[default]
+    Part,
+    /// Every distinct session with token data
+    Session,
+}
+
+/// Number of distinct sessions among the parts that carry token data
+#[must_use]
+pub fn count_sessions<'a>(parts: impl IntoIterator<Item = &'a UsagePart>) -> usize {
+    parts
+        .into_iter()
+        .filter(|part| part.tokens.is_some())
+        .map(|part| part.session_id.as_str())
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+/// Total length of the sessions whose parts carry timestamps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionDurations {
+    pub sessions: usize,
+    pub total: Duration,
+}
+
+impl SessionDurations {
+    /// Average session length
+    #[must_use]
+    pub fn average(&self) -> Duration {
+        u32::try_from(self.sessions)
+            .ok()
+            .filter(|&sessions| sessions > 0)
+            .map_or(Duration::ZERO, |sessions| self.total / sessions)
+    }
+}
+
+/// Length of each session, from its earliest to its latest part timestamp
+///
+/// Parts without timestamps or a session id are ignored; `None` means no part had any.
+#[must_use]
+pub fn session_durations<'a>(
+    parts: impl IntoIterator<Item = &'a UsagePart>,
+) -> Option<SessionDurations> {
+    let mut spans: HashMap<&str, (u64, u64)> = HashMap::new();
+    for part in parts {
+        let Some(time) = part.time.filter(|_| !part.session_id.is_empty()) else {
+            continue;
+        };
+        let (Some(start), Some(end)) = (time.start.or(time.end), time.end.or(time.start)) else {
+            continue;
+        };
+        let span = spans
+            .entry(part.session_id.as_str())
+            .or_insert((start, end));
+        span.0 = span.0.min(start);
+        span.1 = span.1.max(end);
+    }
+    if spans.is_empty() {
+        return None;
+    }
+    let total_ms = spans
+        .values()
+        .map(|&(start, end)| end.saturating_sub(start))
+        .fold(0_u64, u64::saturating_add);
+    Some(SessionDurations {
+        sessions: spans.len(),
+        total: Duration::from_millis(total_ms),
+    })
+}
 
 /// Aggregated usage metrics from `OpenCode`
 #[derive(Debug, Clone, PartialEq)]
@@ -10,33 +85,199 @@ pub struct UsageMetrics {
     pub total_cache_write_tokens: u64,
     pub total_cache_read_tokens: u64,
     pub total_cost: f64,
+    /// Part of `total_cost` attributed to cache reads and writes
+    pub total_cache_cost: f64,
     pub interaction_count: usize,
     pub timestamp: SystemTime,
 }
 
+/// Field-by-field difference between two sets of usage metrics
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageDelta {
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub reasoning_tokens: i64,
+    pub cache_write_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cost: f64,
+    pub interactions: i64,
+}
+
+/// Signed difference of two counters, clamped to the `i64` range
+fn signed_diff(a: u64, b: u64) -> i64 {
+    let diff = i128::from(a) - i128::from(b);
+    i64::try_from(diff).unwrap_or(if diff > 0 { i64::MAX } else { i64::MIN })
+}
+
+impl UsageMetrics {
+    /// Subtract `other` from these metrics field by field (`self - other`)
+    #[must_use]
+    pub fn subtract(&self, other: &Self) -> UsageDelta {
+        UsageDelta {
+            input_tokens: signed_diff(self.total_input_tokens, other.total_input_tokens),
+            output_tokens: signed_diff(self.total_output_tokens, other.total_output_tokens),
+            reasoning_tokens: signed_diff(
+                self.total_reasoning_tokens,
+                other.total_reasoning_tokens,
+            ),
+            cache_write_tokens: signed_diff(
+                self.total_cache_write_tokens,
+                other.total_cache_write_tokens,
+            ),
+            cache_read_tokens: signed_diff(
+                self.total_cache_read_tokens,
+                other.total_cache_read_tokens,
+            ),
+            cost: self.total_cost - other.total_cost,
+            interactions: signed_diff(
+                self.interaction_count as u64,
+                other.interaction_count as u64,
+            ),
+        }
+    }
+
+    /// Cost attributed to cache reads and writes
+    #[must_use]
+    pub fn cache_cost(&self) -> f64 {
+        self.total_cache_cost.max(0.0)
+    }
+
+    /// Whether any part attributed some of its cost to the cache
+    #[must_use]
+    pub fn has_cache_cost(&self) -> bool {
+        self.cache_cost() > 0.0
+    }
+
+    /// Cost not attributed to the cache (everything, when parts don't split their cost)
+    #[must_use]
+    pub fn non_cache_cost(&self) -> f64 {
+        (self.total_cost - self.cache_cost()).max(0.0)
+    }
+
+    /// Total of input, output and reasoning tokens
+    #[must_use]
+    pub fn total_tokens(&self) -> u64 {
+        self.total_input_tokens
+            .saturating_add(self.total_output_tokens)
+            .saturating_add(self.total_reasoning_tokens)
+    }
+}
+
+/// Cost in dollars per 1,000 tokens (input + output + reasoning)
+///
+/// Returns 0 when no tokens were used.
+#[must_use]
+pub fn cost_per_1k_tokens(usage: &UsageMetrics) -> f64 {
+    cost_per_1k(usage.total_cost, usage.total_tokens())
+}
+
+/// Cost in dollars per 1,000 tokens, guarding against zero tokens
+#[must_use]
+pub fn cost_per_1k(cost: f64, tokens: u64) -> f64 {
+    if tokens == 0 {
+        return 0.0;
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let thousands = tokens as f64 / 1_000.0;
+    cost / thousands
+}
+
+/// Average cost in dollars per interaction
+///
+/// Returns `None` when no interactions were recorded.
+#[must_use]
+pub fn cost_per_interaction(cost: f64, interactions: u64) -> Option<f64> {
+    if interactions == 0 {
+        return None;
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let interactions = interactions as f64;
+    Some(cost / interactions)
+}
+
+/// Ratio of cache-read to cache-write tokens, a measure of prompt caching effectiveness
+///
+/// Returns `None` when no cache-write tokens were recorded.
+#[must_use]
+pub fn cache_read_write_ratio(usage: &UsageMetrics) -> Option<f64> {
+    if usage.total_cache_write_tokens == 0 {
+        return None;
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let ratio = usage.total_cache_read_tokens as f64 / usage.total_cache_write_tokens as f64;
+    Some(ratio)
+}
+
+/// Running cost total that parts can be added to and removed from indefinitely
+///
+/// Uses Neumaier's compensated summation, so a long-running process that keeps
+/// replacing parts doesn't pile up rounding drift.
+#[derive(Debug, Clone, Copy, Default)]
+struct CostSum {
+    sum: f64,
+    compensation: f64,
+}
+
+impl CostSum {
+    fn add(&mut self, value: f64) {
+        let total = self.sum + value;
+        if self.sum.abs() >= value.abs() {
+            self.compensation += (self.sum - total) + value;
+        } else {
+            self.compensation += (value - total) + self.sum;
+        }
+        self.sum = total;
+    }
+
+    fn value(self) -> f64 {
+        self.sum + self.compensation
+    }
+}
+
 /// Aggregates usage parts into metrics
+///
+/// Parts can also be removed again, so a cached aggregation can follow single file
+/// changes. Token totals are kept wider than `u64` and only clamped when the metrics
+/// are read, which keeps removal exact even after a total saturated.
+#[derive(Debug, Clone)]
 pub struct UsageAggregator {
-    total_input_tokens: u64,
-    total_output_tokens: u64,
-    total_reasoning_tokens: u64,
-    total_cache_write_tokens: u64,
-    total_cache_read_tokens: u64,
-    total_cost: f64,
+    total_input_tokens: u128,
+    total_output_tokens: u128,
+    total_reasoning_tokens: u128,
+    total_cache_write_tokens: u128,
+    total_cache_read_tokens: u128,
+    total_cost: CostSum,
+    total_cache_cost: CostSum,
     interaction_count: usize,
+    interaction_unit: InteractionUnit,
+    /// Number of parts with token data per session, to count sessions as interactions
+    sessions: HashMap<String, usize>,
 }
 
 impl UsageAggregator {
     /// Create a new aggregator
     #[must_use]
     pub fn new() -> Self {
+        Self::with_interaction_unit(InteractionUnit::Part)
+    }
+
+    /// Create an aggregator counting interactions in the given unit
+    #[must_use]
+    pub fn with_interaction_unit(interaction_unit: InteractionUnit) -> Self {
         Self {
             total_input_tokens: 0,
             total_output_tokens: 0,
             total_reasoning_tokens: 0,
             total_cache_write_tokens: 0,
             total_cache_read_tokens: 0,
-            total_cost: 0.0,
+            total_cost: CostSum::default(),
+            total_cache_cost: CostSum::default(),
             interaction_count: 0,
+            interaction_unit,
+            sessions: HashMap::new(),
         }
     }
 
@@ -44,30 +285,111 @@ impl UsageAggregator {
     pub fn add_part(&mut self, part: &UsagePart) {
         // Only aggregate parts that have token data
         if let Some(tokens) = &part.tokens {
-            self.total_input_tokens += tokens.input;
-            self.total_output_tokens += tokens.output;
-            self.total_reasoning_tokens += tokens.reasoning;
-            self.total_cache_write_tokens += tokens.cache.write;
-            self.total_cache_read_tokens += tokens.cache.read;
-            self.total_cost += part.cost;
-            self.interaction_count += 1;
+            self.total_input_tokens += u128::from(tokens.input);
+            self.total_output_tokens += u128::from(tokens.output);
+            self.total_reasoning_tokens += u128::from(tokens.reasoning);
+            self.total_cache_write_tokens += u128::from(tokens.cache.write);
+            self.total_cache_read_tokens += u128::from(tokens.cache.read);
+            self.total_cost.add(part.cost);
+            self.total_cache_cost.add(part.attributed_cache_cost());
+            match self.interaction_unit {
+                InteractionUnit::Part => {
+                    self.interaction_count = self.interaction_count.saturating_add(1);
+                }
+                InteractionUnit::Session => {
+                    let parts = self.sessions.entry(part.session_id.clone()).or_insert(0);
+                    if *parts == 0 {
+                        self.interaction_count = self.interaction_count.saturating_add(1);
+                    }
+                    *parts += 1;
+                }
+            }
         }
     }
 
-    /// Finalize and return the aggregated metrics
+    /// Remove a usage part previously added to the aggregation (the inverse of `add_part`)
+    pub fn remove_part(&mut self, part: &UsagePart) {
+        if let Some(tokens) = &part.tokens {
+            self.total_input_tokens = self
+                .total_input_tokens
+                .saturating_sub(u128::from(tokens.input));
+            self.total_output_tokens = self
+                .total_output_tokens
+                .saturating_sub(u128::from(tokens.output));
+            self.total_reasoning_tokens = self
+                .total_reasoning_tokens
+                .saturating_sub(u128::from(tokens.reasoning));
+            self.total_cache_write_tokens = self
+                .total_cache_write_tokens
+                .saturating_sub(u128::from(tokens.cache.write));
+            self.total_cache_read_tokens = self
+                .total_cache_read_tokens
+                .saturating_sub(u128::from(tokens.cache.read));
+            self.total_cost.add(-part.cost);
+            self.total_cache_cost.add(-part.attributed_cache_cost());
+            match self.interaction_unit {
+                InteractionUnit::Part => {
+                    self.interaction_count = self.interaction_count.saturating_sub(1);
+                }
+                InteractionUnit::Session => {
+                    if let Some(parts) = self.sessions.get_mut(&part.session_id) {
+                        *parts -= 1;
+                        if *parts == 0 {
+                            self.sessions.remove(&part.session_id);
+                            self.interaction_count = self.interaction_count.saturating_sub(1);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Names of the token totals that exceed `u64::MAX`
+    fn saturated_fields(&self) -> Vec<&'static str> {
+        [
+            ("input", self.total_input_tokens),
+            ("output", self.total_output_tokens),
+            ("reasoning", self.total_reasoning_tokens),
+            ("cache write", self.total_cache_write_tokens),
+            ("cache read", self.total_cache_read_tokens),
+        ]
+        .into_iter()
+        .filter(|&(_, total)| total > u128::from(u64::MAX))
+        .map(|(field, _)| field)
+        .collect()
+    }
+
+    /// The aggregated metrics so far, clamping token totals at `u64::MAX`
+    ///
+    /// Saturated totals are reported once per call.
     #[must_use]
-    pub fn finalize(self) -> UsageMetrics {
+    pub fn metrics(&self) -> UsageMetrics {
+        let saturated_fields = self.saturated_fields();
+        if !saturated_fields.is_empty() {
+            eprintln!(
+                "[Aggregator] {} token total(s) saturated at u64::MAX",
+                saturated_fields.join(", ")
+            );
+        }
+        let clamp = |total: u128| u64::try_from(total).unwrap_or(u64::MAX);
         UsageMetrics {
-            total_input_tokens: self.total_input_tokens,
-            total_output_tokens: self.total_output_tokens,
-            total_reasoning_tokens: self.total_reasoning_tokens,
-            total_cache_write_tokens: self.total_cache_write_tokens,
-            total_cache_read_tokens: self.total_cache_read_tokens,
-            total_cost: self.total_cost,
+            total_input_tokens: clamp(self.total_input_tokens),
+            total_output_tokens: clamp(self.total_output_tokens),
+            total_reasoning_tokens: clamp(self.total_reasoning_tokens),
+            total_cache_write_tokens: clamp(self.total_cache_write_tokens),
+            total_cache_read_tokens: clamp(self.total_cache_read_tokens),
+            total_cost: self.total_cost.value(),
+            total_cache_cost: self.total_cache_cost.value(),
             interaction_count: self.interaction_count,
             timestamp: SystemTime::now(),
         }
     }
+
+    /// Finalize and return the aggregated metrics
+    #[must_use]
+    pub fn finalize(self) -> UsageMetrics {
+        self.metrics()
+    }
 }
 
 impl Default for UsageAggregator {
@@ -80,7 +402,7 @@ impl Default for UsageAggregator {
 #[allow(clippy::float_cmp)] // Tests use exact float comparisons for simplicity
 mod tests {
     use super::*;
-    use crate::core::opencode::parser::{CacheUsage, TokenUsage};
+    use crate::core::opencode::parser::{CacheUsage, PartTime, TokenUsage};
 
     // Test 1: Aggregate a single part correctly
     #[test]
@@ -99,6 +421,8 @@ mod tests {
                 cache: CacheUsage { write: 5, read: 15 },
             }),
             cost: 0.25,
+            cache_cost: None,
+            time: None,
         };
 
         aggregator.add_part(&part);
@@ -130,6 +454,8 @@ mod tests {
                 cache: CacheUsage { write: 5, read: 15 },
             }),
             cost: 0.25,
+            cache_cost: None,
+            time: None,
         };
 
         let part2 = UsagePart {
@@ -147,6 +473,8 @@ mod tests {
                 },
             }),
             cost: 0.50,
+            cache_cost: None,
+            time: None,
         };
 
         let part3 = UsagePart {
@@ -161,6 +489,8 @@ mod tests {
                 cache: CacheUsage { write: 2, read: 8 },
             }),
             cost: 0.10,
+            cache_cost: None,
+            time: None,
         };
 
         aggregator.add_part(&part1);
@@ -214,6 +544,8 @@ mod tests {
                 },
             }),
             cost: 0.0,
+            cache_cost: None,
+            time: None,
         };
 
         aggregator.add_part(&part);
@@ -245,6 +577,8 @@ mod tests {
                     cache: CacheUsage { write: 0, read: 0 },
                 }),
                 cost: 0.1,
+                cache_cost: None,
+                time: None,
             };
             aggregator.add_part(&part);
         }
@@ -253,6 +587,47 @@ mod tests {
         assert_eq!(metrics.interaction_count, 5);
     }
 
+    #[test]
+    fn test_interaction_unit_part_vs_session() {
+        // Five parts spread over two sessions, plus a session with no token data
+        let parts: Vec<UsagePart> = ["ses_a", "ses_a", "ses_b", "ses_a", "ses_b", "ses_c"]
+            .iter()
+            .enumerate()
+            .map(|(i, session)| UsagePart {
+                id: format!("prt_{i}"),
+                message_id: format!("msg_{i}"),
+                session_id: (*session).to_string(),
+                event_type: "step-finish".to_string(),
+                tokens: (*session != "ses_c").then_some(TokenUsage {
+                    input: 100,
+                    output: 50,
+                    reasoning: 0,
+                    cache: CacheUsage { write: 0, read: 0 },
+                }),
+                cost: 0.1,
+                cache_cost: None,
+                time: None,
+            })
+            .collect();
+
+        let mut by_part = UsageAggregator::with_interaction_unit(InteractionUnit::Part);
+        let mut by_session = UsageAggregator::with_interaction_unit(InteractionUnit::Session);
+        for part in &parts {
+            by_part.add_part(part);
+            by_session.add_part(part);
+        }
+        let by_part = by_part.finalize();
+        let by_session = by_session.finalize();
+
+        assert_eq!(by_part.interaction_count, 5);
+        assert_eq!(by_session.interaction_count, 2);
+        assert_eq!(count_sessions(&parts), 2);
+
+        // Only the interaction count depends on the unit
+        assert_eq!(by_session.total_input_tokens, by_part.total_input_tokens);
+        assert_eq!(by_session.total_cost, by_part.total_cost);
+    }
+
     // Test 6: Accumulate costs accurately
     #[test]
     fn test_cost_accumulation() {
@@ -270,6 +645,8 @@ mod tests {
                 cache: CacheUsage { write: 0, read: 0 },
             }),
             cost: 0.123,
+            cache_cost: None,
+            time: None,
         };
 
         let part2 = UsagePart {
@@ -284,6 +661,8 @@ mod tests {
                 cache: CacheUsage { write: 0, read: 0 },
             }),
             cost: 0.456,
+            cache_cost: None,
+            time: None,
         };
 
         aggregator.add_part(&part1);
@@ -307,6 +686,8 @@ mod tests {
             event_type: "step-start".to_string(),
             tokens: None,
             cost: 0.0,
+            cache_cost: None,
+            time: None,
         };
 
         aggregator.add_part(&part_without_tokens);
@@ -329,4 +710,348 @@ mod tests {
         assert!(metrics.timestamp >= before);
         assert!(metrics.timestamp <= after);
     }
+
+    #[test]
+    fn test_cost_per_1k_tokens() {
+        let metrics = UsageMetrics {
+            total_input_tokens: 1_500,
+            total_output_tokens: 400,
+            total_reasoning_tokens: 100,
+            total_cache_write_tokens: 10_000,
+            total_cache_read_tokens: 10_000,
+            total_cost: 0.5,
+            total_cache_cost: 0.0,
+            interaction_count: 2,
+            timestamp: SystemTime::now(),
+        };
+
+        assert_eq!(metrics.total_tokens(), 2_000);
+        assert_eq!(cost_per_1k_tokens(&metrics), 0.25);
+    }
+
+    #[test]
+    fn test_cost_per_1k_tokens_zero_tokens() {
+        let metrics = UsageAggregator::new().finalize();
+        assert_eq!(cost_per_1k_tokens(&metrics), 0.0);
+        assert_eq!(cost_per_1k(1.0, 0), 0.0);
+    }
+
+    #[test]
+    fn test_cost_per_interaction() {
+        assert_eq!(cost_per_interaction(1.5, 3), Some(0.5));
+        assert_eq!(cost_per_interaction(1.5, 0), None);
+    }
+
+    #[test]
+    fn test_aggregator_add_and_remove_part() {
+        let part = UsagePart {
+            id: "prt_test".to_string(),
+            message_id: "msg_test".to_string(),
+            session_id: "ses_test".to_string(),
+            event_type: "step-finish".to_string(),
+            tokens: Some(TokenUsage {
+                input: 100,
+                output: 50,
+                reasoning: 10,
+                cache: CacheUsage { write: 5, read: 15 },
+            }),
+            cost: 0.25,
+            cache_cost: None,
+            time: None,
+        };
+
+        let mut aggregator = UsageAggregator::new();
+        aggregator.add_part(&part);
+        aggregator.add_part(&part);
+        let metrics = aggregator.metrics();
+        assert_eq!(metrics.total_input_tokens, 200);
+        assert_eq!(metrics.total_cache_read_tokens, 30);
+        assert_eq!(metrics.interaction_count, 2);
+
+        aggregator.remove_part(&part);
+        let metrics = aggregator.metrics();
+        assert_eq!(metrics.total_input_tokens, 100);
+        assert_eq!(metrics.total_reasoning_tokens, 10);
+        assert_eq!(metrics.total_cost, 0.25);
+        assert_eq!(metrics.interaction_count, 1);
+    }
+
+    #[test]
+    fn test_cache_read_write_ratio() {
+        let mut metrics = UsageAggregator::new().finalize();
+        metrics.total_cache_write_tokens = 1_000;
+        metrics.total_cache_read_tokens = 3_200;
+        assert_eq!(cache_read_write_ratio(&metrics), Some(3.2));
+
+        metrics.total_cache_read_tokens = 0;
+        assert_eq!(cache_read_write_ratio(&metrics), Some(0.0));
+    }
+
+    #[test]
+    fn test_cache_read_write_ratio_zero_write() {
+        let mut metrics = UsageAggregator::new().finalize();
+        metrics.total_cache_read_tokens = 5_000;
+        assert_eq!(cache_read_write_ratio(&metrics), None);
+    }
+
+    #[test]
+    fn test_cache_cost_split() {
+        let part = |cost: f64, cache_cost: Option<f64>| UsagePart {
+            id: "prt_test".to_string(),
+            message_id: "msg_test".to_string(),
+            session_id: "ses_test".to_string(),
+            event_type: "step-finish".to_string(),
+            tokens: Some(TokenUsage {
+                input: 100,
+                output: 50,
+                reasoning: 0,
+                cache: CacheUsage { write: 5, read: 15 },
+            }),
+            cost,
+            cache_cost,
+            time: None,
+        };
+
+        // Without attribution everything is non-cache
+        let mut aggregator = UsageAggregator::new();
+        aggregator.add_part(&part(0.5, None));
+        let metrics = aggregator.finalize();
+        assert_eq!(metrics.cache_cost(), 0.0);
+        assert_eq!(metrics.non_cache_cost(), 0.5);
+        assert!(!metrics.has_cache_cost());
+
+        // Attributed parts split their cost; over-attribution is capped at the part cost
+        let mut aggregator = UsageAggregator::new();
+        aggregator.add_part(&part(0.5, Some(0.25)));
+        aggregator.add_part(&part(0.25, None));
+        aggregator.add_part(&part(0.25, Some(1.0)));
+        let metrics = aggregator.metrics();
+        assert_eq!(metrics.cache_cost(), 0.5);
+        assert_eq!(metrics.non_cache_cost(), 0.5);
+        assert!(metrics.has_cache_cost());
+
+        // Incremental add/remove keeps the split in step
+        aggregator.remove_part(&part(0.5, Some(0.25)));
+        let metrics = aggregator.finalize();
+        assert_eq!(metrics.cache_cost(), 0.25);
+        assert_eq!(metrics.non_cache_cost(), 0.25);
+    }
+
+    #[test]
+    fn test_subtract_metrics() {
+        let newer = UsageMetrics {
+            total_input_tokens: 1_500,
+            total_output_tokens: 300,
+            total_reasoning_tokens: 0,
+            total_cache_write_tokens: 10,
+            total_cache_read_tokens: 20,
+            total_cost: 2.5,
+            total_cache_cost: 0.0,
+            interaction_count: 7,
+            timestamp: SystemTime::now(),
+        };
+        let older = UsageMetrics {
+            total_input_tokens: 1_000,
+            total_output_tokens: 500,
+            total_reasoning_tokens: 0,
+            total_cache_write_tokens: 10,
+            total_cache_read_tokens: 5,
+            total_cost: 1.0,
+            total_cache_cost: 0.0,
+            interaction_count: 4,
+            timestamp: SystemTime::now(),
+        };
+
+        let delta = newer.subtract(&older);
+
+        assert_eq!(delta.input_tokens, 500);
+        assert_eq!(delta.output_tokens, -200);
+        assert_eq!(delta.reasoning_tokens, 0);
+        assert_eq!(delta.cache_write_tokens, 0);
+        assert_eq!(delta.cache_read_tokens, 15);
+        assert_eq!(delta.cost, 1.5);
+        assert_eq!(delta.interactions, 3);
+    }
+
+    #[test]
+    fn test_signed_diff_clamps() {
+        assert_eq!(signed_diff(u64::MAX, 0), i64::MAX);
+        assert_eq!(signed_diff(0, u64::MAX), i64::MIN);
+    }
+
+    #[test]
+    fn test_aggregate_saturates_near_u64_max() {
+        let mut aggregator = UsageAggregator::new();
+        let part = UsagePart {
+            id: "prt_huge".to_string(),
+            message_id: "msg_huge".to_string(),
+            session_id: "ses_huge".to_string(),
+            event_type: "step-finish".to_string(),
+            tokens: Some(TokenUsage {
+                input: u64::MAX - 1,
+                output: u64::MAX,
+                reasoning: 1,
+                cache: CacheUsage {
+                    write: u64::MAX / 2 + 1,
+                    read: 0,
+                },
+            }),
+            cost: 0.0,
+            cache_cost: None,
+            time: None,
+        };
+
+        aggregator.add_part(&part);
+        aggregator.add_part(&part);
+        aggregator.add_part(&part);
+        // Each saturated total is reported once, however many additions overflow
+        assert_eq!(
+            aggregator.saturated_fields(),
+            vec!["input", "output", "cache write"]
+        );
+        let metrics = aggregator.finalize();
+
+        assert_eq!(metrics.total_input_tokens, u64::MAX);
+        assert_eq!(metrics.total_output_tokens, u64::MAX);
+        assert_eq!(metrics.total_reasoning_tokens, 3);
+        assert_eq!(metrics.total_cache_write_tokens, u64::MAX);
+        assert_eq!(metrics.total_cache_read_tokens, 0);
+        assert_eq!(metrics.interaction_count, 3);
+    }
+
+    #[test]
+    fn test_remove_after_saturated_total_is_exact() {
+        let part = |input: u64| UsagePart {
+            id: "prt_huge".to_string(),
+            message_id: "msg_huge".to_string(),
+            session_id: "ses_huge".to_string(),
+            event_type: "step-finish".to_string(),
+            tokens: Some(TokenUsage {
+                input,
+                output: 1,
+                reasoning: 0,
+                cache: CacheUsage { write: 0, read: 0 },
+            }),
+            cost: 0.1,
+            cache_cost: None,
+            time: None,
+        };
+
+        let mut aggregator = UsageAggregator::new();
+        aggregator.add_part(&part(u64::MAX - 10));
+        aggregator.add_part(&part(100));
+        assert_eq!(aggregator.metrics().total_input_tokens, u64::MAX);
+
+        // Removing the part that overflowed restores the exact total
+        aggregator.remove_part(&part(100));
+        let metrics = aggregator.metrics();
+        assert_eq!(metrics.total_input_tokens, u64::MAX - 10);
+        assert_eq!(metrics.total_output_tokens, 1);
+        assert_eq!(metrics.interaction_count, 1);
+        assert!(aggregator.saturated_fields().is_empty());
+    }
+
+    #[test]
+    fn test_repeated_replacement_does_not_drift() {
+        let part = |cost: f64| UsagePart {
+            id: "prt_cost".to_string(),
+            message_id: "msg_cost".to_string(),
+            session_id: "ses_cost".to_string(),
+            event_type: "step-finish".to_string(),
+            tokens: Some(TokenUsage {
+                input: 1,
+                output: 1,
+                reasoning: 0,
+                cache: CacheUsage { write: 0, read: 0 },
+            }),
+            cost,
+            cache_cost: None,
+            time: None,
+        };
+
+        let mut aggregator = UsageAggregator::new();
+        aggregator.add_part(&part(1_000.0));
+        aggregator.add_part(&part(0.1));
+        // A file rewritten many times over a long session
+        for _ in 0..100_000 {
+            aggregator.remove_part(&part(0.1));
+            aggregator.add_part(&part(0.1));
+        }
+
+        let mut fresh = UsageAggregator::new();
+        fresh.add_part(&part(1_000.0));
+        fresh.add_part(&part(0.1));
+        assert_eq!(aggregator.metrics().total_cost, fresh.finalize().total_cost);
+    }
+
+    #[test]
+    fn test_remove_part_counts_sessions() {
+        let part = |session: &str| UsagePart {
+            id: "prt_session".to_string(),
+            message_id: "msg_session".to_string(),
+            session_id: session.to_string(),
+            event_type: "step-finish".to_string(),
+            tokens: Some(TokenUsage {
+                input: 10,
+                output: 5,
+                reasoning: 0,
+                cache: CacheUsage { write: 0, read: 0 },
+            }),
+            cost: 0.0,
+            cache_cost: None,
+            time: None,
+        };
+
+        let mut aggregator = UsageAggregator::with_interaction_unit(InteractionUnit::Session);
+        aggregator.add_part(&part("ses_a"));
+        aggregator.add_part(&part("ses_a"));
+        aggregator.add_part(&part("ses_b"));
+        assert_eq!(aggregator.metrics().interaction_count, 2);
+
+        // The session still has a part left, so it still counts
+        aggregator.remove_part(&part("ses_a"));
+        assert_eq!(aggregator.metrics().interaction_count, 2);
+
+        aggregator.remove_part(&part("ses_a"));
+        assert_eq!(aggregator.metrics().interaction_count, 1);
+    }
+
+    fn timed_part(session: &str, start: Option<u64>, end: Option<u64>) -> UsagePart {
+        UsagePart {
+            id: "prt_timed".to_string(),
+            message_id: "msg_timed".to_string(),
+            session_id: session.to_string(),
+            event_type: "step-finish".to_string(),
+            tokens: None,
+            cost: 0.0,
+            cache_cost: None,
+            time: Some(PartTime { start, end }),
+        }
+    }
+
+    #[test]
+    fn test_session_durations_from_part_timestamps() {
+        let parts = [
+            timed_part("ses_a", Some(1_000), Some(2_000)),
+            timed_part("ses_a", Some(5_000), Some(61_000)),
+            timed_part("ses_b", Some(10_000), Some(40_000)),
+            // A part with only an end still extends its session
+            timed_part("ses_b", None, Some(70_000)),
+        ];
+
+        let durations = session_durations(&parts).unwrap();
+
+        assert_eq!(durations.sessions, 2);
+        assert_eq!(durations.total, Duration::from_secs(120));
+        assert_eq!(durations.average(), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_session_durations_without_timestamps() {
+        let mut untimed = timed_part("ses_a", None, None);
+        assert_eq!(session_durations([&untimed]), None);
+
+        untimed.time = None;
+        assert_eq!(session_durations([&untimed]), None);
+    }
 }
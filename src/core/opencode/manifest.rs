@@ -0,0 +1,258 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Usage read from a single summary manifest instead of per-part files.
+//!
+//! Some setups (e.g. `OpenCode` running in CI) only keep a JSON summary with one
+//! entry per day:
+//!
+//! ```json
+//! {"days": [{"date": "2025-10-01", "input": 1200, "output": 300, "reasoning": 0,
+//!            "cache_write": 0, "cache_read": 0, "cost": 0.42, "interactions": 3}]}
+//! ```
+//!
+//! Token fields, `cost` and `interactions` default to 0 when left out.
+
+use crate::core::opencode::{ReaderError, UsageAggregator, UsageMetrics, UsageSource};
+use chrono::{Datelike, NaiveDate};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use thiserror::Error;
+
+/// Error types for manifest parsing
+#[derive(Debug, Error)]
+pub enum ManifestError {
+    #[error("Failed to read manifest: {0}")]
+    FileReadError(#[from] std::io::Error),
+
+    #[error("Invalid manifest: {0}")]
+    JsonError(#[from] serde_json::Error),
+}
+
+/// Usage totals for one day of a manifest
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ManifestDay {
+    pub date: NaiveDate,
+    #[serde(default)]
+    pub input: u64,
+    #[serde(default)]
+    pub output: u64,
+    #[serde(default)]
+    pub reasoning: u64,
+    #[serde(default)]
+    pub cache_write: u64,
+    #[serde(default)]
+    pub cache_read: u64,
+    #[serde(default)]
+    pub cost: f64,
+    #[serde(default)]
+    pub interactions: usize,
+}
+
+/// A usage manifest: daily totals in any order
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Manifest {
+    pub days: Vec<ManifestDay>,
+}
+
+impl Manifest {
+    /// Sum the days between optional bounds (inclusive)
+    ///
+    /// # Errors
+    /// Returns `ReaderError::NoDataFound` if no day falls within the bounds.
+    pub fn usage_between(
+        &self,
+        start: Option<NaiveDate>,
+        end: Option<NaiveDate>,
+    ) -> Result<UsageMetrics, ReaderError> {
+        let mut usage = UsageAggregator::new().finalize();
+        let mut found = false;
+        for day in self.days.iter().filter(|day| {
+            !start.is_some_and(|start| day.date < start) && !end.is_some_and(|end| day.date > end)
+        }) {
+            found = true;
+            usage.total_input_tokens = usage.total_input_tokens.saturating_add(day.input);
+            usage.total_output_tokens = usage.total_output_tokens.saturating_add(day.output);
+            usage.total_reasoning_tokens =
+                usage.total_reasoning_tokens.saturating_add(day.reasoning);
+            usage.total_cache_write_tokens = usage
+                .total_cache_write_tokens
+                .saturating_add(day.cache_write);
+            usage.total_cache_read_tokens =
+                usage.total_cache_read_tokens.saturating_add(day.cache_read);
+            usage.total_cost += day.cost;
+            usage.interaction_count = usage.interaction_count.saturating_add(day.interactions);
+        }
+        if !found {
+            return Err(ReaderError::NoDataFound);
+        }
+        usage.timestamp = SystemTime::now();
+        Ok(usage)
+    }
+}
+
+/// Parser for usage manifests
+pub struct ManifestParser;
+
+impl ManifestParser {
+    /// Parse a JSON string into a `Manifest`
+    ///
+    /// # Errors
+    /// Returns an error if the JSON is invalid or does not match the manifest schema.
+    pub fn parse_json(content: &str) -> Result<Manifest, ManifestError> {
+        Ok(serde_json::from_str(content)?)
+    }
+
+    /// Parse a manifest file
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or the JSON cannot be parsed.
+    pub fn parse_file(path: &Path) -> Result<Manifest, ManifestError> {
+        let content = std::fs::read_to_string(path)?;
+        Self::parse_json(&content)
+    }
+}
+
+/// Usage source reading a manifest file, re-read on every request
+#[derive(Debug, Clone)]
+pub struct ManifestUsageSource {
+    path: PathBuf,
+    today: Option<NaiveDate>,
+}
+
+impl ManifestUsageSource {
+    /// Creates a source for the manifest at `path`
+    ///
+    /// # Errors
+    /// Returns `ReaderError::AccessError` if `path` is not a file.
+    pub fn new(path: PathBuf) -> Result<Self, ReaderError> {
+        if !path.is_file() {
+            return Err(ReaderError::AccessError(format!(
+                "Manifest not found: {}",
+                path.display()
+            )));
+        }
+        Ok(Self { path, today: None })
+    }
+
+    /// Uses a fixed date as today instead of the current UTC date
+    #[must_use]
+    pub fn with_today(mut self, today: NaiveDate) -> Self {
+        self.today = Some(today);
+        self
+    }
+
+    fn usage_between(
+        &self,
+        start: Option<NaiveDate>,
+        end: Option<NaiveDate>,
+    ) -> Result<UsageMetrics, ReaderError> {
+        ManifestParser::parse_file(&self.path)
+            .map_err(|e| ReaderError::AccessError(e.to_string()))?
+            .usage_between(start, end)
+    }
+
+    fn today_date(&self) -> NaiveDate {
+        self.today
+            .unwrap_or_else(|| chrono::Utc::now().date_naive())
+    }
+}
+
+impl UsageSource for ManifestUsageSource {
+    fn get_usage(&mut self) -> Result<UsageMetrics, ReaderError> {
+        self.usage_between(None, None)
+    }
+
+    fn get_usage_today(&mut self) -> Result<UsageMetrics, ReaderError> {
+        let today = self.today_date();
+        self.usage_between(Some(today), Some(today))
+    }
+
+    fn get_usage_month(&mut self) -> Result<UsageMetrics, ReaderError> {
+        let today = self.today_date();
+        self.usage_between(today.with_day(1), Some(today))
+    }
+
+    fn get_usage_last_month(&mut self) -> Result<UsageMetrics, ReaderError> {
+        let Some(end) = self
+            .today_date()
+            .with_day(1)
+            .and_then(|first| first.pred_opt())
+        else {
+            return Err(ReaderError::NoDataFound);
+        };
+        self.usage_between(end.with_day(1), Some(end))
+    }
+
+    fn storage_path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    fn fork(&self) -> Result<Box<dyn UsageSource>, ReaderError> {
+        Ok(Box::new(self.clone()))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)] // Tests use exact float comparisons for simplicity
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const SAMPLE: &str = r#"{
+        "days": [
+            {"date": "2025-09-30", "input": 5000, "output": 1000, "cost": 2.0, "interactions": 4},
+            {"date": "2025-10-01", "input": 1200, "output": 300, "reasoning": 50,
+             "cache_write": 10, "cache_read": 20, "cost": 0.5, "interactions": 3},
+            {"date": "2025-10-15", "input": 800, "output": 200, "cost": 0.25, "interactions": 1}
+        ]
+    }"#;
+
+    #[test]
+    fn test_parse_manifest_into_usage_metrics() {
+        let manifest = ManifestParser::parse_json(SAMPLE).unwrap();
+        assert_eq!(manifest.days.len(), 3);
+        assert_eq!(manifest.days[2].reasoning, 0);
+
+        let all = manifest.usage_between(None, None).unwrap();
+        assert_eq!(all.total_input_tokens, 7000);
+        assert_eq!(all.total_output_tokens, 1500);
+        assert_eq!(all.total_reasoning_tokens, 50);
+        assert_eq!(all.total_cache_write_tokens, 10);
+        assert_eq!(all.total_cache_read_tokens, 20);
+        assert_eq!(all.total_cost, 2.75);
+        assert_eq!(all.interaction_count, 8);
+
+        let day = NaiveDate::from_ymd_opt(2025, 10, 2).unwrap();
+        assert!(matches!(
+            manifest.usage_between(Some(day), Some(day)),
+            Err(ReaderError::NoDataFound)
+        ));
+    }
+
+    #[test]
+    fn test_parse_invalid_manifest() {
+        assert!(matches!(
+            ManifestParser::parse_json(r#"{"days": [{"input": 1}]}"#),
+            Err(ManifestError::JsonError(_))
+        ));
+    }
+
+    #[test]
+    fn test_manifest_source_periods() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("usage.json");
+        std::fs::write(&path, SAMPLE).unwrap();
+
+        let mut source = ManifestUsageSource::new(path)
+            .unwrap()
+            .with_today(NaiveDate::from_ymd_opt(2025, 10, 15).unwrap());
+
+        assert_eq!(source.get_usage_today().unwrap().interaction_count, 1);
+        assert_eq!(source.get_usage_month().unwrap().interaction_count, 4);
+        assert_eq!(source.get_usage_last_month().unwrap().interaction_count, 4);
+        assert_eq!(source.get_usage().unwrap().interaction_count, 8);
+
+        assert!(ManifestUsageSource::new(temp_dir.path().join("missing.json")).is_err());
+    }
+}
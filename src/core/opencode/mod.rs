@@ -1,9 +1,15 @@
 pub mod aggregator;
+pub mod manifest;
 pub mod parser;
 pub mod reader;
 pub mod scanner;
+pub mod source;
 
-pub use aggregator::{UsageAggregator, UsageMetrics};
-pub use parser::{CacheUsage, ParserError, TokenUsage, UsageParser, UsagePart};
+pub use aggregator::{
+    cache_read_write_ratio, cost_per_1k_tokens, session_durations, InteractionUnit,
+    SessionDurations, UsageAggregator, UsageDelta, UsageMetrics,
+};
+pub use parser::{CacheUsage, ParserError, PartTime, TokenUsage, UsageParser, UsagePart};
 pub use reader::{OpenCodeUsageReader, ReaderError};
-pub use scanner::{FileMetadata, ScannerError, StorageScanner};
+pub use scanner::{detect_opencode_storage, FileMetadata, ScannerError, StorageScanner};
+pub use source::{MockUsageSource, UsageSource};
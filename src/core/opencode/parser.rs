@@ -1,12 +1,15 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::path::Path;
 use thiserror::Error;
 
 /// Represents the token usage from an `OpenCode` interaction
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct TokenUsage {
+    #[serde(deserialize_with = "deserialize_token_count")]
     pub input: u64,
+    #[serde(deserialize_with = "deserialize_token_count")]
     pub output: u64,
+    #[serde(deserialize_with = "deserialize_token_count")]
     pub reasoning: u64,
     pub cache: CacheUsage,
 }
@@ -14,10 +17,76 @@ pub struct TokenUsage {
 /// Represents cache token usage
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct CacheUsage {
+    #[serde(deserialize_with = "deserialize_token_count")]
     pub write: u64,
+    #[serde(deserialize_with = "deserialize_token_count")]
     pub read: u64,
 }
 
+/// A token count as it may appear in the JSON
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawTokenCount {
+    Unsigned(u64),
+    Signed(i64),
+    Float(f64),
+}
+
+/// Deserialize a token count into a whole, non-negative number
+///
+/// Counts should always be integers, but a float is rounded to the nearest whole
+/// token and a negative value is clamped to 0 (both logged) rather than failing the
+/// whole part, so the same file always yields the same totals.
+fn deserialize_token_count<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match RawTokenCount::deserialize(deserializer)? {
+        RawTokenCount::Unsigned(count) => count,
+        RawTokenCount::Signed(count) => {
+            eprintln!("[Parser] Negative token count {count}, clamping to 0");
+            0
+        }
+        RawTokenCount::Float(count) => {
+            eprintln!("[Parser] Non-integer token count {count}, rounding to a whole token");
+            // NaN and negatives clamp to 0, huge values saturate at u64::MAX
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let rounded = count.round() as u64;
+            rounded
+        }
+    })
+}
+
+/// Start and end of a part, in milliseconds since the Unix epoch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub struct PartTime {
+    #[serde(default)]
+    pub start: Option<u64>,
+    #[serde(default)]
+    pub end: Option<u64>,
+}
+
+/// Deserialize a part's time, ignoring a value of an unexpected shape
+///
+/// The time is optional metadata, so a malformed one is dropped (and logged)
+/// instead of failing the whole part and losing its tokens and cost.
+fn deserialize_part_time<'de, D>(deserializer: D) -> Result<Option<PartTime>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    if value.is_null() {
+        return Ok(None);
+    }
+    match PartTime::deserialize(&value) {
+        Ok(time) => Ok(Some(time)),
+        Err(e) => {
+            eprintln!("[Parser] Ignoring unexpected part time {value}: {e}");
+            Ok(None)
+        }
+    }
+}
+
 /// Represents a usage part from `OpenCode` storage
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct UsagePart {
@@ -30,6 +99,27 @@ pub struct UsagePart {
     pub event_type: String,
     pub tokens: Option<TokenUsage>,
     pub cost: f64,
+    /// Share of `cost` spent on cache reads and writes, when the part records it
+    #[serde(default, rename = "cacheCost", skip_serializing_if = "Option::is_none")]
+    pub cache_cost: Option<f64>,
+    /// When the part started and ended, if the storage records it
+    #[serde(
+        default,
+        deserialize_with = "deserialize_part_time",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub time: Option<PartTime>,
+}
+
+impl UsagePart {
+    /// Cost attributed to the cache, bounded by the part's total cost
+    ///
+    /// Parts without a cache cost attribute nothing to the cache.
+    #[must_use]
+    pub fn attributed_cache_cost(&self) -> f64 {
+        self.cache_cost
+            .map_or(0.0, |cache| cache.clamp(0.0, self.cost.max(0.0)))
+    }
 }
 
 /// Error types for parsing operations
@@ -114,6 +204,49 @@ mod tests {
         assert_eq!(tokens.cache.read, 0);
     }
 
+    #[test]
+    fn test_deserialize_part_time() {
+        let json = r#"{
+            "id": "prt_test",
+            "messageID": "msg_test",
+            "sessionID": "ses_test",
+            "type": "step-finish",
+            "tokens": {"input": 1, "output": 1, "reasoning": 0, "cache": {"write": 0, "read": 0}},
+            "cost": 0,
+            "time": {"start": 1000, "end": 4000}
+        }"#;
+
+        let part: UsagePart = serde_json::from_str(json).expect("Should deserialize");
+        assert_eq!(
+            part.time,
+            Some(PartTime {
+                start: Some(1000),
+                end: Some(4000)
+            })
+        );
+    }
+
+    #[test]
+    fn test_unexpected_part_time_keeps_part() {
+        for time in [r#""yesterday""#, "1000", r#"{"start": "soon"}"#, "null"] {
+            let json = format!(
+                r#"{{
+                    "id": "prt_test",
+                    "messageID": "msg_test",
+                    "sessionID": "ses_test",
+                    "type": "step-finish",
+                    "tokens": {{"input": 7, "output": 1, "reasoning": 0, "cache": {{"write": 0, "read": 0}}}},
+                    "cost": 0.5,
+                    "time": {time}
+                }}"#
+            );
+
+            let part: UsagePart = serde_json::from_str(&json).expect("Should deserialize");
+            assert_eq!(part.time, None, "time {time} should be ignored");
+            assert_eq!(part.tokens.map(|tokens| tokens.input), Some(7));
+        }
+    }
+
     // Test 2: Deserialize with cache read tokens
     #[test]
     fn test_deserialize_with_cache_tokens() {
@@ -141,6 +274,34 @@ mod tests {
         assert_eq!(part.cost, 0.5);
     }
 
+    #[test]
+    fn test_deserialize_cache_cost_attribution() {
+        let json = r#"{
+            "id": "prt_test",
+            "messageID": "msg_test",
+            "sessionID": "ses_test",
+            "type": "step-finish",
+            "tokens": {
+                "input": 100,
+                "output": 50,
+                "reasoning": 0,
+                "cache": { "write": 10, "read": 400 }
+            },
+            "cost": 0.5,
+            "cacheCost": 0.2
+        }"#;
+
+        let part: UsagePart = serde_json::from_str(json).expect("Should deserialize");
+        assert_eq!(part.cache_cost, Some(0.2));
+        assert_eq!(part.attributed_cache_cost(), 0.2);
+
+        // Parts without the field attribute nothing to the cache
+        let json = json.replace(",\n            \"cacheCost\": 0.2", "");
+        let part: UsagePart = serde_json::from_str(&json).expect("Should deserialize");
+        assert_eq!(part.cache_cost, None);
+        assert_eq!(part.attributed_cache_cost(), 0.0);
+    }
+
     // Test 3: Deserialize without tokens field (should be None)
     #[test]
     fn test_deserialize_without_tokens() {
@@ -156,6 +317,39 @@ mod tests {
         assert!(part.tokens.is_none());
     }
 
+    #[test]
+    fn test_float_and_negative_token_counts_are_normalized() {
+        let json = r#"{
+            "id": "prt_float",
+            "messageID": "msg_float",
+            "sessionID": "ses_float",
+            "type": "step-finish",
+            "tokens": {
+                "input": 1200.6,
+                "output": 300.0,
+                "reasoning": -5,
+                "cache": {
+                    "write": 0.4,
+                    "read": 10
+                }
+            },
+            "cost": 0.1
+        }"#;
+
+        // Parsing the same part twice gives the same whole-token counts
+        for _ in 0..2 {
+            let part = UsageParser::parse_json(json)
+                .expect("Float tokens should still parse")
+                .expect("Should have tokens");
+            let tokens = part.tokens.expect("Should have tokens");
+            assert_eq!(tokens.input, 1201);
+            assert_eq!(tokens.output, 300);
+            assert_eq!(tokens.reasoning, 0);
+            assert_eq!(tokens.cache.write, 0);
+            assert_eq!(tokens.cache.read, 10);
+        }
+    }
+
     // Test 4: Round-trip serialization
     #[test]
     fn test_serialization_round_trip() {
@@ -171,6 +365,8 @@ mod tests {
                 cache: CacheUsage { write: 5, read: 15 },
             }),
             cost: 0.25,
+            cache_cost: None,
+            time: None,
         };
 
         let json = serde_json::to_string(&original).expect("Should serialize");
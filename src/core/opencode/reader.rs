@@ -1,10 +1,14 @@
+use crate::core::opencode::aggregator::{session_durations, SessionDurations};
 use crate::core::opencode::{
-    FileMetadata, ScannerError, StorageScanner, UsageAggregator, UsageMetrics, UsageParser,
-    UsagePart,
+    FileMetadata, InteractionUnit, ScannerError, StorageScanner, UsageAggregator, UsageMetrics,
+    UsageParser, UsagePart,
 };
-use chrono::{Datelike, Local, TimeZone};
+use chrono::{Datelike, Local, NaiveDate, TimeZone};
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::{Duration, SystemTime};
 use thiserror::Error;
 
@@ -22,6 +26,12 @@ pub enum ReaderError {
 
     #[error("Failed to access storage: {0}")]
     AccessError(String),
+
+    #[error("Failed to create parsing thread pool: {0}")]
+    ThreadPoolError(String),
+
+    #[error("Day reset hour must be between 0 and 23 (got {0})")]
+    InvalidDayResetHour(u8),
 }
 
 /// Cached parsed file data
@@ -31,10 +41,38 @@ struct CachedFile {
     modified: SystemTime,
 }
 
+impl CachedFile {
+    /// Cache a parsed part, dropping everything it is not aggregated by when `lean` is set
+    ///
+    /// Only tokens and cost feed the aggregation (plus the session id when interactions
+    /// are counted per session), so a lean entry still supports incremental parsing
+    /// while keeping almost no per-file strings in memory.
+    fn new(part: UsagePart, modified: SystemTime, lean: bool, unit: InteractionUnit) -> Self {
+        let part = if lean {
+            let session_id = match unit {
+                InteractionUnit::Part => String::new(),
+                InteractionUnit::Session => part.session_id,
+            };
+            UsagePart {
+                id: String::new(),
+                message_id: String::new(),
+                session_id,
+                event_type: String::new(),
+                ..part
+            }
+        } else {
+            part
+        };
+        Self { part, modified }
+    }
+}
+
 /// Cached usage data with incremental file tracking
 #[derive(Debug, Clone)]
 struct CachedData {
     metrics: UsageMetrics,
+    /// Running aggregation behind `metrics`, so single files can be swapped in and out
+    aggregator: UsageAggregator,
     timestamp: SystemTime,
     /// Map of file path to cached parsed data
     files: HashMap<PathBuf, CachedFile>,
@@ -44,6 +82,15 @@ struct CachedData {
 pub struct OpenCodeUsageReader {
     scanner: StorageScanner,
     cache: Option<CachedData>,
+    /// Bounded pool for all-time scanning and parsing (`None` uses rayon's global pool)
+    pool: Option<Arc<ThreadPool>>,
+    parse_threads: Option<usize>,
+    /// Hour at which "today" starts (0 = midnight)
+    day_reset_hour: u8,
+    /// Cache only the token and cost figures of each parsed file
+    lean_cache: bool,
+    /// What counts as one interaction
+    interaction_unit: InteractionUnit,
 }
 
 impl OpenCodeUsageReader {
@@ -53,10 +100,7 @@ impl OpenCodeUsageReader {
     /// Returns an error if the scanner cannot be initialized.
     pub fn new() -> Result<Self, ReaderError> {
         let scanner = StorageScanner::new()?;
-        Ok(Self {
-            scanner,
-            cache: None,
-        })
+        Ok(Self::with_scanner(scanner))
     }
 
     /// Create a reader with a custom path (useful for testing)
@@ -65,10 +109,7 @@ impl OpenCodeUsageReader {
     /// Returns an error if the scanner cannot be initialized with the given path.
     pub fn new_with_path(path: &str) -> Result<Self, ReaderError> {
         let scanner = StorageScanner::with_path(std::path::PathBuf::from(path))?;
-        Ok(Self {
-            scanner,
-            cache: None,
-        })
+        Ok(Self::with_scanner(scanner))
     }
 
     /// Create a reader with a custom scanner (useful for testing)
@@ -77,15 +118,149 @@ impl OpenCodeUsageReader {
         Self {
             scanner,
             cache: None,
+            pool: None,
+            parse_threads: None,
+            day_reset_hour: 0,
+            lean_cache: false,
+            interaction_unit: InteractionUnit::Part,
+        }
+    }
+
+    /// Bound the number of threads used for all-time scanning and parsing
+    ///
+    /// `None` uses rayon's default global pool.
+    ///
+    /// # Errors
+    /// Returns an error if the thread pool cannot be created.
+    pub fn set_parse_threads(&mut self, threads: Option<usize>) -> Result<(), ReaderError> {
+        self.pool = match threads {
+            Some(count) => {
+                let pool = ThreadPoolBuilder::new()
+                    .num_threads(count)
+                    .thread_name(|i| format!("opencode-parse-{i}"))
+                    .build()
+                    .map_err(|e| ReaderError::ThreadPoolError(e.to_string()))?;
+                Some(Arc::new(pool))
+            }
+            None => None,
+        };
+        self.parse_threads = threads;
+        Ok(())
+    }
+
+    /// Get the configured parse thread limit (`None` means rayon's default)
+    #[must_use]
+    pub fn parse_threads(&self) -> Option<usize> {
+        self.parse_threads
+    }
+
+    /// Shift the start of "today" to the given UTC hour instead of midnight UTC
+    ///
+    /// # Errors
+    /// Returns an error if the hour is not between 0 and 23.
+    pub fn set_day_reset_hour(&mut self, hour: u8) -> Result<(), ReaderError> {
+        if hour > 23 {
+            return Err(ReaderError::InvalidDayResetHour(hour));
+        }
+        self.day_reset_hour = hour;
+        Ok(())
+    }
+
+    /// Get the hour at which "today" starts
+    #[must_use]
+    pub fn day_reset_hour(&self) -> u8 {
+        self.day_reset_hour
+    }
+
+    /// Keep only token and cost figures in the parse cache
+    ///
+    /// Lowers memory use on very large histories. Takes effect for files parsed from now on.
+    pub fn set_lean_cache(&mut self, lean: bool) {
+        self.lean_cache = lean;
+    }
+
+    /// Whether the parse cache keeps only token and cost figures
+    #[must_use]
+    pub fn lean_cache(&self) -> bool {
+        self.lean_cache
+    }
+
+    /// Count interactions per part or per session
+    ///
+    /// Cached totals were counted in the previous unit, so the cache is dropped on change.
+    pub fn set_interaction_unit(&mut self, unit: InteractionUnit) {
+        if self.interaction_unit != unit {
+            self.interaction_unit = unit;
+            self.invalidate_cache();
+        }
+    }
+
+    /// What counts as one interaction
+    #[must_use]
+    pub fn interaction_unit(&self) -> InteractionUnit {
+        self.interaction_unit
+    }
+
+    /// Create an aggregator counting interactions in the configured unit
+    fn aggregator(&self) -> UsageAggregator {
+        UsageAggregator::with_interaction_unit(self.interaction_unit)
+    }
+
+    /// Run work on the bounded pool if one is configured, otherwise on rayon's global pool
+    fn run_bounded<T, F>(&self, work: F) -> T
+    where
+        T: Send,
+        F: FnOnce() -> T + Send,
+    {
+        match &self.pool {
+            Some(pool) => pool.install(work),
+            None => work(),
         }
     }
 
+    /// Drop all cached data so the next read parses every file from disk again
+    pub fn invalidate_cache(&mut self) {
+        self.cache = None;
+    }
+
+    /// List the files held in the all-time cache with the modification times they were parsed at
+    ///
+    /// A diagnostic for stale or wrong totals: a file whose tracked time lags its
+    /// current one is re-parsed on the next read. Sorted by path; empty before the first read.
+    #[must_use]
+    pub fn dump_cache(&self) -> Vec<(PathBuf, SystemTime)> {
+        let mut entries: Vec<_> = self
+            .cache
+            .iter()
+            .flat_map(|cache| cache.files.iter())
+            .map(|(path, cached)| (path.clone(), cached.modified))
+            .collect();
+        entries.sort();
+        entries
+    }
+
     /// Get the storage path
     #[must_use]
     pub fn storage_path(&self) -> &PathBuf {
         self.scanner.storage_path()
     }
 
+    /// Number of storage entries skipped for lack of permission during the last scan
+    #[must_use]
+    pub fn skipped_permission_denied(&self) -> usize {
+        self.scanner.skipped_permission_denied()
+    }
+
+    /// Get the most recently modified part files, newest first
+    ///
+    /// # Errors
+    /// Returns an error if the storage directory cannot be scanned.
+    pub fn recent_files(&self, limit: usize) -> Result<Vec<FileMetadata>, ReaderError> {
+        let mut files = self.scanner.scan_sorted_desc()?;
+        files.truncate(limit);
+        Ok(files)
+    }
+
     /// Get usage metrics, using cache if available and not expired
     ///
     /// # Errors
@@ -99,29 +274,31 @@ impl OpenCodeUsageReader {
         }
 
         // Scan files with metadata
-        let files = self.scanner.scan_with_metadata()?;
+        let files = self.run_bounded(|| self.scanner.scan_with_metadata())?;
 
         if files.is_empty() {
             return Err(ReaderError::NoDataFound);
         }
 
         // Determine which files need to be parsed
-        let (parts_to_aggregate, new_file_cache) = self.incremental_parse(&files)?;
+        let (parts_to_aggregate, new_file_cache) =
+            self.run_bounded(|| self.incremental_parse(&files))?;
 
         if parts_to_aggregate.is_empty() {
             return Err(ReaderError::NoDataFound);
         }
 
         // Aggregate all parts
-        let mut aggregator = UsageAggregator::new();
+        let mut aggregator = self.aggregator();
         for part in parts_to_aggregate {
             aggregator.add_part(&part);
         }
-        let metrics = aggregator.finalize();
+        let metrics = aggregator.metrics();
 
         // Update cache
         self.cache = Some(CachedData {
             metrics: metrics.clone(),
+            aggregator,
             timestamp: metrics.timestamp,
             files: new_file_cache,
         });
@@ -129,13 +306,46 @@ impl OpenCodeUsageReader {
         Ok(metrics)
     }
 
+    /// Feed a single new, changed or deleted part file into the all-time cache
+    ///
+    /// The cached aggregation is adjusted incrementally: the file's previously cached part
+    /// is removed and its freshly parsed part added, so a file-watch event costs one
+    /// parse instead of a full re-scan. Without a cache, this falls back to `get_usage`.
+    ///
+    /// # Errors
+    /// Returns an error if no cache exists and the full read fails.
+    pub fn update_file(&mut self, path: &Path) -> Result<UsageMetrics, ReaderError> {
+        let Some(cache) = &mut self.cache else {
+            return self.get_usage();
+        };
+
+        if let Some(old) = cache.files.remove(path) {
+            cache.aggregator.remove_part(&old.part);
+        }
+
+        // A missing file was deleted, so only its old part had to be removed
+        let modified = std::fs::metadata(path).and_then(|meta| meta.modified());
+        if let Ok(modified) = modified {
+            if let Ok(Some(part)) = UsageParser::parse_file(path) {
+                cache.aggregator.add_part(&part);
+                cache.files.insert(
+                    path.to_path_buf(),
+                    CachedFile::new(part, modified, self.lean_cache, self.interaction_unit),
+                );
+            }
+        }
+
+        cache.metrics = cache.aggregator.metrics();
+        Ok(cache.metrics.clone())
+    }
+
     /// Get usage metrics for today only (files modified today)
     ///
     /// # Errors
     /// Returns an error if no data is found for today or if parsing fails.
     pub fn get_usage_today(&mut self) -> Result<UsageMetrics, ReaderError> {
-        // Calculate start of today (midnight) as cutoff time
-        let cutoff = Self::get_today_start();
+        // Calculate start of today (midnight, or the reset hour) as cutoff time
+        let cutoff = Self::get_today_start(SystemTime::now(), self.day_reset_hour);
 
         // Scan only files modified since start of today
         let today_files = self.scanner.scan_modified_since(cutoff)?;
@@ -167,6 +377,95 @@ impl OpenCodeUsageReader {
         self.parse_and_aggregate(&month_files)
     }
 
+    /// Get the total and average session length for this month
+    ///
+    /// Returns `None` when the parts carry no timestamps (or, with a lean cache,
+    /// no session ids).
+    ///
+    /// # Errors
+    /// Returns an error if scanning or parsing fails.
+    pub fn get_session_durations_month(&mut self) -> Result<Option<SessionDurations>, ReaderError> {
+        let month_files = self.scanner.scan_modified_since(Self::get_month_start())?;
+        let (parts, _) = self.incremental_parse(&month_files)?;
+        Ok(session_durations(&parts))
+    }
+
+    /// Get usage metrics for today and this month from a single directory walk
+    ///
+    /// Files are scanned once and bucketed by modification time into both periods.
+    /// Today's metrics may be all zero; only an empty month is an error.
+    ///
+    /// # Errors
+    /// Returns an error if no data is found for this month or if scanning fails.
+    pub fn get_usage_today_and_month(
+        &mut self,
+    ) -> Result<(UsageMetrics, UsageMetrics), ReaderError> {
+        let today_start = Self::get_today_start(SystemTime::now(), self.day_reset_hour);
+        self.usage_today_and_month_since(today_start, Self::get_month_start())
+    }
+
+    /// Bucket files modified since the earlier of the two cutoffs into today and this month
+    fn usage_today_and_month_since(
+        &mut self,
+        today_start: SystemTime,
+        month_start: SystemTime,
+    ) -> Result<(UsageMetrics, UsageMetrics), ReaderError> {
+        // With a day reset hour, today may start before the month does
+        let files = self
+            .scanner
+            .scan_modified_since(today_start.min(month_start))?;
+        let (_, parsed) = self.incremental_parse(&files)?;
+
+        let mut today = self.aggregator();
+        let mut month = self.aggregator();
+        let mut month_is_empty = true;
+
+        // Walk the scan order (not the map) so sums don't depend on hashing
+        for file in &files {
+            let Some(cached_file) = parsed.get(&file.path) else {
+                continue;
+            };
+            if file.modified >= month_start {
+                month.add_part(&cached_file.part);
+                month_is_empty = false;
+            }
+            if file.modified >= today_start {
+                today.add_part(&cached_file.part);
+            }
+        }
+
+        if month_is_empty {
+            return Err(ReaderError::NoDataFound);
+        }
+
+        Ok((today.finalize(), month.finalize()))
+    }
+
+    /// Get usage metrics for a single past day (files last modified during that day)
+    ///
+    /// Days are bounded the same way as today: UTC midnight shifted by the day reset hour.
+    /// Files edited again on a later day count towards that later day instead.
+    ///
+    /// # Errors
+    /// Returns an error if no data is found for the day or if parsing fails.
+    pub fn get_usage_for_day(&mut self, date: NaiveDate) -> Result<UsageMetrics, ReaderError> {
+        let day_start = Self::get_day_start(date, self.day_reset_hour);
+        let day_end = day_start + Duration::from_secs(86400);
+
+        let day_files: Vec<_> = self
+            .scanner
+            .scan_modified_since(day_start)?
+            .into_iter()
+            .filter(|file| file.modified < day_end)
+            .collect();
+
+        if day_files.is_empty() {
+            return Err(ReaderError::NoDataFound);
+        }
+
+        self.parse_and_aggregate(&day_files)
+    }
+
     /// Get usage metrics for last month only (files modified during last month)
     ///
     /// # Errors
@@ -197,21 +496,41 @@ impl OpenCodeUsageReader {
         self.parse_and_aggregate(&last_month_only)
     }
 
-    /// Get the start of today (midnight) as `SystemTime`
-    fn get_today_start() -> SystemTime {
-        let now = SystemTime::now();
+    /// Get the start of the day containing `now` as `SystemTime`
+    ///
+    /// Days are UTC days. The day starts at `reset_hour` instead of midnight, so with a
+    /// reset hour of 4 anything before 04:00 UTC still belongs to the previous day.
+    fn get_today_start(now: SystemTime, reset_hour: u8) -> SystemTime {
         let now_since_epoch = now
             .duration_since(SystemTime::UNIX_EPOCH)
             .unwrap_or(Duration::from_secs(0));
-        let now_secs = now_since_epoch.as_secs();
+        let reset_offset = u64::from(reset_hour) * 3600;
+        let now_secs = now_since_epoch.as_secs().saturating_sub(reset_offset);
 
-        // Calculate start of today (midnight) in seconds since epoch
+        // Calculate start of today (midnight) in seconds since epoch, then shift it
         // 86400 seconds = 24 hours
-        let today_start_secs = (now_secs / 86400) * 86400;
+        let today_start_secs = (now_secs / 86400) * 86400 + reset_offset;
 
         SystemTime::UNIX_EPOCH + Duration::from_secs(today_start_secs)
     }
 
+    /// Get the start of the day after the one containing `now`
+    ///
+    /// This is when "today" rolls over for the reader, honoring `reset_hour`.
+    #[must_use]
+    pub fn next_day_start(now: SystemTime, reset_hour: u8) -> SystemTime {
+        Self::get_today_start(now, reset_hour) + Duration::from_secs(86400)
+    }
+
+    /// Get the start of `date` (UTC midnight plus the reset hour) as `SystemTime`
+    fn get_day_start(date: NaiveDate, reset_hour: u8) -> SystemTime {
+        let midnight = date.and_time(chrono::NaiveTime::MIN).and_utc().timestamp();
+        // Dates before the epoch clamp to it; no usage files are that old
+        #[allow(clippy::cast_sign_loss)]
+        let midnight_secs = midnight.max(0) as u64;
+        SystemTime::UNIX_EPOCH + Duration::from_secs(midnight_secs + u64::from(reset_hour) * 3600)
+    }
+
     /// Get the start of this month (first day at midnight) as `SystemTime`
     fn get_month_start() -> SystemTime {
         use std::time::UNIX_EPOCH;
@@ -273,7 +592,7 @@ impl OpenCodeUsageReader {
         }
 
         // Aggregate all parts
-        let mut aggregator = UsageAggregator::new();
+        let mut aggregator = self.aggregator();
         for part in parts_to_aggregate {
             aggregator.add_part(&part);
         }
@@ -290,44 +609,43 @@ impl OpenCodeUsageReader {
     ) -> Result<(Vec<UsagePart>, HashMap<PathBuf, CachedFile>), ReaderError> {
         let mut parts = Vec::new();
         let mut new_cache = HashMap::new();
+        let mut to_parse = Vec::new();
 
         for file_meta in files {
-            // Check if we have a cached version of this file
-            let needs_parse = if let Some(cached) = &self.cache {
-                if let Some(cached_file) = cached.files.get(&file_meta.path) {
-                    // File exists in cache - check if modified
-                    if cached_file.modified == file_meta.modified {
-                        // File unchanged - reuse cached result
-                        parts.push(cached_file.part.clone());
-                        new_cache.insert(file_meta.path.clone(), cached_file.clone());
-                        false
-                    } else {
-                        // File modified - needs re-parse
-                        true
-                    }
-                } else {
-                    // New file not in cache - needs parse
-                    true
-                }
+            // Reuse the cached result if the file is unchanged since it was parsed
+            let cached_file = self
+                .cache
+                .as_ref()
+                .and_then(|cached| cached.files.get(&file_meta.path))
+                .filter(|cached_file| cached_file.modified == file_meta.modified);
+
+            if let Some(cached_file) = cached_file {
+                parts.push(cached_file.part.clone());
+                new_cache.insert(file_meta.path.clone(), cached_file.clone());
             } else {
-                // No cache - needs parse
-                true
-            };
+                // New or modified file - needs parse
+                to_parse.push(file_meta);
+            }
+        }
 
-            if needs_parse {
-                // Parse the file
-                if let Ok(Some(part)) = UsageParser::parse_file(&file_meta.path) {
-                    parts.push(part.clone());
-                    new_cache.insert(
-                        file_meta.path.clone(),
-                        CachedFile {
-                            part,
-                            modified: file_meta.modified,
-                        },
-                    );
-                } else {
-                    // File parsed but no tokens, or invalid JSON - skip silently
-                }
+        // Parse in parallel; collecting keeps the input order, so results don't depend
+        // on the number of threads
+        let parsed: Vec<_> = to_parse
+            .par_iter()
+            .map(|file_meta| UsageParser::parse_file(&file_meta.path))
+            .collect();
+
+        for (file_meta, result) in to_parse.into_iter().zip(parsed) {
+            // Files without tokens, or invalid JSON, are skipped silently
+            if let Ok(Some(part)) = result {
+                let cached_file = CachedFile::new(
+                    part,
+                    file_meta.modified,
+                    self.lean_cache,
+                    self.interaction_unit,
+                );
+                parts.push(cached_file.part.clone());
+                new_cache.insert(file_meta.path.clone(), cached_file);
             }
         }
 
@@ -412,6 +730,35 @@ mod tests {
         fs::remove_dir_all(test_dir).ok();
     }
 
+    #[test]
+    fn test_reader_session_durations_month() {
+        let test_dir = create_test_dir("session_durations");
+        for (name, start, end) in [("first", 0, 30_000), ("second", 60_000, 90_000)] {
+            let content = format!(
+                r#"{{"id": "prt_{name}", "messageID": "msg_test", "sessionID": "ses_test",
+                    "type": "step-finish",
+                    "tokens": {{"input": 1, "output": 1, "reasoning": 0,
+                                "cache": {{"write": 0, "read": 0}}}},
+                    "cost": 0, "time": {{"start": {start}, "end": {end}}}}}"#
+            );
+            fs::write(test_dir.join(format!("{name}.json")), content).unwrap();
+        }
+
+        let scanner = StorageScanner::with_path(test_dir.clone()).expect("Should create scanner");
+        let mut reader = OpenCodeUsageReader::with_scanner(scanner);
+        let durations = reader.get_session_durations_month().unwrap().unwrap();
+        assert_eq!(durations.sessions, 1);
+        assert_eq!(durations.total, Duration::from_secs(90));
+
+        // Parts written without timestamps leave the feature out
+        create_usage_file(&test_dir, "untimed", 1, 1, 0.0);
+        fs::remove_file(test_dir.join("first.json")).unwrap();
+        fs::remove_file(test_dir.join("second.json")).unwrap();
+        assert_eq!(reader.get_session_durations_month().unwrap(), None);
+
+        fs::remove_dir_all(test_dir).ok();
+    }
+
     // Test 2: Handle directory with no usage files
     #[test]
     fn test_reader_with_no_data() {
@@ -474,12 +821,14 @@ mod tests {
             total_cache_write_tokens: 0,
             total_cache_read_tokens: 0,
             total_cost: 9.99,
+            total_cache_cost: 0.0,
             interaction_count: 1,
             timestamp: SystemTime::now() - Duration::from_secs(6 * 60), // 6 minutes ago
         };
 
         reader.cache = Some(CachedData {
             metrics: old_metrics.clone(),
+            aggregator: UsageAggregator::new(),
             timestamp: old_metrics.timestamp,
             files: HashMap::new(),
         });
@@ -497,6 +846,68 @@ mod tests {
         fs::remove_dir_all(test_dir).ok();
     }
 
+    #[test]
+    fn test_get_usage_for_day() {
+        let test_dir = create_test_dir("usage_for_day");
+        let day = NaiveDate::from_ymd_opt(2025, 3, 10).unwrap();
+        let at = |date: NaiveDate, hour: u64| {
+            filetime::FileTime::from_system_time(
+                OpenCodeUsageReader::get_day_start(date, 0) + Duration::from_secs(hour * 3600),
+            )
+        };
+
+        create_usage_file(&test_dir, "morning", 100, 50, 0.25);
+        create_usage_file(&test_dir, "evening", 200, 50, 0.50);
+        create_usage_file(&test_dir, "next_day", 400, 50, 1.00);
+        filetime::set_file_mtime(test_dir.join("morning.json"), at(day, 9)).unwrap();
+        filetime::set_file_mtime(test_dir.join("evening.json"), at(day, 23)).unwrap();
+        filetime::set_file_mtime(
+            test_dir.join("next_day.json"),
+            at(day.succ_opt().unwrap(), 1),
+        )
+        .unwrap();
+
+        let mut reader = OpenCodeUsageReader::new_with_path(test_dir.to_str().unwrap())
+            .expect("Should create reader");
+        let metrics = reader.get_usage_for_day(day).expect("Should read the day");
+        assert_eq!(metrics.total_input_tokens, 300);
+        assert_eq!(metrics.interaction_count, 2);
+
+        let empty_day = NaiveDate::from_ymd_opt(2025, 3, 5).unwrap();
+        assert!(matches!(
+            reader.get_usage_for_day(empty_day),
+            Err(ReaderError::NoDataFound)
+        ));
+
+        fs::remove_dir_all(test_dir).ok();
+    }
+
+    #[test]
+    fn test_invalidate_cache_rereads_files() {
+        let test_dir = create_test_dir("invalidate_cache");
+
+        create_usage_file(&test_dir, "file1", 100, 50, 0.25);
+
+        let scanner = StorageScanner::with_path(test_dir.clone()).expect("Should create scanner");
+        let mut reader = OpenCodeUsageReader::with_scanner(scanner);
+        let metrics = reader.get_usage().expect("Should read usage");
+        assert_eq!(metrics.total_input_tokens, 100);
+
+        // Rewrite the file, keeping its timestamp so the cache cannot notice
+        let file_path = test_dir.join("file1.json");
+        let modified = fs::metadata(&file_path).unwrap().modified().unwrap();
+        create_usage_file(&test_dir, "file1", 300, 50, 0.75);
+        filetime::set_file_mtime(&file_path, filetime::FileTime::from_system_time(modified))
+            .expect("Failed to set file time");
+        assert_eq!(reader.get_usage().unwrap().total_input_tokens, 100);
+
+        reader.invalidate_cache();
+        let metrics = reader.get_usage().expect("Should re-read usage");
+        assert_eq!(metrics.total_input_tokens, 300);
+
+        fs::remove_dir_all(test_dir).ok();
+    }
+
     // Test 5: Continue despite invalid JSON files
     #[test]
     fn test_reader_skips_invalid_files() {
@@ -614,6 +1025,57 @@ mod tests {
         fs::remove_dir_all(test_dir).ok();
     }
 
+    // Incremental parsing stays correct when the cache keeps only tokens and cost
+    #[test]
+    fn test_reader_lean_cache_incremental_parsing() {
+        let test_dir = create_test_dir("lean_cache");
+
+        create_usage_file(&test_dir, "file1", 100, 50, 0.25);
+        create_usage_file(&test_dir, "file2", 200, 100, 0.50);
+
+        let scanner = StorageScanner::with_path(test_dir.clone()).expect("Should create scanner");
+        let mut reader = OpenCodeUsageReader::with_scanner(scanner);
+        reader.set_lean_cache(true);
+
+        let metrics1 = reader.get_usage().expect("Should read initial files");
+        assert_eq!(metrics1.total_input_tokens, 300);
+
+        // Cached entries keep no strings, only the figures needed for aggregation
+        let cache = reader.cache.as_ref().unwrap();
+        assert_eq!(cache.files.len(), 2);
+        for cached_file in cache.files.values() {
+            assert!(cached_file.part.id.is_empty());
+            assert!(cached_file.part.message_id.is_empty());
+            assert!(cached_file.part.tokens.is_some());
+        }
+
+        // Modify one file and add another
+        std::thread::sleep(std::time::Duration::from_millis(50)); // Ensure different timestamp
+        create_usage_file(&test_dir, "file1", 500, 250, 1.00);
+        create_usage_file(&test_dir, "file3", 1000, 0, 2.00);
+
+        // Force cache expiry
+        if let Some(ref mut cache) = reader.cache {
+            cache.timestamp = SystemTime::now() - Duration::from_secs(6 * 60);
+        }
+
+        let metrics2 = reader.get_usage().expect("Should read modified files");
+        assert_eq!(metrics2.total_input_tokens, 1700);
+        assert_eq!(metrics2.total_output_tokens, 350);
+        assert_eq!(metrics2.interaction_count, 3);
+        assert!((metrics2.total_cost - 3.50).abs() < 0.001);
+
+        // Incremental single-file updates work from the lean cache too
+        fs::remove_file(test_dir.join("file2.json")).unwrap();
+        let metrics3 = reader
+            .update_file(&test_dir.join("file2.json"))
+            .expect("Should update from cache");
+        assert_eq!(metrics3.total_input_tokens, 1500);
+        assert_eq!(metrics3.interaction_count, 2);
+
+        fs::remove_dir_all(test_dir).ok();
+    }
+
     // Test 8: Incremental parsing - reuse cache for unchanged files
     #[test]
     fn test_reader_incremental_parsing_unchanged_files() {
@@ -1074,4 +1536,309 @@ mod tests {
 
         fs::remove_dir_all(test_dir).ok();
     }
+
+    /// Reads all-time usage from a fresh reader, bypassing any cache
+    fn full_aggregation(dir: &Path) -> UsageMetrics {
+        let scanner = StorageScanner::with_path(dir.to_path_buf()).expect("Should create scanner");
+        OpenCodeUsageReader::with_scanner(scanner)
+            .get_usage()
+            .expect("Should read usage data")
+    }
+
+    fn assert_same_totals(actual: &UsageMetrics, expected: &UsageMetrics) {
+        assert_eq!(actual.total_input_tokens, expected.total_input_tokens);
+        assert_eq!(actual.total_output_tokens, expected.total_output_tokens);
+        assert_eq!(
+            actual.total_reasoning_tokens,
+            expected.total_reasoning_tokens
+        );
+        assert_eq!(
+            actual.total_cache_write_tokens,
+            expected.total_cache_write_tokens
+        );
+        assert_eq!(
+            actual.total_cache_read_tokens,
+            expected.total_cache_read_tokens
+        );
+        assert_eq!(actual.interaction_count, expected.interaction_count);
+        assert!((actual.total_cost - expected.total_cost).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_update_file_added() {
+        let test_dir = create_test_dir("update_file_added");
+        create_usage_file(&test_dir, "file1", 100, 50, 0.25);
+
+        let scanner = StorageScanner::with_path(test_dir.clone()).expect("Should create scanner");
+        let mut reader = OpenCodeUsageReader::with_scanner(scanner);
+        reader.get_usage().expect("Should read initial file");
+
+        create_usage_file(&test_dir, "file2", 200, 100, 0.50);
+        let metrics = reader
+            .update_file(&test_dir.join("file2.json"))
+            .expect("Should update cache");
+
+        assert_eq!(metrics.total_input_tokens, 300);
+        assert_same_totals(&metrics, &full_aggregation(&test_dir));
+        assert_eq!(reader.get_usage().unwrap().total_input_tokens, 300);
+
+        fs::remove_dir_all(test_dir).ok();
+    }
+
+    #[test]
+    fn test_update_file_modified_and_deleted() {
+        let test_dir = create_test_dir("update_file_modified");
+        create_usage_file(&test_dir, "file1", 100, 50, 0.25);
+        create_usage_file(&test_dir, "file2", 200, 100, 0.50);
+
+        let scanner = StorageScanner::with_path(test_dir.clone()).expect("Should create scanner");
+        let mut reader = OpenCodeUsageReader::with_scanner(scanner);
+        reader.get_usage().expect("Should read initial files");
+
+        // Re-parse of a changed file replaces its old contribution
+        create_usage_file(&test_dir, "file1", 500, 250, 1.00);
+        let metrics = reader
+            .update_file(&test_dir.join("file1.json"))
+            .expect("Should update cache");
+        assert_eq!(metrics.total_input_tokens, 700);
+        assert_eq!(metrics.interaction_count, 2);
+        assert_same_totals(&metrics, &full_aggregation(&test_dir));
+
+        // A deleted file is removed from the totals
+        fs::remove_file(test_dir.join("file2.json")).expect("Should delete file");
+        let metrics = reader
+            .update_file(&test_dir.join("file2.json"))
+            .expect("Should update cache");
+        assert_same_totals(&metrics, &full_aggregation(&test_dir));
+
+        fs::remove_dir_all(test_dir).ok();
+    }
+
+    #[test]
+    fn test_interaction_unit_session_counts_distinct_sessions() {
+        let test_dir = create_test_dir("interaction_unit_session");
+        create_usage_file(&test_dir, "file1", 100, 50, 0.25);
+        create_usage_file(&test_dir, "file2", 200, 100, 0.50);
+        create_usage_file(&test_dir, "file3", 300, 150, 0.75);
+
+        let scanner = StorageScanner::with_path(test_dir.clone()).expect("Should create scanner");
+        let mut reader = OpenCodeUsageReader::with_scanner(scanner);
+        assert_eq!(reader.get_usage().unwrap().interaction_count, 3);
+
+        // All three parts belong to the same session
+        reader.set_interaction_unit(InteractionUnit::Session);
+        let metrics = reader.get_usage().unwrap();
+        assert_eq!(metrics.interaction_count, 1);
+        assert_eq!(metrics.total_input_tokens, 600);
+
+        // A part from a second session adds one interaction, removing it takes it away
+        create_usage_file(&test_dir, "file4", 100, 50, 0.25);
+        let other_session = test_dir.join("file4.json");
+        let content = fs::read_to_string(&other_session).unwrap();
+        fs::write(&other_session, content.replace("ses_test", "ses_other")).unwrap();
+        assert_eq!(
+            reader
+                .update_file(&other_session)
+                .unwrap()
+                .interaction_count,
+            2
+        );
+        fs::remove_file(&other_session).unwrap();
+        assert_eq!(
+            reader
+                .update_file(&other_session)
+                .unwrap()
+                .interaction_count,
+            1
+        );
+
+        // Removing one of several parts of a session keeps the session
+        fs::remove_file(test_dir.join("file1.json")).unwrap();
+        let metrics = reader.update_file(&test_dir.join("file1.json")).unwrap();
+        assert_eq!(metrics.interaction_count, 1);
+        assert_eq!(metrics.total_input_tokens, 500);
+
+        fs::remove_dir_all(test_dir).ok();
+    }
+
+    #[test]
+    fn test_dump_cache_lists_cached_files() {
+        let test_dir = create_test_dir("dump_cache");
+        create_usage_file(&test_dir, "file2", 200, 100, 0.50);
+        create_usage_file(&test_dir, "file1", 100, 50, 0.25);
+
+        let scanner = StorageScanner::with_path(test_dir.clone()).expect("Should create scanner");
+        let mut reader = OpenCodeUsageReader::with_scanner(scanner);
+        assert!(reader.dump_cache().is_empty());
+
+        reader.get_usage().expect("Should read files");
+        let dump = reader.dump_cache();
+
+        let paths: Vec<_> = dump.iter().map(|(path, _)| path.clone()).collect();
+        assert_eq!(
+            paths,
+            vec![test_dir.join("file1.json"), test_dir.join("file2.json")]
+        );
+        for (path, modified) in &dump {
+            assert_eq!(*modified, fs::metadata(path).unwrap().modified().unwrap());
+        }
+
+        reader.invalidate_cache();
+        assert!(reader.dump_cache().is_empty());
+
+        fs::remove_dir_all(test_dir).ok();
+    }
+
+    #[test]
+    fn test_update_file_without_cache_reads_everything() {
+        let test_dir = create_test_dir("update_file_no_cache");
+        create_usage_file(&test_dir, "file1", 100, 50, 0.25);
+        create_usage_file(&test_dir, "file2", 200, 100, 0.50);
+
+        let scanner = StorageScanner::with_path(test_dir.clone()).expect("Should create scanner");
+        let mut reader = OpenCodeUsageReader::with_scanner(scanner);
+        let metrics = reader
+            .update_file(&test_dir.join("file2.json"))
+            .expect("Should fall back to a full read");
+
+        assert_same_totals(&metrics, &full_aggregation(&test_dir));
+
+        fs::remove_dir_all(test_dir).ok();
+    }
+
+    #[test]
+    fn test_parse_threads_do_not_change_results() {
+        let test_dir = create_test_dir("parse_threads");
+        for i in 0..40 {
+            create_usage_file(
+                &test_dir,
+                &format!("file{i}"),
+                100 + i,
+                50 + i,
+                0.01 * f64::from(u32::try_from(i).unwrap()),
+            );
+        }
+
+        let read_with = |threads: Option<usize>| {
+            let scanner =
+                StorageScanner::with_path(test_dir.clone()).expect("Should create scanner");
+            let mut reader = OpenCodeUsageReader::with_scanner(scanner);
+            reader
+                .set_parse_threads(threads)
+                .expect("Should create pool");
+            assert_eq!(reader.parse_threads(), threads);
+            reader.get_usage().expect("Should read usage")
+        };
+
+        let default = read_with(None);
+        for threads in [Some(1), Some(2), Some(4)] {
+            let bounded = read_with(threads);
+            assert_eq!(bounded.total_input_tokens, default.total_input_tokens);
+            assert_eq!(bounded.total_output_tokens, default.total_output_tokens);
+            assert_eq!(bounded.interaction_count, default.interaction_count);
+            assert_eq!(
+                bounded.total_cost.to_bits(),
+                default.total_cost.to_bits(),
+                "Cost must be summed in the same order"
+            );
+        }
+        assert_eq!(default.interaction_count, 40);
+
+        fs::remove_dir_all(test_dir).ok();
+    }
+
+    #[test]
+    fn test_today_start_with_reset_hour() {
+        let at = |day: u64, hour: u64| {
+            SystemTime::UNIX_EPOCH + Duration::from_secs(day * 86400 + hour * 3600)
+        };
+
+        // Midnight boundary by default
+        assert_eq!(
+            OpenCodeUsageReader::get_today_start(at(100, 2), 0),
+            at(100, 0)
+        );
+
+        // With a 4am reset, 02:00 still belongs to the previous day
+        assert_eq!(
+            OpenCodeUsageReader::get_today_start(at(100, 2), 4),
+            at(99, 4)
+        );
+
+        // From 04:00 on it is the new day
+        assert_eq!(
+            OpenCodeUsageReader::get_today_start(at(100, 4), 4),
+            at(100, 4)
+        );
+        assert_eq!(
+            OpenCodeUsageReader::get_today_start(at(100, 23), 4),
+            at(100, 4)
+        );
+    }
+
+    #[test]
+    fn test_set_day_reset_hour_validates_range() {
+        let test_dir = create_test_dir("day_reset_hour");
+        let mut reader = OpenCodeUsageReader::new_with_path(test_dir.to_str().unwrap())
+            .expect("Should create reader");
+
+        assert_eq!(reader.day_reset_hour(), 0);
+        reader.set_day_reset_hour(4).expect("4 is a valid hour");
+        assert_eq!(reader.day_reset_hour(), 4);
+        assert!(matches!(
+            reader.set_day_reset_hour(24),
+            Err(ReaderError::InvalidDayResetHour(24))
+        ));
+        assert_eq!(reader.day_reset_hour(), 4, "Invalid hour is not applied");
+
+        fs::remove_dir_all(test_dir).ok();
+    }
+
+    #[test]
+    fn test_usage_today_and_month_single_scan() {
+        let test_dir = create_test_dir("today_and_month");
+        let now = SystemTime::now();
+        let hours_ago = |hours: u64| now - Duration::from_secs(hours * 3600);
+
+        for (name, input, cost, modified) in [
+            ("today", 100, 0.25, hours_ago(1)),
+            ("earlier_this_month", 200, 0.50, hours_ago(24 * 10)),
+            ("last_month", 400, 1.00, hours_ago(24 * 40)),
+        ] {
+            create_usage_file(&test_dir, name, input, 0, cost);
+            filetime::set_file_mtime(
+                test_dir.join(format!("{name}.json")),
+                filetime::FileTime::from_system_time(modified),
+            )
+            .expect("Failed to set file time");
+        }
+
+        let scanner = StorageScanner::with_path(test_dir.clone()).expect("Should create scanner");
+        let mut reader = OpenCodeUsageReader::with_scanner(scanner);
+
+        let (today, month) = reader
+            .usage_today_and_month_since(hours_ago(2), hours_ago(24 * 20))
+            .expect("Month has data");
+        assert_eq!(today.total_input_tokens, 100);
+        assert_eq!(today.interaction_count, 1);
+        assert_eq!(month.total_input_tokens, 300);
+        assert_eq!(month.interaction_count, 2);
+        assert!((month.total_cost - 0.75).abs() < 0.001);
+
+        // An idle today is zero rather than an error
+        let (today, month) = reader
+            .usage_today_and_month_since(now, hours_ago(24 * 20))
+            .expect("Month has data");
+        assert_eq!(today.interaction_count, 0);
+        assert_eq!(today.total_input_tokens, 0);
+        assert_eq!(month.interaction_count, 2);
+
+        // An empty month is an error
+        assert!(matches!(
+            reader.usage_today_and_month_since(now, now),
+            Err(ReaderError::NoDataFound)
+        ));
+
+        fs::remove_dir_all(test_dir).ok();
+    }
 }
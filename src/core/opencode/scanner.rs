@@ -1,5 +1,6 @@
 use rayon::prelude::*;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::SystemTime;
 use thiserror::Error;
 use walkdir::WalkDir;
@@ -27,22 +28,64 @@ pub struct FileMetadata {
     pub modified: SystemTime,
 }
 
+/// Location of usage part files relative to `OpenCode`'s data directory
+const STORAGE_SUBDIR: &str = "opencode/storage/part";
+
+/// Detect `OpenCode`'s usage storage directory from the XDG data location
+///
+/// Checks `$XDG_DATA_HOME/opencode/storage/part`, then `~/.local/share/opencode/storage/part`,
+/// and returns the first one that exists.
+#[must_use]
+pub fn detect_opencode_storage() -> Option<PathBuf> {
+    let xdg_data_home = std::env::var_os("XDG_DATA_HOME").map(PathBuf::from);
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    detect_opencode_storage_in(xdg_data_home.as_deref(), home.as_deref())
+}
+
+/// Detect the storage directory given the XDG data home and home directories
+fn detect_opencode_storage_in(
+    xdg_data_home: Option<&Path>,
+    home: Option<&Path>,
+) -> Option<PathBuf> {
+    // Relative XDG paths are invalid per the spec and must be ignored
+    let xdg_candidate = xdg_data_home
+        .filter(|dir| dir.is_absolute())
+        .map(|dir| dir.join(STORAGE_SUBDIR));
+    let home_candidate = home.map(|dir| dir.join(".local/share").join(STORAGE_SUBDIR));
+
+    xdg_candidate
+        .into_iter()
+        .chain(home_candidate)
+        .find(|candidate| candidate.is_dir())
+}
+
 /// Scans `OpenCode` storage directory for usage part files
 #[derive(Debug)]
 pub struct StorageScanner {
     storage_path: PathBuf,
+    /// Entries skipped for lack of permission during the last scan
+    skipped_permission_denied: AtomicUsize,
 }
 
 impl StorageScanner {
     /// Create a new scanner with the default `OpenCode` storage path
     ///
+    /// The path is auto-detected with `detect_opencode_storage`, falling back to
+    /// `~/.local/share/opencode/storage/part`.
+    ///
     /// # Errors
     /// Returns an error if the HOME environment variable is not set or the storage path doesn't exist.
     pub fn new() -> Result<Self, ScannerError> {
+        if let Some(storage_path) = detect_opencode_storage() {
+            return Self::with_path(storage_path);
+        }
+
         let home = std::env::var("HOME")
             .map_err(|e| ScannerError::AccessError(format!("Cannot get HOME: {e}")))?;
 
-        let storage_path = PathBuf::from(home).join(".local/share/opencode/storage/part");
+        let storage_path = PathBuf::from(home)
+            .join(".local/share")
+            .join(STORAGE_SUBDIR);
 
         Self::with_path(storage_path)
     }
@@ -56,7 +99,47 @@ impl StorageScanner {
             return Err(ScannerError::DirectoryNotFound(storage_path));
         }
 
-        Ok(Self { storage_path })
+        Ok(Self {
+            storage_path,
+            skipped_permission_denied: AtomicUsize::new(0),
+        })
+    }
+
+    /// Number of entries skipped because of missing permissions during the last scan
+    #[must_use]
+    pub fn skipped_permission_denied(&self) -> usize {
+        self.skipped_permission_denied.load(Ordering::Relaxed)
+    }
+
+    /// Walk the storage directory, skipping entries that cannot be read
+    ///
+    /// An unreadable subdirectory is logged and skipped so its siblings are still scanned.
+    fn walk_entries(&self) -> Vec<walkdir::DirEntry> {
+        let mut skipped = 0;
+        let entries = WalkDir::new(&self.storage_path)
+            .follow_links(false)
+            .into_iter()
+            .filter_map(|entry| match entry {
+                Ok(entry) => Some(entry),
+                Err(err) => {
+                    let path = err.path().unwrap_or(self.storage_path.as_path()).display();
+                    if err
+                        .io_error()
+                        .is_some_and(|e| e.kind() == std::io::ErrorKind::PermissionDenied)
+                    {
+                        skipped += 1;
+                        eprintln!("[Scanner] Warning: permission denied, skipping {path}");
+                    } else {
+                        eprintln!("[Scanner] Warning: skipping {path}: {err}");
+                    }
+                    None
+                }
+            })
+            .collect();
+
+        self.skipped_permission_denied
+            .store(skipped, Ordering::Relaxed);
+        entries
     }
 
     /// Scan the storage directory and return paths to all JSON files
@@ -64,10 +147,9 @@ impl StorageScanner {
     /// # Errors
     /// Returns an error if the directory cannot be read or accessed.
     pub fn scan(&self) -> Result<Vec<PathBuf>, ScannerError> {
-        let json_files = WalkDir::new(&self.storage_path)
-            .follow_links(false)
+        let json_files = self
+            .walk_entries()
             .into_iter()
-            .filter_map(std::result::Result::ok)
             .filter_map(|entry| {
                 let path = entry.path();
                 if path.is_file() && path.extension().is_some_and(|ext| ext == "json") {
@@ -93,11 +175,7 @@ impl StorageScanner {
     /// Returns an error if the directory cannot be read or accessed.
     pub fn scan_with_metadata(&self) -> Result<Vec<FileMetadata>, ScannerError> {
         // First, collect all directory entries (fast I/O operation)
-        let entries: Vec<_> = WalkDir::new(&self.storage_path)
-            .follow_links(false)
-            .into_iter()
-            .filter_map(std::result::Result::ok)
-            .collect();
+        let entries = self.walk_entries();
 
         // Then, process entries in parallel using rayon
         let metadata: Vec<FileMetadata> = entries
@@ -131,6 +209,22 @@ impl StorageScanner {
         Ok(metadata)
     }
 
+    /// Scan the storage directory and return file metadata ordered newest first
+    ///
+    /// Files with the same modification time are ordered by path for a stable result.
+    ///
+    /// # Errors
+    /// Returns an error if the directory cannot be read or accessed.
+    pub fn scan_sorted_desc(&self) -> Result<Vec<FileMetadata>, ScannerError> {
+        let mut metadata = self.scan_with_metadata()?;
+        metadata.sort_by(|a, b| {
+            b.modified
+                .cmp(&a.modified)
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        Ok(metadata)
+    }
+
     /// Scan the storage directory and return only files modified after the cutoff time
     /// This is optimized to skip old files during the walk, reducing I/O overhead
     ///
@@ -141,11 +235,7 @@ impl StorageScanner {
         cutoff: SystemTime,
     ) -> Result<Vec<FileMetadata>, ScannerError> {
         // First, collect all directory entries (fast I/O operation)
-        let entries: Vec<_> = WalkDir::new(&self.storage_path)
-            .follow_links(false)
-            .into_iter()
-            .filter_map(std::result::Result::ok)
-            .collect();
+        let entries = self.walk_entries();
 
         // Then, process entries in parallel using rayon, filtering by modification time
         let metadata: Vec<FileMetadata> = entries
@@ -439,4 +529,123 @@ mod tests {
 
         fs::remove_dir_all(test_dir).ok();
     }
+
+    // Test 10: scan_sorted_desc returns newest files first
+    #[test]
+    fn test_scanner_sorted_desc() {
+        use std::time::Duration;
+
+        let test_dir = create_test_dir("sorted_desc");
+
+        let now = SystemTime::now();
+        for (name, hours_ago) in [("b.json", 3), ("a.json", 1), ("c.json", 2), ("d.json", 5)] {
+            create_test_file(&test_dir, name, r#"{"test": 1}"#);
+            filetime::set_file_mtime(
+                test_dir.join(name),
+                filetime::FileTime::from_system_time(now - Duration::from_secs(hours_ago * 3600)),
+            )
+            .expect("Failed to set file time");
+        }
+        create_test_file(&test_dir, "ignored.txt", "not json");
+
+        let scanner = StorageScanner::with_path(test_dir.clone()).expect("Should create scanner");
+        let metadata = scanner
+            .scan_sorted_desc()
+            .expect("Should scan successfully");
+
+        let names: Vec<_> = metadata
+            .iter()
+            .map(|m| m.path.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["a.json", "c.json", "b.json", "d.json"]);
+
+        fs::remove_dir_all(test_dir).ok();
+    }
+
+    // Test 11: an unreadable subdirectory is skipped without aborting the scan
+    #[cfg(unix)]
+    #[test]
+    fn test_scanner_skips_unreadable_subdirectory() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let test_dir = create_test_dir("permission_denied");
+        let readable = test_dir.join("msg_readable");
+        let locked = test_dir.join("msg_locked");
+        fs::create_dir_all(&readable).unwrap();
+        fs::create_dir_all(&locked).unwrap();
+        create_test_file(&readable, "prt_1.json", r#"{"test": 1}"#);
+        create_test_file(&locked, "prt_2.json", r#"{"test": 2}"#);
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+
+        // Privileged users (e.g. root in CI containers) can read the directory anyway
+        let enforced = fs::read_dir(&locked).is_err();
+
+        let scanner = StorageScanner::with_path(test_dir.clone()).expect("Should create scanner");
+        let files = scanner
+            .scan()
+            .expect("Scan should not fail on unreadable entries");
+        let metadata = scanner
+            .scan_with_metadata()
+            .expect("Scan should not fail on unreadable entries");
+
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert!(files.contains(&readable.join("prt_1.json")));
+        assert!(metadata
+            .iter()
+            .any(|m| m.path == readable.join("prt_1.json")));
+        if enforced {
+            assert_eq!(files.len(), 1);
+            assert_eq!(scanner.skipped_permission_denied(), 1);
+        } else {
+            assert_eq!(scanner.skipped_permission_denied(), 0);
+        }
+
+        fs::remove_dir_all(test_dir).ok();
+    }
+
+    // Test 12: detect storage from a simulated XDG data layout
+    #[test]
+    fn test_detect_opencode_storage_xdg_layout() {
+        let test_dir = create_test_dir("detect_xdg");
+        let xdg_data = test_dir.join("xdg-data");
+        let home = test_dir.join("home");
+        fs::create_dir_all(xdg_data.join("opencode/storage/part")).unwrap();
+        fs::create_dir_all(home.join(".local/share/opencode/storage/part")).unwrap();
+
+        assert_eq!(
+            detect_opencode_storage_in(Some(&xdg_data), Some(&home)),
+            Some(xdg_data.join("opencode/storage/part")),
+            "XDG_DATA_HOME takes precedence"
+        );
+        assert_eq!(
+            detect_opencode_storage_in(None, Some(&home)),
+            Some(home.join(".local/share/opencode/storage/part"))
+        );
+        assert_eq!(
+            detect_opencode_storage_in(Some(Path::new("relative/data")), Some(&home)),
+            Some(home.join(".local/share/opencode/storage/part")),
+            "Relative XDG paths are ignored"
+        );
+
+        fs::remove_dir_all(test_dir).ok();
+    }
+
+    // Test 13: no detection when OpenCode is not installed
+    #[test]
+    fn test_detect_opencode_storage_absent() {
+        let test_dir = create_test_dir("detect_absent");
+        let xdg_data = test_dir.join("xdg-data");
+        let home = test_dir.join("home");
+        fs::create_dir_all(&xdg_data).unwrap();
+        fs::create_dir_all(&home).unwrap();
+
+        assert_eq!(
+            detect_opencode_storage_in(Some(&xdg_data), Some(&home)),
+            None
+        );
+        assert_eq!(detect_opencode_storage_in(None, None), None);
+
+        fs::remove_dir_all(test_dir).ok();
+    }
 }
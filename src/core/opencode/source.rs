@@ -0,0 +1,274 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Abstraction over where usage metrics come from.
+//!
+//! The applet talks to a `UsageSource` instead of a concrete reader, so the
+//! message flow can be exercised with injected data rather than real files.
+
+use crate::core::opencode::{OpenCodeUsageReader, ReaderError, UsageAggregator, UsageMetrics};
+use std::path::PathBuf;
+
+/// A source of aggregated `OpenCode` usage metrics
+pub trait UsageSource: Send {
+    /// Get all-time usage metrics
+    ///
+    /// # Errors
+    /// Returns an error if no data is found or if reading fails.
+    fn get_usage(&mut self) -> Result<UsageMetrics, ReaderError>;
+
+    /// Get usage metrics for today only
+    ///
+    /// # Errors
+    /// Returns an error if no data is found for today or if reading fails.
+    fn get_usage_today(&mut self) -> Result<UsageMetrics, ReaderError>;
+
+    /// Get usage metrics for this month only
+    ///
+    /// # Errors
+    /// Returns an error if no data is found for this month or if reading fails.
+    fn get_usage_month(&mut self) -> Result<UsageMetrics, ReaderError>;
+
+    /// Get usage metrics for last month only
+    ///
+    /// # Errors
+    /// Returns an error if no data is found for last month or if reading fails.
+    fn get_usage_last_month(&mut self) -> Result<UsageMetrics, ReaderError>;
+
+    /// Get usage metrics for today and this month together
+    ///
+    /// Today's metrics are all zero when there was no usage today.
+    ///
+    /// # Errors
+    /// Returns an error if no data is found for this month or if reading fails.
+    fn get_usage_today_and_month(&mut self) -> Result<(UsageMetrics, UsageMetrics), ReaderError> {
+        let month = self.get_usage_month()?;
+        let today = match self.get_usage_today() {
+            Ok(today) => today,
+            Err(ReaderError::NoDataFound) => UsageAggregator::new().finalize(),
+            Err(e) => return Err(e),
+        };
+        Ok((today, month))
+    }
+
+    /// Get the storage path the source reads from
+    fn storage_path(&self) -> &PathBuf;
+
+    /// Number of storage entries skipped for lack of permission during the last read
+    fn skipped_permission_denied(&self) -> usize {
+        0
+    }
+
+    /// Create an independent source over the same data, for use in background tasks
+    ///
+    /// # Errors
+    /// Returns an error if the new source cannot be initialized.
+    fn fork(&self) -> Result<Box<dyn UsageSource>, ReaderError>;
+}
+
+impl UsageSource for OpenCodeUsageReader {
+    fn get_usage(&mut self) -> Result<UsageMetrics, ReaderError> {
+        OpenCodeUsageReader::get_usage(self)
+    }
+
+    fn get_usage_today(&mut self) -> Result<UsageMetrics, ReaderError> {
+        OpenCodeUsageReader::get_usage_today(self)
+    }
+
+    fn get_usage_month(&mut self) -> Result<UsageMetrics, ReaderError> {
+        OpenCodeUsageReader::get_usage_month(self)
+    }
+
+    fn get_usage_last_month(&mut self) -> Result<UsageMetrics, ReaderError> {
+        OpenCodeUsageReader::get_usage_last_month(self)
+    }
+
+    fn get_usage_today_and_month(&mut self) -> Result<(UsageMetrics, UsageMetrics), ReaderError> {
+        OpenCodeUsageReader::get_usage_today_and_month(self)
+    }
+
+    fn storage_path(&self) -> &PathBuf {
+        OpenCodeUsageReader::storage_path(self)
+    }
+
+    fn skipped_permission_denied(&self) -> usize {
+        OpenCodeUsageReader::skipped_permission_denied(self)
+    }
+
+    fn fork(&self) -> Result<Box<dyn UsageSource>, ReaderError> {
+        let mut reader =
+            OpenCodeUsageReader::new_with_path(self.storage_path().to_str().unwrap_or(""))?;
+        reader.set_parse_threads(self.parse_threads())?;
+        reader.set_day_reset_hour(self.day_reset_hour())?;
+        reader.set_lean_cache(self.lean_cache());
+        reader.set_interaction_unit(self.interaction_unit());
+        Ok(Box::new(reader))
+    }
+}
+
+/// In-memory usage source returning fixed metrics (useful for testing)
+///
+/// Periods left as `None` report `ReaderError::NoDataFound`.
+#[derive(Debug, Clone, Default)]
+pub struct MockUsageSource {
+    pub all_time: Option<UsageMetrics>,
+    pub today: Option<UsageMetrics>,
+    pub month: Option<UsageMetrics>,
+    pub last_month: Option<UsageMetrics>,
+    pub storage_path: PathBuf,
+    pub skipped_permission_denied: usize,
+}
+
+impl MockUsageSource {
+    /// Create a mock source returning the same metrics for every period
+    #[must_use]
+    pub fn new(metrics: UsageMetrics) -> Self {
+        Self {
+            all_time: Some(metrics.clone()),
+            today: Some(metrics.clone()),
+            month: Some(metrics.clone()),
+            last_month: Some(metrics),
+            storage_path: PathBuf::new(),
+            skipped_permission_denied: 0,
+        }
+    }
+
+    fn period(metrics: Option<&UsageMetrics>) -> Result<UsageMetrics, ReaderError> {
+        metrics.cloned().ok_or(ReaderError::NoDataFound)
+    }
+}
+
+impl UsageSource for MockUsageSource {
+    fn get_usage(&mut self) -> Result<UsageMetrics, ReaderError> {
+        Self::period(self.all_time.as_ref())
+    }
+
+    fn get_usage_today(&mut self) -> Result<UsageMetrics, ReaderError> {
+        Self::period(self.today.as_ref())
+    }
+
+    fn get_usage_month(&mut self) -> Result<UsageMetrics, ReaderError> {
+        Self::period(self.month.as_ref())
+    }
+
+    fn get_usage_last_month(&mut self) -> Result<UsageMetrics, ReaderError> {
+        Self::period(self.last_month.as_ref())
+    }
+
+    fn storage_path(&self) -> &PathBuf {
+        &self.storage_path
+    }
+
+    fn skipped_permission_denied(&self) -> usize {
+        self.skipped_permission_denied
+    }
+
+    fn fork(&self) -> Result<Box<dyn UsageSource>, ReaderError> {
+        Ok(Box::new(self.clone()))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)] // Tests use exact float comparisons for simplicity
+mod tests {
+    use super::*;
+    use std::time::SystemTime;
+
+    fn create_metrics(cost: f64) -> UsageMetrics {
+        UsageMetrics {
+            total_input_tokens: 1000,
+            total_output_tokens: 500,
+            total_reasoning_tokens: 0,
+            total_cache_write_tokens: 0,
+            total_cache_read_tokens: 0,
+            total_cost: cost,
+            total_cache_cost: 0.0,
+            interaction_count: 3,
+            timestamp: SystemTime::now(),
+        }
+    }
+
+    #[test]
+    fn test_mock_source_returns_configured_periods() {
+        let mut source = MockUsageSource {
+            today: Some(create_metrics(1.0)),
+            month: Some(create_metrics(10.0)),
+            ..Default::default()
+        };
+
+        assert_eq!(source.get_usage_today().unwrap().total_cost, 1.0);
+        assert_eq!(source.get_usage_month().unwrap().total_cost, 10.0);
+        assert!(matches!(source.get_usage(), Err(ReaderError::NoDataFound)));
+        assert!(matches!(
+            source.get_usage_last_month(),
+            Err(ReaderError::NoDataFound)
+        ));
+    }
+
+    #[test]
+    fn test_mock_source_today_and_month_defaults_idle_today_to_zero() {
+        let mut source = MockUsageSource {
+            month: Some(create_metrics(10.0)),
+            ..Default::default()
+        };
+
+        let (today, month) = source.get_usage_today_and_month().unwrap();
+        assert_eq!(today.interaction_count, 0);
+        assert_eq!(today.total_cost, 0.0);
+        assert_eq!(month.total_cost, 10.0);
+
+        source.month = None;
+        assert!(matches!(
+            source.get_usage_today_and_month(),
+            Err(ReaderError::NoDataFound)
+        ));
+    }
+
+    #[test]
+    fn test_mock_source_fork_keeps_data() {
+        let source = MockUsageSource::new(create_metrics(2.5));
+        let mut forked = source.fork().expect("Mock fork should succeed");
+
+        assert_eq!(forked.get_usage().unwrap().total_cost, 2.5);
+        assert_eq!(forked.get_usage_today().unwrap().total_cost, 2.5);
+    }
+
+    #[test]
+    fn test_reader_implements_usage_source() {
+        let test_dir = std::env::temp_dir().join("opencode_source_test_reader");
+        let _ = std::fs::remove_dir_all(&test_dir);
+        std::fs::create_dir_all(&test_dir).expect("Failed to create test directory");
+
+        let reader = OpenCodeUsageReader::new_with_path(test_dir.to_str().unwrap())
+            .expect("Should create reader");
+        let source: Box<dyn UsageSource> = Box::new(reader);
+        let forked = source.fork().expect("Reader fork should succeed");
+
+        assert_eq!(forked.storage_path(), &test_dir);
+
+        std::fs::remove_dir_all(test_dir).ok();
+    }
+
+    #[test]
+    fn test_reader_fork_keeps_interaction_unit() {
+        let test_dir = std::env::temp_dir().join("opencode_source_test_fork_unit");
+        let _ = std::fs::remove_dir_all(&test_dir);
+        std::fs::create_dir_all(&test_dir).expect("Failed to create test directory");
+        for name in ["prt_a", "prt_b"] {
+            let part = format!(
+                r#"{{"id": "{name}", "messageID": "msg_test", "sessionID": "ses_test", "type": "step-finish",
+                    "tokens": {{"input": 100, "output": 50, "reasoning": 0, "cache": {{"write": 0, "read": 0}}}},
+                    "cost": 0.1}}"#
+            );
+            std::fs::write(test_dir.join(format!("{name}.json")), part).unwrap();
+        }
+
+        let mut reader = OpenCodeUsageReader::new_with_path(test_dir.to_str().unwrap())
+            .expect("Should create reader");
+        reader.set_interaction_unit(crate::core::opencode::InteractionUnit::Session);
+        let mut forked = reader.fork().expect("Reader fork should succeed");
+
+        assert_eq!(forked.get_usage().unwrap().interaction_count, 1);
+
+        std::fs::remove_dir_all(test_dir).ok();
+    }
+}
@@ -0,0 +1,293 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Optional local socket exposing the current usage metrics as JSON.
+//!
+//! Status bars such as waybar, polybar or eww can read the same figures the
+//! applet shows: every connection receives one JSON document and is closed.
+
+use crate::core::opencode::UsageMetrics;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use thiserror::Error;
+
+/// File name of the socket inside the runtime directory
+pub const SOCKET_FILE_NAME: &str = "cosmic-applet-opencode-usage.sock";
+
+/// Error type for the metrics socket
+#[derive(Debug, Error)]
+pub enum SocketError {
+    #[error("Failed to bind metrics socket at {path}: {source}")]
+    Bind {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+/// Metrics as written to socket clients
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricsJson {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub reasoning_tokens: u64,
+    pub cache_write_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub total_cost: f64,
+    pub interaction_count: usize,
+    /// When the metrics were computed, in seconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+impl From<&UsageMetrics> for MetricsJson {
+    fn from(metrics: &UsageMetrics) -> Self {
+        Self {
+            input_tokens: metrics.total_input_tokens,
+            output_tokens: metrics.total_output_tokens,
+            reasoning_tokens: metrics.total_reasoning_tokens,
+            cache_write_tokens: metrics.total_cache_write_tokens,
+            cache_read_tokens: metrics.total_cache_read_tokens,
+            total_cost: metrics.total_cost,
+            interaction_count: metrics.interaction_count,
+            timestamp: metrics
+                .timestamp
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs()),
+        }
+    }
+}
+
+/// Default socket path: the user's runtime directory, or the temp directory without one
+///
+/// Each data namespace gets its own socket, so namespaced instances don't collide.
+#[must_use]
+pub fn default_socket_path(namespace: Option<&str>) -> PathBuf {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map_or_else(std::env::temp_dir, PathBuf::from)
+        .join(socket_file_name(namespace))
+}
+
+/// File name of the socket for a data namespace
+fn socket_file_name(namespace: Option<&str>) -> String {
+    match namespace {
+        Some(namespace) => format!("cosmic-applet-opencode-usage.{namespace}.sock"),
+        None => SOCKET_FILE_NAME.to_string(),
+    }
+}
+
+/// A running metrics socket server
+///
+/// Serves connections on a background thread until dropped, which also removes
+/// the socket file.
+pub struct MetricsSocket {
+    path: PathBuf,
+    latest: Arc<Mutex<Option<String>>>,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl MetricsSocket {
+    /// Start serving metrics on the socket at `path`
+    ///
+    /// A leftover socket from a previous run is replaced. Clients connecting
+    /// before any metrics are published receive `null`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SocketError::Bind` if the socket cannot be created, or if `path` is
+    /// something other than a stale socket (a regular file, or a socket that is
+    /// still being served).
+    pub fn start(path: &Path) -> Result<Self, SocketError> {
+        let bind_error = |source| SocketError::Bind {
+            path: path.to_path_buf(),
+            source,
+        };
+
+        remove_stale_socket(path).map_err(bind_error)?;
+        let listener = UnixListener::bind(path).map_err(bind_error)?;
+
+        let latest = Arc::new(Mutex::new(None::<String>));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let thread_latest = Arc::clone(&latest);
+        let thread_shutdown = Arc::clone(&shutdown);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                if thread_shutdown.load(Ordering::Relaxed) {
+                    break;
+                }
+                let Ok(mut stream) = stream else {
+                    continue;
+                };
+                let body = thread_latest
+                    .lock()
+                    .ok()
+                    .and_then(|latest| latest.clone())
+                    .unwrap_or_else(|| "null".to_string());
+                if let Err(e) = stream.write_all(body.as_bytes()) {
+                    eprintln!("[MetricsSocket] Failed to write to client: {e}");
+                }
+            }
+        });
+
+        eprintln!("[MetricsSocket] Serving metrics on {}", path.display());
+        Ok(Self {
+            path: path.to_path_buf(),
+            latest,
+            shutdown,
+        })
+    }
+
+    /// Path of the socket file
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Replace the metrics sent to new connections
+    pub fn publish(&self, metrics: &UsageMetrics) {
+        match serde_json::to_string(&MetricsJson::from(metrics)) {
+            Ok(json) => {
+                if let Ok(mut latest) = self.latest.lock() {
+                    *latest = Some(json);
+                }
+            }
+            Err(e) => eprintln!("[MetricsSocket] Failed to serialize metrics: {e}"),
+        }
+    }
+}
+
+/// Removes a leftover socket at `path` that nobody is serving anymore
+///
+/// Anything else at `path` is left alone and reported as an error.
+fn remove_stale_socket(path: &Path) -> std::io::Result<()> {
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    if !metadata.file_type().is_socket() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            "path exists and is not a socket",
+        ));
+    }
+    if UnixStream::connect(path).is_ok() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AddrInUse,
+            "socket is already being served",
+        ));
+    }
+    std::fs::remove_file(path)
+}
+
+impl Drop for MetricsSocket {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+        // Wake the accept loop so the thread sees the shutdown flag
+        let _ = UnixStream::connect(&self.path);
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)] // Tests use exact float comparisons for simplicity
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use tempfile::TempDir;
+
+    fn read_socket(path: &Path) -> String {
+        let mut stream = UnixStream::connect(path).expect("Should connect to socket");
+        let mut response = String::new();
+        stream
+            .read_to_string(&mut response)
+            .expect("Should read response");
+        response
+    }
+
+    #[test]
+    fn test_socket_serves_published_metrics() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(SOCKET_FILE_NAME);
+        let socket = MetricsSocket::start(&path).expect("Should start socket");
+
+        assert_eq!(read_socket(&path), "null");
+
+        let metrics = UsageMetrics {
+            total_input_tokens: 1000,
+            total_output_tokens: 500,
+            total_reasoning_tokens: 100,
+            total_cache_write_tokens: 20,
+            total_cache_read_tokens: 300,
+            total_cost: 1.25,
+            total_cache_cost: 0.0,
+            interaction_count: 4,
+            timestamp: SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000),
+        };
+        socket.publish(&metrics);
+
+        let json: MetricsJson =
+            serde_json::from_str(&read_socket(&path)).expect("Should parse JSON response");
+        assert_eq!(json, MetricsJson::from(&metrics));
+        assert_eq!(json.input_tokens, 1000);
+        assert_eq!(json.total_cost, 1.25);
+        assert_eq!(json.timestamp, 1_700_000_000);
+
+        drop(socket);
+        assert!(!path.exists(), "Dropping the socket removes its file");
+    }
+
+    #[test]
+    fn test_socket_refuses_regular_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(SOCKET_FILE_NAME);
+        std::fs::write(&path, "not a socket").unwrap();
+
+        assert!(matches!(
+            MetricsSocket::start(&path),
+            Err(SocketError::Bind { .. })
+        ));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "not a socket");
+    }
+
+    #[test]
+    fn test_socket_replaces_stale_socket() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(SOCKET_FILE_NAME);
+        // Dropping a listener leaves its socket file behind, like a crashed run
+        drop(UnixListener::bind(&path).unwrap());
+
+        let _socket = MetricsSocket::start(&path).expect("Should replace stale socket");
+        assert_eq!(read_socket(&path), "null");
+    }
+
+    #[test]
+    fn test_socket_refuses_live_socket() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(SOCKET_FILE_NAME);
+        let _socket = MetricsSocket::start(&path).expect("Should start socket");
+
+        assert!(matches!(
+            MetricsSocket::start(&path),
+            Err(SocketError::Bind { .. })
+        ));
+        assert_eq!(read_socket(&path), "null");
+    }
+
+    #[test]
+    fn test_socket_file_name_per_namespace() {
+        assert_eq!(socket_file_name(None), SOCKET_FILE_NAME);
+        assert_eq!(
+            socket_file_name(Some("work")),
+            "cosmic-applet-opencode-usage.work.sock"
+        );
+        assert_ne!(
+            default_socket_path(Some("work")),
+            default_socket_path(Some("personal"))
+        );
+    }
+}
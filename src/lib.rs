@@ -5,6 +5,7 @@
 //! This library provides the core functionality for tracking `OpenCode` usage.
 
 pub mod app;
+pub mod cli;
 pub mod core;
 pub mod ui;
 pub mod viewer;
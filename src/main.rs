@@ -1,14 +1,24 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
 use cosmic_applet_opencode_usage::app::OpenCodeMonitorApplet;
+use cosmic_applet_opencode_usage::cli::{self, CliMode};
 use cosmic_applet_opencode_usage::core::config::AppConfig;
 
 fn main() -> cosmic::iced::Result {
+    let mode = cli::parse_args(std::env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("Error: {err}");
+        std::process::exit(2);
+    });
+
     // Load config from COSMIC config system, fall back to defaults if not found
     let config = AppConfig::load().unwrap_or_else(|err| {
         eprintln!("Warning: Failed to load config ({err}), using defaults");
         AppConfig::default()
     });
 
+    if mode != CliMode::Applet {
+        std::process::exit(cli::run(mode, &config));
+    }
+
     cosmic::applet::run::<OpenCodeMonitorApplet>(config)
 }
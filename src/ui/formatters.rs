@@ -2,9 +2,10 @@
 
 //! UI formatting utilities
 
-use crate::core::config::PanelMetric;
-use crate::core::opencode::UsageMetrics;
+use crate::core::config::{AppConfig, PanelMetric, PrimaryMetric};
+use crate::core::opencode::{UsageDelta, UsageMetrics};
 use chrono::{DateTime, Utc};
+use std::borrow::Cow;
 
 /// Format a number with locale-aware thousand separators
 /// Uses the system locale to determine the appropriate separator
@@ -19,6 +20,22 @@ pub fn format_cost(cost: f64) -> String {
     format!("${cost:.2}")
 }
 
+/// Format cost with four decimals, for users who want the exact figure
+#[must_use]
+pub fn format_cost_raw(cost: f64) -> String {
+    format!("${cost:.4}")
+}
+
+/// Format cost for the popup, rounded to cents unless raw cost display is on
+#[must_use]
+pub fn format_popup_cost(cost: f64, raw: bool) -> String {
+    if raw {
+        format_cost_raw(cost)
+    } else {
+        format_cost(cost)
+    }
+}
+
 /// Format cost compactly for panel display
 #[must_use]
 pub fn format_cost_compact(cost: f64) -> String {
@@ -31,33 +48,72 @@ pub fn format_cost_compact(cost: f64) -> String {
     }
 }
 
+/// Format the cache read/write ratio (e.g., "3.2x"), or "n/a" without cache writes
+#[must_use]
+pub fn format_cache_ratio(usage: &UsageMetrics) -> String {
+    crate::core::opencode::cache_read_write_ratio(usage)
+        .map_or_else(|| "n/a".to_string(), |ratio| format!("{ratio:.1}x"))
+}
+
+/// Format the change since the previous refresh (e.g., "+$0.12, +3 interactions since last check")
+///
+/// Returns `None` when nothing changed.
+#[must_use]
+pub fn format_refresh_delta(delta: &UsageDelta) -> Option<String> {
+    if delta.interactions == 0 && delta.cost.abs() < 0.005 {
+        return None;
+    }
+
+    let sign = if delta.cost < 0.0 { "-" } else { "+" };
+    Some(format!(
+        "{sign}${:.2}, {:+} interactions since last check",
+        delta.cost.abs(),
+        delta.interactions
+    ))
+}
+
 /// Format tokens compactly for panel display (e.g., "1.2k", "15M")
 #[must_use]
 pub fn format_tokens_compact(tokens: u64) -> String {
-    if tokens < 1_000 {
-        tokens.to_string()
+    format_tokens_compact_with_decimals(tokens, 1)
+}
+
+/// Format tokens compactly with up to `decimals` decimals under 10k/10M (e.g., "1.23k")
+///
+/// Values of 10 units or more, and whole values, are shown without decimals.
+/// `decimals` is capped at 2.
+#[must_use]
+pub fn format_tokens_compact_with_decimals(tokens: u64, decimals: u8) -> String {
+    format_tokens_compact_with_threshold(tokens, decimals, DEFAULT_COMPACT_THRESHOLD)
+}
+
+/// Smallest token count shown in compact form by default
+pub const DEFAULT_COMPACT_THRESHOLD: u64 = 1_000;
+
+/// Format tokens compactly, keeping values below `threshold` as exact counts
+///
+/// A threshold of 10000 shows "9999" rather than "10k". Thresholds under 1000 are
+/// treated as 1000, since smaller values have no compact form.
+#[must_use]
+pub fn format_tokens_compact_with_threshold(tokens: u64, decimals: u8, threshold: u64) -> String {
+    let (unit, suffix) = if tokens < threshold.max(DEFAULT_COMPACT_THRESHOLD) {
+        return tokens.to_string();
     } else if tokens < 1_000_000 {
-        #[allow(clippy::cast_precision_loss)]
-        let k = tokens as f64 / 1_000.0;
-        let rounded = k.round();
-        if k >= 10.0 || (k - rounded).abs() < 0.01 {
-            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-            let rounded_u64 = rounded as u64;
-            format!("{rounded_u64}k")
-        } else {
-            format!("{k:.1}k")
-        }
+        (1_000.0, "k")
     } else {
-        #[allow(clippy::cast_precision_loss)]
-        let m = tokens as f64 / 1_000_000.0;
-        let rounded = m.round();
-        if m >= 10.0 || (m - rounded).abs() < 0.01 {
-            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-            let rounded_u64 = rounded as u64;
-            format!("{rounded_u64}M")
-        } else {
-            format!("{m:.1}M")
-        }
+        (1_000_000.0, "M")
+    };
+
+    #[allow(clippy::cast_precision_loss)]
+    let value = tokens as f64 / unit;
+    let rounded = value.round();
+    if decimals == 0 || value >= 10.0 || (value - rounded).abs() < 0.01 {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let rounded_u64 = rounded as u64;
+        format!("{rounded_u64}{suffix}")
+    } else {
+        let precision = usize::from(decimals.min(2));
+        format!("{value:.precision$}{suffix}")
     }
 }
 
@@ -68,6 +124,32 @@ pub fn format_tokens_raw(tokens: u64) -> String {
     format_number_locale(tokens)
 }
 
+/// Format tokens as raw numbers, overriding the locale's thousand separator when one is given
+///
+/// With `None` this is the same as `format_tokens_raw`. With a separator, the digits
+/// are regrouped in threes so the output no longer depends on the system locale.
+#[must_use]
+pub fn format_tokens_raw_with_separator(tokens: u64, separator: Option<char>) -> String {
+    let formatted = format_tokens_raw(tokens);
+    match separator {
+        Some(separator) => apply_thousands_separator(&formatted, separator),
+        None => formatted,
+    }
+}
+
+/// Regroup the digits of a formatted number in threes using the given separator
+fn apply_thousands_separator(formatted: &str, separator: char) -> String {
+    let digits: Vec<char> = formatted.chars().filter(char::is_ascii_digit).collect();
+    let mut result = String::with_capacity(digits.len() * 2);
+    for (i, digit) in digits.iter().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            result.push(separator);
+        }
+        result.push(*digit);
+    }
+    result
+}
+
 /// Format a number with locale-aware thousand separators
 /// Uses the system locale to determine the appropriate separator
 #[must_use]
@@ -81,12 +163,63 @@ pub fn format_number_locale(n: u64) -> String {
     }
 }
 
+/// Prepare usage metrics for display, optionally folding cache tokens into input/output
+///
+/// When `fold_cache_into_io` is set, cache-read tokens are added to input and cache-write
+/// tokens to output. This only affects what is displayed: the stored metrics are never
+/// modified and the cache fields keep their raw values.
+#[must_use]
+pub fn usage_for_display(usage: &UsageMetrics, fold_cache_into_io: bool) -> Cow<'_, UsageMetrics> {
+    if !fold_cache_into_io {
+        return Cow::Borrowed(usage);
+    }
+
+    let mut folded = usage.clone();
+    folded.total_input_tokens = usage
+        .total_input_tokens
+        .saturating_add(usage.total_cache_read_tokens);
+    folded.total_output_tokens = usage
+        .total_output_tokens
+        .saturating_add(usage.total_cache_write_tokens);
+    Cow::Owned(folded)
+}
+
+/// Total tokens shown in compact displays: input + output, plus cache reads/writes if requested
+#[must_use]
+pub fn display_total_tokens(usage: &UsageMetrics, include_cache: bool) -> u64 {
+    let total = usage
+        .total_input_tokens
+        .saturating_add(usage.total_output_tokens);
+    if include_cache {
+        total
+            .saturating_add(usage.total_cache_read_tokens)
+            .saturating_add(usage.total_cache_write_tokens)
+    } else {
+        total
+    }
+}
+
 /// Format panel display ultra-compact for narrow panels (e.g., "15k/$1.2")
 #[must_use]
 pub fn format_panel_display(usage: &UsageMetrics) -> String {
+    format_panel_display_with_options(usage, PanelFormatOptions::default())
+}
+
+/// Format panel display ultra-compact with the given format options
+///
+/// Cache tokens count towards the total when `include_cache_in_total` is set.
+#[must_use]
+pub fn format_panel_display_with_options(
+    usage: &UsageMetrics,
+    options: PanelFormatOptions,
+) -> String {
     let cost = format_cost_compact(usage.total_cost);
-    let total_tokens = usage.total_input_tokens + usage.total_output_tokens;
-    let tokens = format_tokens_compact(total_tokens);
+    let total_tokens = display_total_tokens(usage, options.include_cache_in_total);
+    let tokens = format_tokens_compact_with_threshold(
+        total_tokens,
+        options.compact_token_decimals,
+        options.compact_threshold,
+    );
     format!("{tokens}/{cost}")
 }
 
@@ -126,6 +259,12 @@ pub fn format_panel_interactions_only(usage: &UsageMetrics) -> String {
     format!("{}x", usage.interaction_count)
 }
 
+/// Format interaction count compactly for panel display (e.g., "1.2kx")
+#[must_use]
+pub fn format_panel_interactions_compact(usage: &UsageMetrics) -> String {
+    format!("{}x", format_tokens_compact(usage.interaction_count as u64))
+}
+
 /// Format only input tokens for panel display (e.g., "10k")
 #[must_use]
 pub fn format_panel_input_tokens_only(usage: &UsageMetrics) -> String {
@@ -162,15 +301,50 @@ pub fn format_panel_reasoning_tokens_only_raw(usage: &UsageMetrics) -> String {
     format_tokens_raw(usage.total_reasoning_tokens)
 }
 
-/// Display order for panel metrics (fixed order regardless of selection order)
-/// Cost | Interactions | `InputTokens` | `OutputTokens` | `ReasoningTokens`
-const METRIC_DISPLAY_ORDER: [PanelMetric; 5] = [
-    PanelMetric::Cost,
-    PanelMetric::Interactions,
-    PanelMetric::InputTokens,
-    PanelMetric::OutputTokens,
-    PanelMetric::ReasoningTokens,
-];
+/// Options controlling how panel metrics are formatted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PanelFormatOptions {
+    /// Use raw token values instead of K/M suffixes
+    pub use_raw: bool,
+    /// Format interaction counts compactly (e.g., "1.2kx")
+    pub compact_interactions: bool,
+    /// Add cache read/write tokens to total-token figures
+    pub include_cache_in_total: bool,
+    /// Thousands separator for raw token values, overriding the locale
+    pub raw_token_separator: Option<char>,
+    /// Decimals shown for compact token values under 10k/10M
+    pub compact_token_decimals: u8,
+    /// Smallest token count shown in compact form; lower values stay exact
+    pub compact_threshold: u64,
+}
+
+impl Default for PanelFormatOptions {
+    fn default() -> Self {
+        Self {
+            use_raw: false,
+            compact_interactions: false,
+            include_cache_in_total: false,
+            raw_token_separator: None,
+            compact_token_decimals: 1,
+            compact_threshold: DEFAULT_COMPACT_THRESHOLD,
+        }
+    }
+}
+
+impl PanelFormatOptions {
+    /// Build the panel format options from the app configuration
+    #[must_use]
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            use_raw: config.use_raw_token_display,
+            compact_interactions: config.compact_interactions,
+            include_cache_in_total: config.include_cache_in_total,
+            raw_token_separator: config.raw_token_separator,
+            compact_token_decimals: config.compact_token_decimals,
+            compact_threshold: config.compact_threshold,
+        }
+    }
+}
 
 /// Format panel metric based on the selected metric type
 ///
@@ -186,28 +360,60 @@ const METRIC_DISPLAY_ORDER: [PanelMetric; 5] = [
 /// * Formatted string for the selected metric
 #[must_use]
 pub fn format_panel_metric(usage: &UsageMetrics, metric: PanelMetric, use_raw: bool) -> String {
+    format_panel_metric_with_options(
+        usage,
+        metric,
+        PanelFormatOptions {
+            use_raw,
+            ..PanelFormatOptions::default()
+        },
+    )
+}
+
+/// Format panel metric with the given format options
+#[must_use]
+pub fn format_panel_metric_with_options(
+    usage: &UsageMetrics,
+    metric: PanelMetric,
+    options: PanelFormatOptions,
+) -> String {
+    let use_raw = options.use_raw;
+    let raw = |tokens| format_tokens_raw_with_separator(tokens, options.raw_token_separator);
+    let compact = |tokens| {
+        format_tokens_compact_with_threshold(
+            tokens,
+            options.compact_token_decimals,
+            options.compact_threshold,
+        )
+    };
     match metric {
         PanelMetric::Cost => format_panel_cost_only(usage),
-        PanelMetric::Interactions => format_panel_interactions_only(usage),
+        PanelMetric::Interactions => {
+            if options.compact_interactions {
+                format!("{}x", compact(usage.interaction_count as u64))
+            } else {
+                format_panel_interactions_only(usage)
+            }
+        }
         PanelMetric::InputTokens => {
             if use_raw {
-                format_panel_input_tokens_only_raw(usage)
+                raw(usage.total_input_tokens)
             } else {
-                format_panel_input_tokens_only(usage)
+                compact(usage.total_input_tokens)
             }
         }
         PanelMetric::OutputTokens => {
             if use_raw {
-                format_panel_output_tokens_only_raw(usage)
+                raw(usage.total_output_tokens)
             } else {
-                format_panel_output_tokens_only(usage)
+                compact(usage.total_output_tokens)
             }
         }
         PanelMetric::ReasoningTokens => {
             if use_raw {
-                format_panel_reasoning_tokens_only_raw(usage)
+                raw(usage.total_reasoning_tokens)
             } else {
-                format_panel_reasoning_tokens_only(usage)
+                compact(usage.total_reasoning_tokens)
             }
         }
     }
@@ -237,6 +443,40 @@ pub fn format_multiple_panel_metrics(
     usage: &UsageMetrics,
     metrics: &[PanelMetric],
     use_raw: bool,
+) -> String {
+    format_multiple_panel_metrics_with_options(
+        usage,
+        metrics,
+        PanelFormatOptions {
+            use_raw,
+            ..PanelFormatOptions::default()
+        },
+    )
+}
+
+/// Format multiple panel metrics in the default order with the given format options
+///
+/// See `format_multiple_panel_metrics` for the output format.
+#[must_use]
+pub fn format_multiple_panel_metrics_with_options(
+    usage: &UsageMetrics,
+    metrics: &[PanelMetric],
+    options: PanelFormatOptions,
+) -> String {
+    format_multiple_panel_metrics_in_order(usage, metrics, &PanelMetric::DEFAULT_ORDER, options)
+}
+
+/// Format multiple panel metrics in a user-defined order
+///
+/// Selected metrics are shown in the order they appear in `order`, each at most once.
+/// Selected metrics missing from `order` follow in the default order.
+/// See `format_multiple_panel_metrics` for the output format.
+#[must_use]
+pub fn format_multiple_panel_metrics_in_order(
+    usage: &UsageMetrics,
+    metrics: &[PanelMetric],
+    order: &[PanelMetric],
+    options: PanelFormatOptions,
 ) -> String {
     if metrics.is_empty() {
         return String::new();
@@ -244,13 +484,15 @@ pub fn format_multiple_panel_metrics(
 
     // Convert to a set-like structure for O(1) lookup
     let metric_set: std::collections::HashSet<PanelMetric> = metrics.iter().copied().collect();
+    let mut shown = std::collections::HashSet::new();
 
-    // Format metrics in display order
-    let formatted_metrics: Vec<String> = METRIC_DISPLAY_ORDER
+    // Format metrics in display order, skipping duplicates in the order list
+    let formatted_metrics: Vec<String> = order
         .iter()
-        .filter(|m| metric_set.contains(m))
+        .chain(PanelMetric::DEFAULT_ORDER.iter())
+        .filter(|m| metric_set.contains(m) && shown.insert(**m))
         .map(|metric| {
-            let value = format_panel_metric(usage, *metric, use_raw);
+            let value = format_panel_metric_with_options(usage, *metric, options);
             match metric {
                 PanelMetric::Cost | PanelMetric::Interactions => value,
                 PanelMetric::InputTokens => format!("↑ {value}"),
@@ -263,14 +505,98 @@ pub fn format_multiple_panel_metrics(
     formatted_metrics.join(" ")
 }
 
+/// Fit panel text into the configured width, or `None` to show only the icon
+///
+/// Text longer than `max_chars` is cut to fit with a trailing ellipsis. When that would
+/// leave fewer than `min_chars` characters of text, the panel drops to icon-only
+/// instead; the full metrics stay available in the popup.
+#[must_use]
+pub fn fit_panel_text(text: &str, max_chars: Option<usize>, min_chars: usize) -> Option<String> {
+    if text.is_empty() {
+        return None;
+    }
+    let Some(max_chars) = max_chars else {
+        return Some(text.to_string());
+    };
+    if text.chars().count() <= max_chars {
+        return Some(text.to_string());
+    }
+
+    // One character goes to the ellipsis
+    let kept = max_chars.saturating_sub(1);
+    if kept == 0 || kept < min_chars {
+        return None;
+    }
+    let mut truncated: String = text.chars().take(kept).collect();
+    truncated.push('…');
+    Some(truncated)
+}
+
 /// Get the primary metric to display (total cost)
 #[must_use]
 pub fn get_primary_metric(usage: &UsageMetrics) -> u64 {
-    // Convert cost to cents for display as integer
-    // The cost is always positive and should be within u64 range
-    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-    let cents = (usage.total_cost * 100.0) as u64;
-    cents
+    primary_metric_value(usage, PrimaryMetric::Cost, false)
+}
+
+/// Numeric value of the chosen primary metric
+///
+/// Cost is returned in cents; total tokens include cache tokens when `include_cache` is set.
+#[must_use]
+pub fn primary_metric_value(
+    usage: &UsageMetrics,
+    metric: PrimaryMetric,
+    include_cache: bool,
+) -> u64 {
+    match metric {
+        PrimaryMetric::Cost => {
+            // Convert cost to cents for display as integer
+            // The cost is always positive and should be within u64 range
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let cents = (usage.total_cost * 100.0) as u64;
+            cents
+        }
+        PrimaryMetric::TotalTokens => display_total_tokens(usage, include_cache),
+        PrimaryMetric::Interactions => usage.interaction_count as u64,
+    }
+}
+
+/// Format the primary metric for the panel (e.g., "$1.23", "15.5k" or "5x")
+#[must_use]
+pub fn format_primary_metric(
+    usage: &UsageMetrics,
+    metric: PrimaryMetric,
+    options: PanelFormatOptions,
+) -> String {
+    match metric {
+        PrimaryMetric::Cost => format_panel_metric_with_options(usage, PanelMetric::Cost, options),
+        PrimaryMetric::TotalTokens => {
+            let tokens = display_total_tokens(usage, options.include_cache_in_total);
+            if options.use_raw {
+                format_tokens_raw_with_separator(tokens, options.raw_token_separator)
+            } else {
+                format_tokens_compact_with_threshold(
+                    tokens,
+                    options.compact_token_decimals,
+                    options.compact_threshold,
+                )
+            }
+        }
+        PrimaryMetric::Interactions => {
+            format_panel_metric_with_options(usage, PanelMetric::Interactions, options)
+        }
+    }
+}
+
+/// Append a pinned all-time metric to the panel text (e.g., "$1.2 · All $500")
+#[must_use]
+pub fn append_pinned_note(
+    panel_text: &str,
+    all_time_usage: &UsageMetrics,
+    metric: PanelMetric,
+    options: PanelFormatOptions,
+) -> String {
+    let value = format_panel_metric_with_options(all_time_usage, metric, options);
+    format!("{panel_text} · All {value}")
 }
 
 /// Format tooltip with last update timestamp
@@ -284,6 +610,16 @@ pub fn format_tooltip(last_update: Option<DateTime<Utc>>) -> String {
     }
 }
 
+/// Format the countdown to the next automatic refresh (e.g., "Next refresh in 4m 05s")
+#[must_use]
+pub fn format_refresh_countdown(seconds: u64) -> String {
+    match seconds {
+        0 => "Refresh due".to_string(),
+        1..=59 => format!("Next refresh in {seconds}s"),
+        _ => format!("Next refresh in {}m {:02}s", seconds / 60, seconds % 60),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -345,6 +681,88 @@ mod tests {
         assert_eq!(format_number(1_234_567), format_number_locale(1_234_567));
     }
 
+    #[test]
+    fn test_format_panel_interactions_compact() {
+        let mut usage = UsageMetrics {
+            total_input_tokens: 0,
+            total_output_tokens: 0,
+            total_reasoning_tokens: 0,
+            total_cache_write_tokens: 0,
+            total_cache_read_tokens: 0,
+            total_cost: 0.0,
+            total_cache_cost: 0.0,
+            interaction_count: 999,
+            timestamp: std::time::SystemTime::now(),
+        };
+        let compact = PanelFormatOptions {
+            compact_interactions: true,
+            ..PanelFormatOptions::default()
+        };
+
+        assert_eq!(format_panel_interactions_compact(&usage), "999x");
+        usage.interaction_count = 1_500;
+        assert_eq!(format_panel_interactions_compact(&usage), "1.5kx");
+        usage.interaction_count = 1_200_000;
+        assert_eq!(format_panel_interactions_compact(&usage), "1.2Mx");
+
+        assert_eq!(
+            format_panel_metric_with_options(&usage, PanelMetric::Interactions, compact),
+            "1.2Mx"
+        );
+        // Off by default: full count
+        assert_eq!(
+            format_panel_metric(&usage, PanelMetric::Interactions, false),
+            "1200000x"
+        );
+    }
+
+    #[test]
+    fn test_format_refresh_delta() {
+        let mut delta = UsageDelta {
+            input_tokens: 400,
+            output_tokens: 100,
+            reasoning_tokens: 0,
+            cache_write_tokens: 0,
+            cache_read_tokens: 0,
+            cost: 0.12,
+            interactions: 3,
+        };
+        assert_eq!(
+            format_refresh_delta(&delta).as_deref(),
+            Some("+$0.12, +3 interactions since last check")
+        );
+
+        delta.cost = -0.5;
+        delta.interactions = -1;
+        assert_eq!(
+            format_refresh_delta(&delta).as_deref(),
+            Some("-$0.50, -1 interactions since last check")
+        );
+
+        delta.cost = 0.0;
+        delta.interactions = 0;
+        assert_eq!(format_refresh_delta(&delta), None);
+    }
+
+    #[test]
+    fn test_format_cache_ratio() {
+        let mut usage = UsageMetrics {
+            total_input_tokens: 0,
+            total_output_tokens: 0,
+            total_reasoning_tokens: 0,
+            total_cache_write_tokens: 1_000,
+            total_cache_read_tokens: 3_200,
+            total_cost: 0.0,
+            total_cache_cost: 0.0,
+            interaction_count: 0,
+            timestamp: std::time::SystemTime::now(),
+        };
+        assert_eq!(format_cache_ratio(&usage), "3.2x");
+
+        usage.total_cache_write_tokens = 0;
+        assert_eq!(format_cache_ratio(&usage), "n/a");
+    }
+
     #[test]
     fn test_format_cost() {
         assert_eq!(format_cost(12.5), "$12.50");
@@ -406,6 +824,209 @@ mod tests {
         assert_eq!(format_tokens_compact(9_999_999), "10M");
     }
 
+    #[test]
+    fn test_format_tokens_compact_with_decimals() {
+        assert_eq!(format_tokens_compact_with_decimals(1_234, 0), "1k");
+        assert_eq!(format_tokens_compact_with_decimals(1_234, 1), "1.2k");
+        assert_eq!(format_tokens_compact_with_decimals(1_234, 2), "1.23k");
+        assert_eq!(format_tokens_compact_with_decimals(1_234_567, 0), "1M");
+        assert_eq!(format_tokens_compact_with_decimals(1_234_567, 1), "1.2M");
+        assert_eq!(format_tokens_compact_with_decimals(1_234_567, 2), "1.23M");
+
+        // Large and whole values stay without decimals, and precision is capped at 2
+        assert_eq!(format_tokens_compact_with_decimals(15_678, 2), "16k");
+        assert_eq!(format_tokens_compact_with_decimals(2_000, 2), "2k");
+        assert_eq!(format_tokens_compact_with_decimals(999, 2), "999");
+        assert_eq!(format_tokens_compact_with_decimals(1_234, 9), "1.23k");
+    }
+
+    #[test]
+    fn test_format_tokens_compact_with_threshold() {
+        // Default threshold switches at 1000
+        assert_eq!(format_tokens_compact_with_threshold(999, 1, 1_000), "999");
+        assert_eq!(format_tokens_compact_with_threshold(1_000, 1, 1_000), "1k");
+
+        // A higher threshold keeps exact counts longer
+        assert_eq!(
+            format_tokens_compact_with_threshold(9_999, 1, 10_000),
+            "9999"
+        );
+        assert_eq!(
+            format_tokens_compact_with_threshold(10_000, 1, 10_000),
+            "10k"
+        );
+        assert_eq!(
+            format_tokens_compact_with_threshold(2_500_000, 1, 10_000),
+            "2.5M"
+        );
+
+        // Thresholds below 1000 behave like the default
+        assert_eq!(format_tokens_compact_with_threshold(999, 1, 0), "999");
+        assert_eq!(format_tokens_compact_with_threshold(1_000, 1, 500), "1k");
+    }
+
+    #[test]
+    fn test_panel_metric_uses_compact_threshold() {
+        let usage = UsageMetrics {
+            total_input_tokens: 4_321,
+            total_output_tokens: 0,
+            total_reasoning_tokens: 0,
+            total_cache_write_tokens: 0,
+            total_cache_read_tokens: 0,
+            total_cost: 0.0,
+            total_cache_cost: 0.0,
+            interaction_count: 0,
+            timestamp: std::time::SystemTime::now(),
+        };
+        let config = AppConfig {
+            compact_threshold: 5_000,
+            ..AppConfig::default()
+        };
+
+        assert_eq!(
+            format_panel_metric_with_options(
+                &usage,
+                PanelMetric::InputTokens,
+                PanelFormatOptions::from_config(&config)
+            ),
+            "4321"
+        );
+        assert_eq!(
+            format_panel_metric(&usage, PanelMetric::InputTokens, false),
+            "4.3k"
+        );
+    }
+
+    #[test]
+    fn test_panel_metric_uses_compact_token_decimals() {
+        let usage = UsageMetrics {
+            total_input_tokens: 1_234,
+            total_output_tokens: 0,
+            total_reasoning_tokens: 0,
+            total_cache_write_tokens: 0,
+            total_cache_read_tokens: 0,
+            total_cost: 0.0,
+            total_cache_cost: 0.0,
+            interaction_count: 0,
+            timestamp: std::time::SystemTime::now(),
+        };
+        let config = AppConfig {
+            compact_token_decimals: 2,
+            ..AppConfig::default()
+        };
+
+        assert_eq!(
+            format_panel_metric_with_options(
+                &usage,
+                PanelMetric::InputTokens,
+                PanelFormatOptions::from_config(&config)
+            ),
+            "1.23k"
+        );
+        assert_eq!(
+            format_panel_metric(&usage, PanelMetric::InputTokens, false),
+            "1.2k"
+        );
+    }
+
+    #[test]
+    fn test_append_pinned_all_time_cost() {
+        let today = UsageMetrics {
+            total_input_tokens: 10_000,
+            total_output_tokens: 5_000,
+            total_reasoning_tokens: 0,
+            total_cache_write_tokens: 0,
+            total_cache_read_tokens: 0,
+            total_cost: 1.23,
+            total_cache_cost: 0.0,
+            interaction_count: 3,
+            timestamp: std::time::SystemTime::now(),
+        };
+        let all_time = UsageMetrics {
+            total_cost: 500.0,
+            total_input_tokens: 12_345_678,
+            ..today.clone()
+        };
+        let options = PanelFormatOptions::default();
+        let panel = format_multiple_panel_metrics_in_order(
+            &today,
+            &[PanelMetric::Cost],
+            &PanelMetric::DEFAULT_ORDER,
+            options,
+        );
+
+        assert_eq!(
+            append_pinned_note(&panel, &all_time, PanelMetric::Cost, options),
+            "$1.2 · All $500"
+        );
+        assert_eq!(
+            append_pinned_note(&panel, &all_time, PanelMetric::InputTokens, options),
+            "$1.2 · All 12M"
+        );
+    }
+
+    #[test]
+    fn test_popup_cost_full_panel_cost_compact() {
+        let usage = UsageMetrics {
+            total_input_tokens: 10_000,
+            total_output_tokens: 5_000,
+            total_reasoning_tokens: 0,
+            total_cache_write_tokens: 0,
+            total_cache_read_tokens: 0,
+            total_cost: 123.456,
+            total_cache_cost: 0.0,
+            interaction_count: 3,
+            timestamp: std::time::SystemTime::now(),
+        };
+
+        // The popup keeps the cents at any magnitude
+        assert_eq!(format_popup_cost(usage.total_cost, false), "$123.46");
+        assert_eq!(format_popup_cost(usage.total_cost, true), "$123.4560");
+
+        // The narrow panel stays compact
+        let options = PanelFormatOptions::default();
+        assert_eq!(
+            format_panel_metric_with_options(&usage, PanelMetric::Cost, options),
+            "$123"
+        );
+        assert_eq!(
+            format_panel_display_with_options(&usage, options),
+            "15k/$123"
+        );
+    }
+
+    #[test]
+    fn test_raw_cost_display_applies_to_popup_only() {
+        let usage = UsageMetrics {
+            total_input_tokens: 10_000,
+            total_output_tokens: 5_000,
+            total_reasoning_tokens: 0,
+            total_cache_write_tokens: 0,
+            total_cache_read_tokens: 0,
+            total_cost: 1.234_5,
+            total_cache_cost: 0.0,
+            interaction_count: 3,
+            timestamp: std::time::SystemTime::now(),
+        };
+
+        assert_eq!(format_popup_cost(usage.total_cost, false), "$1.23");
+        assert_eq!(format_popup_cost(usage.total_cost, true), "$1.2345");
+
+        let config = AppConfig {
+            use_raw_cost_display: true,
+            ..AppConfig::default()
+        };
+        let options = PanelFormatOptions::from_config(&config);
+        assert_eq!(
+            format_panel_metric_with_options(&usage, PanelMetric::Cost, options),
+            "$1.2"
+        );
+        assert_eq!(
+            format_panel_display_with_options(&usage, options),
+            format_panel_display_with_options(&usage, PanelFormatOptions::default())
+        );
+    }
+
     #[test]
     fn test_format_tokens_compact_zero() {
         assert_eq!(format_tokens_compact(0), "0");
@@ -420,6 +1041,7 @@ mod tests {
             total_cache_write_tokens: 0,
             total_cache_read_tokens: 0,
             total_cost: 0.05,
+            total_cache_cost: 0.0,
             interaction_count: 1,
             timestamp: std::time::SystemTime::now(),
         };
@@ -435,6 +1057,7 @@ mod tests {
             total_cache_write_tokens: 0,
             total_cache_read_tokens: 0,
             total_cost: 1.23,
+            total_cache_cost: 0.0,
             interaction_count: 5,
             timestamp: std::time::SystemTime::now(),
         };
@@ -450,12 +1073,55 @@ mod tests {
             total_cache_write_tokens: 0,
             total_cache_read_tokens: 0,
             total_cost: 12.50,
+            total_cache_cost: 0.0,
             interaction_count: 25,
             timestamp: std::time::SystemTime::now(),
         };
         assert_eq!(format_panel_display(&usage), "750k/$12");
     }
 
+    #[test]
+    fn test_format_panel_display_cache_in_total() {
+        let usage = UsageMetrics {
+            total_input_tokens: 10_000,
+            total_output_tokens: 5_000,
+            total_reasoning_tokens: 0,
+            total_cache_write_tokens: 2_000,
+            total_cache_read_tokens: 8_000,
+            total_cost: 1.23,
+            total_cache_cost: 0.0,
+            interaction_count: 5,
+            timestamp: std::time::SystemTime::now(),
+        };
+
+        // Excluded by default
+        assert_eq!(display_total_tokens(&usage, false), 15_000);
+        assert_eq!(
+            format_panel_display_with_options(&usage, PanelFormatOptions::default()),
+            "15k/$1.2"
+        );
+
+        let with_cache = PanelFormatOptions {
+            include_cache_in_total: true,
+            ..PanelFormatOptions::default()
+        };
+        assert_eq!(display_total_tokens(&usage, true), 25_000);
+        assert_eq!(
+            format_panel_display_with_options(&usage, with_cache),
+            "25k/$1.2"
+        );
+    }
+
+    #[test]
+    fn test_panel_format_options_from_config_cache_in_total() {
+        let config = AppConfig {
+            include_cache_in_total: true,
+            ..AppConfig::default()
+        };
+        assert!(PanelFormatOptions::from_config(&config).include_cache_in_total);
+        assert!(!PanelFormatOptions::from_config(&AppConfig::default()).include_cache_in_total);
+    }
+
     #[test]
     fn test_format_panel_display_detailed_small() {
         let usage = UsageMetrics {
@@ -465,6 +1131,7 @@ mod tests {
             total_cache_write_tokens: 0,
             total_cache_read_tokens: 0,
             total_cost: 0.05,
+            total_cache_cost: 0.0,
             interaction_count: 1,
             timestamp: std::time::SystemTime::now(),
         };
@@ -483,6 +1150,7 @@ mod tests {
             total_cache_write_tokens: 0,
             total_cache_read_tokens: 0,
             total_cost: 1.23,
+            total_cache_cost: 0.0,
             interaction_count: 15,
             timestamp: std::time::SystemTime::now(),
         };
@@ -501,6 +1169,7 @@ mod tests {
             total_cache_write_tokens: 0,
             total_cache_read_tokens: 0,
             total_cost: 125.50,
+            total_cache_cost: 0.0,
             interaction_count: 1234,
             timestamp: std::time::SystemTime::now(),
         };
@@ -519,6 +1188,7 @@ mod tests {
             total_cache_write_tokens: 0,
             total_cache_read_tokens: 0,
             total_cost: 1.23,
+            total_cache_cost: 0.0,
             interaction_count: 15,
             timestamp: std::time::SystemTime::now(),
         };
@@ -588,6 +1258,72 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_format_tokens_raw_with_separator_override() {
+        assert_eq!(format_tokens_raw_with_separator(999, Some(',')), "999");
+        assert_eq!(format_tokens_raw_with_separator(1_000, Some(',')), "1,000");
+        assert_eq!(
+            format_tokens_raw_with_separator(1_234_567, Some(',')),
+            "1,234,567"
+        );
+        assert_eq!(
+            format_tokens_raw_with_separator(1_234_567, Some(' ')),
+            "1 234 567"
+        );
+        assert_eq!(
+            format_tokens_raw_with_separator(25_000_000, Some('.')),
+            "25.000.000"
+        );
+        assert_eq!(
+            format_tokens_raw_with_separator(1_234_567, None),
+            format_tokens_raw(1_234_567)
+        );
+    }
+
+    #[test]
+    fn test_apply_thousands_separator_replaces_locale_separator() {
+        assert_eq!(
+            apply_thousands_separator("1\u{a0}234\u{a0}567", ','),
+            "1,234,567"
+        );
+        assert_eq!(apply_thousands_separator("1.234", ' '), "1 234");
+        assert_eq!(apply_thousands_separator("0", ','), "0");
+    }
+
+    #[test]
+    fn test_panel_metric_uses_raw_token_separator() {
+        let usage = UsageMetrics {
+            total_input_tokens: 12_345,
+            total_output_tokens: 6_789,
+            total_reasoning_tokens: 1_000_000,
+            total_cache_write_tokens: 0,
+            total_cache_read_tokens: 0,
+            total_cost: 1.0,
+            total_cache_cost: 0.0,
+            interaction_count: 2,
+            timestamp: std::time::SystemTime::now(),
+        };
+        let config = AppConfig {
+            use_raw_token_display: true,
+            raw_token_separator: Some(' '),
+            ..AppConfig::default()
+        };
+        let options = PanelFormatOptions::from_config(&config);
+
+        assert_eq!(
+            format_multiple_panel_metrics_with_options(
+                &usage,
+                &[
+                    PanelMetric::InputTokens,
+                    PanelMetric::OutputTokens,
+                    PanelMetric::ReasoningTokens
+                ],
+                options,
+            ),
+            "↑ 12 345 ↓ 6 789 RT: 1 000 000"
+        );
+    }
+
     #[test]
     fn test_format_panel_display_detailed_raw_small() {
         let usage = UsageMetrics {
@@ -597,6 +1333,7 @@ mod tests {
             total_cache_write_tokens: 0,
             total_cache_read_tokens: 0,
             total_cost: 0.05,
+            total_cache_cost: 0.0,
             interaction_count: 1,
             timestamp: std::time::SystemTime::now(),
         };
@@ -614,6 +1351,7 @@ mod tests {
             total_cache_write_tokens: 0,
             total_cache_read_tokens: 0,
             total_cost: 125.50,
+            total_cache_cost: 0.0,
             interaction_count: 1234,
             timestamp: std::time::SystemTime::now(),
         };
@@ -639,6 +1377,7 @@ mod tests {
             total_cache_write_tokens: 0,
             total_cache_read_tokens: 0,
             total_cost: 1.23,
+            total_cache_cost: 0.0,
             interaction_count: 5,
             timestamp: std::time::SystemTime::now(),
         };
@@ -654,6 +1393,7 @@ mod tests {
             total_cache_write_tokens: 0,
             total_cache_read_tokens: 0,
             total_cost: 1.23,
+            total_cache_cost: 0.0,
             interaction_count: 5,
             timestamp: std::time::SystemTime::now(),
         };
@@ -669,6 +1409,7 @@ mod tests {
             total_cache_write_tokens: 0,
             total_cache_read_tokens: 0,
             total_cost: 1.23,
+            total_cache_cost: 0.0,
             interaction_count: 5,
             timestamp: std::time::SystemTime::now(),
         };
@@ -684,6 +1425,7 @@ mod tests {
             total_cache_write_tokens: 0,
             total_cache_read_tokens: 0,
             total_cost: 1.23,
+            total_cache_cost: 0.0,
             interaction_count: 5,
             timestamp: std::time::SystemTime::now(),
         };
@@ -699,6 +1441,7 @@ mod tests {
             total_cache_write_tokens: 0,
             total_cache_read_tokens: 0,
             total_cost: 1.23,
+            total_cache_cost: 0.0,
             interaction_count: 5,
             timestamp: std::time::SystemTime::now(),
         };
@@ -715,6 +1458,7 @@ mod tests {
             total_cache_write_tokens: 0,
             total_cache_read_tokens: 0,
             total_cost: 1.23,
+            total_cache_cost: 0.0,
             interaction_count: 5,
             timestamp: std::time::SystemTime::now(),
         };
@@ -733,6 +1477,7 @@ mod tests {
             total_cache_write_tokens: 0,
             total_cache_read_tokens: 0,
             total_cost: 1.23,
+            total_cache_cost: 0.0,
             interaction_count: 5,
             timestamp: std::time::SystemTime::now(),
         };
@@ -750,6 +1495,7 @@ mod tests {
             total_cache_write_tokens: 0,
             total_cache_read_tokens: 0,
             total_cost: 1.23,
+            total_cache_cost: 0.0,
             interaction_count: 5,
             timestamp: std::time::SystemTime::now(),
         };
@@ -837,6 +1583,7 @@ mod tests {
             total_cache_write_tokens: 0,
             total_cache_read_tokens: 0,
             total_cost: 1.23,
+            total_cache_cost: 0.0,
             interaction_count: 5,
             timestamp: std::time::SystemTime::now(),
         }
@@ -983,4 +1730,209 @@ mod tests {
         );
         assert_eq!(result, "$1.2 5x");
     }
+
+    #[test]
+    fn test_format_multiple_panel_metrics_custom_order() {
+        let usage = create_test_usage();
+        let order = [
+            PanelMetric::Interactions,
+            PanelMetric::InputTokens,
+            PanelMetric::OutputTokens,
+            PanelMetric::ReasoningTokens,
+            PanelMetric::Cost,
+        ];
+        let result = format_multiple_panel_metrics_in_order(
+            &usage,
+            &PanelMetric::DEFAULT_ORDER,
+            &order,
+            PanelFormatOptions::default(),
+        );
+        assert_eq!(result, "5x ↑ 10k ↓ 5k RT: 2k $1.2");
+
+        // Only selected metrics are shown, still in the custom order
+        let result = format_multiple_panel_metrics_in_order(
+            &usage,
+            &[PanelMetric::Cost, PanelMetric::Interactions],
+            &order,
+            PanelFormatOptions::default(),
+        );
+        assert_eq!(result, "5x $1.2");
+    }
+
+    #[test]
+    fn test_format_multiple_panel_metrics_custom_order_deduplicated() {
+        let usage = create_test_usage();
+        // Duplicates in the order are shown once; missing metrics follow in default order
+        let result = format_multiple_panel_metrics_in_order(
+            &usage,
+            &[
+                PanelMetric::Cost,
+                PanelMetric::Interactions,
+                PanelMetric::OutputTokens,
+            ],
+            &[
+                PanelMetric::OutputTokens,
+                PanelMetric::OutputTokens,
+                PanelMetric::Interactions,
+            ],
+            PanelFormatOptions::default(),
+        );
+        assert_eq!(result, "↓ 5k 5x $1.2");
+    }
+
+    // ===== CACHE FOLDING TESTS =====
+
+    fn create_cache_usage() -> UsageMetrics {
+        UsageMetrics {
+            total_input_tokens: 10_000,
+            total_output_tokens: 5_000,
+            total_reasoning_tokens: 0,
+            total_cache_write_tokens: 2_000,
+            total_cache_read_tokens: 40_000,
+            total_cost: 1.0,
+            total_cache_cost: 0.0,
+            interaction_count: 1,
+            timestamp: std::time::SystemTime::now(),
+        }
+    }
+
+    #[test]
+    fn test_usage_for_display_unfolded() {
+        let usage = create_cache_usage();
+        let display = usage_for_display(&usage, false);
+
+        assert!(matches!(display, Cow::Borrowed(_)));
+        assert_eq!(
+            format_multiple_panel_metrics(
+                &display,
+                &[PanelMetric::InputTokens, PanelMetric::OutputTokens],
+                false
+            ),
+            "↑ 10k ↓ 5k"
+        );
+    }
+
+    #[test]
+    fn test_usage_for_display_folded() {
+        let usage = create_cache_usage();
+        let display = usage_for_display(&usage, true);
+
+        assert_eq!(
+            format_multiple_panel_metrics(
+                &display,
+                &[PanelMetric::InputTokens, PanelMetric::OutputTokens],
+                false
+            ),
+            "↑ 50k ↓ 7k"
+        );
+        // Cache fields and the original metrics are left intact
+        assert_eq!(display.total_cache_read_tokens, 40_000);
+        assert_eq!(display.total_cache_write_tokens, 2_000);
+        assert_eq!(usage.total_input_tokens, 10_000);
+        assert_eq!(usage.total_output_tokens, 5_000);
+    }
+
+    #[test]
+    fn test_usage_for_display_folded_saturates() {
+        let mut usage = create_cache_usage();
+        usage.total_input_tokens = u64::MAX;
+
+        let display = usage_for_display(&usage, true);
+        assert_eq!(display.total_input_tokens, u64::MAX);
+    }
+
+    #[test]
+    fn test_format_refresh_countdown() {
+        assert_eq!(format_refresh_countdown(0), "Refresh due");
+        assert_eq!(format_refresh_countdown(1), "Next refresh in 1s");
+        assert_eq!(format_refresh_countdown(59), "Next refresh in 59s");
+        assert_eq!(format_refresh_countdown(60), "Next refresh in 1m 00s");
+        assert_eq!(format_refresh_countdown(245), "Next refresh in 4m 05s");
+    }
+
+    #[test]
+    fn test_fit_panel_text() {
+        let text = "$1.23 14x ↑ 12k";
+
+        // No limit, or a limit the text fits in, keeps it whole
+        assert_eq!(fit_panel_text(text, None, 4), Some(text.to_string()));
+        assert_eq!(fit_panel_text(text, Some(15), 4), Some(text.to_string()));
+
+        // Truncation that still leaves enough text adds an ellipsis
+        assert_eq!(
+            fit_panel_text(text, Some(10), 4),
+            Some("$1.23 14x…".to_string())
+        );
+        assert_eq!(fit_panel_text(text, Some(5), 4), Some("$1.2…".to_string()));
+
+        // Truncating below the threshold drops to icon-only
+        assert_eq!(fit_panel_text(text, Some(4), 4), None);
+        assert_eq!(fit_panel_text(text, Some(1), 0), None);
+        assert_eq!(fit_panel_text(text, Some(0), 0), None);
+
+        // Nothing to show is icon-only too
+        assert_eq!(fit_panel_text("", None, 4), None);
+    }
+
+    #[test]
+    fn test_primary_metric_value_per_choice() {
+        let usage = UsageMetrics {
+            total_input_tokens: 10_000,
+            total_output_tokens: 5_000,
+            total_reasoning_tokens: 2_000,
+            total_cache_write_tokens: 1_000,
+            total_cache_read_tokens: 4_000,
+            total_cost: 1.23,
+            total_cache_cost: 0.0,
+            interaction_count: 7,
+            timestamp: std::time::SystemTime::now(),
+        };
+
+        assert_eq!(
+            primary_metric_value(&usage, PrimaryMetric::Cost, false),
+            123
+        );
+        assert_eq!(
+            primary_metric_value(&usage, PrimaryMetric::TotalTokens, false),
+            15_000
+        );
+        assert_eq!(
+            primary_metric_value(&usage, PrimaryMetric::TotalTokens, true),
+            20_000
+        );
+        assert_eq!(
+            primary_metric_value(&usage, PrimaryMetric::Interactions, false),
+            7
+        );
+        assert_eq!(get_primary_metric(&usage), 123);
+    }
+
+    #[test]
+    fn test_format_primary_metric() {
+        let usage = UsageMetrics {
+            total_input_tokens: 10_000,
+            total_output_tokens: 5_500,
+            total_reasoning_tokens: 0,
+            total_cache_write_tokens: 0,
+            total_cache_read_tokens: 0,
+            total_cost: 1.23,
+            total_cache_cost: 0.0,
+            interaction_count: 7,
+            timestamp: std::time::SystemTime::now(),
+        };
+        let options = PanelFormatOptions::default();
+
+        assert_eq!(
+            format_primary_metric(&usage, PrimaryMetric::Cost, options),
+            "$1.23"
+        );
+        assert_eq!(
+            format_primary_metric(&usage, PrimaryMetric::TotalTokens, options),
+            "15.5k"
+        );
+        assert_eq!(
+            format_primary_metric(&usage, PrimaryMetric::Interactions, options),
+            "7x"
+        );
+    }
 }
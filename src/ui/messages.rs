@@ -15,16 +15,25 @@ pub type MetricsFetchResult =
 pub enum Message {
     /// Trigger a metrics fetch from `OpenCode` usage files
     FetchMetrics,
+    /// Fetch metrics at the user's request, confirming success visually
+    RefreshNow,
+    /// Hide the "refreshed" confirmation once it has expired
+    ClearRefreshedFlash,
     /// Metrics fetch completed (success or error)
     /// Contains generation ID (to ignore outdated responses), main metrics,
-    /// optionally today's metrics, and optionally month metrics for panel display
-    MetricsFetched(u64, Box<MetricsFetchResult>),
+    /// optionally today's metrics, and optionally month metrics for panel display,
+    /// then the number of storage entries skipped for lack of permission
+    MetricsFetched(u64, Box<MetricsFetchResult>, usize),
     /// Config changed externally (from another instance via COSMIC's `watch_config`)
     ConfigChanged(AppConfig),
     /// Theme changed (visual refresh needed)
     ThemeChanged,
     /// Tooltip needs update
     UpdateTooltip,
+    /// Detect the `OpenCode` storage path from the first-run introduction
+    DetectStoragePath,
+    /// Dismiss the first-run introduction for good
+    CompleteOnboarding,
     /// Open settings dialog
     OpenSettings,
     /// Close settings dialog
@@ -35,18 +44,130 @@ pub enum Message {
     TogglePanelMetric(crate::core::config::PanelMetric),
     /// Reset panel metrics to default (all 5 metrics)
     ResetPanelMetricsToDefaults,
+    /// Move the panel metric at the first index to the second index in the order
+    ReorderPanelMetric(usize, usize),
+    /// Start dragging the panel metric at this index in the order
+    StartPanelMetricDrag(usize),
+    /// Reset the panel metric order to the default order
+    ResetPanelMetricOrder,
     /// Toggle raw token display setting
     ToggleRawTokenDisplay(bool),
+    /// Toggle local usage history collection setting
+    ToggleHistoryEnabled(bool),
+    /// Toggle folding cache tokens into input/output display
+    ToggleFoldCacheIntoIo(bool),
+    /// Update monthly budget input in settings
+    UpdateMonthlyBudget(String),
+    /// Update budget warning color input in settings
+    UpdateBudgetWarningColor(String),
+    /// Update fetch timeout input in settings
+    UpdateFetchTimeout(String),
+    /// Toggle switching to a warning icon when over budget
+    ToggleBudgetWarningIcon(bool),
+    /// Toggle compact interaction counts in the panel
+    ToggleCompactInteractions(bool),
+    /// Toggle showing only the icon in the panel
+    TogglePanelIconOnly(bool),
+    /// Toggle serving metrics on a local socket
+    ToggleExposeSocket(bool),
+    /// Toggle compact popup layout
+    ToggleCompactUi(bool),
+    /// Toggle flagging of decreased all-time usage
+    ToggleFlagUsageDecrease(bool),
+    /// Toggle panel-only mode (no database, history or viewer)
+    TogglePanelOnly(bool),
+    /// Toggle full-precision cost in the popup
+    ToggleRawCostDisplay(bool),
     /// Save configuration
     SaveConfig,
     /// Toggle popup visibility
     TogglePopup,
+    /// Auto-close timer fired for the popup with this id
+    PopupAutoClose(cosmic::iced::window::Id),
+    /// One-second tick redrawing the refresh countdown in the popup with this id
+    RefreshCountdownTick(cosmic::iced::window::Id),
     /// Select a specific display mode (Today, Month, or `AllTime`)
     SelectDisplayMode(DisplayMode),
     /// Periodic timer tick for auto-refresh
     Tick,
+    /// Periodic reload of the config from disk, in case `watch_config` stopped delivering
+    ReloadConfigFallback,
     /// Open the viewer application
     OpenViewer,
     /// No-op message for event handling
     None,
 }
+
+impl Message {
+    /// Whether the message comes from the user interacting with the popup
+    ///
+    /// Used to reset the popup auto-close timer. Background messages (timers, fetch
+    /// results, config and theme updates) do not count. The match lists every variant,
+    /// so a new message has to be classified here.
+    #[must_use]
+    pub fn is_popup_interaction(&self) -> bool {
+        match self {
+            Message::RefreshNow
+            | Message::DetectStoragePath
+            | Message::CompleteOnboarding
+            | Message::OpenSettings
+            | Message::CloseSettings
+            | Message::UpdateRefreshInterval(_)
+            | Message::TogglePanelMetric(_)
+            | Message::ResetPanelMetricsToDefaults
+            | Message::ReorderPanelMetric(..)
+            | Message::StartPanelMetricDrag(_)
+            | Message::ResetPanelMetricOrder
+            | Message::ToggleRawTokenDisplay(_)
+            | Message::ToggleHistoryEnabled(_)
+            | Message::ToggleFoldCacheIntoIo(_)
+            | Message::UpdateMonthlyBudget(_)
+            | Message::UpdateBudgetWarningColor(_)
+            | Message::UpdateFetchTimeout(_)
+            | Message::ToggleBudgetWarningIcon(_)
+            | Message::ToggleCompactInteractions(_)
+            | Message::TogglePanelIconOnly(_)
+            | Message::ToggleExposeSocket(_)
+            | Message::ToggleCompactUi(_)
+            | Message::ToggleFlagUsageDecrease(_)
+            | Message::TogglePanelOnly(_)
+            | Message::ToggleRawCostDisplay(_)
+            | Message::SaveConfig
+            | Message::TogglePopup
+            | Message::SelectDisplayMode(_)
+            | Message::OpenViewer => true,
+            Message::FetchMetrics
+            | Message::MetricsFetched(..)
+            | Message::ConfigChanged(_)
+            | Message::ThemeChanged
+            | Message::UpdateTooltip
+            | Message::Tick
+            | Message::ReloadConfigFallback
+            | Message::ClearRefreshedFlash
+            | Message::PopupAutoClose(_)
+            | Message::RefreshCountdownTick(_)
+            | Message::None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_popup_interaction_messages() {
+        assert!(Message::OpenSettings.is_popup_interaction());
+        assert!(Message::SelectDisplayMode(DisplayMode::Month).is_popup_interaction());
+        assert!(Message::RefreshNow.is_popup_interaction());
+        assert!(!Message::Tick.is_popup_interaction());
+        assert!(!Message::ReloadConfigFallback.is_popup_interaction());
+        assert!(!Message::ClearRefreshedFlash.is_popup_interaction());
+        assert!(!Message::FetchMetrics.is_popup_interaction());
+        assert!(!Message::PopupAutoClose(cosmic::iced::window::Id::unique()).is_popup_interaction());
+        assert!(
+            !Message::RefreshCountdownTick(cosmic::iced::window::Id::unique())
+                .is_popup_interaction()
+        );
+    }
+}
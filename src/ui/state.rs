@@ -3,10 +3,13 @@
 //! Panel state management for the UI
 
 use crate::core::config::AppConfig;
-use crate::core::opencode::UsageMetrics;
+use crate::core::opencode::{UsageDelta, UsageMetrics};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+/// How long the "refreshed" confirmation stays visible after a manual refresh
+pub const REFRESHED_FLASH_SECONDS: u32 = 1;
+
 /// Represents the current state of the panel display
 #[derive(Debug, Clone)]
 pub enum PanelState {
@@ -74,8 +77,8 @@ pub enum DisplayMode {
 pub struct AppState {
     /// Current panel state
     pub panel_state: PanelState,
-    /// Timestamp of last successful data update
-    pub last_update: Option<DateTime<Utc>>,
+    /// Last successful data update and when the next automatic refresh is due
+    pub refresh_schedule: RefreshSchedule,
     /// Application configuration
     pub config: AppConfig,
     /// Current display mode (Today or `AllTime`)
@@ -86,27 +89,150 @@ pub struct AppState {
     pub month_usage: Option<UsageMetrics>,
     /// Last month's usage for panel display (cached)
     pub last_month_usage: Option<UsageMetrics>,
+    /// Whether any reasoning tokens were ever recorded (`None` until determined)
+    pub has_reasoning_history: Option<bool>,
+    /// Metrics of the previous successful fetch in the current display mode
+    pub refresh_baseline: Option<UsageMetrics>,
+    /// Change between the previous and the latest successful fetch
+    pub refresh_delta: Option<UsageDelta>,
+    /// Whether the in-flight fetch was requested manually by the user
+    pub manual_refresh_pending: bool,
+    /// When a manual refresh last succeeded, while its confirmation is visible
+    pub refreshed_at: Option<DateTime<Utc>>,
+    /// All-time totals from the previous all-time scan
+    pub previous_all_time: Option<UsageMetrics>,
+    /// Whether the latest all-time scan came out lower than the previous one
+    pub usage_decreased: bool,
+    /// Fetches that failed in a row since the last success
+    pub consecutive_failures: u32,
+    /// Storage entries the latest fetch skipped for lack of permission
+    pub skipped_permission_denied: usize,
+}
+
+/// When the metrics were last fetched and when the next automatic refresh is due
+///
+/// The single source of truth for both the refresh timer and the countdown shown
+/// in the popup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefreshSchedule {
+    last_fetch: Option<DateTime<Utc>>,
+    interval_seconds: u32,
+    next_due: Option<DateTime<Utc>>,
+}
+
+impl RefreshSchedule {
+    /// Creates a schedule with no fetch yet, so a refresh is due right away
+    #[must_use]
+    pub fn new(interval_seconds: u32) -> Self {
+        Self {
+            last_fetch: None,
+            interval_seconds,
+            next_due: None,
+        }
+    }
+
+    /// Time of the last successful fetch
+    #[must_use]
+    pub fn last_fetch(&self) -> Option<DateTime<Utc>> {
+        self.last_fetch
+    }
+
+    /// Seconds between automatic refreshes
+    #[must_use]
+    pub fn interval_seconds(&self) -> u32 {
+        self.interval_seconds
+    }
+
+    /// When the next automatic refresh is due (`None` before the first fetch)
+    #[must_use]
+    pub fn next_due(&self) -> Option<DateTime<Utc>> {
+        self.next_due
+    }
+
+    /// Record a successful fetch, starting a new interval
+    pub fn record_fetch(&mut self, at: DateTime<Utc>) {
+        self.last_fetch = Some(at);
+        self.update_next_due();
+    }
+
+    /// Change the interval, moving the deadline of the current interval at once
+    pub fn set_interval(&mut self, interval_seconds: u32) {
+        self.interval_seconds = interval_seconds;
+        self.update_next_due();
+    }
+
+    fn update_next_due(&mut self) {
+        self.next_due = self
+            .last_fetch
+            .map(|last| last + chrono::Duration::seconds(i64::from(self.interval_seconds)));
+    }
+
+    /// Seconds left until the next refresh
+    ///
+    /// Returns `None` before the first fetch and 0 once the refresh is due.
+    #[must_use]
+    pub fn seconds_until_next(&self, now: DateTime<Utc>) -> Option<u64> {
+        let due = self.next_due?;
+        Some(u64::try_from((due - now).num_seconds()).unwrap_or(0))
+    }
+
+    /// Whether an automatic refresh is due (always before the first fetch)
+    #[must_use]
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        match self.next_due {
+            None => true,
+            Some(due) => now >= due,
+        }
+    }
+}
+
+/// Whether `current` is lower than `previous` in tokens, cost or interactions
+///
+/// Costs within a millionth of a dollar count as equal, since summing in a different
+/// order can shift the last digits.
+#[must_use]
+pub fn usage_decreased(previous: &UsageMetrics, current: &UsageMetrics) -> bool {
+    current.total_tokens() < previous.total_tokens()
+        || current.total_cost < previous.total_cost - 1e-6
+        || current.interaction_count < previous.interaction_count
 }
 
 impl AppState {
     /// Creates a new `AppState` with Loading state
     #[must_use]
     pub fn new(config: AppConfig) -> Self {
-        let display_mode = config.display_mode;
+        // The primary period, if set, takes precedence over the last-used one
+        let display_mode = config.primary_period.unwrap_or(config.display_mode);
         AppState {
             panel_state: PanelState::Loading,
-            last_update: None,
+            refresh_schedule: RefreshSchedule::new(config.refresh_interval_seconds),
             config,
             display_mode,
             today_usage: None,
             month_usage: None,
             last_month_usage: None,
+            has_reasoning_history: None,
+            refresh_baseline: None,
+            refresh_delta: None,
+            manual_refresh_pending: false,
+            refreshed_at: None,
+            previous_all_time: None,
+            usage_decreased: false,
+            consecutive_failures: 0,
+            skipped_permission_denied: 0,
         }
     }
 
+    /// Whether the period currently shown is the configured primary period
+    #[must_use]
+    pub fn is_primary_period(&self) -> bool {
+        self.config.primary_period == Some(self.display_mode)
+    }
+
     /// Sets state to loading, preserving existing data if available
     pub fn set_loading(&mut self) {
-        // If we have existing data, preserve it during loading
+        // If we have existing data, preserve it during loading.
+        // An error has no data to keep, so it is cleared right away (e.g. on retry).
         if let Some(usage) = self.panel_state.get_usage() {
             self.panel_state = PanelState::LoadingWithData(usage.clone());
         } else {
@@ -115,15 +241,74 @@ impl AppState {
     }
 
     /// Updates state with successful data fetch
+    ///
+    /// Also records what changed since the previous successful fetch in the same mode.
     pub fn update_success(&mut self, usage: UsageMetrics) {
+        self.refresh_delta = self
+            .refresh_baseline
+            .as_ref()
+            .map(|previous| usage.subtract(previous));
+        self.refresh_baseline = Some(usage.clone());
         self.panel_state = PanelState::Success(usage);
-        self.last_update = Some(Utc::now());
+        let now = Utc::now();
+        self.refresh_schedule.record_fetch(now);
+        self.consecutive_failures = 0;
+        if self.manual_refresh_pending {
+            self.manual_refresh_pending = false;
+            self.refreshed_at = Some(now);
+        }
+    }
+
+    /// Mark the next fetch as user-requested so its success is confirmed visually
+    pub fn begin_manual_refresh(&mut self) {
+        self.manual_refresh_pending = true;
+        self.refreshed_at = None;
+    }
+
+    /// Whether the "refreshed" confirmation should be shown
+    #[must_use]
+    pub fn show_refreshed_flash(&self) -> bool {
+        self.refreshed_at.is_some()
+    }
+
+    /// Hide the "refreshed" confirmation once it has been visible long enough
+    pub fn clear_expired_refreshed_flash(&mut self, now: DateTime<Utc>) {
+        if self.refreshed_at.is_some_and(|at| {
+            now - at >= chrono::Duration::seconds(i64::from(REFRESHED_FLASH_SECONDS))
+        }) {
+            self.refreshed_at = None;
+        }
+    }
+
+    /// Forget the previous fetch so the next one starts a new baseline (e.g. on mode switch)
+    pub fn reset_refresh_baseline(&mut self) {
+        self.refresh_baseline = None;
+        self.refresh_delta = None;
     }
 
     /// Updates state with error
     pub fn update_error(&mut self, error: String) {
         self.panel_state = PanelState::Error(error);
-        // Don't update last_update timestamp on error
+        self.manual_refresh_pending = false;
+        self.refreshed_at = None;
+        // Don't record a fetch on error, so the refresh stays due
+    }
+
+    /// Handle a failed fetch, keeping the last data as stale within the grace period
+    ///
+    /// Up to `error_grace_failures` failures in a row only mark the data as stale, so
+    /// a momentary hiccup (e.g. a directory away during a sync) does not flash an
+    /// error. Without data to keep, the error is shown right away.
+    pub fn record_fetch_failure(&mut self, error: String) {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        let within_grace = self.consecutive_failures <= self.config.error_grace_failures;
+        if within_grace && self.panel_state.get_usage().is_some() {
+            self.manual_refresh_pending = false;
+            self.refreshed_at = None;
+            self.mark_stale();
+        } else {
+            self.update_error(error);
+        }
     }
 
     /// Marks current data as stale
@@ -133,16 +318,30 @@ impl AppState {
         }
     }
 
-    /// Checks if data should be refreshed based on last update time and config interval
+    /// Timestamp of the last successful data update
+    #[must_use]
+    pub fn last_update(&self) -> Option<DateTime<Utc>> {
+        self.refresh_schedule.last_fetch()
+    }
+
+    /// Apply the configured refresh interval to the schedule (after a config change)
+    pub fn sync_refresh_interval(&mut self) {
+        self.refresh_schedule
+            .set_interval(self.config.refresh_interval_seconds);
+    }
+
+    /// Checks if data should be refreshed based on the refresh schedule
     #[must_use]
     pub fn needs_refresh(&self) -> bool {
-        match self.last_update {
-            None => true,
-            Some(last) => {
-                let elapsed = Utc::now() - last;
-                elapsed > chrono::Duration::seconds(i64::from(self.config.refresh_interval_seconds))
-            }
-        }
+        self.refresh_schedule.is_due(Utc::now())
+    }
+
+    /// Seconds left until the data is due for an automatic refresh
+    ///
+    /// Returns `None` before the first successful update and 0 once the refresh is due.
+    #[must_use]
+    pub fn seconds_until_refresh(&self, now: DateTime<Utc>) -> Option<u64> {
+        self.refresh_schedule.seconds_until_next(now)
     }
 
     /// Checks if the application configuration is valid
@@ -151,6 +350,58 @@ impl AppState {
         self.config.validate().is_ok()
     }
 
+    /// Record whether reasoning tokens appear in fetched metrics
+    ///
+    /// Any period with reasoning tokens proves the all-time total is nonzero. A zero
+    /// total is only conclusive for all-time metrics. The result is cached across
+    /// display modes and, once reasoning tokens were seen, only ever stays true.
+    pub fn record_reasoning_presence(&mut self, usage: &UsageMetrics, is_all_time: bool) {
+        if usage.total_reasoning_tokens > 0 {
+            self.has_reasoning_history = Some(true);
+        } else if is_all_time && self.has_reasoning_history.is_none() {
+            self.has_reasoning_history = Some(false);
+        }
+    }
+
+    /// Compare an all-time scan with the previous one and remember it
+    ///
+    /// All-time totals only grow in normal use, so a drop in tokens, cost or
+    /// interactions usually means usage files were removed.
+    pub fn record_all_time_total(&mut self, usage: &UsageMetrics) {
+        self.usage_decreased = self
+            .previous_all_time
+            .as_ref()
+            .is_some_and(|previous| usage_decreased(previous, usage));
+        self.previous_all_time = Some(usage.clone());
+    }
+
+    /// Whether to flag a decrease in the all-time totals being shown
+    #[must_use]
+    pub fn show_usage_decrease(&self) -> bool {
+        self.config.flag_usage_decrease
+            && self.display_mode == DisplayMode::AllTime
+            && self.usage_decreased
+    }
+
+    /// Whether the reasoning tokens row should be shown
+    #[must_use]
+    pub fn show_reasoning(&self) -> bool {
+        self.config
+            .reasoning_visibility
+            .should_show(self.has_reasoning_history)
+    }
+
+    /// Whether this month's cost has reached the configured monthly budget
+    ///
+    /// Always false when no budget is set or this month's usage is not cached yet.
+    #[must_use]
+    pub fn is_over_budget(&self) -> bool {
+        match (self.config.monthly_budget, &self.month_usage) {
+            (Some(budget), Some(month)) => month.total_cost >= budget,
+            _ => false,
+        }
+    }
+
     /// Update today's usage for panel display
     pub fn update_today_usage(&mut self, usage: UsageMetrics) {
         self.today_usage = Some(usage);
@@ -196,6 +447,7 @@ mod tests {
             total_cache_write_tokens: 100,
             total_cache_read_tokens: 50,
             total_cost: 0.15,
+            total_cache_cost: 0.0,
             interaction_count: 5,
             timestamp: SystemTime::now(),
         }
@@ -208,6 +460,7 @@ mod tests {
             panel_metrics: vec![PanelMetric::Cost],
             use_raw_token_display: false,
             display_mode: DisplayMode::Today,
+            ..AppConfig::default()
         }
     }
 
@@ -218,7 +471,7 @@ mod tests {
         let state = AppState::new(config.clone());
 
         assert!(state.panel_state.is_loading());
-        assert_eq!(state.last_update, None);
+        assert_eq!(state.last_update(), None);
         assert_eq!(state.config, config);
     }
 
@@ -247,7 +500,7 @@ mod tests {
 
         assert!(matches!(state.panel_state, PanelState::Success(_)));
         assert_eq!(state.panel_state.get_usage(), Some(&usage));
-        assert!(state.last_update.is_some());
+        assert!(state.last_update().is_some());
     }
 
     #[test]
@@ -263,7 +516,7 @@ mod tests {
             _ => panic!("Expected Error state"),
         }
         // last_update should remain None (no successful update yet)
-        assert_eq!(state.last_update, None);
+        assert_eq!(state.last_update(), None);
     }
 
     #[test]
@@ -309,11 +562,75 @@ mod tests {
         let mut state = AppState::new(config);
 
         // Manually set old timestamp (16 minutes ago, beyond 15 min interval)
-        state.last_update = Some(Utc::now() - chrono::Duration::seconds(960));
+        state
+            .refresh_schedule
+            .record_fetch(Utc::now() - chrono::Duration::seconds(960));
 
         assert!(state.needs_refresh());
     }
 
+    #[test]
+    fn test_seconds_until_refresh() {
+        let config = create_mock_config(); // 900 seconds (15 min) interval
+        let mut state = AppState::new(config);
+        let now = Utc::now();
+
+        assert_eq!(state.seconds_until_refresh(now), None);
+
+        state
+            .refresh_schedule
+            .record_fetch(now - chrono::Duration::seconds(300));
+        assert_eq!(state.seconds_until_refresh(now), Some(600));
+
+        // A shorter interval moves the deadline closer straight away
+        state.config.refresh_interval_seconds = 360;
+        state.sync_refresh_interval();
+        assert_eq!(state.seconds_until_refresh(now), Some(60));
+
+        // Past the deadline the refresh is due
+        state.config.refresh_interval_seconds = 120;
+        state.sync_refresh_interval();
+        assert_eq!(state.seconds_until_refresh(now), Some(0));
+    }
+
+    #[test]
+    fn test_refresh_schedule_math() {
+        let start = Utc::now();
+        let mut schedule = RefreshSchedule::new(60);
+
+        // Nothing fetched yet: due at once, no countdown
+        assert!(schedule.is_due(start));
+        assert_eq!(schedule.next_due(), None);
+        assert_eq!(schedule.seconds_until_next(start), None);
+
+        schedule.record_fetch(start);
+        assert_eq!(schedule.last_fetch(), Some(start));
+        assert_eq!(
+            schedule.next_due(),
+            Some(start + chrono::Duration::seconds(60))
+        );
+        assert!(!schedule.is_due(start + chrono::Duration::seconds(59)));
+        assert_eq!(
+            schedule.seconds_until_next(start + chrono::Duration::seconds(15)),
+            Some(45)
+        );
+        assert!(schedule.is_due(start + chrono::Duration::seconds(60)));
+
+        // A longer interval pushes the current deadline back
+        schedule.set_interval(300);
+        assert_eq!(schedule.interval_seconds(), 300);
+        assert!(!schedule.is_due(start + chrono::Duration::seconds(60)));
+        assert_eq!(
+            schedule.seconds_until_next(start + chrono::Duration::seconds(60)),
+            Some(240)
+        );
+
+        // A new fetch starts a new interval
+        let later = start + chrono::Duration::seconds(400);
+        schedule.record_fetch(later);
+        assert_eq!(schedule.seconds_until_next(later), Some(300));
+    }
+
     #[test]
     fn test_is_initialized_valid_config() {
         let config = create_mock_config();
@@ -330,6 +647,7 @@ mod tests {
             panel_metrics: vec![PanelMetric::Cost],
             use_raw_token_display: false,
             display_mode: DisplayMode::Today,
+            ..AppConfig::default()
         };
         let state = AppState::new(invalid_config);
 
@@ -360,6 +678,25 @@ mod tests {
         assert_eq!(state2.display_mode, DisplayMode::AllTime);
     }
 
+    #[test]
+    fn test_primary_period_selects_initial_mode() {
+        let mut config = create_mock_config();
+        config.display_mode = DisplayMode::Today;
+        config.primary_period = Some(DisplayMode::Month);
+
+        let state = AppState::new(config);
+        assert_eq!(state.display_mode, DisplayMode::Month);
+        assert!(state.is_primary_period());
+
+        // Without a primary period the last-used mode is kept and nothing is emphasized
+        let mut config = create_mock_config();
+        config.display_mode = DisplayMode::AllTime;
+
+        let state = AppState::new(config);
+        assert_eq!(state.display_mode, DisplayMode::AllTime);
+        assert!(!state.is_primary_period());
+    }
+
     #[test]
     fn test_display_mode_month_variant_exists() {
         let mode = DisplayMode::Month;
@@ -538,4 +875,272 @@ mod tests {
         let error = PanelState::Error("test".to_string());
         assert_eq!(error.get_usage(), None);
     }
+
+    #[test]
+    fn test_error_shown_only_after_grace_failures() {
+        let mut state = AppState::new(AppConfig {
+            error_grace_failures: 2,
+            ..create_mock_config()
+        });
+        let usage = create_mock_usage_metrics();
+        state.update_success(usage.clone());
+
+        for failures in 1..=2 {
+            state.set_loading();
+            state.record_fetch_failure("Directory unavailable".to_string());
+            assert_eq!(state.consecutive_failures, failures);
+            assert!(matches!(state.panel_state, PanelState::Stale(_)));
+            assert_eq!(state.panel_state.get_usage(), Some(&usage));
+        }
+
+        state.set_loading();
+        state.record_fetch_failure("Directory unavailable".to_string());
+        assert!(state.panel_state.is_error());
+
+        // A success starts the grace period over
+        state.update_success(usage);
+        assert_eq!(state.consecutive_failures, 0);
+        state.record_fetch_failure("Directory unavailable".to_string());
+        assert!(matches!(state.panel_state, PanelState::Stale(_)));
+    }
+
+    #[test]
+    fn test_fetch_failure_without_grace_or_data_is_an_error() {
+        let mut state = AppState::new(create_mock_config());
+        state.update_success(create_mock_usage_metrics());
+        state.record_fetch_failure("Failed to read".to_string());
+        assert!(state.panel_state.is_error());
+
+        let mut fresh = AppState::new(AppConfig {
+            error_grace_failures: 3,
+            ..create_mock_config()
+        });
+        fresh.record_fetch_failure("Failed to read".to_string());
+        assert!(fresh.panel_state.is_error());
+    }
+
+    #[test]
+    fn test_set_loading_clears_error() {
+        let mut state = AppState::new(create_mock_config());
+        state.update_error("Failed to read".to_string());
+        assert!(state.panel_state.is_error());
+
+        state.set_loading();
+
+        assert!(matches!(state.panel_state, PanelState::Loading));
+        assert!(!state.panel_state.is_error());
+    }
+
+    #[test]
+    fn test_reasoning_presence_auto_hides_when_all_time_zero() {
+        use crate::core::config::ReasoningVisibility;
+
+        let mut state = AppState::new(AppConfig {
+            reasoning_visibility: ReasoningVisibility::Auto,
+            ..create_mock_config()
+        });
+        let mut usage = create_mock_usage_metrics();
+        usage.total_reasoning_tokens = 0;
+
+        // Unknown until determined
+        assert!(state.show_reasoning());
+
+        // A zero period total is not conclusive
+        state.record_reasoning_presence(&usage, false);
+        assert_eq!(state.has_reasoning_history, None);
+        assert!(state.show_reasoning());
+
+        // A zero all-time total hides the row
+        state.record_reasoning_presence(&usage, true);
+        assert_eq!(state.has_reasoning_history, Some(false));
+        assert!(!state.show_reasoning());
+    }
+
+    #[test]
+    fn test_reasoning_presence_auto_shows_when_nonzero() {
+        use crate::core::config::ReasoningVisibility;
+
+        let mut state = AppState::new(AppConfig {
+            reasoning_visibility: ReasoningVisibility::Auto,
+            ..create_mock_config()
+        });
+        let mut usage = create_mock_usage_metrics();
+        usage.total_reasoning_tokens = 42;
+
+        state.record_reasoning_presence(&usage, false);
+        assert_eq!(state.has_reasoning_history, Some(true));
+        assert!(state.show_reasoning());
+    }
+
+    #[test]
+    fn test_reasoning_presence_today_zero_keeps_all_time_nonzero() {
+        use crate::core::config::ReasoningVisibility;
+
+        let mut state = AppState::new(AppConfig {
+            reasoning_visibility: ReasoningVisibility::Auto,
+            ..create_mock_config()
+        });
+        let mut all_time = create_mock_usage_metrics();
+        all_time.total_reasoning_tokens = 42;
+        let mut today = create_mock_usage_metrics();
+        today.total_reasoning_tokens = 0;
+
+        state.record_reasoning_presence(&all_time, true);
+
+        // Switching to Today with no reasoning tokens today keeps the row
+        state.display_mode = DisplayMode::Today;
+        state.record_reasoning_presence(&today, false);
+        assert_eq!(state.has_reasoning_history, Some(true));
+        assert!(state.show_reasoning());
+
+        // A later all-time total of zero does not flip it back
+        state.record_reasoning_presence(&today, true);
+        assert_eq!(state.has_reasoning_history, Some(true));
+        assert!(state.show_reasoning());
+    }
+
+    #[test]
+    fn test_reasoning_presence_flips_to_true_after_zero() {
+        let mut state = AppState::new(create_mock_config());
+        let mut usage = create_mock_usage_metrics();
+        usage.total_reasoning_tokens = 0;
+        state.record_reasoning_presence(&usage, true);
+        assert_eq!(state.has_reasoning_history, Some(false));
+
+        usage.total_reasoning_tokens = 1;
+        state.record_reasoning_presence(&usage, false);
+        assert_eq!(state.has_reasoning_history, Some(true));
+    }
+
+    #[test]
+    fn test_is_over_budget() {
+        let mut state = AppState::new(create_mock_config());
+        state.update_month_usage(create_mock_usage_metrics());
+        assert!(!state.is_over_budget(), "No budget configured");
+
+        state.config.monthly_budget = Some(1.0);
+        assert!(!state.is_over_budget());
+
+        state.config.monthly_budget = Some(0.15);
+        assert!(state.is_over_budget(), "Reaching the budget counts as over");
+
+        state.clear_month_usage();
+        assert!(!state.is_over_budget(), "Unknown month usage");
+    }
+
+    #[test]
+    fn test_refresh_delta_since_last_fetch() {
+        let mut state = AppState::new(create_mock_config());
+        state.update_success(create_mock_usage_metrics());
+        assert!(state.refresh_delta.is_none(), "First fetch has no baseline");
+
+        let mut newer = create_mock_usage_metrics();
+        newer.total_cost += 0.12;
+        newer.interaction_count += 3;
+        newer.total_input_tokens += 400;
+        state.update_success(newer);
+
+        let delta = state
+            .refresh_delta
+            .as_ref()
+            .expect("Delta after second fetch");
+        assert!((delta.cost - 0.12).abs() < 1e-9);
+        assert_eq!(delta.interactions, 3);
+        assert_eq!(delta.input_tokens, 400);
+        assert_eq!(delta.output_tokens, 0);
+    }
+
+    #[test]
+    fn test_refresh_baseline_reset_on_mode_switch() {
+        let mut state = AppState::new(create_mock_config());
+        state.update_success(create_mock_usage_metrics());
+        state.update_success(create_mock_usage_metrics());
+        assert!(state.refresh_delta.is_some());
+
+        state.reset_refresh_baseline();
+        assert!(state.refresh_delta.is_none());
+
+        // The first fetch in the new mode only sets the baseline
+        let mut month = create_mock_usage_metrics();
+        month.total_cost = 42.0;
+        state.update_success(month);
+        assert!(state.refresh_delta.is_none());
+    }
+
+    #[test]
+    fn test_refreshed_flash_set_and_cleared() {
+        let mut state = AppState::new(create_mock_config());
+
+        // Automatic refreshes show no confirmation
+        state.update_success(create_mock_usage_metrics());
+        assert!(!state.show_refreshed_flash());
+
+        state.begin_manual_refresh();
+        state.update_success(create_mock_usage_metrics());
+        assert!(state.show_refreshed_flash());
+        assert!(!state.manual_refresh_pending);
+
+        // Still visible before the duration has elapsed
+        let shown_at = state.refreshed_at.unwrap();
+        state.clear_expired_refreshed_flash(shown_at + chrono::Duration::milliseconds(500));
+        assert!(state.show_refreshed_flash());
+
+        state.clear_expired_refreshed_flash(
+            shown_at + chrono::Duration::seconds(i64::from(REFRESHED_FLASH_SECONDS)),
+        );
+        assert!(!state.show_refreshed_flash());
+    }
+
+    #[test]
+    fn test_refreshed_flash_not_shown_after_failed_manual_refresh() {
+        let mut state = AppState::new(create_mock_config());
+
+        state.begin_manual_refresh();
+        state.update_error("Failed to read".to_string());
+        assert!(!state.manual_refresh_pending);
+
+        // A later automatic success is not mistaken for the manual one
+        state.update_success(create_mock_usage_metrics());
+        assert!(!state.show_refreshed_flash());
+    }
+
+    #[test]
+    fn test_all_time_decrease_detection() {
+        let usage = |tokens: u64, cost: f64| UsageMetrics {
+            total_input_tokens: tokens,
+            total_output_tokens: 0,
+            total_reasoning_tokens: 0,
+            total_cache_write_tokens: 0,
+            total_cache_read_tokens: 0,
+            total_cost: cost,
+            total_cache_cost: 0.0,
+            interaction_count: 5,
+            timestamp: std::time::SystemTime::now(),
+        };
+        let mut state = AppState::new(AppConfig {
+            display_mode: DisplayMode::AllTime,
+            flag_usage_decrease: true,
+            ..AppConfig::default()
+        });
+
+        // The first scan has nothing to compare with
+        state.record_all_time_total(&usage(1_000, 2.0));
+        assert!(!state.show_usage_decrease());
+
+        state.record_all_time_total(&usage(1_500, 2.5));
+        assert!(!state.show_usage_decrease());
+
+        state.record_all_time_total(&usage(1_200, 2.5));
+        assert!(state.show_usage_decrease());
+        assert!(usage_decreased(&usage(1_000, 2.0), &usage(1_000, 1.5)));
+
+        // Only shown when enabled
+        state.config.flag_usage_decrease = false;
+        assert!(!state.show_usage_decrease());
+        state.config.flag_usage_decrease = true;
+
+        // Growing again from the lower total clears the flag
+        state.record_all_time_total(&usage(1_300, 2.6));
+        assert!(!state.show_usage_decrease());
+    }
 }
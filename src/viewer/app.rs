@@ -2,13 +2,22 @@
 
 //! Viewer application core logic and COSMIC Application trait implementation.
 
+use crate::core::collector::DataCollector;
+use crate::core::config::{AppConfig, CentralTendency, ChartPalette};
 use crate::core::database::{
-    repository::{UsageRepository, WeekSummary},
-    DatabaseManager,
+    repository::{UsageRepository, UsageSnapshot, WeekSummary},
+    DatabaseError, DatabaseManager,
 };
+use crate::viewer::diff::SnapshotDiff;
+use crate::viewer::export::{
+    default_export_path, save_png, ExportTarget, DEFAULT_EXPORT_HEIGHT, DEFAULT_EXPORT_WIDTH,
+};
+use crate::viewer::range::{initial_range_preset, parse_range_presets, RangePreset};
+use crate::viewer::verify::FreshRead;
 use chrono::{Datelike, NaiveDate};
 use cosmic::{app::Core, Application, Element};
 use image::RgbaImage;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 /// Messages that can be sent within the viewer application.
@@ -16,13 +25,47 @@ use std::sync::Arc;
 pub enum Message {
     /// Exit the application
     Exit,
+    /// Update the first date (YYYY-MM-DD) of the snapshot diff
+    DiffFromChanged(String),
+    /// Update the second date (YYYY-MM-DD) of the snapshot diff
+    DiffToChanged(String),
+    /// Copy the usage database path to the clipboard
+    CopyDatabasePath,
+    /// Open the folder containing the usage database in the file manager
+    OpenDatabaseFolder,
+    /// Recompute today and this month directly from the usage files
+    ForceReread,
+    /// Result of re-reading the usage files
+    FreshReadLoaded(Result<FreshRead, String>),
+    /// Show the history charts for a quick-range preset
+    SelectRange(RangePreset),
+    /// Return the charts and history table to the view the viewer opens with
+    ResetToToday,
+    /// Fill the missing dates of the selected range from the usage files
+    BackfillMissing,
+    /// Result of a backfill: how many dates were filled
+    BackfillFinished(Result<usize, String>),
+    /// Show a page of the history table (0-based)
+    HistoryPage(usize),
+    /// Update the file path of the chart export
+    ExportPathChanged(String),
+    /// Update the width (pixels) of the chart export
+    ExportWidthChanged(String),
+    /// Update the height (pixels) of the chart export
+    ExportHeightChanged(String),
+    /// Render the token usage chart for the selected range to a PNG file
+    ExportChart,
+    /// Result of a chart export: the written file
+    ExportFinished(Result<PathBuf, String>),
 }
 
 /// The main viewer application structure.
 pub struct ViewerApp {
     core: Core,
-    database_manager: Arc<DatabaseManager>,
-    repository: Arc<UsageRepository>,
+    /// Database manager (`None` when history is disabled in the applet settings)
+    database_manager: Option<Arc<DatabaseManager>>,
+    /// Usage repository (`None` when history is disabled in the applet settings)
+    repository: Option<Arc<UsageRepository>>,
     /// This week's summary data (pre-loaded)
     this_week: Option<WeekSummary>,
     /// Last week's summary data (pre-loaded)
@@ -31,8 +74,72 @@ pub struct ViewerApp {
     this_week_start: NaiveDate,
     /// Start date of last week
     last_week_start: NaiveDate,
-    /// Pre-rendered chart image (generated once, cached)
+    /// Quick-range presets offered above the history charts
+    range_presets: Vec<RangePreset>,
+    /// Preset the history charts currently cover
+    selected_range: RangePreset,
+    /// Dates in the selected range with no recorded snapshot
+    missing_dates: Vec<NaiveDate>,
+    /// Dates of snapshots from older versions that may hold display totals
+    legacy_dates: Vec<NaiveDate>,
+    /// Whether missing dates are being filled from the usage files
+    backfill_running: bool,
+    /// Outcome of the last backfill
+    backfill_status: Option<String>,
+    /// Pre-rendered chart image (regenerated when the range changes)
     chart_image: RgbaImage,
+    /// Pre-rendered cost per 1k tokens chart image
+    efficiency_chart_image: RgbaImage,
+    /// Pre-rendered average cost per interaction chart for the selected range
+    cost_per_interaction_chart_image: RgbaImage,
+    /// Total cost per day of the week (Monday first)
+    weekday_costs: [f64; 7],
+    /// Pre-rendered cost by weekday chart image
+    weekday_chart_image: RgbaImage,
+    /// Series colors used when rendering the charts
+    chart_palette: ChartPalette,
+    /// Divide daily averages by active days instead of calendar days
+    average_per_active_day: bool,
+    /// Whether daily summaries show the mean or the median
+    central_tendency: CentralTendency,
+    /// Decimals shown in week-over-week percent changes
+    percent_precision: u8,
+    /// Consecutive days with usage up to today
+    streak: u32,
+    /// This week's daily snapshots (for the median)
+    this_week_snapshots: Vec<UsageSnapshot>,
+    /// Rows per page in the history table
+    history_page_size: usize,
+    /// Current page of the history table (0-based)
+    history_page: usize,
+    /// Number of stored snapshots, for the page count
+    history_total: usize,
+    /// Snapshots on the current page, newest first
+    history_rows: Vec<UsageSnapshot>,
+    /// First date input of the snapshot diff
+    diff_from_input: String,
+    /// Second date input of the snapshot diff
+    diff_to_input: String,
+    /// Diff between the snapshots of the two selected dates (`None` if a date is invalid)
+    snapshot_diff: Option<SnapshotDiff>,
+    /// File path input of the chart export
+    export_path_input: String,
+    /// Width input (pixels) of the chart export
+    export_width_input: String,
+    /// Height input (pixels) of the chart export
+    export_height_input: String,
+    /// Outcome of the last chart export
+    export_status: Option<String>,
+    /// Whether a chart export is being rendered and written
+    export_running: bool,
+    /// Why the usage database could not be opened (`None` if it opened or history is disabled)
+    database_error: Option<String>,
+    /// Whether the usage files are being re-read
+    fresh_read_loading: bool,
+    /// Last re-read of the usage files (`None` until requested)
+    fresh_read: Option<Result<FreshRead, String>>,
+    /// Today's stored snapshot at the time of the last re-read
+    fresh_read_snapshot: Option<UsageSnapshot>,
 }
 
 impl Application for ViewerApp {
@@ -50,90 +157,601 @@ impl Application for ViewerApp {
     }
 
     fn init(mut core: Core, _flags: Self::Flags) -> (Self, cosmic::app::Task<Self::Message>) {
-        // Initialize database
-        let database_manager = match DatabaseManager::new() {
+        // Configure window title
+        core.window.header_title = "OpenCode Usage History".to_string();
+
+        let config = AppConfig::load().unwrap_or_default();
+
+        // Respect the applet's history setting: don't create a database if it is disabled
+        if !config.keeps_history() {
+            eprintln!("History is disabled, not opening the usage database");
+            return (
+                Self::without_database(core, &config, None),
+                cosmic::app::Task::none(),
+            );
+        }
+
+        // Opening creates the database, so a viewer started before the applet ever ran starts empty
+        let opened = DatabaseManager::new_or_in_memory(config.data_namespace.as_deref());
+        (
+            Self::with_database(core, &config, opened),
+            cosmic::app::Task::none(),
+        )
+    }
+
+    fn update(&mut self, message: Self::Message) -> cosmic::app::Task<Self::Message> {
+        match message {
+            Message::Exit => {
+                // Close the window by returning exit task
+                std::process::exit(0);
+            }
+            Message::DiffFromChanged(value) => {
+                self.diff_from_input = value;
+                self.refresh_snapshot_diff();
+                cosmic::app::Task::none()
+            }
+            Message::DiffToChanged(value) => {
+                self.diff_to_input = value;
+                self.refresh_snapshot_diff();
+                cosmic::app::Task::none()
+            }
+            Message::CopyDatabasePath => match self.database_path_text() {
+                Some(path) => cosmic::iced::clipboard::write(path),
+                None => cosmic::app::Task::none(),
+            },
+            Message::ForceReread => {
+                if self.fresh_read_loading {
+                    return cosmic::app::Task::none();
+                }
+                self.fresh_read_loading = true;
+
+                // Re-read the config so the current storage path is used
+                let config = AppConfig::load().unwrap_or_default();
+                cosmic::Task::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || {
+                            crate::viewer::verify::read_fresh(&config)
+                        })
+                        .await
+                        .unwrap_or_else(|e| Err(format!("Re-read task failed: {e}")))
+                    },
+                    |result| cosmic::Action::App(Message::FreshReadLoaded(result)),
+                )
+            }
+            Message::SelectRange(preset) => {
+                self.load_range(preset);
+                cosmic::app::Task::none()
+            }
+            Message::ResetToToday => {
+                self.load_range(initial_range_preset(&self.range_presets));
+                self.load_history_page(0);
+                cosmic::app::Task::none()
+            }
+            Message::BackfillMissing => {
+                let Some(database_manager) = self.database_manager.clone() else {
+                    return cosmic::app::Task::none();
+                };
+                if self.backfill_running
+                    || (self.missing_dates.is_empty() && self.legacy_dates.is_empty())
+                {
+                    return cosmic::app::Task::none();
+                }
+                self.backfill_running = true;
+                self.backfill_status = None;
+
+                // Legacy snapshots are recomputed along with the gaps
+                let dates: Vec<NaiveDate> = self
+                    .missing_dates
+                    .iter()
+                    .chain(&self.legacy_dates)
+                    .copied()
+                    .collect();
+                let config = AppConfig::load().unwrap_or_default();
+                cosmic::Task::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || {
+                            let mut reader = crate::viewer::verify::reader_for_config(&config)?;
+                            DataCollector::new(database_manager)
+                                .backfill_dates(&mut reader, &dates)
+                                .map_err(|e| e.to_string())
+                        })
+                        .await
+                        .unwrap_or_else(|e| Err(format!("Backfill task failed: {e}")))
+                    },
+                    |result| cosmic::Action::App(Message::BackfillFinished(result)),
+                )
+            }
+            Message::BackfillFinished(result) => {
+                self.backfill_running = false;
+                let missing = self.missing_dates.len() + self.legacy_dates.len();
+                self.backfill_status = Some(match result {
+                    Ok(filled) => format!("Filled {filled} of {missing} days from usage files"),
+                    Err(e) => format!("Backfill failed: {e}"),
+                });
+                self.load_range(self.selected_range);
+                self.load_history_page(self.history_page);
+                if let Some(repository) = &self.repository {
+                    self.legacy_dates = repository.legacy_dates().unwrap_or_default();
+                    self.streak = repository
+                        .current_streak(chrono::Utc::now().date_naive())
+                        .unwrap_or(0);
+                }
+                cosmic::app::Task::none()
+            }
+            Message::HistoryPage(page) => {
+                self.load_history_page(page);
+                cosmic::app::Task::none()
+            }
+            Message::ExportPathChanged(value) => {
+                self.export_path_input = value;
+                cosmic::app::Task::none()
+            }
+            Message::ExportWidthChanged(value) => {
+                self.export_width_input = value;
+                cosmic::app::Task::none()
+            }
+            Message::ExportHeightChanged(value) => {
+                self.export_height_input = value;
+                cosmic::app::Task::none()
+            }
+            Message::ExportChart => self.export_chart(),
+            Message::ExportFinished(result) => {
+                self.export_running = false;
+                self.export_status = Some(match result {
+                    Ok(path) => format!("Exported chart to {}", path.display()),
+                    Err(e) => e,
+                });
+                cosmic::app::Task::none()
+            }
+            Message::FreshReadLoaded(result) => {
+                self.fresh_read_loading = false;
+                let today = chrono::Utc::now().date_naive();
+                self.fresh_read_snapshot = self
+                    .repository
+                    .as_ref()
+                    .and_then(|repository| repository.get_snapshot(today).ok().flatten());
+                self.fresh_read = Some(result);
+                cosmic::app::Task::none()
+            }
+            Message::OpenDatabaseFolder => {
+                if let Some(manager) = &self.database_manager {
+                    let folder = crate::core::desktop::containing_folder(manager.path());
+                    if let Err(e) = crate::core::desktop::open_folder(&folder) {
+                        eprintln!("Failed to open {}: {e}", folder.display());
+                    }
+                }
+                cosmic::app::Task::none()
+            }
+        }
+    }
+
+    fn header_end(&self) -> Vec<Element<'_, Self::Message>> {
+        use cosmic::widget::button;
+
+        if self.database_manager.is_none() {
+            return Vec::new();
+        }
+
+        vec![
+            button::standard("Copy DB Path")
+                .on_press(Message::CopyDatabasePath)
+                .into(),
+            button::standard("Open DB Folder")
+                .on_press(Message::OpenDatabaseFolder)
+                .into(),
+        ]
+    }
+
+    fn view(&self) -> Element<'_, Self::Message> {
+        if let Some(error) = &self.database_error {
+            return crate::viewer::ui::view_database_error(error);
+        }
+        if !self.history_enabled() {
+            return crate::viewer::ui::view_history_disabled();
+        }
+        crate::viewer::ui::view_content(
+            self.this_week.clone(),
+            self.last_week.clone(),
+            (self.this_week_start, self.last_week_start),
+            crate::viewer::ui::HistoryCharts {
+                presets: &self.range_presets,
+                selected: self.selected_range,
+                missing_dates: &self.missing_dates,
+                legacy_dates: &self.legacy_dates,
+                backfill_running: self.backfill_running,
+                backfill_status: self.backfill_status.as_deref(),
+                history_empty: self.history_is_empty(),
+                token_chart: &self.chart_image,
+                efficiency_chart: &self.efficiency_chart_image,
+                cost_per_interaction_chart: &self.cost_per_interaction_chart_image,
+            },
+            crate::viewer::ui::DailySummaryOptions {
+                per_active_day: self.average_per_active_day,
+                central_tendency: self.central_tendency,
+                daily_snapshots: &self.this_week_snapshots,
+            },
+            self.percent_precision,
+            cosmic::widget::column()
+                .push_maybe(
+                    crate::viewer::ui::format_streak(self.streak)
+                        .map(|streak| cosmic::widget::text(streak).size(16)),
+                )
+                .push(crate::viewer::ui::view_chart_export(
+                    &self.export_path_input,
+                    &self.export_width_input,
+                    &self.export_height_input,
+                    self.export_status.as_deref(),
+                    self.export_running,
+                ))
+                .push(crate::viewer::ui::view_weekday_costs(
+                    &self.weekday_chart_image,
+                    &self.weekday_costs,
+                ))
+                .push(crate::viewer::ui::view_snapshot_diff(
+                    &self.diff_from_input,
+                    &self.diff_to_input,
+                    self.snapshot_diff.as_ref(),
+                ))
+                .push(crate::viewer::ui::view_history_table(
+                    &self.history_rows,
+                    self.history_page,
+                    crate::viewer::ui::page_count(self.history_total, self.history_page_size),
+                ))
+                .push(crate::viewer::ui::view_fresh_read(
+                    self.fresh_read_loading,
+                    self.fresh_read.as_ref(),
+                    self.fresh_read_snapshot.as_ref(),
+                ))
+                .spacing(20)
+                .align_x(cosmic::iced::Alignment::Center)
+                .into(),
+        )
+    }
+}
+
+impl ViewerApp {
+    /// Builds the viewer without a usage database: history is disabled, or it failed to open.
+    fn without_database(core: Core, config: &AppConfig, database_error: Option<String>) -> Self {
+        let today = chrono::Utc::now().date_naive();
+        let this_week_start = Self::get_week_start(today);
+        let last_week_start = this_week_start - chrono::Duration::days(7);
+
+        Self {
+            core,
+            database_manager: None,
+            repository: None,
+            this_week: None,
+            last_week: None,
+            this_week_start,
+            last_week_start,
+            range_presets: Vec::new(),
+            selected_range: initial_range_preset(&[]),
+            missing_dates: Vec::new(),
+            legacy_dates: Vec::new(),
+            backfill_running: false,
+            backfill_status: None,
+            chart_image: crate::viewer::charts::generate_token_usage_chart(&[], 800, 400),
+            efficiency_chart_image: crate::viewer::charts::generate_cost_efficiency_chart(
+                &[],
+                800,
+                200,
+            ),
+            cost_per_interaction_chart_image:
+                crate::viewer::charts::generate_cost_per_interaction_chart(&[], 800, 200),
+            weekday_costs: [0.0; 7],
+            weekday_chart_image: crate::viewer::charts::generate_weekday_cost_chart(
+                &[0.0; 7], 420, 200,
+            ),
+            chart_palette: config.chart_palette,
+            average_per_active_day: config.average_per_active_day,
+            central_tendency: config.central_tendency,
+            percent_precision: config.percent_precision,
+            streak: 0,
+            this_week_snapshots: Vec::new(),
+            history_page_size: config.viewer_page_size.max(1),
+            history_page: 0,
+            history_total: 0,
+            history_rows: Vec::new(),
+            diff_from_input: String::new(),
+            diff_to_input: String::new(),
+            snapshot_diff: None,
+            export_path_input: default_export_path(),
+            export_width_input: DEFAULT_EXPORT_WIDTH.to_string(),
+            export_height_input: DEFAULT_EXPORT_HEIGHT.to_string(),
+            export_status: None,
+            export_running: false,
+            database_error,
+            fresh_read_loading: false,
+            fresh_read: None,
+            fresh_read_snapshot: None,
+        }
+    }
+
+    /// Builds the viewer on an opened database, pre-loading the data shown on first render.
+    ///
+    /// An open error is shown in the window instead of aborting the viewer.
+    pub(crate) fn with_database(
+        core: Core,
+        config: &AppConfig,
+        opened: Result<DatabaseManager, DatabaseError>,
+    ) -> Self {
+        let today = chrono::Utc::now().date_naive();
+        let this_week_start = Self::get_week_start(today);
+        let last_week_start = this_week_start - chrono::Duration::days(7);
+
+        let database_manager = match opened {
             Ok(manager) => Arc::new(manager),
             Err(e) => {
-                eprintln!("Failed to initialize database: {e}");
-                // For now, we'll panic. In production, we'd show an error dialog
-                panic!("Database initialization failed: {e}");
+                eprintln!("Failed to open the usage database: {e}");
+                return Self::without_database(core, config, Some(e.to_string()));
             }
         };
 
         // Create repository
-        let repository = Arc::new(UsageRepository::new(Arc::clone(&database_manager)));
+        let repository = Arc::new(
+            UsageRepository::new(Arc::clone(&database_manager))
+                .with_active_day_definition(config.active_day_definition),
+        );
 
         // Pre-load all data needed for view
-        let today = chrono::Utc::now().date_naive();
-        let this_week_start = Self::get_week_start(today);
-        let last_week_start = this_week_start - chrono::Duration::days(7);
-
         let this_week = repository.get_week_summary(this_week_start).ok();
         let last_week = repository.get_week_summary(last_week_start).ok();
-
-        // Load chart data for last 30 days
-        let end_date = today;
-        let start_date = today - chrono::Duration::days(30);
-        let chart_snapshots = repository
-            .get_range(start_date, end_date)
+        let this_week_snapshots = repository
+            .get_range(this_week_start, this_week_start + chrono::Duration::days(6))
             .unwrap_or_default();
 
-        // Pre-render chart image once (800x400 size)
-        let chart_image =
-            crate::viewer::charts::generate_token_usage_chart(&chart_snapshots, 800, 400);
+        // Pre-render the charts for the initial quick-range preset
+        let range_presets = parse_range_presets(&config.viewer_range_presets);
+        let selected_range = initial_range_preset(&range_presets);
+        let (start_date, end_date) = Self::preset_dates(&repository, selected_range, today);
+        let (chart_image, efficiency_chart_image, cost_per_interaction_chart_image) =
+            Self::render_range_charts(&repository, start_date, end_date, config.chart_palette);
+        let missing_dates = repository
+            .missing_dates(start_date, end_date)
+            .unwrap_or_default();
+        let legacy_dates = repository.legacy_dates().unwrap_or_default();
 
-        // Configure window title
-        core.window.header_title = "OpenCode Usage History".to_string();
+        // Weekday totals cover the whole stored history
+        let weekday_costs = repository.cost_by_weekday().unwrap_or_default();
+        let weekday_chart_image = crate::viewer::charts::generate_weekday_cost_chart_with_palette(
+            &weekday_costs,
+            420,
+            200,
+            config.chart_palette,
+        );
 
-        let app = Self {
+        let mut app = Self {
             core,
-            database_manager,
-            repository,
+            database_manager: Some(database_manager),
+            repository: Some(repository),
             this_week,
             last_week,
             this_week_start,
             last_week_start,
+            range_presets,
+            selected_range,
+            missing_dates,
+            legacy_dates,
+            backfill_running: false,
+            backfill_status: None,
             chart_image,
+            efficiency_chart_image,
+            cost_per_interaction_chart_image,
+            weekday_costs,
+            weekday_chart_image,
+            chart_palette: config.chart_palette,
+            average_per_active_day: config.average_per_active_day,
+            central_tendency: config.central_tendency,
+            percent_precision: config.percent_precision,
+            streak: repository.current_streak(today).unwrap_or(0),
+            this_week_snapshots,
+            history_page_size: config.viewer_page_size.max(1),
+            history_page: 0,
+            history_total: 0,
+            history_rows: Vec::new(),
+            diff_from_input: (today - chrono::Duration::days(1)).to_string(),
+            diff_to_input: today.to_string(),
+            snapshot_diff: None,
+            export_path_input: default_export_path(),
+            export_width_input: DEFAULT_EXPORT_WIDTH.to_string(),
+            export_height_input: DEFAULT_EXPORT_HEIGHT.to_string(),
+            export_status: None,
+            export_running: false,
+            database_error: None,
+            fresh_read_loading: false,
+            fresh_read: None,
+            fresh_read_snapshot: None,
         };
+        app.refresh_snapshot_diff();
+        app.load_history_page(0);
 
-        (app, cosmic::app::Task::none())
+        app
     }
 
-    fn update(&mut self, message: Self::Message) -> cosmic::app::Task<Self::Message> {
-        match message {
-            Message::Exit => {
-                // Close the window by returning exit task
-                std::process::exit(0);
-            }
+    /// Calculates the start of the week (Monday) for a given date.
+    fn get_week_start(date: NaiveDate) -> NaiveDate {
+        let weekday = date.weekday().num_days_from_monday();
+        date - chrono::Duration::days(i64::from(weekday))
+    }
+
+    /// Loads a page of the history table, clamped to the last page.
+    ///
+    /// Only the rows of that page are queried, so long histories stay cheap.
+    fn load_history_page(&mut self, page: usize) {
+        let Some(repository) = &self.repository else {
+            return;
+        };
+        self.history_total = repository.snapshot_count().unwrap_or(0);
+        let last_page =
+            crate::viewer::ui::page_count(self.history_total, self.history_page_size) - 1;
+        self.history_page = page.min(last_page);
+        self.history_rows = repository
+            .get_page(self.history_page, self.history_page_size)
+            .unwrap_or_default();
+    }
+
+    /// Shows a quick-range preset: re-renders the charts and recomputes its data gaps.
+    fn load_range(&mut self, preset: RangePreset) {
+        let Some(repository) = &self.repository else {
+            return;
+        };
+        let today = chrono::Utc::now().date_naive();
+        let (start_date, end_date) = Self::preset_dates(repository, preset, today);
+        (
+            self.chart_image,
+            self.efficiency_chart_image,
+            self.cost_per_interaction_chart_image,
+        ) = Self::render_range_charts(repository, start_date, end_date, self.chart_palette);
+        self.missing_dates = repository
+            .missing_dates(start_date, end_date)
+            .unwrap_or_default();
+        self.selected_range = preset;
+    }
+
+    /// Renders the token usage chart for the selected range at the requested size and saves it.
+    ///
+    /// Rendering and writing run off the UI thread; an existing file is never replaced.
+    fn export_chart(&mut self) -> cosmic::app::Task<Message> {
+        if self.export_running {
+            return cosmic::app::Task::none();
         }
+        let Some(repository) = &self.repository else {
+            return cosmic::app::Task::none();
+        };
+        let target = match ExportTarget::parse(
+            &self.export_path_input,
+            &self.export_width_input,
+            &self.export_height_input,
+        ) {
+            Ok(target) => target,
+            Err(e) => {
+                self.export_status = Some(e.to_string());
+                return cosmic::app::Task::none();
+            }
+        };
+
+        let today = chrono::Utc::now().date_naive();
+        let (start_date, end_date) = Self::preset_dates(repository, self.selected_range, today);
+        let snapshots = repository
+            .get_range(start_date, end_date)
+            .unwrap_or_default();
+        let palette = self.chart_palette;
+        self.export_running = true;
+        self.export_status = None;
+
+        cosmic::Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    let image = crate::viewer::charts::generate_token_usage_chart_with_palette(
+                        &snapshots,
+                        target.width,
+                        target.height,
+                        palette,
+                    );
+                    save_png(&image, &target.path)
+                        .map(|()| target.path)
+                        .map_err(|e| e.to_string())
+                })
+                .await
+                .unwrap_or_else(|e| Err(format!("Export task failed: {e}")))
+            },
+            |result| cosmic::Action::App(Message::ExportFinished(result)),
+        )
     }
 
-    fn view(&self) -> Element<'_, Self::Message> {
-        crate::viewer::ui::view_content(
-            self.this_week.clone(),
-            self.last_week.clone(),
-            (self.this_week_start, self.last_week_start),
-            &self.chart_image,
+    /// Resolves a quick-range preset to dates, using the oldest snapshot for "All".
+    fn preset_dates(
+        repository: &UsageRepository,
+        preset: RangePreset,
+        today: NaiveDate,
+    ) -> (NaiveDate, NaiveDate) {
+        let earliest = repository
+            .get_earliest()
+            .ok()
+            .flatten()
+            .map(|snapshot| snapshot.date);
+        preset.range(today, earliest)
+    }
+
+    /// Renders the token, cost efficiency and cost per interaction charts for a date range.
+    fn render_range_charts(
+        repository: &UsageRepository,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        palette: ChartPalette,
+    ) -> (RgbaImage, RgbaImage, RgbaImage) {
+        use crate::viewer::charts::{
+            generate_cost_efficiency_chart_with_palette,
+            generate_cost_per_interaction_chart_with_palette,
+            generate_token_usage_chart_with_palette,
+        };
+
+        let snapshots = repository
+            .get_range(start_date, end_date)
+            .unwrap_or_default();
+
+        (
+            generate_token_usage_chart_with_palette(&snapshots, 800, 400, palette),
+            generate_cost_efficiency_chart_with_palette(&snapshots, 800, 200, palette),
+            generate_cost_per_interaction_chart_with_palette(&snapshots, 800, 200, palette),
         )
     }
-}
 
-impl ViewerApp {
-    /// Calculates the start of the week (Monday) for a given date.
-    fn get_week_start(date: NaiveDate) -> NaiveDate {
-        let weekday = date.weekday().num_days_from_monday();
-        date - chrono::Duration::days(i64::from(weekday))
+    /// Recomputes the snapshot diff from the two date inputs.
+    ///
+    /// The diff is cleared while either input is not a valid `YYYY-MM-DD` date.
+    fn refresh_snapshot_diff(&mut self) {
+        let Some(repository) = &self.repository else {
+            self.snapshot_diff = None;
+            return;
+        };
+
+        let parse = |s: &str| NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").ok();
+        self.snapshot_diff = match (parse(&self.diff_from_input), parse(&self.diff_to_input)) {
+            (Some(from), Some(to)) => Some(SnapshotDiff::compute(
+                repository.get_snapshot(from).ok().flatten(),
+                repository.get_snapshot(to).ok().flatten(),
+            )),
+            _ => None,
+        };
     }
-    /// Gets a reference to the database manager.
+
+    /// Gets a reference to the database manager, if history is enabled.
     #[must_use]
-    pub fn database_manager(&self) -> &Arc<DatabaseManager> {
-        &self.database_manager
+    pub fn database_manager(&self) -> Option<&Arc<DatabaseManager>> {
+        self.database_manager.as_ref()
     }
 
-    /// Gets a reference to the usage repository.
+    /// Gets a reference to the usage repository, if history is enabled.
     #[must_use]
-    pub fn repository(&self) -> &Arc<UsageRepository> {
-        &self.repository
+    pub fn repository(&self) -> Option<&Arc<UsageRepository>> {
+        self.repository.as_ref()
+    }
+
+    /// Returns the resolved database path as copied to the clipboard.
+    #[must_use]
+    pub fn database_path_text(&self) -> Option<String> {
+        self.database_manager
+            .as_ref()
+            .map(|manager| manager.path().display().to_string())
+    }
+
+    /// Returns whether usage history is available to display.
+    #[must_use]
+    pub fn history_enabled(&self) -> bool {
+        self.repository.is_some()
+    }
+
+    /// Returns whether the database is open but has no snapshots yet.
+    #[must_use]
+    pub fn history_is_empty(&self) -> bool {
+        self.history_enabled() && self.history_total == 0
+    }
+
+    /// Returns why the usage database could not be opened, if it failed.
+    #[must_use]
+    pub fn database_error(&self) -> Option<&str> {
+        self.database_error.as_deref()
     }
 
     /// Creates a new `ViewerApp` for testing purposes.
@@ -149,13 +767,52 @@ impl ViewerApp {
 
         Self {
             core,
-            database_manager,
-            repository,
+            database_manager: Some(database_manager),
+            repository: Some(repository),
             this_week: None,
             last_week: None,
             this_week_start,
             last_week_start,
+            range_presets: parse_range_presets(&AppConfig::default().viewer_range_presets),
+            selected_range: initial_range_preset(&[]),
+            missing_dates: Vec::new(),
+            legacy_dates: Vec::new(),
+            backfill_running: false,
+            backfill_status: None,
             chart_image: crate::viewer::charts::generate_token_usage_chart(&[], 800, 400),
+            efficiency_chart_image: crate::viewer::charts::generate_cost_efficiency_chart(
+                &[],
+                800,
+                200,
+            ),
+            cost_per_interaction_chart_image:
+                crate::viewer::charts::generate_cost_per_interaction_chart(&[], 800, 200),
+            weekday_costs: [0.0; 7],
+            weekday_chart_image: crate::viewer::charts::generate_weekday_cost_chart(
+                &[0.0; 7], 420, 200,
+            ),
+            chart_palette: ChartPalette::default(),
+            average_per_active_day: false,
+            central_tendency: CentralTendency::default(),
+            percent_precision: 0,
+            streak: 0,
+            this_week_snapshots: Vec::new(),
+            history_page_size: 50,
+            history_page: 0,
+            history_total: 0,
+            history_rows: Vec::new(),
+            diff_from_input: String::new(),
+            diff_to_input: String::new(),
+            snapshot_diff: None,
+            export_path_input: default_export_path(),
+            export_width_input: DEFAULT_EXPORT_WIDTH.to_string(),
+            export_height_input: DEFAULT_EXPORT_HEIGHT.to_string(),
+            export_status: None,
+            export_running: false,
+            database_error: None,
+            fresh_read_loading: false,
+            fresh_read: None,
+            fresh_read_snapshot: None,
         }
     }
 }
@@ -193,4 +850,271 @@ mod tests {
             "com.vasilvestre.CosmicAppletOpencodeUsageViewer"
         );
     }
+
+    #[test]
+    fn test_refresh_snapshot_diff_from_inputs() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let database_manager = Arc::new(DatabaseManager::new_with_path(&db_path).unwrap());
+        let repository = Arc::new(UsageRepository::new(Arc::clone(&database_manager)));
+
+        let metrics = crate::core::opencode::UsageMetrics {
+            total_input_tokens: 1000,
+            total_output_tokens: 500,
+            total_reasoning_tokens: 0,
+            total_cache_write_tokens: 0,
+            total_cache_read_tokens: 0,
+            total_cost: 1.0,
+            total_cache_cost: 0.0,
+            interaction_count: 3,
+            timestamp: std::time::SystemTime::now(),
+        };
+        repository
+            .save_snapshot(NaiveDate::from_ymd_opt(2025, 10, 1).unwrap(), &metrics)
+            .unwrap();
+
+        let mut app = ViewerApp::new_for_test(Core::default(), database_manager, repository);
+
+        let _ = app.update(Message::DiffFromChanged("2025-10-01".to_string()));
+        assert!(app.snapshot_diff.is_none(), "Second date still empty");
+
+        let _ = app.update(Message::DiffToChanged("2025-10-02".to_string()));
+        let diff = app.snapshot_diff.as_ref().expect("Both dates are valid");
+        assert!(diff.from.is_some());
+        assert!(diff.to.is_none(), "No snapshot stored for the second date");
+        assert!(diff.delta.is_none());
+    }
+
+    #[test]
+    fn test_database_path_text() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let database_manager = Arc::new(DatabaseManager::new_with_path(&db_path).unwrap());
+        let repository = Arc::new(UsageRepository::new(Arc::clone(&database_manager)));
+
+        let app = ViewerApp::new_for_test(Core::default(), database_manager, repository);
+
+        assert_eq!(
+            app.database_path_text(),
+            Some(db_path.display().to_string())
+        );
+    }
+
+    #[test]
+    fn test_fresh_read_loaded_records_result_and_snapshot() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let database_manager = Arc::new(DatabaseManager::new_with_path(&db_path).unwrap());
+        let repository = Arc::new(UsageRepository::new(Arc::clone(&database_manager)));
+
+        let metrics = crate::core::opencode::UsageMetrics {
+            total_input_tokens: 1000,
+            total_output_tokens: 500,
+            total_reasoning_tokens: 0,
+            total_cache_write_tokens: 0,
+            total_cache_read_tokens: 0,
+            total_cost: 1.0,
+            total_cache_cost: 0.0,
+            interaction_count: 3,
+            timestamp: std::time::SystemTime::now(),
+        };
+        repository
+            .save_snapshot(chrono::Utc::now().date_naive(), &metrics)
+            .unwrap();
+
+        let mut app = ViewerApp::new_for_test(Core::default(), database_manager, repository);
+        app.fresh_read_loading = true;
+
+        let _ = app.update(Message::FreshReadLoaded(Ok(FreshRead {
+            today: metrics.clone(),
+            month: metrics,
+            sessions: None,
+        })));
+
+        assert!(!app.fresh_read_loading);
+        assert!(matches!(app.fresh_read, Some(Ok(_))));
+        assert!(app.fresh_read_snapshot.is_some());
+    }
+
+    #[test]
+    fn test_select_range_updates_selected_preset() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let database_manager = Arc::new(DatabaseManager::new_with_path(&db_path).unwrap());
+        let repository = Arc::new(UsageRepository::new(Arc::clone(&database_manager)));
+
+        let mut app = ViewerApp::new_for_test(Core::default(), database_manager, repository);
+        assert_eq!(app.selected_range, RangePreset::Days(30));
+
+        let _ = app.update(Message::SelectRange(RangePreset::Days(7)));
+        assert_eq!(app.selected_range, RangePreset::Days(7));
+        // Nothing is stored, so every day of the range is a gap
+        assert_eq!(app.missing_dates.len(), 8);
+    }
+
+    #[test]
+    fn test_reset_to_today_restores_default_range() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let database_manager = Arc::new(DatabaseManager::new_with_path(&db_path).unwrap());
+        let repository = Arc::new(UsageRepository::new(Arc::clone(&database_manager)));
+
+        let mut app = ViewerApp::new_for_test(Core::default(), database_manager, repository);
+        let _ = app.update(Message::SelectRange(RangePreset::YearToDate));
+        app.history_page = 3;
+
+        let _ = app.update(Message::ResetToToday);
+
+        assert_eq!(app.selected_range, RangePreset::Days(30));
+        assert_eq!(app.history_page, 0);
+        assert_eq!(app.missing_dates.len(), 31);
+    }
+
+    #[test]
+    fn test_backfill_finished_reports_and_refreshes_gaps() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let database_manager = Arc::new(DatabaseManager::new_with_path(&db_path).unwrap());
+        let repository = Arc::new(UsageRepository::new(Arc::clone(&database_manager)));
+
+        let mut app = ViewerApp::new_for_test(Core::default(), database_manager, repository);
+        let _ = app.update(Message::SelectRange(RangePreset::Days(7)));
+        app.backfill_running = true;
+
+        let _ = app.update(Message::BackfillFinished(Ok(0)));
+
+        assert!(!app.backfill_running);
+        assert_eq!(
+            app.backfill_status.as_deref(),
+            Some("Filled 0 of 8 days from usage files")
+        );
+        assert_eq!(app.missing_dates.len(), 8);
+    }
+
+    #[test]
+    fn test_export_finished_reports_outcome() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let database_manager = Arc::new(DatabaseManager::new_with_path(&db_path).unwrap());
+        let repository = Arc::new(UsageRepository::new(Arc::clone(&database_manager)));
+
+        let mut app = ViewerApp::new_for_test(Core::default(), database_manager, repository);
+        app.export_running = true;
+        let _ = app.update(Message::ExportFinished(Ok(PathBuf::from("/tmp/chart.png"))));
+        assert!(!app.export_running);
+        assert_eq!(
+            app.export_status.as_deref(),
+            Some("Exported chart to /tmp/chart.png")
+        );
+
+        let refused = "/tmp/chart.png already exists, choose another file name";
+        let _ = app.update(Message::ExportFinished(Err(refused.to_string())));
+        assert_eq!(app.export_status.as_deref(), Some(refused));
+    }
+
+    #[test]
+    fn test_history_page_navigation_clamps() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let database_manager = Arc::new(DatabaseManager::new_with_path(&db_path).unwrap());
+        let repository = Arc::new(UsageRepository::new(Arc::clone(&database_manager)));
+
+        let metrics = crate::core::opencode::UsageMetrics {
+            total_input_tokens: 1000,
+            total_output_tokens: 500,
+            total_reasoning_tokens: 0,
+            total_cache_write_tokens: 0,
+            total_cache_read_tokens: 0,
+            total_cost: 1.0,
+            total_cache_cost: 0.0,
+            interaction_count: 3,
+            timestamp: std::time::SystemTime::now(),
+        };
+        for day in 1..=5 {
+            let date = NaiveDate::from_ymd_opt(2025, 10, day).unwrap();
+            repository.save_snapshot(date, &metrics).unwrap();
+        }
+
+        let mut app = ViewerApp::new_for_test(Core::default(), database_manager, repository);
+        app.history_page_size = 2;
+
+        let _ = app.update(Message::HistoryPage(1));
+        assert_eq!(app.history_page, 1);
+        assert_eq!(app.history_total, 5);
+        assert_eq!(app.history_rows.len(), 2);
+
+        // Past the end lands on the partial last page
+        let _ = app.update(Message::HistoryPage(10));
+        assert_eq!(app.history_page, 2);
+        assert_eq!(app.history_rows.len(), 1);
+    }
+
+    #[test]
+    fn test_with_database_on_missing_path_shows_empty_state() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("not-yet-created").join("usage.db");
+        assert!(!db_path.exists());
+
+        let app = ViewerApp::with_database(
+            Core::default(),
+            &AppConfig::default(),
+            DatabaseManager::new_with_path(&db_path),
+        );
+
+        assert!(db_path.exists());
+        assert!(app.history_enabled());
+        assert!(app.history_is_empty());
+        assert!(app.database_error().is_none());
+        // Every date of the range is missing, so the backfill action is offered
+        assert!(!app.missing_dates.is_empty());
+    }
+
+    #[test]
+    fn test_legacy_snapshots_are_offered_for_recompute() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("usage.db");
+        let database_manager = DatabaseManager::new_with_path(&db_path).unwrap();
+        let repository =
+            UsageRepository::new(Arc::new(DatabaseManager::new_with_path(&db_path).unwrap()));
+        let date = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+        let metrics = crate::core::opencode::UsageMetrics {
+            total_input_tokens: 1000,
+            total_output_tokens: 500,
+            total_reasoning_tokens: 0,
+            total_cache_write_tokens: 0,
+            total_cache_read_tokens: 0,
+            total_cost: 1.0,
+            total_cache_cost: 0.0,
+            interaction_count: 3,
+            timestamp: std::time::SystemTime::now(),
+        };
+        repository.save_snapshot(date, &metrics).unwrap();
+        database_manager
+            .get_connection()
+            .execute("UPDATE usage_snapshots SET per_day = 0", [])
+            .unwrap();
+
+        let app =
+            ViewerApp::with_database(Core::default(), &AppConfig::default(), Ok(database_manager));
+
+        assert_eq!(app.legacy_dates, vec![date]);
+    }
+
+    #[test]
+    fn test_with_database_open_error_keeps_viewer_running() {
+        let app = ViewerApp::with_database(
+            Core::default(),
+            &AppConfig::default(),
+            Err(DatabaseError::ConnectionFailed(
+                "disk unavailable".to_string(),
+            )),
+        );
+
+        assert!(!app.history_enabled());
+        assert!(!app.history_is_empty());
+        assert_eq!(
+            app.database_error(),
+            Some("Database connection failed: disk unavailable")
+        );
+    }
 }
@@ -2,11 +2,54 @@
 
 //! Static chart image generation for usage data visualization.
 
+use crate::core::config::ChartPalette;
 use crate::core::database::repository::UsageSnapshot;
 use chrono::NaiveDate;
 use image::RgbaImage;
 use tiny_skia::{Color, Paint, PathBuilder, Pixmap, Stroke, Transform};
 
+/// Series index of input tokens.
+pub const INPUT_SERIES: usize = 0;
+/// Series index of output tokens.
+pub const OUTPUT_SERIES: usize = 1;
+/// Series index of reasoning tokens.
+pub const REASONING_SERIES: usize = 2;
+/// Series index of cost figures (cost efficiency and weekday costs).
+pub const COST_SERIES: usize = 3;
+/// Series index of the average cost per interaction.
+pub const COST_PER_INTERACTION_SERIES: usize = 4;
+
+/// Original series colors: blue, red, green, orange and purple.
+const DEFAULT_SERIES_COLORS: [[u8; 3]; 5] = [
+    [50, 100, 200],
+    [200, 50, 50],
+    [50, 150, 50],
+    [220, 130, 30],
+    [140, 80, 200],
+];
+
+/// Okabe-Ito series colors: blue, vermillion, bluish green, orange and reddish purple.
+const COLOR_BLIND_SAFE_SERIES_COLORS: [[u8; 3]; 5] = [
+    [0, 114, 178],
+    [213, 94, 0],
+    [0, 158, 115],
+    [230, 159, 0],
+    [204, 121, 167],
+];
+
+/// Returns the color of a chart series in the given palette.
+///
+/// Indices past the end of the palette wrap around.
+#[must_use]
+pub fn series_color(palette: ChartPalette, index: usize) -> Color {
+    let colors = match palette {
+        ChartPalette::Default => &DEFAULT_SERIES_COLORS,
+        ChartPalette::ColorBlindSafe => &COLOR_BLIND_SAFE_SERIES_COLORS,
+    };
+    let [r, g, b] = colors[index % colors.len()];
+    Color::from_rgba8(r, g, b, 255)
+}
+
 /// Prepares daily token usage data for charting.
 ///
 /// Returns a vector of (date, `input_tokens`, `output_tokens`, `reasoning_tokens`) tuples
@@ -47,6 +90,262 @@ pub fn prepare_daily_interactions_data(snapshots: &[UsageSnapshot]) -> Vec<(Naiv
     data
 }
 
+/// Prepares daily cost efficiency data (dollars per 1k tokens) for charting.
+///
+/// Returns a vector of (date, `cost_per_1k_tokens`) tuples sorted by date ascending.
+/// Days without tokens report 0.
+#[must_use]
+pub fn prepare_daily_cost_per_1k_data(snapshots: &[UsageSnapshot]) -> Vec<(NaiveDate, f64)> {
+    let mut data: Vec<_> = snapshots
+        .iter()
+        .map(|s| (s.date, s.cost_per_1k_tokens()))
+        .collect();
+
+    data.sort_by_key(|(date, _)| *date);
+    data
+}
+
+/// Prepares daily average cost per interaction for charting.
+///
+/// Returns a vector of (date, `cost_per_interaction`) tuples sorted by date ascending.
+/// Days without interactions are `None` so the chart shows them as gaps.
+#[must_use]
+pub fn prepare_daily_cost_per_interaction_data(
+    snapshots: &[UsageSnapshot],
+) -> Vec<(NaiveDate, Option<f64>)> {
+    let mut data: Vec<_> = snapshots
+        .iter()
+        .map(|s| (s.date, s.cost_per_interaction()))
+        .collect();
+
+    data.sort_by_key(|(date, _)| *date);
+    data
+}
+
+/// Generates a static cost efficiency chart (dollars per 1k tokens) as an `RgbaImage`.
+///
+/// Spikes indicate days with expensive model usage.
+///
+/// # Panics
+///
+/// Panics if the pixmap or image buffer creation fails due to invalid dimensions.
+#[must_use]
+pub fn generate_cost_efficiency_chart(
+    snapshots: &[UsageSnapshot],
+    width: u32,
+    height: u32,
+) -> RgbaImage {
+    generate_cost_efficiency_chart_with_palette(snapshots, width, height, ChartPalette::Default)
+}
+
+/// Like [`generate_cost_efficiency_chart`], drawing the series in the colors of `palette`.
+///
+/// # Panics
+///
+/// Panics if the pixmap or image buffer creation fails due to invalid dimensions.
+#[must_use]
+pub fn generate_cost_efficiency_chart_with_palette(
+    snapshots: &[UsageSnapshot],
+    width: u32,
+    height: u32,
+    palette: ChartPalette,
+) -> RgbaImage {
+    let data = prepare_daily_cost_per_1k_data(snapshots);
+
+    let mut pixmap = Pixmap::new(width, height).expect("Failed to create pixmap");
+    pixmap.fill(Color::WHITE);
+
+    if data.is_empty() {
+        return pixmap_to_rgba_image(&pixmap);
+    }
+
+    let margin = 40.0;
+    #[allow(clippy::cast_precision_loss)]
+    let (width_f, height_f) = (width as f32, height as f32);
+    let chart_width = width_f - 2.0 * margin;
+    let chart_height = height_f - 2.0 * margin;
+
+    // Find max value for scaling (guard against all-zero days)
+    let max_value = data.iter().map(|(_, value)| *value).fold(0.0_f64, f64::max);
+    #[allow(clippy::cast_possible_truncation)]
+    let max_value_f = if max_value > 0.0 {
+        max_value as f32
+    } else {
+        1.0
+    };
+
+    let mut paint = Paint {
+        anti_alias: true,
+        ..Default::default()
+    };
+    let mut stroke = Stroke {
+        width: 1.0,
+        ..Default::default()
+    };
+
+    // Draw axes
+    paint.set_color(Color::from_rgba8(180, 180, 180, 255));
+    let mut pb = PathBuilder::new();
+    pb.move_to(margin, margin);
+    pb.line_to(margin, height_f - margin);
+    pb.line_to(width_f - margin, height_f - margin);
+    if let Some(path) = pb.finish() {
+        pixmap.stroke_path(&path, &paint, &stroke, Transform::identity(), None);
+    }
+
+    if data.len() < 2 {
+        return pixmap_to_rgba_image(&pixmap);
+    }
+
+    // Draw cost per 1k tokens line (orange)
+    paint.set_color(series_color(palette, COST_SERIES));
+    stroke.width = 2.0;
+    let mut pb = PathBuilder::new();
+
+    for (i, (_, value)) in data.iter().enumerate() {
+        #[allow(clippy::cast_precision_loss)]
+        let x = margin + (i as f32 / (data.len() - 1) as f32) * chart_width;
+        #[allow(clippy::cast_possible_truncation)]
+        let y = height_f - margin - (*value as f32 / max_value_f) * chart_height;
+
+        if i == 0 {
+            pb.move_to(x, y);
+        } else {
+            pb.line_to(x, y);
+        }
+    }
+
+    if let Some(path) = pb.finish() {
+        pixmap.stroke_path(&path, &paint, &stroke, Transform::identity(), None);
+    }
+
+    pixmap_to_rgba_image(&pixmap)
+}
+
+/// Generates a static average cost per interaction chart as an `RgbaImage`.
+///
+/// Days without interactions break the line; isolated days are drawn as dots.
+///
+/// # Panics
+///
+/// Panics if the pixmap or image buffer creation fails due to invalid dimensions.
+#[must_use]
+pub fn generate_cost_per_interaction_chart(
+    snapshots: &[UsageSnapshot],
+    width: u32,
+    height: u32,
+) -> RgbaImage {
+    generate_cost_per_interaction_chart_with_palette(
+        snapshots,
+        width,
+        height,
+        ChartPalette::Default,
+    )
+}
+
+/// Like [`generate_cost_per_interaction_chart`], drawing the series in the colors of `palette`.
+///
+/// # Panics
+///
+/// Panics if the pixmap or image buffer creation fails due to invalid dimensions.
+#[must_use]
+pub fn generate_cost_per_interaction_chart_with_palette(
+    snapshots: &[UsageSnapshot],
+    width: u32,
+    height: u32,
+    palette: ChartPalette,
+) -> RgbaImage {
+    let data = prepare_daily_cost_per_interaction_data(snapshots);
+
+    let mut pixmap = Pixmap::new(width, height).expect("Failed to create pixmap");
+    pixmap.fill(Color::WHITE);
+
+    if data.is_empty() {
+        return pixmap_to_rgba_image(&pixmap);
+    }
+
+    let margin = 40.0;
+    #[allow(clippy::cast_precision_loss)]
+    let (width_f, height_f) = (width as f32, height as f32);
+    let chart_width = width_f - 2.0 * margin;
+    let chart_height = height_f - 2.0 * margin;
+
+    // Find max value for scaling (guard against all-gap or all-zero days)
+    let max_value = data
+        .iter()
+        .filter_map(|(_, value)| *value)
+        .fold(0.0_f64, f64::max);
+    #[allow(clippy::cast_possible_truncation)]
+    let max_value_f = if max_value > 0.0 {
+        max_value as f32
+    } else {
+        1.0
+    };
+
+    let mut paint = Paint {
+        anti_alias: true,
+        ..Default::default()
+    };
+    let mut stroke = Stroke {
+        width: 1.0,
+        ..Default::default()
+    };
+
+    // Draw axes
+    paint.set_color(Color::from_rgba8(180, 180, 180, 255));
+    let mut pb = PathBuilder::new();
+    pb.move_to(margin, margin);
+    pb.line_to(margin, height_f - margin);
+    pb.line_to(width_f - margin, height_f - margin);
+    if let Some(path) = pb.finish() {
+        pixmap.stroke_path(&path, &paint, &stroke, Transform::identity(), None);
+    }
+
+    // Draw cost per interaction line (purple), starting a new segment after each gap
+    paint.set_color(series_color(palette, COST_PER_INTERACTION_SERIES));
+    stroke.width = 2.0;
+    let mut pb = PathBuilder::new();
+    let step = if data.len() > 1 {
+        #[allow(clippy::cast_precision_loss)]
+        let intervals = (data.len() - 1) as f32;
+        chart_width / intervals
+    } else {
+        0.0
+    };
+
+    for (i, (_, value)) in data.iter().enumerate() {
+        let Some(value) = value else {
+            continue;
+        };
+        #[allow(clippy::cast_precision_loss)]
+        let x = margin + i as f32 * step;
+        #[allow(clippy::cast_possible_truncation)]
+        let y = height_f - margin - (*value as f32 / max_value_f) * chart_height;
+
+        let continues_line = i > 0 && data[i - 1].1.is_some();
+        let starts_line = data.get(i + 1).is_some_and(|(_, next)| next.is_some());
+        if continues_line {
+            pb.line_to(x, y);
+        } else if starts_line {
+            pb.move_to(x, y);
+        } else if let Some(dot) = PathBuilder::from_circle(x, y, 2.5) {
+            pixmap.fill_path(
+                &dot,
+                &paint,
+                tiny_skia::FillRule::Winding,
+                Transform::identity(),
+                None,
+            );
+        }
+    }
+
+    if let Some(path) = pb.finish() {
+        pixmap.stroke_path(&path, &paint, &stroke, Transform::identity(), None);
+    }
+
+    pixmap_to_rgba_image(&pixmap)
+}
+
 /// Generates a static token usage chart as an `RgbaImage`.
 ///
 /// This creates a pre-rendered image that can be displayed without per-frame rendering,
@@ -56,11 +355,26 @@ pub fn prepare_daily_interactions_data(snapshots: &[UsageSnapshot]) -> Vec<(Naiv
 ///
 /// Panics if the pixmap or image buffer creation fails due to invalid dimensions.
 #[must_use]
-#[allow(clippy::too_many_lines)]
 pub fn generate_token_usage_chart(
     snapshots: &[UsageSnapshot],
     width: u32,
     height: u32,
+) -> RgbaImage {
+    generate_token_usage_chart_with_palette(snapshots, width, height, ChartPalette::Default)
+}
+
+/// Like [`generate_token_usage_chart`], drawing the series in the colors of `palette`.
+///
+/// # Panics
+///
+/// Panics if the pixmap or image buffer creation fails due to invalid dimensions.
+#[must_use]
+#[allow(clippy::too_many_lines)]
+pub fn generate_token_usage_chart_with_palette(
+    snapshots: &[UsageSnapshot],
+    width: u32,
+    height: u32,
+    palette: ChartPalette,
 ) -> RgbaImage {
     let data = prepare_daily_tokens_data(snapshots);
 
@@ -131,7 +445,7 @@ pub fn generate_token_usage_chart(
     }
 
     // Draw input tokens line (blue)
-    paint.set_color(Color::from_rgba8(50, 100, 200, 255));
+    paint.set_color(series_color(palette, INPUT_SERIES));
     stroke.width = 2.0;
     let mut pb = PathBuilder::new();
     let mut first = true;
@@ -155,7 +469,7 @@ pub fn generate_token_usage_chart(
     }
 
     // Draw output tokens line (red)
-    paint.set_color(Color::from_rgba8(200, 50, 50, 255));
+    paint.set_color(series_color(palette, OUTPUT_SERIES));
     let mut pb = PathBuilder::new();
     let mut first = true;
 
@@ -178,7 +492,7 @@ pub fn generate_token_usage_chart(
     }
 
     // Draw reasoning tokens line (green)
-    paint.set_color(Color::from_rgba8(50, 150, 50, 255));
+    paint.set_color(series_color(palette, REASONING_SERIES));
     let mut pb = PathBuilder::new();
     let mut first = true;
 
@@ -203,6 +517,78 @@ pub fn generate_token_usage_chart(
     pixmap_to_rgba_image(&pixmap)
 }
 
+/// Generates a static bar chart of cost per day of the week (Monday first) as an `RgbaImage`.
+///
+/// # Panics
+///
+/// Panics if the pixmap or image buffer creation fails due to invalid dimensions.
+#[must_use]
+pub fn generate_weekday_cost_chart(costs: &[f64; 7], width: u32, height: u32) -> RgbaImage {
+    generate_weekday_cost_chart_with_palette(costs, width, height, ChartPalette::Default)
+}
+
+/// Like [`generate_weekday_cost_chart`], drawing the series in the colors of `palette`.
+///
+/// # Panics
+///
+/// Panics if the pixmap or image buffer creation fails due to invalid dimensions.
+#[must_use]
+pub fn generate_weekday_cost_chart_with_palette(
+    costs: &[f64; 7],
+    width: u32,
+    height: u32,
+    palette: ChartPalette,
+) -> RgbaImage {
+    let mut pixmap = Pixmap::new(width, height).expect("Failed to create pixmap");
+    pixmap.fill(Color::WHITE);
+
+    let margin = 40.0;
+    #[allow(clippy::cast_precision_loss)]
+    let (width_f, height_f) = (width as f32, height as f32);
+    let chart_width = width_f - 2.0 * margin;
+    let chart_height = height_f - 2.0 * margin;
+
+    let mut paint = Paint {
+        anti_alias: true,
+        ..Default::default()
+    };
+
+    // Draw baseline
+    paint.set_color(Color::from_rgba8(180, 180, 180, 255));
+    let mut pb = PathBuilder::new();
+    pb.move_to(margin, height_f - margin);
+    pb.line_to(width_f - margin, height_f - margin);
+    if let Some(path) = pb.finish() {
+        let stroke = Stroke {
+            width: 1.0,
+            ..Default::default()
+        };
+        pixmap.stroke_path(&path, &paint, &stroke, Transform::identity(), None);
+    }
+
+    let max_cost = costs.iter().copied().fold(0.0_f64, f64::max);
+    if max_cost <= 0.0 {
+        return pixmap_to_rgba_image(&pixmap);
+    }
+
+    // Draw one bar per weekday (orange, like the cost efficiency line)
+    paint.set_color(series_color(palette, COST_SERIES));
+    let slot_width = chart_width / 7.0;
+    let bar_width = slot_width * 0.6;
+    for (i, cost) in costs.iter().enumerate() {
+        #[allow(clippy::cast_possible_truncation)]
+        let bar_height = (cost / max_cost) as f32 * chart_height;
+        #[allow(clippy::cast_precision_loss)]
+        let x = margin + i as f32 * slot_width + (slot_width - bar_width) / 2.0;
+        let y = height_f - margin - bar_height;
+        if let Some(rect) = tiny_skia::Rect::from_xywh(x, y, bar_width, bar_height) {
+            pixmap.fill_rect(rect, &paint, Transform::identity(), None);
+        }
+    }
+
+    pixmap_to_rgba_image(&pixmap)
+}
+
 /// Converts a `tiny_skia::Pixmap` to an `image::RgbaImage`.
 ///
 /// Uses direct buffer conversion to avoid per-pixel overhead and bounds checks.
@@ -378,6 +764,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_prepare_daily_cost_per_1k_data() {
+        let mut zero_day =
+            create_test_snapshot(NaiveDate::from_ymd_opt(2025, 10, 2).unwrap(), 0, 0);
+        zero_day.reasoning_tokens = 0;
+        let snapshots = vec![
+            zero_day,
+            // 1000 + 400 + 100 reasoning = 1500 tokens for $1.50
+            create_test_snapshot(NaiveDate::from_ymd_opt(2025, 10, 1).unwrap(), 1000, 400),
+        ];
+
+        let data = prepare_daily_cost_per_1k_data(&snapshots);
+
+        assert_eq!(data.len(), 2);
+        assert_eq!(data[0].0, NaiveDate::from_ymd_opt(2025, 10, 1).unwrap());
+        assert!((data[0].1 - 1.0).abs() < 1e-9);
+        // Zero tokens are guarded instead of dividing by zero
+        assert!(data[1].1.abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_generate_cost_efficiency_chart_creates_image() {
+        let snapshots = vec![
+            create_test_snapshot(NaiveDate::from_ymd_opt(2025, 10, 1).unwrap(), 1000, 100),
+            create_test_snapshot(NaiveDate::from_ymd_opt(2025, 10, 2).unwrap(), 0, 0),
+        ];
+
+        let img = generate_cost_efficiency_chart(&snapshots, 800, 200);
+        assert_eq!(img.width(), 800);
+        assert_eq!(img.height(), 200);
+
+        let empty = generate_cost_efficiency_chart(&[], 800, 200);
+        assert_eq!(empty.width(), 800);
+    }
+
+    #[test]
+    fn test_generate_weekday_cost_chart_draws_bars() {
+        let image = generate_weekday_cost_chart(&[1.0, 0.0, 2.0, 0.0, 0.0, 0.0, 0.5], 350, 150);
+        assert_eq!(image.width(), 350);
+        assert_eq!(image.height(), 150);
+        assert!(
+            image.pixels().any(|p| p.0 == [220, 130, 30, 255]),
+            "Bars should be drawn"
+        );
+
+        let empty = generate_weekday_cost_chart(&[0.0; 7], 350, 150);
+        assert!(!empty.pixels().any(|p| p.0 == [220, 130, 30, 255]));
+    }
+
     #[test]
     fn test_pixmap_to_rgba_image_direct_conversion() {
         // Test that direct buffer conversion produces correct image
@@ -404,4 +839,80 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_prepare_daily_cost_per_interaction_data_marks_idle_days_as_gaps() {
+        let mut idle = create_test_snapshot(NaiveDate::from_ymd_opt(2025, 10, 2).unwrap(), 0, 0);
+        idle.interaction_count = 0;
+        idle.total_cost = 0.0;
+        let snapshots = vec![
+            create_test_snapshot(NaiveDate::from_ymd_opt(2025, 10, 3).unwrap(), 1000, 100),
+            idle,
+            create_test_snapshot(NaiveDate::from_ymd_opt(2025, 10, 1).unwrap(), 1000, 100),
+        ];
+
+        let data = prepare_daily_cost_per_interaction_data(&snapshots);
+
+        assert_eq!(data.len(), 3);
+        assert_eq!(
+            data[0],
+            (NaiveDate::from_ymd_opt(2025, 10, 1).unwrap(), Some(0.15))
+        );
+        assert_eq!(
+            data[1],
+            (NaiveDate::from_ymd_opt(2025, 10, 2).unwrap(), None)
+        );
+        assert_eq!(
+            data[2],
+            (NaiveDate::from_ymd_opt(2025, 10, 3).unwrap(), Some(0.15))
+        );
+    }
+
+    #[test]
+    fn test_generate_cost_per_interaction_chart_with_gaps() {
+        let mut idle = create_test_snapshot(NaiveDate::from_ymd_opt(2025, 10, 2).unwrap(), 0, 0);
+        idle.interaction_count = 0;
+        let snapshots = vec![
+            create_test_snapshot(NaiveDate::from_ymd_opt(2025, 10, 1).unwrap(), 1000, 100),
+            idle,
+            create_test_snapshot(NaiveDate::from_ymd_opt(2025, 10, 3).unwrap(), 1000, 100),
+        ];
+
+        let image = generate_cost_per_interaction_chart(&snapshots, 400, 200);
+
+        assert_eq!(image.width(), 400);
+        assert_eq!(image.height(), 200);
+        // The purple line is drawn despite the gap day
+        assert!(image
+            .pixels()
+            .any(|p| p.0[2] > p.0[1].saturating_add(60) && p.0[0] > p.0[1]));
+    }
+
+    #[test]
+    fn test_series_color_is_distinct_and_deterministic() {
+        for palette in [ChartPalette::Default, ChartPalette::ColorBlindSafe] {
+            let colors: Vec<_> = (0..5)
+                .map(|index| series_color(palette, index).to_color_u8())
+                .collect();
+            for (i, color) in colors.iter().enumerate() {
+                assert!(
+                    colors[i + 1..].iter().all(|other| other != color),
+                    "{palette:?} repeats the color of series {i}"
+                );
+                assert_eq!(series_color(palette, i).to_color_u8(), *color);
+            }
+            // Indices past the palette wrap around
+            assert_eq!(series_color(palette, 5).to_color_u8(), colors[0]);
+        }
+
+        // The default palette keeps the original colors
+        assert_eq!(
+            series_color(ChartPalette::Default, INPUT_SERIES),
+            Color::from_rgba8(50, 100, 200, 255)
+        );
+        assert_ne!(
+            series_color(ChartPalette::ColorBlindSafe, INPUT_SERIES),
+            series_color(ChartPalette::Default, INPUT_SERIES)
+        );
+    }
 }
@@ -0,0 +1,160 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Field-by-field comparison of two stored daily snapshots.
+
+use crate::core::database::repository::UsageSnapshot;
+use crate::core::opencode::UsageDelta;
+
+/// A displayed diff row: (label, from value, to value, delta).
+pub type DiffRow = (&'static str, String, String, String);
+
+/// A compared integer field: label, snapshot accessor and delta accessor.
+type IntField = (
+    &'static str,
+    fn(&UsageSnapshot) -> i64,
+    fn(&UsageDelta) -> i64,
+);
+
+/// Comparison between the snapshots of two days.
+///
+/// Either side may be missing if no snapshot was stored for that date,
+/// in which case there is no delta.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotDiff {
+    pub from: Option<UsageSnapshot>,
+    pub to: Option<UsageSnapshot>,
+    pub delta: Option<UsageDelta>,
+}
+
+impl SnapshotDiff {
+    /// Computes the difference `to - from` between two optional snapshots.
+    #[must_use]
+    pub fn compute(from: Option<UsageSnapshot>, to: Option<UsageSnapshot>) -> Self {
+        let delta = match (&from, &to) {
+            (Some(from), Some(to)) => {
+                Some(to.to_usage_metrics().subtract(&from.to_usage_metrics()))
+            }
+            _ => None,
+        };
+
+        Self { from, to, delta }
+    }
+
+    /// Returns the rows to display as (label, from, to, delta) strings.
+    ///
+    /// Missing snapshots are shown as "no data".
+    #[must_use]
+    pub fn rows(&self) -> Vec<DiffRow> {
+        let int_fields: [IntField; 6] = [
+            ("Input", |s| s.input_tokens, |d| d.input_tokens),
+            ("Output", |s| s.output_tokens, |d| d.output_tokens),
+            ("Reasoning", |s| s.reasoning_tokens, |d| d.reasoning_tokens),
+            (
+                "Cache Write",
+                |s| s.cache_write_tokens,
+                |d| d.cache_write_tokens,
+            ),
+            (
+                "Cache Read",
+                |s| s.cache_read_tokens,
+                |d| d.cache_read_tokens,
+            ),
+            ("Interactions", |s| s.interaction_count, |d| d.interactions),
+        ];
+
+        let mut rows: Vec<DiffRow> = int_fields
+            .iter()
+            .map(|(label, value, delta)| {
+                (
+                    *label,
+                    self.from
+                        .as_ref()
+                        .map_or_else(no_data, |s| value(s).to_string()),
+                    self.to
+                        .as_ref()
+                        .map_or_else(no_data, |s| value(s).to_string()),
+                    self.delta
+                        .as_ref()
+                        .map_or_else(no_data, |d| format!("{:+}", delta(d))),
+                )
+            })
+            .collect();
+
+        rows.push((
+            "Cost",
+            self.from
+                .as_ref()
+                .map_or_else(no_data, |s| format!("${:.2}", s.total_cost)),
+            self.to
+                .as_ref()
+                .map_or_else(no_data, |s| format!("${:.2}", s.total_cost)),
+            self.delta.as_ref().map_or_else(no_data, |d| {
+                let sign = if d.cost < 0.0 { "-" } else { "+" };
+                format!("{sign}${:.2}", d.cost.abs())
+            }),
+        ));
+
+        rows
+    }
+}
+
+/// Placeholder for values without a stored snapshot.
+fn no_data() -> String {
+    "no data".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn create_snapshot(day: u32, input: i64, cost: f64, interactions: i64) -> UsageSnapshot {
+        UsageSnapshot {
+            date: NaiveDate::from_ymd_opt(2025, 10, day).unwrap(),
+            input_tokens: input,
+            output_tokens: 500,
+            reasoning_tokens: 0,
+            cache_write_tokens: 0,
+            cache_read_tokens: 100,
+            total_cost: cost,
+            interaction_count: interactions,
+        }
+    }
+
+    #[test]
+    fn test_compute_diff_between_known_snapshots() {
+        let diff = SnapshotDiff::compute(
+            Some(create_snapshot(1, 1000, 1.5, 10)),
+            Some(create_snapshot(2, 400, 2.0, 12)),
+        );
+
+        let delta = diff.delta.as_ref().expect("Both sides present");
+        assert_eq!(delta.input_tokens, -600);
+        assert_eq!(delta.output_tokens, 0);
+        assert_eq!(delta.interactions, 2);
+        assert!((delta.cost - 0.5).abs() < 1e-9);
+
+        let rows = diff.rows();
+        assert_eq!(
+            rows[0],
+            ("Input", "1000".into(), "400".into(), "-600".into())
+        );
+        assert_eq!(rows[1].3, "+0");
+        assert_eq!(
+            rows.last().unwrap(),
+            &("Cost", "$1.50".into(), "$2.00".into(), "+$0.50".into())
+        );
+    }
+
+    #[test]
+    fn test_compute_diff_with_missing_snapshot() {
+        let diff = SnapshotDiff::compute(None, Some(create_snapshot(2, 400, 2.0, 12)));
+
+        assert!(diff.delta.is_none());
+        let rows = diff.rows();
+        assert_eq!(
+            rows[0],
+            ("Input", "no data".into(), "400".into(), "no data".into())
+        );
+    }
+}
@@ -0,0 +1,218 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Exporting the token usage chart to a PNG file.
+//!
+//! The chart is re-rendered offscreen with `tiny-skia` at the requested size,
+//! so the export is not limited to the resolution shown in the viewer.
+
+use image::RgbaImage;
+use std::fs::OpenOptions;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Smallest accepted export width or height in pixels.
+pub const MIN_EXPORT_DIMENSION: u32 = 100;
+/// Largest accepted export width or height in pixels.
+pub const MAX_EXPORT_DIMENSION: u32 = 8000;
+/// Width offered by default in the export form.
+pub const DEFAULT_EXPORT_WIDTH: u32 = 1600;
+/// Height offered by default in the export form.
+pub const DEFAULT_EXPORT_HEIGHT: u32 = 800;
+
+/// Default export path: a file in the home directory, or empty if it is unknown.
+#[must_use]
+pub fn default_export_path() -> String {
+    std::env::var("HOME")
+        .map(|home| format!("{home}/opencode-usage-chart.png"))
+        .unwrap_or_default()
+}
+
+/// Error for an invalid export request or a failed write.
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("Enter a file path to export to")]
+    EmptyPath,
+    #[error("Export path must end in .png: {0}")]
+    NotPng(String),
+    #[error("Invalid {name} {value:?}, expected a whole number of pixels")]
+    InvalidDimension { name: &'static str, value: String },
+    #[error(
+        "The {name} must be between {min} and {max} pixels, got {value}",
+        min = MIN_EXPORT_DIMENSION,
+        max = MAX_EXPORT_DIMENSION
+    )]
+    DimensionOutOfRange { name: &'static str, value: u32 },
+    #[error("{0} already exists, choose another file name")]
+    AlreadyExists(String),
+    #[error("Failed to write {path}: {source}")]
+    Write {
+        path: String,
+        #[source]
+        source: image::ImageError,
+    },
+}
+
+/// A validated export destination and chart size.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportTarget {
+    pub path: PathBuf,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ExportTarget {
+    /// Validates the export form inputs.
+    ///
+    /// A path without an extension gets `.png` appended. Dimensions must lie between
+    /// [`MIN_EXPORT_DIMENSION`] and [`MAX_EXPORT_DIMENSION`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an `ExportError` describing the first invalid input.
+    pub fn parse(path: &str, width: &str, height: &str) -> Result<Self, ExportError> {
+        let path = path.trim();
+        if path.is_empty() {
+            return Err(ExportError::EmptyPath);
+        }
+
+        let mut path = PathBuf::from(path);
+        match path.extension().and_then(|ext| ext.to_str()) {
+            None => {
+                path.set_extension("png");
+            }
+            Some(ext) if ext.eq_ignore_ascii_case("png") => {}
+            Some(_) => return Err(ExportError::NotPng(path.display().to_string())),
+        }
+
+        Ok(Self {
+            path,
+            width: parse_dimension("width", width)?,
+            height: parse_dimension("height", height)?,
+        })
+    }
+}
+
+/// Parses one export dimension and checks it is in range.
+fn parse_dimension(name: &'static str, value: &str) -> Result<u32, ExportError> {
+    let parsed = value
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| ExportError::InvalidDimension {
+            name,
+            value: value.to_string(),
+        })?;
+
+    if (MIN_EXPORT_DIMENSION..=MAX_EXPORT_DIMENSION).contains(&parsed) {
+        Ok(parsed)
+    } else {
+        Err(ExportError::DimensionOutOfRange {
+            name,
+            value: parsed,
+        })
+    }
+}
+
+/// Writes a rendered chart to `path` as a PNG image, never replacing an existing file.
+///
+/// # Errors
+///
+/// Returns `ExportError::AlreadyExists` if `path` exists, or `ExportError::Write` if
+/// the file cannot be encoded or written.
+pub fn save_png(image: &RgbaImage, path: &Path) -> Result<(), ExportError> {
+    let write_error = |source| ExportError::Write {
+        path: path.display().to_string(),
+        source,
+    };
+    let file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::AlreadyExists {
+                ExportError::AlreadyExists(path.display().to_string())
+            } else {
+                write_error(image::ImageError::IoError(e))
+            }
+        })?;
+    image
+        .write_to(&mut BufWriter::new(file), image::ImageFormat::Png)
+        .map_err(write_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_valid_target() {
+        let target = ExportTarget::parse(" /tmp/chart.PNG ", "1600", " 800 ").unwrap();
+        assert_eq!(
+            target,
+            ExportTarget {
+                path: PathBuf::from("/tmp/chart.PNG"),
+                width: 1600,
+                height: 800,
+            }
+        );
+
+        // A missing extension defaults to .png
+        let target = ExportTarget::parse("/tmp/chart", "100", "8000").unwrap();
+        assert_eq!(target.path, PathBuf::from("/tmp/chart.png"));
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_inputs() {
+        assert!(matches!(
+            ExportTarget::parse("  ", "800", "400"),
+            Err(ExportError::EmptyPath)
+        ));
+        assert!(matches!(
+            ExportTarget::parse("/tmp/chart.jpg", "800", "400"),
+            Err(ExportError::NotPng(_))
+        ));
+        assert!(matches!(
+            ExportTarget::parse("/tmp/chart.png", "wide", "400"),
+            Err(ExportError::InvalidDimension { name: "width", .. })
+        ));
+        assert!(matches!(
+            ExportTarget::parse("/tmp/chart.png", "800", "99"),
+            Err(ExportError::DimensionOutOfRange {
+                name: "height",
+                value: 99
+            })
+        ));
+        assert!(matches!(
+            ExportTarget::parse("/tmp/chart.png", "8001", "400"),
+            Err(ExportError::DimensionOutOfRange {
+                name: "width",
+                value: 8001
+            })
+        ));
+    }
+
+    #[test]
+    fn test_save_png_writes_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("chart.png");
+        let image = RgbaImage::new(120, 100);
+
+        save_png(&image, &path).unwrap();
+
+        let saved = image::open(&path).unwrap();
+        assert_eq!((saved.width(), saved.height()), (120, 100));
+    }
+
+    #[test]
+    fn test_save_png_refuses_to_overwrite() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("chart.png");
+        std::fs::write(&path, "keep me").unwrap();
+
+        assert!(matches!(
+            save_png(&RgbaImage::new(120, 100), &path),
+            Err(ExportError::AlreadyExists(_))
+        ));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep me");
+    }
+}
@@ -7,6 +7,10 @@
 
 pub mod app;
 pub mod charts;
+pub mod diff;
+pub mod export;
+pub mod range;
 pub mod ui;
+pub mod verify;
 
 pub use app::{Message, ViewerApp};
@@ -0,0 +1,233 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Quick-range presets for the viewer charts.
+//!
+//! Presets come from the applet config as short tokens such as `"30d"`,
+//! `"YTD"` or `"All"` and are resolved to date ranges relative to today.
+
+use chrono::{Datelike, Days, NaiveDate};
+use thiserror::Error;
+
+/// Error for preset tokens that cannot be parsed.
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+pub enum RangePresetError {
+    #[error("Unknown range preset {0:?}, expected e.g. \"30d\", \"YTD\" or \"All\"")]
+    Unknown(String),
+}
+
+/// A quick-range preset for the viewer charts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangePreset {
+    /// The last `n` days up to today
+    Days(u32),
+    /// From January 1st of the current year
+    YearToDate,
+    /// The whole stored history
+    All,
+}
+
+/// Preset selected when the viewer opens, matching the former fixed 30-day chart.
+pub const DEFAULT_RANGE_PRESET: RangePreset = RangePreset::Days(30);
+
+impl RangePreset {
+    /// Parses a preset token: `"<n>d"`, `"YTD"` or `"All"` (case-insensitive).
+    ///
+    /// # Errors
+    ///
+    /// Returns `RangePresetError::Unknown` for any other token, including `"0d"`.
+    pub fn parse(token: &str) -> Result<Self, RangePresetError> {
+        let trimmed = token.trim();
+        let lower = trimmed.to_ascii_lowercase();
+        match lower.as_str() {
+            "ytd" => Ok(Self::YearToDate),
+            "all" => Ok(Self::All),
+            _ => lower
+                .strip_suffix('d')
+                .and_then(|days| days.parse::<u32>().ok())
+                .filter(|&days| days > 0)
+                .map(Self::Days)
+                .ok_or_else(|| RangePresetError::Unknown(trimmed.to_string())),
+        }
+    }
+
+    /// Short label for the preset button.
+    #[must_use]
+    pub fn label(self) -> String {
+        match self {
+            Self::Days(days) => format!("{days}d"),
+            Self::YearToDate => "YTD".to_string(),
+            Self::All => "All".to_string(),
+        }
+    }
+
+    /// Chart title for the preset.
+    #[must_use]
+    pub fn title(self) -> String {
+        match self {
+            Self::Days(days) => format!("{days}-Day History"),
+            Self::YearToDate => "Year-to-Date History".to_string(),
+            Self::All => "Full History".to_string(),
+        }
+    }
+
+    /// Resolves the preset to an inclusive `(from, to)` range ending today.
+    ///
+    /// `All` starts at `earliest`, the oldest stored date, or today when nothing is stored.
+    /// A day count reaching past the earliest representable date is treated as `All`.
+    #[must_use]
+    pub fn range(self, today: NaiveDate, earliest: Option<NaiveDate>) -> (NaiveDate, NaiveDate) {
+        let from = match self {
+            Self::Days(days) => match today.checked_sub_days(Days::new(u64::from(days))) {
+                Some(from) => from,
+                None => return Self::All.range(today, earliest),
+            },
+            Self::YearToDate => NaiveDate::from_ymd_opt(today.year(), 1, 1).unwrap_or(today),
+            Self::All => earliest.map_or(today, |date| date.min(today)),
+        };
+        (from, today)
+    }
+}
+
+/// Picks the preset selected when the viewer opens.
+///
+/// Uses the default 30-day range when configured, otherwise the first preset.
+#[must_use]
+pub fn initial_range_preset(presets: &[RangePreset]) -> RangePreset {
+    if presets.contains(&DEFAULT_RANGE_PRESET) {
+        DEFAULT_RANGE_PRESET
+    } else {
+        presets.first().copied().unwrap_or(DEFAULT_RANGE_PRESET)
+    }
+}
+
+/// Parses the configured preset tokens, skipping (and logging) invalid ones.
+///
+/// Falls back to the default preset when no token is valid.
+#[must_use]
+pub fn parse_range_presets(tokens: &[String]) -> Vec<RangePreset> {
+    let mut presets = Vec::new();
+    for token in tokens {
+        match RangePreset::parse(token) {
+            Ok(preset) if !presets.contains(&preset) => presets.push(preset),
+            Ok(_) => {}
+            Err(e) => eprintln!("Ignoring viewer range preset: {e}"),
+        }
+    }
+    if presets.is_empty() {
+        presets.push(DEFAULT_RANGE_PRESET);
+    }
+    presets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_parse_preset_tokens() {
+        assert_eq!(RangePreset::parse("7d"), Ok(RangePreset::Days(7)));
+        assert_eq!(RangePreset::parse(" 90D "), Ok(RangePreset::Days(90)));
+        assert_eq!(RangePreset::parse("YTD"), Ok(RangePreset::YearToDate));
+        assert_eq!(RangePreset::parse("all"), Ok(RangePreset::All));
+        for invalid in ["0d", "d", "7w", "", "-3d"] {
+            assert!(
+                RangePreset::parse(invalid).is_err(),
+                "{invalid:?} should fail"
+            );
+        }
+    }
+
+    #[test]
+    fn test_day_preset_ranges() {
+        let today = date(2025, 3, 15);
+        assert_eq!(
+            RangePreset::Days(7).range(today, None),
+            (date(2025, 3, 8), today)
+        );
+        assert_eq!(
+            RangePreset::Days(30).range(today, None),
+            (date(2025, 2, 13), today)
+        );
+        assert_eq!(
+            RangePreset::Days(90).range(today, None),
+            (date(2024, 12, 15), today)
+        );
+    }
+
+    #[test]
+    fn test_huge_day_preset_falls_back_to_all() {
+        let today = date(2025, 3, 15);
+        assert_eq!(
+            RangePreset::parse("4294967295d").map(|preset| preset.range(today, None)),
+            Ok((today, today))
+        );
+        assert_eq!(
+            RangePreset::Days(u32::MAX).range(today, Some(date(2023, 6, 1))),
+            (date(2023, 6, 1), today)
+        );
+    }
+
+    #[test]
+    fn test_ytd_preset_range() {
+        assert_eq!(
+            RangePreset::YearToDate.range(date(2025, 3, 15), None),
+            (date(2025, 1, 1), date(2025, 3, 15))
+        );
+        // On January 1st the range is just today
+        assert_eq!(
+            RangePreset::YearToDate.range(date(2026, 1, 1), None),
+            (date(2026, 1, 1), date(2026, 1, 1))
+        );
+    }
+
+    #[test]
+    fn test_all_preset_range() {
+        let today = date(2025, 3, 15);
+        assert_eq!(
+            RangePreset::All.range(today, Some(date(2023, 6, 1))),
+            (date(2023, 6, 1), today)
+        );
+        assert_eq!(RangePreset::All.range(today, None), (today, today));
+    }
+
+    #[test]
+    fn test_parse_range_presets_skips_invalid_and_duplicates() {
+        let tokens: Vec<String> = ["7d", "bogus", "YTD", "7D", "All"]
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            parse_range_presets(&tokens),
+            vec![
+                RangePreset::Days(7),
+                RangePreset::YearToDate,
+                RangePreset::All
+            ]
+        );
+        assert_eq!(parse_range_presets(&[]), vec![DEFAULT_RANGE_PRESET]);
+    }
+
+    #[test]
+    fn test_initial_range_preset() {
+        assert_eq!(
+            initial_range_preset(&[RangePreset::Days(7), RangePreset::Days(30)]),
+            RangePreset::Days(30)
+        );
+        assert_eq!(
+            initial_range_preset(&[RangePreset::YearToDate, RangePreset::All]),
+            RangePreset::YearToDate
+        );
+    }
+
+    #[test]
+    fn test_default_config_presets_parse() {
+        let config = crate::core::config::AppConfig::default();
+        let presets = parse_range_presets(&config.viewer_range_presets);
+        assert_eq!(presets.len(), 5);
+        assert!(presets.contains(&DEFAULT_RANGE_PRESET));
+    }
+}
@@ -2,14 +2,18 @@
 
 //! UI rendering logic for the viewer application.
 
-use crate::core::database::repository::WeekSummary;
+use crate::core::config::{ActiveDayDefinition, CentralTendency};
+use crate::core::database::repository::{UsageSnapshot, WeekSummary};
+use crate::viewer::diff::SnapshotDiff;
+use crate::viewer::range::RangePreset;
+use crate::viewer::verify::{find_discrepancies, FreshRead};
 use crate::viewer::Message;
 use ::image::RgbaImage;
 use chrono::NaiveDate;
 use cosmic::{
     iced::{Alignment, Length},
     iced_core::image::Handle,
-    widget::{column, container, image as cosmic_image, row, text},
+    widget::{column, container, image as cosmic_image, row, scrollable, text, text_input},
     Element,
 };
 
@@ -31,49 +35,40 @@ fn format_cost(cost: f64) -> String {
     format!("${cost:.2}")
 }
 
-/// Calculates percentage change and returns formatted string with arrow.
-fn format_change(current: i64, previous: i64) -> (String, String) {
-    if previous == 0 {
-        if current == 0 {
-            return ("0%".to_string(), "-".to_string());
-        }
-        return ("+INF%".to_string(), "UP".to_string());
-    }
-
-    #[allow(clippy::cast_precision_loss)]
-    let change_pct = ((current - previous) as f64 / previous as f64) * 100.0;
+/// Percentage change from `previous` to `current`, or `None` when `previous` is zero.
+fn percent_change(current: f64, previous: f64) -> Option<f64> {
+    (previous != 0.0).then(|| (current - previous) / previous * 100.0)
+}
 
-    let arrow = if change_pct > 0.0 {
-        "UP"
-    } else if change_pct < 0.0 {
-        "DN"
-    } else {
-        "--"
-    };
+/// Formats a signed percent change with 0 to 2 decimals, or "n/a" without a baseline.
+fn format_percent_change(change: Option<f64>, precision: u8) -> String {
+    change.map_or_else(
+        || "n/a".to_string(),
+        |change| format!("{change:+.prec$}%", prec = usize::from(precision.min(2))),
+    )
+}
 
-    (format!("{change_pct:+.1}%"), arrow.to_string())
+/// Calculates percentage change and returns formatted string with arrow.
+#[allow(clippy::cast_precision_loss)]
+fn format_change(current: i64, previous: i64, precision: u8) -> (String, String) {
+    format_cost_change(current as f64, previous as f64, precision)
 }
 
 /// Calculates percentage change for costs.
-fn format_cost_change(current: f64, previous: f64) -> (String, String) {
-    if previous == 0.0 {
-        if current == 0.0 {
-            return ("0%".to_string(), "-".to_string());
-        }
-        return ("+INF%".to_string(), "UP".to_string());
+fn format_cost_change(current: f64, previous: f64, precision: u8) -> (String, String) {
+    if previous == 0.0 && current == 0.0 {
+        return ("0%".to_string(), "-".to_string());
     }
 
-    let change_pct = ((current - previous) / previous) * 100.0;
-
-    let arrow = if change_pct > 0.0 {
-        "UP"
-    } else if change_pct < 0.0 {
-        "DN"
-    } else {
-        "--"
+    let change = percent_change(current, previous);
+    let arrow = match change {
+        None => "UP",
+        Some(change_pct) if change_pct > 0.0 => "UP",
+        Some(change_pct) if change_pct < 0.0 => "DN",
+        Some(_) => "--",
     };
 
-    (format!("{change_pct:+.1}%"), arrow.to_string())
+    (format_percent_change(change, precision), arrow.to_string())
 }
 
 /// Renders a metric block with current value, change indicator, and previous value.
@@ -82,8 +77,9 @@ fn metric_block(
     icon: &str,
     current: i64,
     previous: i64,
+    percent_precision: u8,
 ) -> cosmic::Element<'static, Message> {
-    let (change_text, arrow) = format_change(current, previous);
+    let (change_text, arrow) = format_change(current, previous, percent_precision);
     let current_str = format_number(current);
     let previous_str = format_number(previous);
 
@@ -102,14 +98,201 @@ fn metric_block(
         .into()
 }
 
+/// History charts and the quick-range presets that choose what they cover.
+pub struct HistoryCharts<'a> {
+    /// Presets offered as buttons above the charts
+    pub presets: &'a [RangePreset],
+    /// Preset the charts currently cover
+    pub selected: RangePreset,
+    /// Dates in the selected range with no recorded snapshot
+    pub missing_dates: &'a [NaiveDate],
+    /// Dates of snapshots from older versions that may hold display totals
+    pub legacy_dates: &'a [NaiveDate],
+    /// Whether missing dates are being filled from the usage files
+    pub backfill_running: bool,
+    /// Outcome of the last backfill
+    pub backfill_status: Option<&'a str>,
+    /// Whether no daily snapshot has been recorded yet
+    pub history_empty: bool,
+    /// Pre-rendered token usage chart
+    pub token_chart: &'a RgbaImage,
+    /// Pre-rendered cost per 1k tokens chart
+    pub efficiency_chart: &'a RgbaImage,
+    /// Pre-rendered average cost per interaction chart
+    pub cost_per_interaction_chart: &'a RgbaImage,
+}
+
+/// Renders a row of quick-range buttons, highlighting the selected preset.
+fn view_range_presets<'a>(presets: &[RangePreset], selected: RangePreset) -> Element<'a, Message> {
+    let mut buttons = row().spacing(8);
+    for &preset in presets {
+        let button = if preset == selected {
+            cosmic::widget::button::suggested(preset.label())
+        } else {
+            cosmic::widget::button::standard(preset.label())
+        };
+        buttons = buttons.push(button.on_press(Message::SelectRange(preset)));
+    }
+    // Kept apart from the presets: it also resets the history table
+    buttons
+        .push(text("|"))
+        .push(cosmic::widget::button::text("Today").on_press(Message::ResetToToday))
+        .into()
+}
+
+/// Formats the current usage streak, or `None` without one.
+#[must_use]
+pub fn format_streak(days: u32) -> Option<String> {
+    (days > 0).then(|| format!("{days}-day streak 🔥"))
+}
+
+/// Formats a session length as hours and minutes, or seconds when under a minute.
+fn format_session_length(length: std::time::Duration) -> String {
+    let seconds = length.as_secs();
+    match (seconds / 3600, seconds % 3600 / 60) {
+        (0, 0) => format!("{seconds}s"),
+        (0, minutes) => format!("{minutes}m"),
+        (hours, minutes) => format!("{hours}h {minutes:02}m"),
+    }
+}
+
+/// Most gap dates listed before the rest are summarized as a count.
+const MAX_LISTED_GAPS: usize = 8;
+
+/// Formats the data gaps line, e.g. "3 days with no recorded data: Oct 03, Oct 04, Oct 07".
+fn format_data_gaps(missing_dates: &[NaiveDate]) -> String {
+    match missing_dates.len() {
+        0 => "No gaps in recorded data for this range".to_string(),
+        count => {
+            let days = if count == 1 { "day" } else { "days" };
+            let mut listed = missing_dates
+                .iter()
+                .take(MAX_LISTED_GAPS)
+                .map(|date| date.format("%b %d").to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            if count > MAX_LISTED_GAPS {
+                listed.push_str(&format!(" and {} more", count - MAX_LISTED_GAPS));
+            }
+            format!("{count} {days} with no recorded data: {listed}")
+        }
+    }
+}
+
+/// Formats the note on snapshots from older versions, `None` when there are none.
+fn format_legacy_snapshots(legacy_dates: &[NaiveDate]) -> Option<String> {
+    match legacy_dates.len() {
+        0 => None,
+        1 => Some(
+            "1 older snapshot may hold month or all-time totals and is left out of sums"
+                .to_string(),
+        ),
+        count => Some(format!(
+            "{count} older snapshots may hold month or all-time totals and are left out of sums"
+        )),
+    }
+}
+
+/// Renders the data gaps line with a button to backfill the gaps from the usage files.
+///
+/// Snapshots from older versions are recomputed by the same backfill.
+fn view_data_gaps<'a>(charts: &HistoryCharts<'a>) -> Element<'a, Message> {
+    let mut gaps = column()
+        .push(text(format_data_gaps(charts.missing_dates)).size(12))
+        .push_maybe(format_legacy_snapshots(charts.legacy_dates).map(|note| text(note).size(12)))
+        .spacing(6)
+        .align_x(Alignment::Center);
+
+    if !charts.missing_dates.is_empty() || !charts.legacy_dates.is_empty() {
+        let label = if charts.backfill_running {
+            "Backfilling…"
+        } else {
+            "Backfill from files"
+        };
+        gaps = gaps.push(
+            cosmic::widget::button::standard(label)
+                .on_press_maybe((!charts.backfill_running).then_some(Message::BackfillMissing)),
+        );
+    }
+    if let Some(status) = charts.backfill_status {
+        gaps = gaps.push(text(status.to_string()).size(12));
+    }
+    gaps.into()
+}
+
+/// Options controlling the daily summary line below the weekly comparison.
+#[derive(Debug, Clone, Copy)]
+pub struct DailySummaryOptions<'a> {
+    /// Only count days with usage
+    pub per_active_day: bool,
+    /// Whether to show the mean or the median
+    pub central_tendency: CentralTendency,
+    /// This week's daily snapshots, used to compute the median
+    pub daily_snapshots: &'a [UsageSnapshot],
+}
+
+/// Formats the daily summary line for a week summary, as a mean or a median.
+fn format_daily_summary(summary: &WeekSummary, options: DailySummaryOptions<'_>) -> String {
+    match options.central_tendency {
+        CentralTendency::Mean => format_daily_average(summary, options.per_active_day),
+        CentralTendency::Median => format_daily_median(summary, options),
+    }
+}
+
+/// Formats the daily median line for a week summary.
+///
+/// Idle days count as 0 unless `per_active_day` is set.
+#[allow(clippy::cast_precision_loss)]
+fn format_daily_median(summary: &WeekSummary, options: DailySummaryOptions<'_>) -> String {
+    let cost = summary.median_daily(options.daily_snapshots, options.per_active_day, |s| {
+        s.total_cost
+    });
+    let interactions = summary.median_daily(options.daily_snapshots, options.per_active_day, |s| {
+        s.interaction_count as f64
+    });
+
+    format!(
+        "Daily median ({}): {} - {:.1} interactions",
+        format_basis(summary, options.per_active_day),
+        format_cost(cost),
+        interactions
+    )
+}
+
+/// Describes which days a daily statistic is computed over.
+fn format_basis(summary: &WeekSummary, per_active_day: bool) -> String {
+    if per_active_day {
+        format!(
+            "per active day, {} of {} days",
+            summary.active_days,
+            summary.calendar_days()
+        )
+    } else {
+        "per calendar day".to_string()
+    }
+}
+
+/// Formats the daily average line for a week summary.
+///
+/// Averages are divided by calendar days, or by days with usage when `per_active_day` is set.
+fn format_daily_average(summary: &WeekSummary, per_active_day: bool) -> String {
+    format!(
+        "Daily average ({}): {} - {:.1} interactions",
+        format_basis(summary, per_active_day),
+        format_cost(summary.average_daily_cost(per_active_day)),
+        summary.average_daily_interactions(per_active_day)
+    )
+}
+
 /// Renders a metric block for cost with current value, change indicator, and previous value.
 fn cost_metric_block(
     label: &str,
     icon: &str,
     current: f64,
     previous: f64,
+    percent_precision: u8,
 ) -> cosmic::Element<'static, Message> {
-    let (change_text, arrow) = format_cost_change(current, previous);
+    let (change_text, arrow) = format_cost_change(current, previous, percent_precision);
     let current_str = format_cost(current);
     let previous_str = format_cost(previous);
 
@@ -143,17 +326,344 @@ fn render_chart_image(chart_image: &RgbaImage) -> Element<'static, Message> {
         .into()
 }
 
+/// Renders a notice explaining that usage history is disabled.
+#[must_use]
+pub fn view_history_disabled() -> Element<'static, Message> {
+    container(
+        column()
+            .push(text("Usage History Disabled").size(28))
+            .push(
+                text("History collection is turned off in the applet settings. Enable \"Keep daily usage history\" to start recording daily snapshots.")
+                    .size(14),
+            )
+            .spacing(20)
+            .align_x(Alignment::Center),
+    )
+    .padding(40)
+    .center_x(Length::Fill)
+    .center_y(Length::Fill)
+    .into()
+}
+
+/// Renders the guidance shown in place of the weekly comparison before any daily
+/// snapshot has been recorded.
+#[must_use]
+pub fn view_empty_history() -> Element<'static, Message> {
+    column()
+        .push(text("No Usage History Yet").size(20))
+        .push(
+            text("Daily snapshots are recorded by the OpenCode Usage applet. Add it to your panel and keep it running, or backfill past days from the usage files below.")
+                .size(14),
+        )
+        .spacing(10)
+        .align_x(Alignment::Center)
+        .into()
+}
+
+/// Renders the error shown when the usage database cannot be opened.
+#[must_use]
+pub fn view_database_error(error: &str) -> Element<'static, Message> {
+    container(
+        column()
+            .push(text("Usage History Unavailable").size(28))
+            .push(text(format!("The usage database could not be opened: {error}")).size(14))
+            .push(text("Check that the data folder is writable, then reopen the viewer.").size(14))
+            .spacing(20)
+            .align_x(Alignment::Center),
+    )
+    .padding(40)
+    .center_x(Length::Fill)
+    .center_y(Length::Fill)
+    .into()
+}
+
+/// Renders the snapshot diff section with its two date inputs.
+///
+/// Shows raw values for both days and the delta for each field, or a hint
+/// while either date is not a valid `YYYY-MM-DD` date.
+#[must_use]
+pub fn view_snapshot_diff<'a>(
+    from_input: &'a str,
+    to_input: &'a str,
+    diff: Option<&SnapshotDiff>,
+) -> Element<'a, Message> {
+    let inputs = row()
+        .push(text_input("From (YYYY-MM-DD)", from_input).on_input(Message::DiffFromChanged))
+        .push(text_input("To (YYYY-MM-DD)", to_input).on_input(Message::DiffToChanged))
+        .spacing(10)
+        .width(Length::Fixed(420.0));
+
+    let mut section = column()
+        .push(text("Compare Two Days").size(20))
+        .push(inputs)
+        .spacing(8)
+        .align_x(Alignment::Center);
+
+    let Some(diff) = diff else {
+        return section
+            .push(text("Enter two valid dates to compare their snapshots").size(12))
+            .into();
+    };
+
+    let header = row()
+        .push(text("Field").size(14).width(Length::Fixed(120.0)))
+        .push(text("From").size(14).width(Length::Fixed(100.0)))
+        .push(text("To").size(14).width(Length::Fixed(100.0)))
+        .push(text("Delta").size(14).width(Length::Fixed(100.0)));
+    section = section.push(header);
+
+    for (label, from, to, delta) in diff.rows() {
+        section = section.push(
+            row()
+                .push(text(label).size(12).width(Length::Fixed(120.0)))
+                .push(text(from).size(12).width(Length::Fixed(100.0)))
+                .push(text(to).size(12).width(Length::Fixed(100.0)))
+                .push(text(delta).size(12).width(Length::Fixed(100.0))),
+        );
+    }
+
+    section.into()
+}
+
+/// Renders the form exporting the token usage chart of the selected range to a PNG file.
+#[must_use]
+pub fn view_chart_export<'a>(
+    path_input: &'a str,
+    width_input: &'a str,
+    height_input: &'a str,
+    status: Option<&'a str>,
+    running: bool,
+) -> Element<'a, Message> {
+    let label = if running {
+        "Exporting…"
+    } else {
+        "Export PNG"
+    };
+    let inputs = row()
+        .push(
+            text_input("File path (.png)", path_input)
+                .on_input(Message::ExportPathChanged)
+                .width(Length::Fixed(300.0)),
+        )
+        .push(
+            text_input("Width", width_input)
+                .on_input(Message::ExportWidthChanged)
+                .width(Length::Fixed(80.0)),
+        )
+        .push(
+            text_input("Height", height_input)
+                .on_input(Message::ExportHeightChanged)
+                .width(Length::Fixed(80.0)),
+        )
+        .push(
+            cosmic::widget::button::standard(label)
+                .on_press_maybe((!running).then_some(Message::ExportChart)),
+        )
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+    column()
+        .push(text("Export Token Chart").size(20))
+        .push(inputs)
+        .push_maybe(status.map(|status| text(status).size(12)))
+        .spacing(8)
+        .align_x(Alignment::Center)
+        .into()
+}
+
+/// Renders the section that re-reads the usage files and checks today's snapshot.
+///
+/// Lists each field where the stored snapshot and the files disagree.
+#[must_use]
+pub fn view_fresh_read<'a>(
+    loading: bool,
+    fresh_read: Option<&Result<FreshRead, String>>,
+    stored_today: Option<&UsageSnapshot>,
+) -> Element<'a, Message> {
+    let mut section = column()
+        .push(text("Verify Against Files").size(20))
+        .push(
+            cosmic::widget::button::standard("Re-read from disk")
+                .on_press_maybe((!loading).then_some(Message::ForceReread)),
+        )
+        .spacing(8)
+        .align_x(Alignment::Center);
+
+    if loading {
+        return section.push(text("Reading usage files…").size(12)).into();
+    }
+
+    match fresh_read {
+        None => section
+            .push(text("Recompute today and this month from the usage files").size(12))
+            .into(),
+        Some(Err(err)) => section
+            .push(text(format!("Re-read failed: {err}")).size(12))
+            .into(),
+        Some(Ok(fresh)) => {
+            section = section.push(
+                text(format!(
+                    "Files today: {}, {} interactions · This month: {}, {} interactions",
+                    format_cost(fresh.today.total_cost),
+                    fresh.today.interaction_count,
+                    format_cost(fresh.month.total_cost),
+                    fresh.month.interaction_count
+                ))
+                .size(12),
+            );
+            if let Some(sessions) = fresh.sessions {
+                section = section.push(
+                    text(format!(
+                        "Sessions this month: {}, {} total, {} on average",
+                        sessions.sessions,
+                        format_session_length(sessions.total),
+                        format_session_length(sessions.average())
+                    ))
+                    .size(12),
+                );
+            }
+
+            let Some(stored) = stored_today else {
+                return section
+                    .push(text("No snapshot stored for today to compare").size(12))
+                    .into();
+            };
+
+            let discrepancies = find_discrepancies(&stored.to_usage_metrics(), &fresh.today);
+            if discrepancies.is_empty() {
+                return section
+                    .push(text("✓ Today's stored snapshot matches the files").size(12))
+                    .into();
+            }
+
+            for discrepancy in discrepancies {
+                section = section.push(
+                    text(format!(
+                        "⚠ {}: stored {}, files {}",
+                        discrepancy.field, discrepancy.stored, discrepancy.fresh
+                    ))
+                    .size(12),
+                );
+            }
+            section.into()
+        }
+    }
+}
+
+/// Day names for weekday breakdowns, Monday first.
+const WEEKDAY_NAMES: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// Formats the per-weekday cost totals (e.g., "Mon $1.50 · Tue $0.00 · ...").
+fn format_weekday_costs(costs: &[f64; 7]) -> String {
+    WEEKDAY_NAMES
+        .iter()
+        .zip(costs)
+        .map(|(day, cost)| format!("{day} {}", format_cost(*cost)))
+        .collect::<Vec<_>>()
+        .join(" · ")
+}
+
+/// Number of pages needed to show `total` rows, at least one.
+#[must_use]
+pub fn page_count(total: usize, page_size: usize) -> usize {
+    total.div_ceil(page_size.max(1)).max(1)
+}
+
+/// Renders one page of the daily history table with previous/next controls.
+///
+/// `page` is 0-based and shown 1-based as "Page X of Y".
+#[must_use]
+pub fn view_history_table<'a>(
+    rows: &[UsageSnapshot],
+    page: usize,
+    pages: usize,
+) -> Element<'a, Message> {
+    let mut section = column()
+        .push(text("Daily History").size(20))
+        .spacing(8)
+        .align_x(Alignment::Center);
+
+    if rows.is_empty() {
+        return section
+            .push(text("No snapshots recorded yet").size(12))
+            .into();
+    }
+
+    section = section.push(
+        row()
+            .push(text("Date").size(14).width(Length::Fixed(120.0)))
+            .push(text("Tokens").size(14).width(Length::Fixed(120.0)))
+            .push(text("Cost").size(14).width(Length::Fixed(100.0)))
+            .push(text("Interactions").size(14).width(Length::Fixed(100.0))),
+    );
+    for snapshot in rows {
+        section = section.push(
+            row()
+                .push(
+                    text(snapshot.date.format("%Y-%m-%d").to_string())
+                        .size(12)
+                        .width(Length::Fixed(120.0)),
+                )
+                .push(
+                    text(format_number(snapshot.total_tokens()))
+                        .size(12)
+                        .width(Length::Fixed(120.0)),
+                )
+                .push(
+                    text(format_cost(snapshot.total_cost))
+                        .size(12)
+                        .width(Length::Fixed(100.0)),
+                )
+                .push(
+                    text(format_number(snapshot.interaction_count))
+                        .size(12)
+                        .width(Length::Fixed(100.0)),
+                ),
+        );
+    }
+
+    let controls = row()
+        .push(
+            cosmic::widget::button::standard("Previous")
+                .on_press_maybe(page.checked_sub(1).map(Message::HistoryPage)),
+        )
+        .push(text(format!("Page {} of {pages}", page + 1)).size(12))
+        .push(
+            cosmic::widget::button::standard("Next")
+                .on_press_maybe((page + 1 < pages).then_some(Message::HistoryPage(page + 1))),
+        )
+        .spacing(12)
+        .align_y(Alignment::Center);
+
+    section.push(controls).into()
+}
+
+/// Renders the cost by weekday section with its pre-rendered bar chart.
+#[must_use]
+pub fn view_weekday_costs<'a>(chart_image: &RgbaImage, costs: &[f64; 7]) -> Element<'a, Message> {
+    column()
+        .push(text("Cost by Weekday").size(20))
+        .push(render_chart_image(chart_image))
+        .push(text(format_weekday_costs(costs)).size(12))
+        .spacing(8)
+        .align_x(Alignment::Center)
+        .into()
+}
+
 /// Renders the main content view for the viewer application.
 ///
 /// Displays week-over-week comparison in a 5-column horizontal layout,
 /// with a static pre-rendered chart below.
 #[must_use]
-pub fn view_content(
+pub fn view_content<'a>(
     this_week: Option<WeekSummary>,
     last_week: Option<WeekSummary>,
     week_starts: (NaiveDate, NaiveDate),
-    chart_image: &RgbaImage,
-) -> Element<'_, Message> {
+    charts: HistoryCharts<'a>,
+    daily_summary: DailySummaryOptions<'a>,
+    percent_precision: u8,
+    extra_sections: Element<'a, Message>,
+) -> Element<'a, Message> {
     let (_this_week_start, last_week_start) = week_starts;
 
     let mut content = column()
@@ -183,6 +693,10 @@ pub fn view_content(
     content = content.push(text("").size(10)); // Spacer
 
     match (this_week, last_week) {
+        // Keep the charts below so the backfill controls stay reachable
+        _ if charts.history_empty => {
+            content = content.push(view_empty_history());
+        }
         (Some(tw), Some(lw)) => {
             // Show all metrics in a horizontal row layout
             let metrics_row = row()
@@ -191,35 +705,42 @@ pub fn view_content(
                     "📝",
                     tw.total_input_tokens,
                     lw.total_input_tokens,
+                    percent_precision,
                 ))
                 .push(metric_block(
                     "Output",
                     "📤",
                     tw.total_output_tokens,
                     lw.total_output_tokens,
+                    percent_precision,
                 ))
                 .push(metric_block(
                     "Reasoning",
                     "🧠",
                     tw.total_reasoning_tokens,
                     lw.total_reasoning_tokens,
+                    percent_precision,
                 ))
                 .push(cost_metric_block(
                     "Cost",
                     "💰",
                     tw.total_cost,
                     lw.total_cost,
+                    percent_precision,
                 ))
                 .push(metric_block(
                     "Interactions",
                     "🔄",
                     tw.total_interactions,
                     lw.total_interactions,
+                    percent_precision,
                 ))
                 .spacing(10)
                 .width(Length::Fill);
 
-            content = content.push(metrics_row);
+            content = content
+                .push(metrics_row)
+                .push(text(format_daily_summary(&tw, daily_summary)).size(14));
         }
         (Some(tw), None) => {
             // Only this week data - reuse metric block helpers with 0 for previous values
@@ -228,20 +749,47 @@ pub fn view_content(
                 .push(text("").size(10));
 
             let metrics_row = row()
-                .push(metric_block("Input", "📝", tw.total_input_tokens, 0))
-                .push(metric_block("Output", "📤", tw.total_output_tokens, 0))
+                .push(metric_block(
+                    "Input",
+                    "📝",
+                    tw.total_input_tokens,
+                    0,
+                    percent_precision,
+                ))
+                .push(metric_block(
+                    "Output",
+                    "📤",
+                    tw.total_output_tokens,
+                    0,
+                    percent_precision,
+                ))
                 .push(metric_block(
                     "Reasoning",
                     "🧠",
                     tw.total_reasoning_tokens,
                     0,
+                    percent_precision,
+                ))
+                .push(cost_metric_block(
+                    "Cost",
+                    "💰",
+                    tw.total_cost,
+                    0.0,
+                    percent_precision,
+                ))
+                .push(metric_block(
+                    "Interactions",
+                    "🔄",
+                    tw.total_interactions,
+                    0,
+                    percent_precision,
                 ))
-                .push(cost_metric_block("Cost", "💰", tw.total_cost, 0.0))
-                .push(metric_block("Interactions", "🔄", tw.total_interactions, 0))
                 .spacing(10)
                 .width(Length::Fill);
 
-            content = content.push(metrics_row);
+            content = content
+                .push(metrics_row)
+                .push(text(format_daily_summary(&tw, daily_summary)).size(14));
         }
         (None, Some(_lw)) => {
             content = content.push(text("No data available for this week yet").size(14));
@@ -254,13 +802,24 @@ pub fn view_content(
         }
     }
 
-    // Add the pre-rendered static chart
+    // Add the pre-rendered charts for the selected range
     content = content
         .push(text("").size(20)) // Spacer
-        .push(text("30-Day History").size(20))
-        .push(render_chart_image(chart_image));
-
-    container(content)
+        .push(text(charts.selected.title()).size(20))
+        .push(view_range_presets(charts.presets, charts.selected))
+        .push(view_data_gaps(&charts))
+        .push(render_chart_image(charts.token_chart))
+        .push(text("").size(10)) // Spacer
+        .push(text("Cost per 1K Tokens").size(20))
+        .push(render_chart_image(charts.efficiency_chart))
+        .push(text("").size(10)) // Spacer
+        .push(text("Cost per Interaction").size(20))
+        .push(render_chart_image(charts.cost_per_interaction_chart))
+        .push(text("").size(10)) // Spacer
+        .push(extra_sections);
+
+    // Scrollable so both charts stay reachable in smaller windows
+    container(scrollable(content))
         .width(Length::Fill)
         .height(Length::Fill)
         .center_x(Length::Fill)
@@ -271,6 +830,20 @@ pub fn view_content(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_format_streak() {
+        assert_eq!(format_streak(0), None);
+        assert_eq!(format_streak(7).as_deref(), Some("7-day streak 🔥"));
+    }
+
+    #[test]
+    fn test_format_session_length() {
+        use std::time::Duration;
+        assert_eq!(format_session_length(Duration::from_secs(42)), "42s");
+        assert_eq!(format_session_length(Duration::from_secs(754)), "12m");
+        assert_eq!(format_session_length(Duration::from_secs(3_900)), "1h 05m");
+    }
+
     #[test]
     fn test_format_number() {
         assert_eq!(format_number(1000), "1,000");
@@ -278,18 +851,169 @@ mod tests {
         assert_eq!(format_number(123), "123");
     }
 
+    #[test]
+    fn test_format_weekday_costs() {
+        let formatted = format_weekday_costs(&[1.5, 0.0, 2.25, 0.0, 0.0, 0.0, 4.0]);
+        assert!(formatted.starts_with("Mon $1.50 · Tue $0.00 · Wed $2.25"));
+        assert!(formatted.ends_with("Sun $4.00"));
+    }
+
     #[test]
     fn test_format_change() {
-        let (pct, arrow) = format_change(120, 100);
+        let (pct, arrow) = format_change(120, 100, 1);
         assert_eq!(arrow, "UP");
         assert!(pct.contains("20"));
 
-        let (pct, arrow) = format_change(80, 100);
+        let (pct, arrow) = format_change(80, 100, 1);
         assert_eq!(arrow, "DN");
         assert!(pct.contains("-20"));
 
-        let (pct, arrow) = format_change(100, 100);
+        let (pct, arrow) = format_change(100, 100, 1);
         assert_eq!(arrow, "--");
         assert_eq!(pct, "+0.0%");
     }
+
+    #[test]
+    fn test_format_percent_change_precision() {
+        let rise = percent_change(118.33, 100.0);
+        assert_eq!(format_percent_change(rise, 0), "+18%");
+        assert_eq!(format_percent_change(rise, 1), "+18.3%");
+        assert_eq!(format_percent_change(rise, 2), "+18.33%");
+
+        let drop = percent_change(74.4, 100.0);
+        assert_eq!(format_percent_change(drop, 0), "-26%");
+        assert_eq!(format_percent_change(drop, 1), "-25.6%");
+        assert_eq!(format_percent_change(drop, 2), "-25.60%");
+
+        assert_eq!(percent_change(5.0, 0.0), None);
+        for precision in 0..=2 {
+            assert_eq!(format_percent_change(None, precision), "n/a");
+        }
+        assert_eq!(
+            format_cost_change(5.0, 0.0, 0),
+            ("n/a".to_string(), "UP".to_string())
+        );
+    }
+
+    #[test]
+    fn test_format_daily_average() {
+        let summary = WeekSummary {
+            start_date: NaiveDate::from_ymd_opt(2025, 10, 6).unwrap(),
+            end_date: NaiveDate::from_ymd_opt(2025, 10, 12).unwrap(),
+            total_input_tokens: 0,
+            total_output_tokens: 0,
+            total_reasoning_tokens: 0,
+            total_cache_write_tokens: 0,
+            total_cache_read_tokens: 0,
+            total_cost: 7.0,
+            total_interactions: 14,
+            active_days: 2,
+            active_day_definition: ActiveDayDefinition::AnyUsage,
+        };
+
+        assert_eq!(
+            format_daily_average(&summary, false),
+            "Daily average (per calendar day): $1.00 - 2.0 interactions"
+        );
+        assert_eq!(
+            format_daily_average(&summary, true),
+            "Daily average (per active day, 2 of 7 days): $3.50 - 7.0 interactions"
+        );
+    }
+
+    #[test]
+    fn test_format_daily_summary_median() {
+        let start = NaiveDate::from_ymd_opt(2025, 10, 6).unwrap();
+        let summary = WeekSummary {
+            start_date: start,
+            end_date: start + chrono::Duration::days(6),
+            total_input_tokens: 0,
+            total_output_tokens: 0,
+            total_reasoning_tokens: 0,
+            total_cache_write_tokens: 0,
+            total_cache_read_tokens: 0,
+            total_cost: 0.0,
+            total_interactions: 0,
+            active_days: 2,
+            active_day_definition: ActiveDayDefinition::AnyUsage,
+        };
+        let snapshots = [
+            UsageSnapshot {
+                date: start,
+                input_tokens: 1000,
+                output_tokens: 0,
+                reasoning_tokens: 0,
+                cache_write_tokens: 0,
+                cache_read_tokens: 0,
+                total_cost: 1.0,
+                interaction_count: 1,
+            },
+            UsageSnapshot {
+                date: start + chrono::Duration::days(1),
+                input_tokens: 3000,
+                output_tokens: 0,
+                reasoning_tokens: 0,
+                cache_write_tokens: 0,
+                cache_read_tokens: 0,
+                total_cost: 3.0,
+                interaction_count: 4,
+            },
+        ];
+        let options = DailySummaryOptions {
+            per_active_day: true,
+            central_tendency: CentralTendency::Median,
+            daily_snapshots: &snapshots,
+        };
+
+        assert_eq!(
+            format_daily_summary(&summary, options),
+            "Daily median (per active day, 2 of 7 days): $2.00 - 2.5 interactions"
+        );
+    }
+
+    #[test]
+    fn test_format_legacy_snapshots() {
+        let date = |day| NaiveDate::from_ymd_opt(2025, 10, day).unwrap();
+
+        assert_eq!(format_legacy_snapshots(&[]), None);
+        assert_eq!(
+            format_legacy_snapshots(&[date(1)]).as_deref(),
+            Some("1 older snapshot may hold month or all-time totals and is left out of sums")
+        );
+        assert_eq!(
+            format_legacy_snapshots(&[date(1), date(2)]).as_deref(),
+            Some("2 older snapshots may hold month or all-time totals and are left out of sums")
+        );
+    }
+
+    #[test]
+    fn test_format_data_gaps() {
+        let date = |day| NaiveDate::from_ymd_opt(2025, 10, day).unwrap();
+
+        assert_eq!(
+            format_data_gaps(&[]),
+            "No gaps in recorded data for this range"
+        );
+        assert_eq!(
+            format_data_gaps(&[date(3)]),
+            "1 day with no recorded data: Oct 03"
+        );
+
+        let many: Vec<NaiveDate> = (1..=12).map(date).collect();
+        assert_eq!(
+            format_data_gaps(&many),
+            "12 days with no recorded data: Oct 01, Oct 02, Oct 03, Oct 04, Oct 05, Oct 06, \
+             Oct 07, Oct 08 and 4 more"
+        );
+    }
+
+    #[test]
+    fn test_page_count() {
+        assert_eq!(page_count(0, 50), 1);
+        assert_eq!(page_count(50, 50), 1);
+        assert_eq!(page_count(51, 50), 2);
+        assert_eq!(page_count(7, 3), 3);
+        // A zero page size is treated as one row per page
+        assert_eq!(page_count(4, 0), 4);
+    }
 }
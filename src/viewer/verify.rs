@@ -0,0 +1,187 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Verification of stored snapshots against a fresh read of the usage files.
+
+use crate::core::config::AppConfig;
+use crate::core::opencode::{OpenCodeUsageReader, SessionDurations, UsageMetrics};
+
+/// Cost differences below this are rounding noise, not discrepancies.
+const COST_TOLERANCE: f64 = 0.005;
+
+/// A field whose stored value differs from the value read from the files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Discrepancy {
+    pub field: &'static str,
+    pub stored: String,
+    pub fresh: String,
+}
+
+/// Today's and this month's usage read straight from the usage files.
+#[derive(Debug, Clone)]
+pub struct FreshRead {
+    pub today: UsageMetrics,
+    pub month: UsageMetrics,
+    /// This month's session lengths, when the usage files record timestamps
+    pub sessions: Option<SessionDurations>,
+}
+
+/// Lists the fields that differ between stored metrics and a fresh read.
+///
+/// Token and interaction counts must match exactly; costs may differ by
+/// less than half a cent.
+#[must_use]
+pub fn find_discrepancies(stored: &UsageMetrics, fresh: &UsageMetrics) -> Vec<Discrepancy> {
+    let counts = [
+        ("Input", stored.total_input_tokens, fresh.total_input_tokens),
+        (
+            "Output",
+            stored.total_output_tokens,
+            fresh.total_output_tokens,
+        ),
+        (
+            "Reasoning",
+            stored.total_reasoning_tokens,
+            fresh.total_reasoning_tokens,
+        ),
+        (
+            "Cache Write",
+            stored.total_cache_write_tokens,
+            fresh.total_cache_write_tokens,
+        ),
+        (
+            "Cache Read",
+            stored.total_cache_read_tokens,
+            fresh.total_cache_read_tokens,
+        ),
+        (
+            "Interactions",
+            stored.interaction_count as u64,
+            fresh.interaction_count as u64,
+        ),
+    ];
+
+    let mut discrepancies: Vec<Discrepancy> = counts
+        .into_iter()
+        .filter(|(_, stored, fresh)| stored != fresh)
+        .map(|(field, stored, fresh)| Discrepancy {
+            field,
+            stored: stored.to_string(),
+            fresh: fresh.to_string(),
+        })
+        .collect();
+
+    if (stored.total_cost - fresh.total_cost).abs() >= COST_TOLERANCE {
+        discrepancies.push(Discrepancy {
+            field: "Cost",
+            stored: format!("${:.2}", stored.total_cost),
+            fresh: format!("${:.2}", fresh.total_cost),
+        });
+    }
+
+    discrepancies
+}
+
+/// Creates a usage reader with the storage path and day reset hour from the applet config.
+///
+/// # Errors
+///
+/// Returns a message if the storage directory cannot be found or the reset hour is invalid.
+pub fn reader_for_config(config: &AppConfig) -> Result<OpenCodeUsageReader, String> {
+    let mut reader = match &config.storage_path {
+        Some(path) => OpenCodeUsageReader::new_with_path(path.to_str().unwrap_or("")),
+        None => OpenCodeUsageReader::new(),
+    }
+    .map_err(|e| format!("Failed to create reader: {e}"))?;
+    reader
+        .set_day_reset_hour(config.day_reset_hour)
+        .map_err(|e| e.to_string())?;
+    Ok(reader)
+}
+
+/// Reads today's and this month's usage from disk with an empty reader cache.
+///
+/// Uses the storage path and day reset hour from the applet configuration.
+///
+/// # Errors
+///
+/// Returns a message if the storage cannot be read or holds no usage this month.
+pub fn read_fresh(config: &AppConfig) -> Result<FreshRead, String> {
+    let mut reader = reader_for_config(config)?;
+
+    // A new reader starts empty; clearing makes the intent explicit. Session lengths
+    // need the session ids a lean cache would drop.
+    reader.set_lean_cache(false);
+    reader.invalidate_cache();
+    let (today, month) = reader
+        .get_usage_today_and_month()
+        .map_err(|e| format!("Failed to read usage files: {e}"))?;
+    let sessions = reader.get_session_durations_month().ok().flatten();
+
+    Ok(FreshRead {
+        today,
+        month,
+        sessions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::SystemTime;
+
+    fn metrics(input: u64, cost: f64, interactions: usize) -> UsageMetrics {
+        UsageMetrics {
+            total_input_tokens: input,
+            total_output_tokens: 500,
+            total_reasoning_tokens: 0,
+            total_cache_write_tokens: 10,
+            total_cache_read_tokens: 20,
+            total_cost: cost,
+            total_cache_cost: 0.0,
+            interaction_count: interactions,
+            timestamp: SystemTime::now(),
+        }
+    }
+
+    #[test]
+    fn test_find_discrepancies_matching_metrics() {
+        assert!(find_discrepancies(&metrics(1000, 1.0, 3), &metrics(1000, 1.0, 3)).is_empty());
+        // Sub-cent cost differences are rounding noise
+        assert!(find_discrepancies(&metrics(1000, 1.0, 3), &metrics(1000, 1.004, 3)).is_empty());
+    }
+
+    #[test]
+    fn test_find_discrepancies_flags_each_field() {
+        let found = find_discrepancies(&metrics(1000, 1.0, 3), &metrics(1200, 1.5, 4));
+
+        assert_eq!(
+            found,
+            vec![
+                Discrepancy {
+                    field: "Input",
+                    stored: "1000".to_string(),
+                    fresh: "1200".to_string(),
+                },
+                Discrepancy {
+                    field: "Interactions",
+                    stored: "3".to_string(),
+                    fresh: "4".to_string(),
+                },
+                Discrepancy {
+                    field: "Cost",
+                    stored: "$1.00".to_string(),
+                    fresh: "$1.50".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_fresh_missing_storage_fails() {
+        let config = AppConfig {
+            storage_path: Some("/nonexistent/opencode/storage".into()),
+            ..AppConfig::default()
+        };
+        assert!(read_fresh(&config).is_err());
+    }
+}
@@ -0,0 +1,131 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! End-to-end test of the fetch → collect → store flow.
+//!
+//! Runs entirely against temporary directories, never the real home directory.
+
+use cosmic_applet_opencode_usage::core::collector::DataCollector;
+use cosmic_applet_opencode_usage::core::database::{repository::UsageRepository, DatabaseManager};
+use cosmic_applet_opencode_usage::core::opencode::OpenCodeUsageReader;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+/// Writes a usage part file the way `OpenCode` lays them out (one directory per message)
+fn create_part_file(
+    storage: &Path,
+    message: &str,
+    part: &str,
+    tokens: (u64, u64, u64, u64, u64),
+    cost: f64,
+) {
+    let (input, output, reasoning, cache_write, cache_read) = tokens;
+    let dir = storage.join(message);
+    fs::create_dir_all(&dir).expect("Failed to create message directory");
+
+    let content = format!(
+        r#"{{
+  "id": "{part}",
+  "messageID": "{message}",
+  "sessionID": "ses_flow",
+  "type": "step-finish",
+  "tokens": {{
+    "input": {input},
+    "output": {output},
+    "reasoning": {reasoning},
+    "cache": {{
+      "write": {cache_write},
+      "read": {cache_read}
+    }}
+  }},
+  "cost": {cost}
+}}"#
+    );
+    fs::write(dir.join(format!("{part}.json")), content).expect("Failed to write part file");
+}
+
+#[test]
+fn test_fetch_collect_store_roundtrip() {
+    let storage_dir = TempDir::new().unwrap();
+    let db_dir = TempDir::new().unwrap();
+
+    create_part_file(
+        storage_dir.path(),
+        "msg_1",
+        "prt_1",
+        (1000, 500, 100, 50, 200),
+        0.25,
+    );
+    create_part_file(
+        storage_dir.path(),
+        "msg_1",
+        "prt_2",
+        (2000, 1500, 0, 0, 300),
+        0.50,
+    );
+    create_part_file(
+        storage_dir.path(),
+        "msg_2",
+        "prt_3",
+        (500, 250, 50, 10, 0),
+        0.10,
+    );
+    // Non-usage files must not affect the totals
+    fs::write(storage_dir.path().join("notes.txt"), "ignored").unwrap();
+
+    // Fetch
+    let mut reader = OpenCodeUsageReader::new_with_path(storage_dir.path().to_str().unwrap())
+        .expect("Should create reader");
+    let metrics = reader.get_usage().expect("Should read usage");
+    assert_eq!(metrics.interaction_count, 3);
+
+    // Collect
+    let db = Arc::new(DatabaseManager::new_with_path(&db_dir.path().join("usage.db")).unwrap());
+    let collector = DataCollector::new(Arc::clone(&db));
+    assert!(collector
+        .collect_and_save(&metrics)
+        .expect("Should collect"));
+    assert!(
+        !collector
+            .collect_and_save(&metrics)
+            .expect("Should collect"),
+        "Second collection on the same day is skipped"
+    );
+
+    // Store
+    let date = collector
+        .get_last_collection_date()
+        .expect("Collection date should be recorded");
+    let repository = UsageRepository::new(db);
+    let snapshot = repository
+        .get_snapshot(date)
+        .expect("Should query snapshot")
+        .expect("Snapshot should be stored");
+
+    assert_eq!(snapshot.input_tokens, 3500);
+    assert_eq!(snapshot.output_tokens, 2250);
+    assert_eq!(snapshot.reasoning_tokens, 150);
+    assert_eq!(snapshot.cache_write_tokens, 60);
+    assert_eq!(snapshot.cache_read_tokens, 500);
+    assert_eq!(snapshot.interaction_count, 3);
+    assert!((snapshot.total_cost - 0.85).abs() < 1e-9);
+
+    // The stored snapshot converts back to the fetched totals
+    let restored = snapshot.to_usage_metrics();
+    assert_eq!(restored.total_input_tokens, metrics.total_input_tokens);
+    assert_eq!(restored.total_output_tokens, metrics.total_output_tokens);
+    assert_eq!(
+        restored.total_reasoning_tokens,
+        metrics.total_reasoning_tokens
+    );
+    assert_eq!(
+        restored.total_cache_write_tokens,
+        metrics.total_cache_write_tokens
+    );
+    assert_eq!(
+        restored.total_cache_read_tokens,
+        metrics.total_cache_read_tokens
+    );
+    assert_eq!(restored.interaction_count, metrics.interaction_count);
+}